@@ -0,0 +1,831 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bots::{notify_bot, notify_tracker, Bot, CardsTracker};
+use crate::eval::Evaluator;
+use crate::fsm::Action;
+use crate::game::{available_actions_for, Game, GameView, PlayerView, Settings};
+use crate::input::{open_file, InputError, LineReader};
+use crate::interactive::to_game_command;
+use crate::run::{get_action_type_name, make_bot, make_bots_rng, run_game, BotType, TimeControl};
+use crate::GameParams;
+
+const SPARKLINE: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Copy)]
+pub enum AnalyzeMode {
+    Agreement,
+    WinProbability,
+}
+
+impl FromStr for AnalyzeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "agreement" => Ok(AnalyzeMode::Agreement),
+            "win_probability" => Ok(AnalyzeMode::WinProbability),
+            _ => Err(format!("invalid analyze mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum WinProbabilityFormat {
+    Csv,
+    Sparkline,
+}
+
+impl FromStr for WinProbabilityFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(WinProbabilityFormat::Csv),
+            "sparkline" => Ok(WinProbabilityFormat::Sparkline),
+            _ => Err(format!("invalid win probability format: {}", s)),
+        }
+    }
+}
+
+pub struct WinProbabilitySeries {
+    path: String,
+    steps: Vec<usize>,
+    values: Vec<Vec<f64>>,
+    actions: Vec<Action>,
+    decisions: Vec<Option<DecisionPoint>>,
+    moments: Vec<CriticalMoment>,
+}
+
+struct DecisionPoint {
+    tracker: CardsTracker,
+    view: GameView,
+}
+
+struct CriticalMoment {
+    index: usize,
+    player: usize,
+    swing: f64,
+    alternative: Option<Alternative>,
+}
+
+struct Alternative {
+    played: f64,
+    best: Action,
+    best_value: f64,
+}
+
+pub struct Rollouts {
+    pub bot_type: BotType,
+    pub number: usize,
+    pub max_steps: usize,
+    pub evaluator: Box<dyn Evaluator>,
+}
+
+#[derive(Default)]
+struct SeriesBuilder {
+    steps: Vec<usize>,
+    values: Vec<Vec<f64>>,
+    actions: Vec<Action>,
+    decisions: Vec<Option<DecisionPoint>>,
+}
+
+#[derive(Default)]
+pub struct AgreementStats {
+    games: usize,
+    seat_games: usize,
+    decisions: BTreeMap<&'static str, usize>,
+    agreement: BTreeMap<(BotType, &'static str), f64>,
+}
+
+struct Replay {
+    params: GameParams,
+    actions: Vec<Action>,
+}
+
+struct SeatRecord {
+    settings: Settings,
+    views: Vec<GameView>,
+    actions: Vec<Action>,
+}
+
+enum Recording {
+    Replay(Replay),
+    Seat(SeatRecord),
+}
+
+impl SeatRecord {
+    fn reconstruct(&self) -> Result<CardsTracker, String> {
+        let mut tracker = CardsTracker::from_view(&self.views[0].player_view(), &self.settings);
+        for (index, (action, view)) in self.actions.iter().zip(self.views[1..].iter()).enumerate() {
+            notify_tracker(&mut tracker, &view.player_view(), action);
+            if tracker.summary().states == 0 {
+                return Err(format!(
+                    "no consistent game state after action {}: {:?}",
+                    index, action
+                ));
+            }
+        }
+        Ok(tracker)
+    }
+}
+
+pub fn collect_agreement_stats(
+    dir: &str,
+    seed: u64,
+    bot_types: &[BotType],
+) -> Result<AgreementStats, String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read directory {}: {}", dir, e))?
+        .filter_map(|entry| entry.ok().map(|v| v.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+    let mut stats = AgreementStats::default();
+    for path in paths.iter() {
+        let path = path.to_string_lossy();
+        match read_recording(&path) {
+            Ok(Recording::Replay(replay)) => {
+                add_replay_agreement(&replay, seed, bot_types, &mut stats)
+                    .map_err(|e| format!("failed to analyze {}: {}", path, e))?
+            }
+            Ok(Recording::Seat(record)) => match record.reconstruct() {
+                Ok(..) => add_seat_agreement(&record, seed, bot_types, &mut stats)
+                    .map_err(|e| format!("failed to analyze {}: {}", path, e))?,
+                Err(e) => tracing::warn!(path = %path, error = %e, "skip seat record"),
+            },
+            Err(e) => tracing::warn!(path = %path, error = %e, "skip replay"),
+        }
+    }
+    Ok(stats)
+}
+
+pub fn collect_win_probabilities(
+    dir: &str,
+    seed: u64,
+    rollouts: &Rollouts,
+    moments: usize,
+) -> Result<Vec<WinProbabilitySeries>, String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read directory {}: {}", dir, e))?
+        .filter_map(|entry| entry.ok().map(|v| v.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut result = Vec::new();
+    for path in paths.iter() {
+        let path = path.to_string_lossy();
+        let (builder, settings) = match read_recording(&path) {
+            Ok(Recording::Replay(replay)) => (
+                get_replay_win_probabilities(&replay, rollouts, &mut rng),
+                replay.params.settings,
+            ),
+            Ok(Recording::Seat(record)) => (
+                get_seat_win_probabilities(&record, rollouts, &mut rng),
+                record.settings,
+            ),
+            Err(e) => {
+                tracing::warn!(path = %path, error = %e, "skip replay");
+                continue;
+            }
+        };
+        match builder {
+            Ok(builder) => {
+                let mut series = WinProbabilitySeries {
+                    path: path.to_string(),
+                    steps: builder.steps,
+                    values: builder.values,
+                    actions: builder.actions,
+                    decisions: builder.decisions,
+                    moments: Vec::new(),
+                };
+                series.moments =
+                    find_critical_moments(&series, moments, &settings, rollouts, &mut rng);
+                result.push(series);
+            }
+            Err(e) => tracing::warn!(path = %path, error = %e, "skip recording"),
+        }
+    }
+    Ok(result)
+}
+
+fn get_replay_win_probabilities(
+    replay: &Replay,
+    rollouts: &Rollouts,
+    rng: &mut StdRng,
+) -> Result<SeriesBuilder, String> {
+    let settings = &replay.params.settings;
+    let mut trackers: Vec<CardsTracker> = Vec::new();
+    let mut previous: Option<Game> = None;
+    let mut builder = SeriesBuilder::default();
+    Game::from_history_with(
+        settings.clone(),
+        replay.params.seed,
+        &replay.actions,
+        |game, action| {
+            let players = settings.players_number;
+            match (action, previous.as_ref()) {
+                (Some(action), Some(previous)) => {
+                    builder.actions.push(action.clone());
+                    builder.decisions.push(Some(DecisionPoint {
+                        tracker: trackers[action.player].clone(),
+                        view: GameView::from(&previous.get_player_view(action.player)),
+                    }));
+                    for (player, tracker) in trackers.iter_mut().enumerate() {
+                        if game.is_player_active(player) {
+                            notify_tracker(tracker, &game.get_player_view(player), action);
+                        }
+                    }
+                }
+                _ => {
+                    trackers = (0..players)
+                        .map(|player| {
+                            CardsTracker::from_view(&game.get_player_view(player), settings)
+                        })
+                        .collect();
+                }
+            }
+            builder.steps.push(game.step());
+            builder.values.push(
+                (0..players)
+                    .map(|player| {
+                        if !game.is_player_active(player) {
+                            0.0
+                        } else if game.is_done() {
+                            1.0
+                        } else {
+                            estimate_win_probabilities(
+                                &trackers[player],
+                                &game.get_player_view(player),
+                                settings,
+                                rollouts,
+                                rng,
+                            )[player]
+                        }
+                    })
+                    .collect(),
+            );
+            previous = Some(game.clone());
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(builder)
+}
+
+fn get_seat_win_probabilities(
+    record: &SeatRecord,
+    rollouts: &Rollouts,
+    rng: &mut StdRng,
+) -> Result<SeriesBuilder, String> {
+    let mut tracker = CardsTracker::from_view(&record.views[0].player_view(), &record.settings);
+    let mut builder = SeriesBuilder::default();
+    for (index, view) in record.views.iter().enumerate() {
+        let view = view.player_view();
+        if index > 0 {
+            let action = &record.actions[index - 1];
+            builder.actions.push(action.clone());
+            builder.decisions.push(if action.player == view.player {
+                Some(DecisionPoint {
+                    tracker: tracker.clone(),
+                    view: GameView::from(&record.views[index - 1].player_view()),
+                })
+            } else {
+                None
+            });
+            notify_tracker(&mut tracker, &view, action);
+            if tracker.summary().states == 0 {
+                return Err(format!(
+                    "no consistent game state after action {}: {:?}",
+                    index - 1,
+                    action
+                ));
+            }
+        }
+        let active: Vec<usize> = (0..view.player_hands.len())
+            .filter(|player| view.player_hands[*player] > 0)
+            .collect();
+        builder.steps.push(view.step);
+        builder.values.push(if active.len() <= 1 {
+            (0..view.player_hands.len())
+                .map(|player| if active.contains(&player) { 1.0 } else { 0.0 })
+                .collect()
+        } else {
+            estimate_win_probabilities(&tracker, &view, &record.settings, rollouts, rng)
+        });
+    }
+    Ok(builder)
+}
+
+fn estimate_win_probabilities(
+    tracker: &CardsTracker,
+    view: &PlayerView,
+    settings: &Settings,
+    rollouts: &Rollouts,
+    rng: &mut StdRng,
+) -> Vec<f64> {
+    let mut wins = vec![0.0; settings.players_number];
+    for _ in 0..rollouts.number {
+        let game = Game::from_full_state(tracker.sample_world(rng))
+            .with_progress(view.step, view.turn, view.round);
+        for (player, value) in rollout(game, settings, rollouts, rng).iter().enumerate() {
+            wins[player] += value;
+        }
+    }
+    wins.iter()
+        .map(|v| v / rollouts.number.max(1) as f64)
+        .collect()
+}
+
+fn estimate_action_win_probability(
+    decision: &DecisionPoint,
+    action: &Action,
+    settings: &Settings,
+    rollouts: &Rollouts,
+    rng: &mut StdRng,
+) -> f64 {
+    let view = decision.view.player_view();
+    let mut wins = 0.0;
+    for _ in 0..rollouts.number {
+        let mut game = Game::from_full_state(decision.tracker.sample_world(rng))
+            .with_progress(view.step, view.turn, view.round);
+        if game.play(action, rng).is_err() {
+            continue;
+        }
+        wins += rollout(game, settings, rollouts, rng)[action.player];
+    }
+    wins / rollouts.number.max(1) as f64
+}
+
+fn rollout(mut game: Game, settings: &Settings, rollouts: &Rollouts, rng: &mut StdRng) -> Vec<f64> {
+    let mut bots_rng = make_bots_rng(rng.gen());
+    let mut bots: Vec<Box<dyn Bot>> = (0..settings.players_number)
+        .map(|player| {
+            make_bot(
+                rollouts.bot_type,
+                &game.get_player_view(player),
+                settings,
+                &mut bots_rng,
+            )
+        })
+        .collect();
+    let max_steps = if rollouts.max_steps == 0 {
+        0
+    } else {
+        game.step() + rollouts.max_steps
+    };
+    run_game(
+        &mut bots,
+        &mut game,
+        rng,
+        false,
+        None,
+        max_steps,
+        TimeControl::default(),
+    );
+    match game.get_winner() {
+        Some(winner) => (0..settings.players_number)
+            .map(|player| if player == winner { 1.0 } else { 0.0 })
+            .collect(),
+        None => rollouts
+            .evaluator
+            .get_win_probabilities(&game.get_player_view(0)),
+    }
+}
+
+fn find_critical_moments(
+    series: &WinProbabilitySeries,
+    number: usize,
+    settings: &Settings,
+    rollouts: &Rollouts,
+    rng: &mut StdRng,
+) -> Vec<CriticalMoment> {
+    let mut moments: Vec<CriticalMoment> = series
+        .values
+        .windows(2)
+        .enumerate()
+        .map(|(index, values)| {
+            let (player, swing) = values[0]
+                .iter()
+                .zip(values[1].iter())
+                .map(|(before, after)| after - before)
+                .enumerate()
+                .max_by(|(_, l), (_, r)| l.abs().total_cmp(&r.abs()))
+                .unwrap();
+            CriticalMoment {
+                index,
+                player,
+                swing,
+                alternative: None,
+            }
+        })
+        .collect();
+    moments.sort_by(|l, r| r.swing.abs().total_cmp(&l.swing.abs()));
+    moments.truncate(number);
+    for moment in moments.iter_mut() {
+        let action = &series.actions[moment.index];
+        let decision = match &series.decisions[moment.index] {
+            Some(v) => v,
+            None => continue,
+        };
+        let view = decision.view.player_view();
+        let available_actions: Vec<Action> = available_actions_for(&view, action.player).collect();
+        if available_actions.len() <= 1 {
+            continue;
+        }
+        let values: Vec<f64> = available_actions
+            .iter()
+            .map(|v| estimate_action_win_probability(decision, v, settings, rollouts, rng))
+            .collect();
+        let (best, best_value) = available_actions
+            .iter()
+            .zip(values.iter())
+            .max_by(|(_, l), (_, r)| l.total_cmp(r))
+            .unwrap();
+        let played = available_actions
+            .iter()
+            .position(|v| v == action)
+            .map_or(0.0, |index| values[index]);
+        moment.alternative = Some(Alternative {
+            played,
+            best: best.clone(),
+            best_value: *best_value,
+        });
+    }
+    moments
+}
+
+pub fn print_win_probabilities(series: &[WinProbabilitySeries], format: WinProbabilityFormat) {
+    match format {
+        WinProbabilityFormat::Csv => {
+            println!("path,step,player,win_probability");
+            for v in series.iter() {
+                for (step, values) in v.steps.iter().zip(v.values.iter()) {
+                    for (player, value) in values.iter().enumerate() {
+                        println!("{},{},{},{:.4}", v.path, step, player, value);
+                    }
+                }
+            }
+        }
+        WinProbabilityFormat::Sparkline => {
+            for v in series.iter() {
+                println!("{}", v.path);
+                let players = v.values.first().map_or(0, |values| values.len());
+                for player in 0..players {
+                    let line: String = v
+                        .values
+                        .iter()
+                        .map(|values| get_sparkline_char(values[player]))
+                        .collect();
+                    println!(
+                        "{:>3} {} {:.3}",
+                        player,
+                        line,
+                        v.values.last().unwrap()[player]
+                    );
+                }
+                print_critical_moments(v, players);
+            }
+        }
+    }
+}
+
+fn print_critical_moments(series: &WinProbabilitySeries, players: usize) {
+    if series.moments.is_empty() {
+        return;
+    }
+    let player_names: Vec<String> = (0..players).map(|v| format!("{}", v)).collect();
+    println!("game summary:");
+    for moment in series.moments.iter() {
+        let action = &series.actions[moment.index];
+        print!(
+            "  step {}: {} changed player {} win probability by {:+.3}",
+            series.steps[moment.index],
+            to_game_command(action, &player_names),
+            moment.player,
+            moment.swing
+        );
+        match &moment.alternative {
+            Some(alternative) if alternative.best != *action => println!(
+                ", better: {} ({:.3} vs {:.3})",
+                to_game_command(&alternative.best, &player_names),
+                alternative.best_value,
+                alternative.played
+            ),
+            Some(alternative) => println!(", played the best action ({:.3})", alternative.played),
+            None => println!(),
+        }
+    }
+}
+
+fn get_sparkline_char(value: f64) -> char {
+    let index = (value * SPARKLINE.len() as f64).floor() as usize;
+    SPARKLINE[index.min(SPARKLINE.len() - 1)]
+}
+
+fn read_recording(path: &str) -> Result<Recording, InputError> {
+    let mut file = LineReader::new(open_file(path)?);
+    let header: serde_json::Value = file.read_required("game params or settings")?;
+    if header.get("seed").is_none() {
+        let settings: Settings =
+            serde_json::from_value(header).map_err(|e| file.invalid_error(e.to_string()))?;
+        settings.validate().map_err(|e| file.invalid_error(e))?;
+        let mut views = vec![file.read_required::<GameView>("initial player view")?];
+        let mut actions = Vec::new();
+        while let Some(action) = file.read::<Action>()? {
+            actions.push(action);
+            views.push(file.read_required("player view")?);
+        }
+        return Ok(Recording::Seat(SeatRecord {
+            settings,
+            views,
+            actions,
+        }));
+    }
+    let params: GameParams =
+        serde_json::from_value(header).map_err(|e| file.invalid_error(e.to_string()))?;
+    params.validate().map_err(|e| file.invalid_error(e))?;
+    let mut actions = Vec::new();
+    while let Some(action) = file.read::<Action>()? {
+        actions.push(action);
+    }
+    Ok(Recording::Replay(Replay { params, actions }))
+}
+
+fn add_decision_agreement(
+    view: &PlayerView,
+    action: &Action,
+    bot_types: &[BotType],
+    bots: &mut [Box<dyn Bot>],
+    stats: &mut AgreementStats,
+) {
+    let available_actions: Vec<Action> = available_actions_for(view, view.player).collect();
+    if available_actions.len() <= 1 {
+        return;
+    }
+    let name = get_action_type_name(&action.action_type);
+    *stats.decisions.entry(name).or_insert(0) += 1;
+    for (bot_type, bot) in bot_types.iter().zip(bots.iter_mut()) {
+        let suggested = bot.suggest_actions(view, &available_actions);
+        let score = if suggested.contains(&action) {
+            1.0 / suggested.len() as f64
+        } else {
+            0.0
+        };
+        *stats.agreement.entry((*bot_type, name)).or_insert(0.0) += score;
+    }
+}
+
+fn make_analyzed_bots(
+    seed: u64,
+    bot_types: &[BotType],
+    view: &PlayerView,
+    settings: &Settings,
+) -> Vec<Box<dyn Bot>> {
+    let mut bots_rng = make_bots_rng(seed);
+    let mut bots: Vec<Box<dyn Bot>> = bot_types
+        .iter()
+        .map(|bot_type| make_bot(*bot_type, view, settings, &mut bots_rng))
+        .collect();
+    for bot in bots.iter_mut() {
+        bot.on_game_start(settings, view.player);
+    }
+    bots
+}
+
+fn add_seat_agreement(
+    record: &SeatRecord,
+    seed: u64,
+    bot_types: &[BotType],
+    stats: &mut AgreementStats,
+) -> Result<(), String> {
+    let last = record.views.last().unwrap();
+    let player = last.player;
+    if last.player_hands.iter().filter(|hand| **hand > 0).count() > 1 {
+        return Err(String::from("game is not finished"));
+    }
+    if last.player_hands[player] == 0 {
+        return Ok(());
+    }
+    let mut bots = make_analyzed_bots(
+        seed,
+        bot_types,
+        &record.views[0].player_view(),
+        &record.settings,
+    );
+    for (action, views) in record.actions.iter().zip(record.views.windows(2)) {
+        if action.player == player {
+            add_decision_agreement(&views[0].player_view(), action, bot_types, &mut bots, stats);
+        }
+        if views[1].player_hands[player] > 0 {
+            let view = views[1].player_view();
+            for bot in bots.iter_mut() {
+                notify_bot(bot.as_mut(), &view, action);
+            }
+        }
+    }
+    stats.games += 1;
+    stats.seat_games += 1;
+    Ok(())
+}
+
+fn add_replay_agreement(
+    replay: &Replay,
+    seed: u64,
+    bot_types: &[BotType],
+    stats: &mut AgreementStats,
+) -> Result<(), String> {
+    let settings = &replay.params.settings;
+    let end = Game::from_history(settings.clone(), replay.params.seed, &replay.actions)
+        .map_err(|e| e.to_string())?;
+    let winner = match end.get_winner() {
+        Some(v) => v,
+        None => return Err(String::from("game is not finished")),
+    };
+    let mut rng = StdRng::seed_from_u64(replay.params.seed);
+    let mut game = Game::new(settings.clone(), &mut rng);
+    let mut bots = make_analyzed_bots(seed, bot_types, &game.get_player_view(winner), settings);
+    for action in replay.actions.iter() {
+        if action.player == winner {
+            add_decision_agreement(
+                &game.get_player_view(winner),
+                action,
+                bot_types,
+                &mut bots,
+                stats,
+            );
+        }
+        game.play(action, &mut rng)?;
+        if game.is_player_active(winner) {
+            let view = game.get_player_view(winner);
+            for bot in bots.iter_mut() {
+                notify_bot(bot.as_mut(), &view, action);
+            }
+        }
+    }
+    stats.games += 1;
+    Ok(())
+}
+
+pub fn print_agreement_stats(stats: &AgreementStats, bot_types: &[BotType]) {
+    println!("games: {}", stats.games);
+    println!("seat_games: {}", stats.seat_games);
+    print!("{:<24}", "bot_type");
+    for name in stats.decisions.keys() {
+        print!(" {:>18}", name);
+    }
+    println!(" {:>18}", "total");
+    print!("{:<24}", "decisions");
+    for decisions in stats.decisions.values() {
+        print!(" {:>18}", decisions);
+    }
+    println!(" {:>18}", stats.decisions.values().sum::<usize>());
+    for bot_type in bot_types.iter() {
+        print!("{:<24}", format!("{:?}", bot_type));
+        let mut total = 0.0;
+        for (name, decisions) in stats.decisions.iter() {
+            let agreement = stats
+                .agreement
+                .get(&(*bot_type, *name))
+                .cloned()
+                .unwrap_or(0.0);
+            total += agreement;
+            print!(" {:>18.3}", agreement / *decisions as f64);
+        }
+        println!(
+            " {:>18.3}",
+            total / stats.decisions.values().sum::<usize>().max(1) as f64
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::LinearEvaluator;
+    use crate::fsm::Rules;
+    use crate::game::{CardCounts, Decision};
+    use crate::run::{run_game_with_bots, TimeControl};
+
+    use super::*;
+
+    #[test]
+    fn seat_record_should_be_reconstructed_and_analyzed() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let bot_types = [BotType::HonestCarefulRandom, BotType::Random];
+        let result = run_game_with_bots(
+            42,
+            &bot_types,
+            settings.clone(),
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        let winner = result.end.get_winner().unwrap();
+        let actions: Vec<Action> = result
+            .end
+            .decisions()
+            .filter_map(|decision| match decision {
+                Decision::Play(action) => Some(action.clone()),
+                Decision::Forfeit(..) => None,
+            })
+            .collect();
+        let mut lines = vec![serde_json::to_string(&settings).unwrap()];
+        Game::from_history_with(settings, 42, &actions, |game, action| {
+            if let Some(action) = action {
+                lines.push(serde_json::to_string(action).unwrap());
+            }
+            lines.push(serde_json::to_string(&game.get_player_view(winner)).unwrap());
+        })
+        .unwrap();
+        let dir = std::env::temp_dir().join(format!("coup-analyze-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("seat.jsonl");
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let recording = read_recording(&path.to_string_lossy());
+        let stats = collect_agreement_stats(&dir.to_string_lossy(), 1, &bot_types);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let record = match recording.unwrap() {
+            Recording::Seat(v) => v,
+            Recording::Replay(..) => panic!("expected seat record"),
+        };
+        assert_eq!(record.views.len(), actions.len() + 1);
+        assert!(record.reconstruct().unwrap().summary().states > 0);
+        let stats = stats.unwrap();
+        assert_eq!(stats.games, 1);
+        assert_eq!(stats.seat_games, 1);
+        assert!(!stats.decisions.is_empty());
+    }
+
+    #[test]
+    fn win_probabilities_should_be_estimated_for_each_step() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let result = run_game_with_bots(
+            7,
+            &[BotType::Random, BotType::Random],
+            settings.clone(),
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        let winner = result.end.get_winner().unwrap();
+        let mut lines = vec![serde_json::to_string(&GameParams {
+            seed: 7,
+            seed_phrase: None,
+            settings,
+        })
+        .unwrap()];
+        for decision in result.end.decisions() {
+            if let Decision::Play(action) = decision {
+                lines.push(serde_json::to_string(action).unwrap());
+            }
+        }
+        let dir = std::env::temp_dir().join(format!("coup-win-probability-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("replay.jsonl"), lines.join("\n")).unwrap();
+        let rollouts = Rollouts {
+            bot_type: BotType::Random,
+            number: 4,
+            max_steps: 0,
+            evaluator: Box::new(LinearEvaluator::default()),
+        };
+        let series = collect_win_probabilities(&dir.to_string_lossy(), 1, &rollouts, 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let series = series.unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].steps.len(), lines.len());
+        assert_eq!(series[0].steps.len(), series[0].values.len());
+        assert!(series[0]
+            .values
+            .iter()
+            .flatten()
+            .all(|v| (0.0..=1.0).contains(v)));
+        let last = series[0].values.last().unwrap();
+        assert_eq!(last[winner], 1.0);
+        assert_eq!(last[1 - winner], 0.0);
+        assert_eq!(series[0].actions.len(), lines.len() - 1);
+        assert_eq!(series[0].moments.len(), 2);
+        assert!(series[0].moments[0].swing.abs() >= series[0].moments[1].swing.abs());
+        assert!(series[0]
+            .moments
+            .iter()
+            .all(|v| v.alternative.is_none() || series[0].decisions[v.index].is_some()));
+        assert_eq!(get_sparkline_char(0.0), '▁');
+        assert_eq!(get_sparkline_char(1.0), '█');
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Decision, Settings};
+use crate::run::{get_action_type_name, BotType, RunResult};
+
+const GAMES_FILE: &str = "games";
+const INDEX_FILE: &str = "index.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub settings: Settings,
+    pub bot_types: Vec<BotType>,
+    pub decisions: Vec<Decision>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub id: usize,
+    pub seed: u64,
+    pub bot_types: Vec<BotType>,
+    pub winner: usize,
+    pub winner_bot_type: BotType,
+    pub rounds: usize,
+    pub turns: usize,
+    pub steps: usize,
+    pub action_types: BTreeSet<String>,
+    pub offset: u64,
+    pub length: u64,
+    pub compressed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchivedGame {
+    record: GameRecord,
+    entry: ArchiveEntry,
+}
+
+impl ArchivedGame {
+    pub fn new(seed: u64, bot_types: &[BotType], result: &RunResult) -> Self {
+        let winner = result.end.get_winner().unwrap();
+        let action_types = result
+            .end
+            .decisions()
+            .filter_map(|decision| match decision {
+                Decision::Play(action) => {
+                    Some(String::from(get_action_type_name(&action.action_type)))
+                }
+                Decision::Forfeit(..) => None,
+            })
+            .collect();
+        Self {
+            record: GameRecord {
+                seed,
+                settings: result.begin.get_settings(),
+                bot_types: bot_types.to_vec(),
+                decisions: result.end.decisions().cloned().collect(),
+            },
+            entry: ArchiveEntry {
+                id: 0,
+                seed,
+                bot_types: bot_types.to_vec(),
+                winner,
+                winner_bot_type: bot_types[winner],
+                rounds: result.end.round(),
+                turns: result.end.turn(),
+                steps: result.end.step(),
+                action_types,
+                offset: 0,
+                length: 0,
+                compressed: false,
+            },
+        }
+    }
+}
+
+pub struct Archive {
+    games: File,
+    index: File,
+    compress: bool,
+    offset: u64,
+    next_id: usize,
+    keys: HashSet<(u64, Vec<BotType>)>,
+}
+
+impl Archive {
+    pub fn open(dir: &str, compress: bool) -> Result<Self, String> {
+        if compress && !cfg!(feature = "zstd") {
+            return Err(String::from(
+                "archive compression requires building with zstd feature",
+            ));
+        }
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("failed to create directory {}: {}", dir, e))?;
+        let entries = read_index(dir)?;
+        let games_path = Path::new(dir).join(GAMES_FILE);
+        let games = open_append(&games_path)?;
+        let offset = games
+            .metadata()
+            .map_err(|e| format!("failed to read {}: {}", games_path.display(), e))?
+            .len();
+        Ok(Self {
+            games,
+            index: open_append(&Path::new(dir).join(INDEX_FILE))?,
+            compress,
+            offset,
+            next_id: entries.iter().map(|v| v.id + 1).max().unwrap_or(0),
+            keys: entries.into_iter().map(|v| (v.seed, v.bot_types)).collect(),
+        })
+    }
+
+    pub fn add(&mut self, game: ArchivedGame) -> Result<Option<usize>, String> {
+        let ArchivedGame { record, mut entry } = game;
+        if !self.keys.insert((record.seed, record.bot_types.clone())) {
+            return Ok(None);
+        }
+        let mut data = serde_json::to_vec(&record).unwrap();
+        data.push(b'\n');
+        if self.compress {
+            data = compress(&data)?;
+        }
+        self.games
+            .write_all(&data)
+            .map_err(|e| format!("failed to write archived game: {}", e))?;
+        entry.id = self.next_id;
+        entry.offset = self.offset;
+        entry.length = data.len() as u64;
+        entry.compressed = self.compress;
+        let mut line = serde_json::to_vec(&entry).unwrap();
+        line.push(b'\n');
+        self.index
+            .write_all(&line)
+            .map_err(|e| format!("failed to write archive index: {}", e))?;
+        self.offset += entry.length;
+        self.next_id += 1;
+        Ok(Some(entry.id))
+    }
+}
+
+pub fn read_index(dir: &str) -> Result<Vec<ArchiveEntry>, String> {
+    let path = Path::new(dir).join(INDEX_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file =
+        File::open(&path).map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+    let mut entries = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        entries.push(serde_json::from_str(&line).map_err(|e| {
+            format!(
+                "failed to parse {} line {}: {}",
+                path.display(),
+                number + 1,
+                e
+            )
+        })?);
+    }
+    Ok(entries)
+}
+
+pub fn read_game(dir: &str, entry: &ArchiveEntry) -> Result<GameRecord, String> {
+    let path: PathBuf = Path::new(dir).join(GAMES_FILE);
+    let mut file =
+        File::open(&path).map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+    let mut data = vec![0; entry.length as usize];
+    file.seek(SeekFrom::Start(entry.offset))
+        .and_then(|_| file.read_exact(&mut data))
+        .map_err(|e| {
+            format!(
+                "failed to read game {} from {}: {}",
+                entry.id,
+                path.display(),
+                e
+            )
+        })?;
+    if entry.compressed {
+        data = decompress(&data)?;
+    }
+    serde_json::from_slice(&data).map_err(|e| format!("failed to parse game {}: {}", entry.id, e))
+}
+
+const ACTION_TYPE_NAMES: [&str; 18] = [
+    "Income",
+    "ForeignAid",
+    "Coup",
+    "Tax",
+    "Assassinate",
+    "Exchange",
+    "Steal",
+    "BlockForeignAid",
+    "BlockAssassination",
+    "BlockSteal",
+    "PassChallenge",
+    "PassBlock",
+    "Challenge",
+    "ShowCard",
+    "RevealCard",
+    "TakeCard",
+    "ShuffleDeck",
+    "DropCard",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum QueryField {
+    Id,
+    Winner,
+    Rounds,
+    Turns,
+    Steps,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum QueryOperator {
+    Equal,
+    Less,
+    Greater,
+}
+
+#[derive(Debug, Clone)]
+enum QueryCondition {
+    Field {
+        field: QueryField,
+        operator: QueryOperator,
+        value: usize,
+    },
+    WinnerBot(BotType),
+    Bot(BotType),
+    Contains(String),
+}
+
+impl QueryCondition {
+    fn matches(&self, entry: &ArchiveEntry) -> bool {
+        match self {
+            QueryCondition::Field {
+                field,
+                operator,
+                value,
+            } => {
+                let entry_value = match field {
+                    QueryField::Id => entry.id,
+                    QueryField::Winner => entry.winner,
+                    QueryField::Rounds => entry.rounds,
+                    QueryField::Turns => entry.turns,
+                    QueryField::Steps => entry.steps,
+                };
+                match operator {
+                    QueryOperator::Equal => entry_value == *value,
+                    QueryOperator::Less => entry_value < *value,
+                    QueryOperator::Greater => entry_value > *value,
+                }
+            }
+            QueryCondition::WinnerBot(bot_type) => entry.winner_bot_type == *bot_type,
+            QueryCondition::Bot(bot_type) => entry.bot_types.contains(bot_type),
+            QueryCondition::Contains(action_type) => entry.action_types.contains(action_type),
+        }
+    }
+}
+
+impl FromStr for QueryCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(action_type) = s
+            .strip_prefix("contains(")
+            .and_then(|v| v.strip_suffix(')'))
+        {
+            if !ACTION_TYPE_NAMES.contains(&action_type) {
+                return Err(format!(
+                    "invalid action type {}, expected one of: {}",
+                    action_type,
+                    ACTION_TYPE_NAMES.join(", ")
+                ));
+            }
+            return Ok(QueryCondition::Contains(String::from(action_type)));
+        }
+        let (position, operator) = s
+            .char_indices()
+            .find_map(|(i, c)| match c {
+                '=' => Some((i, QueryOperator::Equal)),
+                '<' => Some((i, QueryOperator::Less)),
+                '>' => Some((i, QueryOperator::Greater)),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                format!(
+                    "invalid condition, expected <field>(=|<|>)<value> or contains(<action>): {}",
+                    s
+                )
+            })?;
+        let name = &s[..position];
+        let value = &s[position + 1..];
+        let field = match name {
+            "winner_bot" | "bot" => {
+                if !matches!(operator, QueryOperator::Equal) {
+                    return Err(format!("only = is supported for {}: {}", name, s));
+                }
+                let bot_type = BotType::from_str(value)?;
+                return Ok(if name == "bot" {
+                    QueryCondition::Bot(bot_type)
+                } else {
+                    QueryCondition::WinnerBot(bot_type)
+                });
+            }
+            "id" => QueryField::Id,
+            "winner" => QueryField::Winner,
+            "rounds" => QueryField::Rounds,
+            "turns" => QueryField::Turns,
+            "steps" => QueryField::Steps,
+            v => return Err(format!("invalid query field: {}", v)),
+        };
+        let value = value
+            .parse::<usize>()
+            .map_err(|e| format!("invalid query value {}: {}", value, e))?;
+        Ok(QueryCondition::Field {
+            field,
+            operator,
+            value,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveQuery {
+    conditions: Vec<QueryCondition>,
+}
+
+impl ArchiveQuery {
+    pub fn matches(&self, entry: &ArchiveEntry) -> bool {
+        self.conditions.iter().all(|v| v.matches(entry))
+    }
+}
+
+impl FromStr for ArchiveQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(Self {
+                conditions: Vec::new(),
+            });
+        }
+        Ok(Self {
+            conditions: s
+                .split(" AND ")
+                .map(|v| QueryCondition::from_str(v.trim()))
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+fn open_append(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("failed to open {}: {}", path.display(), e))
+}
+
+#[cfg(feature = "zstd")]
+fn compress(data: &[u8]) -> Result<Vec<u8>, String> {
+    zstd::encode_all(data, 0).map_err(|e| format!("failed to compress game: {}", e))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn compress(_: &[u8]) -> Result<Vec<u8>, String> {
+    Err(String::from(
+        "archive compression requires building with zstd feature",
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    zstd::decode_all(data).map_err(|e| format!("failed to decompress game: {}", e))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress(_: &[u8]) -> Result<Vec<u8>, String> {
+    Err(String::from(
+        "archive is compressed, build with zstd feature to read it",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+    use crate::run::{run_game_with_bots, TimeControl};
+
+    use super::*;
+
+    fn make_game(seed: u64) -> ArchivedGame {
+        let bot_types = [BotType::Random, BotType::HonestCarefulRandom];
+        let result = run_game_with_bots(
+            seed,
+            &bot_types,
+            Settings {
+                players_number: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        ArchivedGame::new(seed, &bot_types, &result)
+    }
+
+    #[test]
+    fn archive_should_skip_duplicates_and_read_games_by_index() {
+        let dir = std::env::temp_dir()
+            .join(format!("coup-archive-{}", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        {
+            let mut archive = Archive::open(&dir, false).unwrap();
+            assert_eq!(archive.add(make_game(1)).unwrap(), Some(0));
+            assert_eq!(archive.add(make_game(2)).unwrap(), Some(1));
+            assert_eq!(archive.add(make_game(1)).unwrap(), None);
+        }
+        let mut archive = Archive::open(&dir, false).unwrap();
+        assert_eq!(archive.add(make_game(2)).unwrap(), None);
+        assert_eq!(archive.add(make_game(3)).unwrap(), Some(2));
+        let entries = read_index(&dir).unwrap();
+        assert_eq!(entries.len(), 3);
+        let record = read_game(&dir, &entries[1]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(record.seed, 2);
+        assert_eq!(record.decisions.len(), entries[1].steps);
+    }
+
+    fn make_entry(winner_bot_type: BotType, rounds: usize, action_types: &[&str]) -> ArchiveEntry {
+        ArchiveEntry {
+            id: 0,
+            seed: 42,
+            bot_types: vec![BotType::Random, BotType::HonestCarefulRandom],
+            winner: 0,
+            winner_bot_type,
+            rounds,
+            turns: rounds * 2,
+            steps: rounds * 5,
+            action_types: action_types.iter().map(|v| String::from(*v)).collect(),
+            offset: 0,
+            length: 0,
+            compressed: false,
+        }
+    }
+
+    #[test]
+    fn archive_query_should_match_all_conditions() {
+        let query = ArchiveQuery::from_str(
+            "winner_bot=honest_careful_random AND rounds<6 AND contains(Assassinate)",
+        )
+        .unwrap();
+        assert!(query.matches(&make_entry(
+            BotType::HonestCarefulRandom,
+            5,
+            &["Assassinate", "Income"]
+        )));
+        assert!(!query.matches(&make_entry(BotType::Random, 5, &["Assassinate"])));
+        assert!(!query.matches(&make_entry(
+            BotType::HonestCarefulRandom,
+            6,
+            &["Assassinate"]
+        )));
+        assert!(!query.matches(&make_entry(BotType::HonestCarefulRandom, 5, &["Tax"])));
+        assert!(ArchiveQuery::from_str("contains(Assasinate)").is_err());
+        assert!(ArchiveQuery::from_str("winner_bot<random").is_err());
+        assert!(ArchiveQuery::from_str("rounds~6").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::game::Settings;
+use crate::run::{run_game_with_bots, BotType, TimeControl};
+
+const INITIAL_RATING: f64 = 1500.0;
+const RATING_K: f64 = 32.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaDb {
+    seed: u64,
+    settings: Settings,
+    games: usize,
+    ratings: BTreeMap<BotType, Rating>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rating {
+    rating: f64,
+    games: usize,
+    wins: usize,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            games: 0,
+            wins: 0,
+        }
+    }
+}
+
+impl ArenaDb {
+    pub fn new(seed: u64, settings: Settings) -> Self {
+        Self {
+            seed,
+            settings,
+            games: 0,
+            ratings: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read arena db from {}: {}", path, e))
+    }
+
+    pub fn load_or_new(path: &str, seed: u64, settings: Settings) -> Result<Self, String> {
+        if !Path::new(path).exists() {
+            return Ok(Self::new(seed, settings));
+        }
+        Self::resume(path, settings)
+    }
+
+    pub fn resume(path: &str, settings: Settings) -> Result<Self, String> {
+        let db = Self::load(path)?;
+        if db.settings != settings {
+            return Err(format!(
+                "arena db {} has different settings: {:?}",
+                path, db.settings
+            ));
+        }
+        Ok(db)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let tmp_path = format!("{}.tmp", path);
+        let file =
+            File::create(&tmp_path).map_err(|e| format!("failed to create {}: {}", tmp_path, e))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|e| format!("failed to write arena db to {}: {}", tmp_path, e))?;
+        std::fs::rename(&tmp_path, path)
+            .map_err(|e| format!("failed to rename {} to {}: {}", tmp_path, path, e))
+    }
+
+    pub fn register(&mut self, bot_type: BotType) {
+        self.ratings.entry(bot_type).or_default();
+    }
+
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    pub fn play_match(&mut self, time_control: TimeControl) {
+        let registered: Vec<BotType> = self.ratings.keys().cloned().collect();
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.games as u64));
+        let bot_types: Vec<BotType> = (0..self.settings.players_number)
+            .map(|_| *registered.choose(&mut rng).unwrap())
+            .collect();
+        let result = run_game_with_bots(
+            rng.gen(),
+            &bot_types,
+            self.settings.clone(),
+            false,
+            None,
+            0,
+            time_control,
+        );
+        self.add_result(&bot_types, result.end.get_winner().unwrap());
+    }
+
+    fn add_result(&mut self, bot_types: &[BotType], winner: usize) {
+        let ratings: Vec<f64> = bot_types
+            .iter()
+            .map(|bot_type| self.ratings.entry(*bot_type).or_default().rating)
+            .collect();
+        let mut deltas = vec![0.0; bot_types.len()];
+        let k = RATING_K / (bot_types.len() - 1) as f64;
+        for loser in 0..bot_types.len() {
+            if loser == winner || bot_types[loser] == bot_types[winner] {
+                continue;
+            }
+            let expected = 1.0 / (1.0 + 10f64.powf((ratings[loser] - ratings[winner]) / 400.0));
+            let delta = k * (1.0 - expected);
+            deltas[winner] += delta;
+            deltas[loser] -= delta;
+        }
+        for (player, bot_type) in bot_types.iter().enumerate() {
+            self.ratings.get_mut(bot_type).unwrap().rating += deltas[player];
+        }
+        // A bot type taking several seats plays and wins the match once.
+        for bot_type in bot_types.iter().unique() {
+            self.ratings.get_mut(bot_type).unwrap().games += 1;
+        }
+        self.ratings.get_mut(&bot_types[winner]).unwrap().wins += 1;
+        self.games += 1;
+    }
+
+    pub fn print_leaderboard(&self) {
+        let mut ratings: Vec<(&BotType, &Rating)> = self.ratings.iter().collect();
+        ratings.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+        println!("games: {}", self.games);
+        for (place, (bot_type, rating)) in ratings.iter().enumerate() {
+            println!(
+                "{} {:?} rating={:.1} games={} wins={} win_rate={:.3}",
+                place + 1,
+                bot_type,
+                rating.rating,
+                rating.games,
+                rating.wins,
+                rating.wins as f64 / rating.games.max(1) as f64
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    fn make_db() -> ArenaDb {
+        let mut db = ArenaDb::new(
+            42,
+            Settings {
+                players_number: 3,
+                card_counts: CardCounts::uniform(3),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+        );
+        db.register(BotType::Random);
+        db.register(BotType::HonestCarefulRandom);
+        db
+    }
+
+    #[test]
+    fn add_result_should_move_rating_from_losers_to_winner() {
+        let mut db = make_db();
+        db.add_result(
+            &[
+                BotType::Random,
+                BotType::HonestCarefulRandom,
+                BotType::HonestCarefulRandom,
+            ],
+            0,
+        );
+        let random = &db.ratings[&BotType::Random];
+        let honest = &db.ratings[&BotType::HonestCarefulRandom];
+        assert_eq!(db.games, 1);
+        assert_eq!((random.games, random.wins), (1, 1));
+        assert_eq!((honest.games, honest.wins), (1, 0));
+        assert!(random.rating > INITIAL_RATING);
+        assert!((random.rating + honest.rating - 2.0 * INITIAL_RATING).abs() < 1e-9);
+    }
+
+    #[test]
+    fn add_result_should_count_match_once_per_bot_type() {
+        let mut db = make_db();
+        let bot_types = [
+            BotType::HonestCarefulRandom,
+            BotType::Random,
+            BotType::HonestCarefulRandom,
+        ];
+        db.add_result(&bot_types, 2);
+        db.add_result(&bot_types, 0);
+        db.add_result(&bot_types, 1);
+        let random = &db.ratings[&BotType::Random];
+        let honest = &db.ratings[&BotType::HonestCarefulRandom];
+        assert_eq!(db.games, 3);
+        assert_eq!((random.games, random.wins), (3, 1));
+        assert_eq!((honest.games, honest.wins), (3, 2));
+    }
+
+    #[test]
+    fn play_match_should_be_reproducible() {
+        let mut a = make_db();
+        let mut b = make_db();
+        for _ in 0..10 {
+            a.play_match(TimeControl::default());
+            b.play_match(TimeControl::default());
+        }
+        assert_eq!(
+            serde_json::to_string(&a).unwrap(),
+            serde_json::to_string(&b).unwrap()
+        );
+    }
+}
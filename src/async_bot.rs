@@ -0,0 +1,402 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use futures_util::future::join_all;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tokio::task::LocalSet;
+
+use crate::bots::{notify_bot, Bot};
+use crate::fsm::{resolve_responses, Action};
+use crate::game::{get_available_actions, ActionsIterator, Decision, Game, PlayerView, Settings};
+use crate::run::{get_responders, make_bots, BotType};
+
+pub type BotFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+pub trait AsyncBot {
+    fn get_action<'a>(
+        &'a mut self,
+        view: &'a PlayerView<'a>,
+        available_actions: &'a [Action],
+    ) -> BotFuture<'a, Action>;
+
+    fn get_optional_action<'a>(
+        &'a mut self,
+        view: &'a PlayerView<'a>,
+        available_actions: &'a [Action],
+    ) -> BotFuture<'a, Option<Action>>;
+
+    fn after_action(&mut self, view: &PlayerView, action: &Action);
+
+    fn after_forfeit(&mut self, view: &PlayerView, player: usize);
+
+    fn on_game_start(&mut self, settings: &Settings, player: usize);
+}
+
+// Yields before calling the bot to let other games make progress, the call itself still blocks
+// the runtime thread.
+pub struct SyncBot<B>(pub B);
+
+impl<B: AsMut<dyn Bot>> AsyncBot for SyncBot<B> {
+    fn get_action<'a>(
+        &'a mut self,
+        view: &'a PlayerView<'a>,
+        available_actions: &'a [Action],
+    ) -> BotFuture<'a, Action> {
+        Box::pin(async move {
+            tokio::task::yield_now().await;
+            self.0.as_mut().get_action(view, available_actions)
+        })
+    }
+
+    fn get_optional_action<'a>(
+        &'a mut self,
+        view: &'a PlayerView<'a>,
+        available_actions: &'a [Action],
+    ) -> BotFuture<'a, Option<Action>> {
+        Box::pin(async move {
+            tokio::task::yield_now().await;
+            self.0.as_mut().get_optional_action(view, available_actions)
+        })
+    }
+
+    fn after_action(&mut self, view: &PlayerView, action: &Action) {
+        notify_bot(self.0.as_mut(), view, action);
+    }
+
+    fn after_forfeit(&mut self, view: &PlayerView, player: usize) {
+        self.0.as_mut().after_forfeit(view, player);
+    }
+
+    fn on_game_start(&mut self, settings: &Settings, player: usize) {
+        self.0.as_mut().on_game_start(settings, player);
+    }
+}
+
+pub async fn run_async_game(bots: &mut [Box<dyn AsyncBot>], game: &mut Game, rng: &mut StdRng) {
+    let settings = game.get_settings();
+    for (player, bot) in bots.iter_mut().enumerate() {
+        bot.on_game_start(&settings, player);
+    }
+    while !game.is_done() {
+        let view = game.get_anonymous_view();
+        let available_actions = get_available_actions(
+            view.state_type,
+            view.player_coins,
+            view.player_hands,
+            view.rules,
+        );
+        let action = match get_async_decision(&available_actions, bots, game, rng).await {
+            Decision::Play(action) => action,
+            Decision::Forfeit(player) => {
+                forfeit(bots, game, player);
+                continue;
+            }
+        };
+        if let Err(e) = game.play(&action, rng) {
+            tracing::warn!(player = action.player, action = ?action.action_type, error = %e, "async bot played illegal action");
+            forfeit(bots, game, action.player);
+            continue;
+        }
+        for (player, bot) in bots.iter_mut().enumerate() {
+            if game.is_player_active(player) {
+                bot.after_action(&game.get_player_view(player), &action);
+            }
+        }
+    }
+}
+
+fn forfeit(bots: &mut [Box<dyn AsyncBot>], game: &mut Game, player: usize) {
+    game.forfeit(player);
+    for (index, bot) in bots.iter_mut().enumerate() {
+        if game.is_player_active(index) {
+            bot.after_forfeit(&game.get_player_view(index), player);
+        }
+    }
+}
+
+async fn get_async_decision(
+    available_actions: &[Action],
+    bots: &mut [Box<dyn AsyncBot>],
+    game: &Game,
+    rng: &mut StdRng,
+) -> Decision {
+    let (required, players) = get_responders(available_actions);
+    let player_actions: Vec<Vec<Action>> = (0..bots.len())
+        .map(|player| {
+            available_actions
+                .iter()
+                .cloned()
+                .of_player(player)
+                .collect()
+        })
+        .collect();
+    let views: Vec<PlayerView> = (0..bots.len())
+        .map(|player| game.get_player_view(player))
+        .collect();
+    let priority = game.get_anonymous_view().rules.response_priority;
+    let views = &views;
+    let player_actions = &player_actions;
+    let futures: Vec<_> = bots
+        .iter_mut()
+        .enumerate()
+        .filter(|(player, _)| players.contains(player))
+        .map(|(player, bot)| async move {
+            let response = bot
+                .get_optional_action(&views[player], &player_actions[player])
+                .await;
+            (player, response)
+        })
+        .collect();
+    let mut results = join_all(futures).await;
+    results.sort_by_key(|(player, _)| players.iter().position(|v| v == player));
+    let mut responses = Vec::new();
+    for (player, response) in results {
+        if let Some(action) = response {
+            if !player_actions[player].contains(&action) {
+                return Decision::Forfeit(player);
+            }
+            responses.push(action);
+        }
+    }
+    if let Some(action) = resolve_responses(&responses, required, bots.len(), priority, rng) {
+        return Decision::Play(action.clone());
+    }
+    let action = bots[required]
+        .get_action(&views[required], &player_actions[required])
+        .await;
+    if !player_actions[required].contains(&action) {
+        return Decision::Forfeit(required);
+    }
+    Decision::Play(action)
+}
+
+pub fn play_async_games(seeds: &[u64], bot_types: &[BotType], settings: &Settings) -> Vec<Game> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    LocalSet::new().block_on(&runtime, async {
+        let games: Vec<_> = seeds
+            .iter()
+            .map(|seed| {
+                let seed = *seed;
+                let bot_types = bot_types.to_vec();
+                let settings = settings.clone();
+                tokio::task::spawn_local(async move {
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    let mut game = Game::new(settings, &mut rng);
+                    let mut bots: Vec<Box<dyn AsyncBot>> = make_bots(seed, &bot_types, &game, None)
+                        .into_iter()
+                        .map(|bot| Box::new(SyncBot(bot)) as Box<dyn AsyncBot>)
+                        .collect();
+                    run_async_game(&mut bots, &mut game, &mut rng).await;
+                    game
+                })
+            })
+            .collect();
+        let mut result = Vec::with_capacity(games.len());
+        for game in games {
+            result.push(game.await.unwrap());
+        }
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bots::{is_allowed_action_type, ActionView};
+    use crate::fsm::{ActionType, ResponsePriority, Rules};
+    use crate::game::CardCounts;
+    use crate::run::{run_game, TimeControl};
+
+    use super::*;
+
+    struct IllegalBot;
+
+    impl AsyncBot for IllegalBot {
+        fn get_action<'a>(
+            &'a mut self,
+            view: &'a PlayerView<'a>,
+            _: &'a [Action],
+        ) -> BotFuture<'a, Action> {
+            Box::pin(std::future::ready(Action {
+                player: view.player,
+                action_type: ActionType::Coup(0),
+            }))
+        }
+
+        fn get_optional_action<'a>(
+            &'a mut self,
+            _: &'a PlayerView<'a>,
+            _: &'a [Action],
+        ) -> BotFuture<'a, Option<Action>> {
+            Box::pin(std::future::ready(None))
+        }
+
+        fn after_action(&mut self, _: &PlayerView, _: &Action) {}
+
+        fn after_forfeit(&mut self, _: &PlayerView, _: usize) {}
+
+        fn on_game_start(&mut self, _: &Settings, _: usize) {}
+    }
+
+    struct ChallengeBot {
+        claim: bool,
+        challenge: bool,
+    }
+
+    impl Bot for ChallengeBot {
+        fn suggest_actions<'a>(
+            &mut self,
+            _: &PlayerView,
+            available_actions: &'a [Action],
+        ) -> Vec<&'a Action> {
+            available_actions.iter().collect()
+        }
+
+        fn suggest_optional_actions<'a>(
+            &mut self,
+            _: &PlayerView,
+            available_actions: &'a [Action],
+        ) -> Vec<&'a Action> {
+            available_actions.iter().collect()
+        }
+
+        fn get_action(&mut self, view: &PlayerView, available_actions: &[Action]) -> Action {
+            let tax = available_actions
+                .iter()
+                .find(|v| self.claim && v.action_type == ActionType::Tax);
+            tax.or_else(|| {
+                available_actions
+                    .iter()
+                    .find(|v| is_allowed_action_type(&v.action_type, view.cards))
+            })
+            .unwrap()
+            .clone()
+        }
+
+        fn get_optional_action(
+            &mut self,
+            _: &PlayerView,
+            available_actions: &[Action],
+        ) -> Option<Action> {
+            available_actions
+                .iter()
+                .find(|v| self.challenge && v.action_type == ActionType::Challenge)
+                .cloned()
+        }
+
+        fn after_player_action(&mut self, _: &PlayerView, _: &Action) {}
+
+        fn after_opponent_action(&mut self, _: &PlayerView, _: &ActionView) {}
+
+        fn query(&self, _: &str) {}
+    }
+
+    fn make_challenge_bots() -> Vec<Box<dyn Bot>> {
+        [(false, true), (false, false), (true, false), (false, true)]
+            .iter()
+            .map(|(claim, challenge)| {
+                Box::new(ChallengeBot {
+                    claim: *claim,
+                    challenge: *challenge,
+                }) as Box<dyn Bot>
+            })
+            .collect()
+    }
+
+    fn get_settings() -> Settings {
+        Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        }
+    }
+
+    #[test]
+    fn play_async_games_should_finish_all_games_deterministically() {
+        let settings = get_settings();
+        let bot_types = [
+            BotType::Random,
+            BotType::HonestCarefulRandom,
+            BotType::Random,
+        ];
+        let seeds: Vec<u64> = (0..8).collect();
+        let games = play_async_games(&seeds, &bot_types, &settings);
+        assert_eq!(games.len(), seeds.len());
+        assert!(games.iter().all(|v| v.get_winner().is_some()));
+        assert_eq!(games, play_async_games(&seeds, &bot_types, &settings));
+    }
+
+    #[test]
+    fn runners_should_resolve_responses_by_same_priority() {
+        for (priority, challenger) in [
+            (ResponsePriority::Seat, 0),
+            (ResponsePriority::TurnOrder, 3),
+        ] {
+            let settings = Settings {
+                players_number: 4,
+                rules: Rules {
+                    response_priority: priority,
+                    ..Rules::default()
+                },
+                ..get_settings()
+            };
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut sync_game = Game::new(settings, &mut rng);
+            let mut async_game = sync_game.clone();
+            let mut async_rng = rng.clone();
+            run_game(
+                &mut make_challenge_bots(),
+                &mut sync_game,
+                &mut rng,
+                false,
+                None,
+                0,
+                TimeControl::default(),
+            );
+            let mut bots: Vec<Box<dyn AsyncBot>> = make_challenge_bots()
+                .into_iter()
+                .map(|bot| Box::new(SyncBot(bot)) as Box<dyn AsyncBot>)
+                .collect();
+            async_game.record_history();
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            LocalSet::new().block_on(
+                &runtime,
+                run_async_game(&mut bots, &mut async_game, &mut async_rng),
+            );
+            let first_challenger = sync_game.decisions().find_map(|v| match v {
+                Decision::Play(action) if action.action_type == ActionType::Challenge => {
+                    Some(action.player)
+                }
+                _ => None,
+            });
+            assert_eq!(first_challenger, Some(challenger), "{:?}", priority);
+            assert!(
+                sync_game.decisions().eq(async_game.decisions()),
+                "{:?}",
+                priority
+            );
+        }
+    }
+
+    #[test]
+    fn run_async_game_should_forfeit_player_with_illegal_action() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::new(get_settings(), &mut rng);
+        let mut bots: Vec<Box<dyn AsyncBot>> = make_bots(42, &[BotType::Random; 3], &game, None)
+            .into_iter()
+            .map(|bot| Box::new(SyncBot(bot)) as Box<dyn AsyncBot>)
+            .collect();
+        bots[0] = Box::new(IllegalBot);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        LocalSet::new().block_on(&runtime, run_async_game(&mut bots, &mut game, &mut rng));
+        assert!(game.is_done());
+        assert!(!game.is_player_active(0));
+    }
+}
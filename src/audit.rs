@@ -0,0 +1,410 @@
+use crate::fsm::{
+    get_bank, Action, ActionType, StateType, ASSASSINATION_COST, COUP_COST, FOREIGN_AID, INCOME,
+    MAX_STEAL, TAX,
+};
+use crate::game::{Game, ReplayError, Settings};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PlayerLedger {
+    pub initial_coins: usize,
+    pub final_coins: usize,
+    pub income: usize,
+    pub foreign_aid: usize,
+    pub tax: usize,
+    pub steal_gained: usize,
+    pub steal_lost: usize,
+    pub coup_paid: usize,
+    pub assassination_paid: usize,
+    pub lost_by_challenge: usize,
+    pub lost_by_coup: usize,
+    pub lost_by_assassination: usize,
+}
+
+impl PlayerLedger {
+    pub fn earned(&self) -> usize {
+        self.income + self.foreign_aid + self.tax + self.steal_gained
+    }
+
+    pub fn spent(&self) -> usize {
+        self.coup_paid + self.assassination_paid + self.steal_lost
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inconsistency {
+    pub step: usize,
+    pub player: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct AuditReport {
+    pub players: Vec<PlayerLedger>,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+#[derive(Default)]
+struct Auditor {
+    report: AuditReport,
+    player_coins: Vec<usize>,
+    player_hands: Vec<usize>,
+    state_type: Option<StateType>,
+    turn_action: Option<Action>,
+    step: usize,
+}
+
+pub fn audit_game(
+    settings: Settings,
+    seed: u64,
+    actions: &[Action],
+) -> Result<AuditReport, ReplayError> {
+    let mut auditor = Auditor::default();
+    Game::from_history_with(settings, seed, actions, |game, action| match action {
+        Some(action) => auditor.record(game, action),
+        None => auditor.start(game),
+    })?;
+    auditor.finish();
+    Ok(auditor.report)
+}
+
+impl Auditor {
+    fn start(&mut self, game: &Game) {
+        let view = game.get_anonymous_view();
+        self.report.players = view
+            .player_coins
+            .iter()
+            .map(|coins| PlayerLedger {
+                initial_coins: *coins,
+                ..Default::default()
+            })
+            .collect();
+        self.update(game);
+    }
+
+    fn record(&mut self, game: &Game, action: &Action) {
+        let step = game.step() - 1;
+        let view = game.get_anonymous_view();
+        let turn_start = matches!(
+            self.state_type,
+            Some(StateType::Turn { player }) if player == action.player
+        );
+        if turn_start {
+            self.turn_action = Some(action.clone());
+        }
+        let bank = get_bank(view.rules, &self.player_coins);
+        for player in 0..view.player_coins.len() {
+            let before = self.player_coins[player];
+            let after = view.player_coins[player];
+            if after > before {
+                self.record_gain(step, player, after - before, bank);
+            } else if after < before {
+                self.record_loss(step, player, before - after, turn_start, view.player_coins);
+            }
+            let lost = self.player_hands[player].saturating_sub(view.player_hands[player]);
+            if view.player_hands[player] > self.player_hands[player] {
+                self.flag(step, player, String::from("influence was restored"));
+            } else if lost > 0 {
+                self.record_influence_loss(step, player, lost, action);
+            } else if action.player == player
+                && matches!(action.action_type, ActionType::RevealCard(..))
+            {
+                self.flag(
+                    step,
+                    player,
+                    String::from("revealed card didn't cost influence"),
+                );
+            }
+        }
+        self.update(game);
+    }
+
+    fn record_gain(&mut self, step: usize, player: usize, coins: usize, bank: Option<usize>) {
+        let Some(turn_action) = self.turn_action.clone() else {
+            self.flag(
+                step,
+                player,
+                format!("gained {} coins before any turn", coins),
+            );
+            return;
+        };
+        if turn_action.player != player {
+            self.flag(
+                step,
+                player,
+                format!(
+                    "gained {} coins on {:?} of player {}",
+                    coins, turn_action.action_type, turn_action.player
+                ),
+            );
+            return;
+        }
+        let ledger = &mut self.report.players[player];
+        let expected = match turn_action.action_type {
+            ActionType::Income => {
+                ledger.income += coins;
+                get_payout(INCOME, bank)
+            }
+            ActionType::ForeignAid => {
+                ledger.foreign_aid += coins;
+                get_payout(FOREIGN_AID, bank)
+            }
+            ActionType::Tax => {
+                ledger.tax += coins;
+                get_payout(TAX, bank)
+            }
+            ActionType::Steal(target) => {
+                ledger.steal_gained += coins;
+                MAX_STEAL.min(self.player_coins[target])
+            }
+            action_type => {
+                self.flag(
+                    step,
+                    player,
+                    format!("gained {} coins on {:?}", coins, action_type),
+                );
+                return;
+            }
+        };
+        if coins != expected {
+            self.flag(
+                step,
+                player,
+                format!(
+                    "gained {} coins on {:?}, expected {}",
+                    coins, turn_action.action_type, expected
+                ),
+            );
+        }
+    }
+
+    fn record_loss(
+        &mut self,
+        step: usize,
+        player: usize,
+        coins: usize,
+        turn_start: bool,
+        player_coins: &[usize],
+    ) {
+        let Some(turn_action) = self.turn_action.clone() else {
+            self.flag(
+                step,
+                player,
+                format!("lost {} coins before any turn", coins),
+            );
+            return;
+        };
+        let ledger = &mut self.report.players[player];
+        let expected = match turn_action.action_type {
+            ActionType::Coup(..) if turn_start && turn_action.player == player => {
+                ledger.coup_paid += coins;
+                COUP_COST
+            }
+            ActionType::Assassinate(..) if turn_start && turn_action.player == player => {
+                ledger.assassination_paid += coins;
+                ASSASSINATION_COST
+            }
+            ActionType::Steal(target) if target == player => {
+                ledger.steal_lost += coins;
+                player_coins[turn_action.player] - self.player_coins[turn_action.player]
+            }
+            ref action_type => {
+                self.flag(
+                    step,
+                    player,
+                    format!(
+                        "lost {} coins on {:?} of player {}",
+                        coins, action_type, turn_action.player
+                    ),
+                );
+                return;
+            }
+        };
+        if coins != expected {
+            self.flag(
+                step,
+                player,
+                format!(
+                    "lost {} coins on {:?}, expected {}",
+                    coins, turn_action.action_type, expected
+                ),
+            );
+        }
+    }
+
+    fn record_influence_loss(&mut self, step: usize, player: usize, lost: usize, action: &Action) {
+        if lost > 1 || action.player != player {
+            self.flag(
+                step,
+                player,
+                format!("lost {} influence on {:?}", lost, action.action_type),
+            );
+            return;
+        }
+        if !matches!(action.action_type, ActionType::RevealCard(..)) {
+            self.flag(
+                step,
+                player,
+                format!(
+                    "lost influence without revealing a card on {:?}",
+                    action.action_type
+                ),
+            );
+            return;
+        }
+        let ledger = &mut self.report.players[player];
+        match (
+            &self.state_type,
+            self.turn_action.as_ref().map(|v| &v.action_type),
+        ) {
+            (Some(StateType::Challenge { .. }), _) => ledger.lost_by_challenge += 1,
+            (_, Some(ActionType::Coup(..))) => ledger.lost_by_coup += 1,
+            (_, Some(ActionType::Assassinate(..))) => ledger.lost_by_assassination += 1,
+            (state_type, _) => {
+                let message = format!("lost influence in {:?}", state_type);
+                self.flag(step, player, message);
+            }
+        }
+    }
+
+    fn update(&mut self, game: &Game) {
+        let view = game.get_anonymous_view();
+        self.player_coins = view.player_coins.to_vec();
+        self.player_hands = view.player_hands.to_vec();
+        self.state_type = Some(view.state_type.clone());
+        self.step = game.step();
+    }
+
+    fn finish(&mut self) {
+        for (player, coins) in self.player_coins.iter().enumerate() {
+            self.report.players[player].final_coins = *coins;
+        }
+        for (player, ledger) in self.report.players.clone().iter().enumerate() {
+            if ledger.initial_coins + ledger.earned() != ledger.final_coins + ledger.spent() {
+                self.report.inconsistencies.push(Inconsistency {
+                    step: self.step,
+                    player,
+                    message: format!(
+                        "{} initial + {} earned - {} spent != {} final coins",
+                        ledger.initial_coins,
+                        ledger.earned(),
+                        ledger.spent(),
+                        ledger.final_coins
+                    ),
+                });
+            }
+        }
+    }
+
+    fn flag(&mut self, step: usize, player: usize, message: String) {
+        self.report.inconsistencies.push(Inconsistency {
+            step,
+            player,
+            message,
+        });
+    }
+}
+
+fn get_payout(coins: usize, bank: Option<usize>) -> usize {
+    bank.map_or(coins, |bank| coins.min(bank))
+}
+
+pub fn print_audit_report(report: &AuditReport) {
+    for (player, ledger) in report.players.iter().enumerate() {
+        println!(
+            "player {}: coins {} -> {}",
+            player, ledger.initial_coins, ledger.final_coins
+        );
+        println!(
+            "  earned {}: income {} foreign_aid {} tax {} steal {}",
+            ledger.earned(),
+            ledger.income,
+            ledger.foreign_aid,
+            ledger.tax,
+            ledger.steal_gained
+        );
+        println!(
+            "  spent {}: coup {} assassination {} stolen {}",
+            ledger.spent(),
+            ledger.coup_paid,
+            ledger.assassination_paid,
+            ledger.steal_lost
+        );
+        println!(
+            "  influence lost: challenge {} coup {} assassination {}",
+            ledger.lost_by_challenge, ledger.lost_by_coup, ledger.lost_by_assassination
+        );
+    }
+    if report.inconsistencies.is_empty() {
+        println!("no inconsistencies");
+    }
+    for inconsistency in report.inconsistencies.iter() {
+        println!(
+            "step {} player {}: {}",
+            inconsistency.step, inconsistency.player, inconsistency.message
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::{get_example_actions, get_example_settings, CardCounts, Decision};
+    use crate::run::{run_game_with_bots, BotType, TimeControl};
+
+    use super::*;
+
+    #[test]
+    fn audit_example_game_should_balance_coins() {
+        let settings = get_example_settings();
+        let report = audit_game(settings, 42, &get_example_actions()).unwrap();
+        assert_eq!(report.inconsistencies, Vec::new());
+        assert_eq!(report.players.len(), 6);
+        for ledger in report.players.iter() {
+            assert_eq!(
+                ledger.initial_coins + ledger.earned(),
+                ledger.final_coins + ledger.spent()
+            );
+        }
+        assert!(report.players.iter().any(|v| v.income > 0));
+    }
+
+    #[test]
+    fn audit_bot_games_should_find_no_inconsistencies() {
+        let bot_types = [
+            BotType::Random,
+            BotType::HonestCarefulRandom,
+            BotType::Random,
+        ];
+        for (seed, bank_coins) in [(1, None), (2, None), (3, Some(12)), (4, Some(20))] {
+            let settings = Settings {
+                players_number: 3,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules {
+                    bank_coins,
+                    ..Default::default()
+                },
+            };
+            let result = run_game_with_bots(
+                seed,
+                &bot_types,
+                settings.clone(),
+                false,
+                None,
+                0,
+                TimeControl::default(),
+            );
+            let actions: Vec<Action> = result
+                .end
+                .decisions()
+                .filter_map(|decision| match decision {
+                    Decision::Play(action) => Some(action.clone()),
+                    Decision::Forfeit(..) => None,
+                })
+                .collect();
+            assert!(!actions.is_empty());
+            let report = audit_game(settings, seed, &actions).unwrap();
+            assert_eq!(report.inconsistencies, Vec::new(), "seed {}", seed);
+        }
+    }
+}
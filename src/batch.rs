@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+
+use crate::bots::{notify_bot, ActionView, CardsTracker, TrackerSummary};
+use crate::fsm::Action;
+use crate::game::{GameView, Settings};
+use crate::input::{open_file, InputError, LineReader};
+use crate::run::{make_bot, BotType};
+use crate::session::{make_suggestion, Suggestion};
+
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<Suggestion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracker: Option<TrackerSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub fn suggest_batch(
+    dir: &str,
+    bot_type: BotType,
+    seed: u64,
+    workers: usize,
+) -> Result<(), String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read directory {}: {}", dir, e))?
+        .filter_map(|entry| entry.ok().map(|v| v.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+    let paths = Arc::new(paths);
+    let next = Arc::new(Mutex::new(0));
+    let (sender, receiver) = mpsc::channel();
+    let threads = (0..workers.max(1))
+        .map(|_| {
+            let worker_paths = paths.clone();
+            let worker_next = next.clone();
+            let worker_sender = sender.clone();
+            std::thread::spawn(move || loop {
+                let index = {
+                    let mut locked_next = worker_next.lock().unwrap();
+                    if *locked_next >= worker_paths.len() {
+                        break;
+                    }
+                    *locked_next += 1;
+                    *locked_next - 1
+                };
+                let path = worker_paths[index].to_string_lossy().to_string();
+                let result = suggest_from_log(&path, bot_type, seed);
+                if worker_sender.send((index, result)).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(sender);
+    let mut pending = BTreeMap::new();
+    let mut expected = 0;
+    for (index, result) in receiver {
+        pending.insert(index, result);
+        while let Some(result) = pending.remove(&expected) {
+            println!("{}", serde_json::to_string(&result).unwrap());
+            expected += 1;
+        }
+    }
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    Ok(())
+}
+
+fn suggest_from_log(path: &str, bot_type: BotType, seed: u64) -> BatchResult {
+    match read_log(path, bot_type, seed) {
+        Ok((view, suggestion, tracker)) => BatchResult {
+            path: String::from(path),
+            step: Some(view.step),
+            suggestion: Some(suggestion),
+            tracker: Some(tracker),
+            error: None,
+        },
+        Err(e) => BatchResult {
+            path: String::from(path),
+            step: None,
+            suggestion: None,
+            tracker: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+fn read_log(
+    path: &str,
+    bot_type: BotType,
+    seed: u64,
+) -> Result<(GameView, Suggestion, TrackerSummary), InputError> {
+    let mut file = LineReader::new(open_file(path)?);
+    let settings: Settings = file.read_required("settings")?;
+    settings.validate().map_err(|e| file.invalid_error(e))?;
+    let mut view: GameView = file.read_required("game view")?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut bot = make_bot(bot_type, &view.player_view(), &settings, &mut rng);
+    bot.on_game_start(&settings, view.player);
+    let mut tracker = CardsTracker::from_view(&view.player_view(), &settings);
+    while let Some(action) = file.read::<Action>()? {
+        let next = match file.read::<GameView>()? {
+            Some(v) => v,
+            None => break,
+        };
+        notify_bot(bot.as_mut(), &next.player_view(), &action);
+        if next.player == action.player {
+            tracker.after_player_action(&next.player_view(), &action);
+        } else {
+            tracker.after_opponent_action(&next.player_view(), &ActionView::from_action(&action));
+        }
+        view = next;
+    }
+    let suggestion = make_suggestion(bot.as_mut(), &view);
+    Ok((view, suggestion, tracker.summary()))
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::sync::OnceLock;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::bots::{
+    is_allowed_action_type, ActionHistory, ActionScore, ActionView, Bot, CardsTracker,
+};
+use crate::fsm::{Action, ActionType};
+use crate::game::{PlayerView, Settings};
+use crate::run::{for_each_played_action, run_game_with_bots, BotType, TimeControl};
+use crate::state_class::StateClass;
+use crate::symmetry::SeatRotation;
+
+static BOOK: OnceLock<OpeningBook> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookMove {
+    pub action_type: ActionType,
+    pub win_rate: f64,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningBook {
+    settings: Settings,
+    rounds: usize,
+    moves: BTreeMap<String, BookMove>,
+}
+
+impl OpeningBook {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read opening book from {}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("failed to create {}: {}", path, e))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|e| format!("failed to write opening book to {}: {}", path, e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn lookup(&self, view: &PlayerView) -> Option<Action> {
+        if view.round >= self.rounds {
+            return None;
+        }
+        let book_move = self.moves.get(&StateClass::classify(view).to_string())?;
+        let rotation = SeatRotation::new(view.player, view.player_hands.len());
+        Some(rotation.original_action(&Action {
+            player: 0,
+            action_type: book_move.action_type.clone(),
+        }))
+    }
+}
+
+pub fn load(path: &str) -> Result<(), String> {
+    let book = OpeningBook::load(path)?;
+    tracing::info!(path, moves = book.len(), "opening book loaded");
+    BOOK.set(book)
+        .map_err(|_| String::from("opening book is already loaded"))
+}
+
+pub fn get() -> Option<&'static OpeningBook> {
+    BOOK.get()
+}
+
+#[derive(Default)]
+struct MoveStats {
+    wins: usize,
+    samples: usize,
+}
+
+pub struct BookParams {
+    pub seed: u64,
+    pub games: usize,
+    pub rounds: usize,
+    pub min_samples: usize,
+}
+
+pub fn build_opening_book(
+    params: &BookParams,
+    bot_types: &[BotType],
+    settings: Settings,
+) -> Result<OpeningBook, String> {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut stats: BTreeMap<String, Vec<(ActionType, MoveStats)>> = BTreeMap::new();
+    for _ in 0..params.games {
+        let seed = rng.gen::<u64>();
+        let result = run_game_with_bots(
+            seed,
+            bot_types,
+            settings.clone(),
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        let winner = result.end.get_winner().unwrap();
+        for_each_played_action(seed, &result, |game, action| {
+            if game.round() >= params.rounds {
+                return;
+            }
+            let rotation = SeatRotation::new(action.player, settings.players_number);
+            let action_type = rotation.canonical_action(action).action_type;
+            let moves = stats
+                .entry(StateClass::classify(&game.get_player_view(action.player)).to_string())
+                .or_default();
+            let index = match moves.iter().position(|(v, _)| *v == action_type) {
+                Some(v) => v,
+                None => {
+                    moves.push((action_type, MoveStats::default()));
+                    moves.len() - 1
+                }
+            };
+            moves[index].1.samples += 1;
+            if winner == action.player {
+                moves[index].1.wins += 1;
+            }
+        })?;
+    }
+    let moves = stats
+        .into_iter()
+        .filter_map(|(state_class, moves)| {
+            moves
+                .into_iter()
+                .filter(|(_, v)| v.samples >= params.min_samples.max(1))
+                .map(|(action_type, v)| BookMove {
+                    action_type,
+                    win_rate: v.wins as f64 / v.samples as f64,
+                    samples: v.samples,
+                })
+                .max_by(|a, b| a.win_rate.partial_cmp(&b.win_rate).unwrap())
+                .map(|v| (state_class, v))
+        })
+        .collect();
+    Ok(OpeningBook {
+        settings,
+        rounds: params.rounds,
+        moves,
+    })
+}
+
+pub struct BookBot {
+    book: &'static OpeningBook,
+    inner: Box<dyn Bot>,
+}
+
+impl BookBot {
+    pub fn new(book: &'static OpeningBook, inner: Box<dyn Bot>) -> Self {
+        Self { book, inner }
+    }
+
+    fn get_book_action(&self, view: &PlayerView, available_actions: &[Action]) -> Option<Action> {
+        self.book.lookup(view).filter(|action| {
+            available_actions.contains(action)
+                && is_allowed_action_type(&action.action_type, view.cards)
+        })
+    }
+}
+
+impl Bot for BookBot {
+    fn suggest_actions<'a>(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        self.inner.suggest_actions(view, available_actions)
+    }
+
+    fn suggest_optional_actions<'a>(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        self.inner.suggest_optional_actions(view, available_actions)
+    }
+
+    fn get_action(&mut self, view: &PlayerView, available_actions: &[Action]) -> Action {
+        match self.get_book_action(view, available_actions) {
+            Some(action) => action,
+            None => self.inner.get_action(view, available_actions),
+        }
+    }
+
+    fn get_optional_action(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Option<Action> {
+        match self.get_book_action(view, available_actions) {
+            Some(action) => Some(action),
+            None => self.inner.get_optional_action(view, available_actions),
+        }
+    }
+
+    fn after_player_action(&mut self, view: &PlayerView, action: &Action) {
+        self.inner.after_player_action(view, action);
+    }
+
+    fn after_opponent_action(&mut self, view: &PlayerView, action: &ActionView) {
+        self.inner.after_opponent_action(view, action);
+    }
+
+    fn score_actions(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Vec<ActionScore> {
+        self.inner.score_actions(view, available_actions)
+    }
+
+    fn after_forfeit(&mut self, view: &PlayerView, player: usize) {
+        self.inner.after_forfeit(view, player);
+    }
+
+    fn on_game_start(&mut self, settings: &Settings, player: usize) {
+        self.inner.on_game_start(settings, player);
+    }
+
+    fn history_mut(&mut self) -> Option<&mut ActionHistory> {
+        self.inner.history_mut()
+    }
+
+    fn cards_tracker(&self) -> Option<&CardsTracker> {
+        self.inner.cards_tracker()
+    }
+
+    fn query(&self, query: &str) {
+        self.inner.query(query);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::{CardCounts, Game};
+
+    use super::*;
+
+    #[test]
+    fn opening_book_should_suggest_actions_from_first_rounds() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let book = build_opening_book(
+            &BookParams {
+                seed: 42,
+                games: 50,
+                rounds: 2,
+                min_samples: 2,
+            },
+            &[BotType::HonestCarefulRandom, BotType::HonestCarefulRandom],
+            settings.clone(),
+        )
+        .unwrap();
+        assert!(book.len() > 0);
+        for book_move in book.moves.values() {
+            assert!(book_move.samples >= 2);
+            assert!((0.0..=1.0).contains(&book_move.win_rate));
+        }
+        let game = Game::new(settings, &mut StdRng::seed_from_u64(1));
+        let view = game.get_player_view(0);
+        if let Some(action) = book.lookup(&view) {
+            assert_eq!(action.player, 0);
+        }
+    }
+}
@@ -1,16 +1,26 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-
 use itertools::Itertools;
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::fsm::{
-    play_action, Action, ActionType, Card, ConstRng, Deck, Error, PlayerCards, State, StateType,
-    CARDS_PER_PLAYER, MAX_CARDS_TO_EXCHANGE,
+    get_next_player, play_action, Action, ActionType, Card, Cards, ChallengeState, ConstRng, Deck,
+    Error, PlayerCards, Rules, State, StateType, FOREIGN_AID, INCOME,
 };
-use crate::game::{PlayerView, Settings, ALL_CARDS, INITIAL_COINS};
+use crate::game::{
+    default_cards_per_player, CardCounts, FullGameState, Game, GameView, PlayerView, Settings,
+    ALL_CARDS, INITIAL_COINS,
+};
+use crate::metrics;
+use crate::run::{get_action_type_name, MatchContext};
+use crate::tablebase::Tablebase;
 
 pub trait Bot {
     fn suggest_actions<'a>(
@@ -38,9 +48,52 @@ pub trait Bot {
     fn after_opponent_action(&mut self, view: &PlayerView, action: &ActionView);
 
     fn query(&self, command: &str);
+
+    fn score_actions(&mut self, _: &PlayerView, _: &[Action]) -> Vec<ActionScore> {
+        Vec::new()
+    }
+
+    fn after_forfeit(&mut self, _: &PlayerView, _: usize) {}
+
+    fn on_game_start(&mut self, _: &Settings, _: usize) {}
+
+    fn history_mut(&mut self) -> Option<&mut ActionHistory> {
+        None
+    }
+
+    fn cards_tracker(&self) -> Option<&CardsTracker> {
+        None
+    }
+}
+
+pub fn notify_tracker(tracker: &mut CardsTracker, view: &PlayerView, action: &Action) {
+    if view.player == action.player {
+        tracker.after_player_action(view, action);
+    } else {
+        tracker.after_opponent_action(view, &ActionView::from_action(action));
+    }
 }
 
-#[derive(Debug, Clone)]
+pub fn notify_bot<B: Bot + ?Sized>(bot: &mut B, view: &PlayerView, action: &Action) {
+    let action_view = ActionView::from_action(action);
+    if let Some(history) = bot.history_mut() {
+        history.push(action_view.clone());
+    }
+    if view.player == action.player {
+        bot.after_player_action(view, action);
+    } else {
+        bot.after_opponent_action(view, &action_view);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionScore {
+    pub action: Action,
+    pub win_probability: f64,
+    pub simulations: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActionView {
     player: usize,
     action_type: ActionTypeView,
@@ -55,7 +108,54 @@ impl ActionView {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Default)]
+pub struct ActionHistory {
+    actions: Vec<ActionView>,
+}
+
+impl ActionHistory {
+    pub fn push(&mut self, action: ActionView) {
+        self.actions.push(action);
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn extend(&mut self, other: ActionHistory) {
+        self.actions.extend(other.actions);
+    }
+
+    pub fn relabeled(&self, labels: &[usize]) -> Self {
+        Self {
+            actions: self
+                .actions
+                .iter()
+                .map(|action| ActionView {
+                    player: labels[action.player],
+                    action_type: match &action.action_type {
+                        ActionTypeView::Coup(target) => ActionTypeView::Coup(labels[*target]),
+                        ActionTypeView::Assassinate(target) => {
+                            ActionTypeView::Assassinate(labels[*target])
+                        }
+                        ActionTypeView::Steal(target) => ActionTypeView::Steal(labels[*target]),
+                        v => v.clone(),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    pub fn count(&self, player: usize, action_type: &ActionType) -> usize {
+        let kind = std::mem::discriminant(&ActionTypeView::from_action_type(action_type));
+        self.actions
+            .iter()
+            .filter(|v| v.player == player && std::mem::discriminant(&v.action_type) == kind)
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 enum ActionTypeView {
     Income,
     ForeignAid,
@@ -131,19 +231,11 @@ pub struct RandomBot {
 }
 
 impl RandomBot {
-    pub fn new(view: &PlayerView) -> Self {
-        Self {
-            rng: make_rng_from_cards(view.cards),
-        }
+    pub fn new(rng: StdRng) -> Self {
+        Self { rng }
     }
 }
 
-fn make_rng_from_cards(cards: &[Card]) -> StdRng {
-    let mut hasher = DefaultHasher::new();
-    cards.hash(&mut hasher);
-    StdRng::seed_from_u64(hasher.finish())
-}
-
 impl Bot for RandomBot {
     fn suggest_actions<'a>(
         &mut self,
@@ -191,9 +283,9 @@ impl Bot for RandomBot {
     fn query(&self, _: &str) {}
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 struct CardCollection {
-    known: Vec<Card>,
+    known: Cards,
     unknown: usize,
 }
 
@@ -264,9 +356,9 @@ impl PlayerCards for CardCollection {
     }
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 enum GamePlayerCards {
-    Player(Vec<Card>),
+    Player(Cards),
     Opponent(CardCollection),
 }
 
@@ -361,18 +453,176 @@ impl PlayerCards for GamePlayerCards {
     }
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+impl PlayerCards for Arc<GamePlayerCards> {
+    fn has_card(&self, card: Card) -> bool {
+        self.as_ref().has_card(card)
+    }
+
+    fn count(&self) -> usize {
+        self.as_ref().count()
+    }
+
+    fn add_card(&mut self, card: Card) {
+        Arc::make_mut(self).add_card(card)
+    }
+
+    fn drop_card(&mut self, card: Card) {
+        Arc::make_mut(self).drop_card(card)
+    }
+}
+
+fn is_sub_multiset(cards: &[Card], other: &[Card]) -> bool {
+    cards.iter().all(|card| {
+        cards.iter().filter(|v| *v == card).count() <= other.iter().filter(|v| *v == card).count()
+    })
+}
+
+fn declare_challenge(source: &StateType, initiator: usize) -> Option<StateType> {
+    let (current_player, target, card) = match source {
+        StateType::Tax { player } => (*player, *player, Card::Duke),
+        StateType::Exchange { player } => (*player, *player, Card::Ambassador),
+        StateType::Assassination {
+            player,
+            can_challenge: true,
+            ..
+        } => (*player, *player, Card::Assassin),
+        StateType::Steal {
+            player,
+            can_challenge: true,
+            ..
+        } => (*player, *player, Card::Captain),
+        StateType::BlockForeignAid { player, target } => (*target, *player, Card::Duke),
+        StateType::BlockAssassination { player, target } => (*target, *player, Card::Contessa),
+        StateType::BlockSteal {
+            player,
+            target,
+            card,
+        } => (*target, *player, *card),
+        _ => return None,
+    };
+    if target == initiator {
+        return None;
+    }
+    Some(StateType::Challenge {
+        current_player,
+        state: ChallengeState::Initial {
+            initiator,
+            target,
+            card,
+        },
+        source: Rc::new(source.clone()),
+    })
+}
+
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 struct GameState {
     valid: bool,
     state_type: StateType,
     player_coins: Vec<usize>,
     player_hands: Vec<usize>,
     player_cards_counter: Vec<usize>,
-    player_cards: Vec<GamePlayerCards>,
+    player_cards: Vec<Arc<GamePlayerCards>>,
     revealed_cards: Vec<Card>,
     deck: CardCollection,
 }
 
+impl Clone for GameState {
+    fn clone(&self) -> Self {
+        Self {
+            valid: self.valid,
+            state_type: self.state_type.clone(),
+            player_coins: self.player_coins.clone(),
+            player_hands: self.player_hands.clone(),
+            player_cards_counter: self.player_cards_counter.clone(),
+            player_cards: self.player_cards.clone(),
+            revealed_cards: self.revealed_cards.clone(),
+            deck: self.deck.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.valid = source.valid;
+        self.state_type.clone_from(&source.state_type);
+        self.player_coins.clone_from(&source.player_coins);
+        self.player_hands.clone_from(&source.player_hands);
+        self.player_cards_counter
+            .clone_from(&source.player_cards_counter);
+        self.player_cards.clone_from(&source.player_cards);
+        self.revealed_cards.clone_from(&source.revealed_cards);
+        self.deck.known.clone_from(&source.deck.known);
+        self.deck.unknown = source.deck.unknown;
+    }
+}
+
+const MAX_POOL_SIZE: usize = 4096;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    pub allocated: usize,
+    pub reused: usize,
+    pub released: usize,
+}
+
+impl PoolStats {
+    pub fn add(&mut self, other: &PoolStats) {
+        self.allocated += other.allocated;
+        self.reused += other.reused;
+        self.released += other.released;
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackerAccuracy {
+    pub steps: usize,
+    pub hypotheses: usize,
+    pub max_hypotheses: usize,
+    pub true_state_found: usize,
+    pub hands: usize,
+    pub true_hand_probability: f64,
+    pub true_hand_rank: usize,
+}
+
+impl TrackerAccuracy {
+    pub fn add(&mut self, other: &TrackerAccuracy) {
+        self.steps += other.steps;
+        self.hypotheses += other.hypotheses;
+        self.max_hypotheses = self.max_hypotheses.max(other.max_hypotheses);
+        self.true_state_found += other.true_state_found;
+        self.hands += other.hands;
+        self.true_hand_probability += other.true_hand_probability;
+        self.true_hand_rank += other.true_hand_rank;
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct GameStatePool {
+    free: Vec<GameState>,
+    stats: PoolStats,
+}
+
+impl GameStatePool {
+    fn clone_state(&mut self, source: &GameState) -> GameState {
+        match self.free.pop() {
+            Some(mut state) => {
+                state.clone_from(source);
+                self.stats.reused += 1;
+                state
+            }
+            None => {
+                self.stats.allocated += 1;
+                source.clone()
+            }
+        }
+    }
+
+    fn release(&mut self, state: GameState) {
+        self.stats.released += 1;
+        if self.free.len() < MAX_POOL_SIZE {
+            self.free.push(state);
+        }
+    }
+}
+
 impl GameState {
     fn initial(player: usize, cards: &[Card], settings: &Settings) -> Vec<Self> {
         let mut ordered_cards = cards.to_owned();
@@ -380,49 +630,51 @@ impl GameState {
         let mut unique_cards = ordered_cards.clone();
         unique_cards.dedup();
         let deck_len =
-            settings.cards_per_type * ALL_CARDS.len() - settings.players_number * CARDS_PER_PLAYER;
+            settings.card_counts.total() - settings.players_number * settings.cards_per_player;
         let base_game_state = Self {
             valid: true,
             state_type: StateType::Turn { player: 0 },
             player_coins: std::iter::repeat(INITIAL_COINS)
                 .take(settings.players_number)
                 .collect(),
-            player_hands: std::iter::repeat(CARDS_PER_PLAYER)
+            player_hands: std::iter::repeat(settings.cards_per_player)
                 .take(settings.players_number)
                 .collect(),
-            player_cards_counter: std::iter::repeat(CARDS_PER_PLAYER)
+            player_cards_counter: std::iter::repeat(settings.cards_per_player)
                 .take(settings.players_number)
                 .collect(),
             player_cards: (0..settings.players_number)
                 .map(|index| {
                     if index == player {
-                        GamePlayerCards::Player(cards.to_owned())
+                        Arc::new(GamePlayerCards::Player(Cards::from_slice(cards)))
                     } else {
-                        GamePlayerCards::Opponent(CardCollection {
-                            known: Vec::with_capacity(CARDS_PER_PLAYER + MAX_CARDS_TO_EXCHANGE),
-                            unknown: CARDS_PER_PLAYER,
-                        })
+                        Arc::new(GamePlayerCards::Opponent(CardCollection {
+                            known: Cards::new(),
+                            unknown: settings.cards_per_player,
+                        }))
                     }
                 })
                 .collect(),
-            revealed_cards: Vec::with_capacity(settings.cards_per_type * ALL_CARDS.len()),
+            revealed_cards: Vec::with_capacity(settings.card_counts.total()),
             deck: CardCollection {
-                known: Vec::with_capacity(CARDS_PER_PLAYER + MAX_CARDS_TO_EXCHANGE),
+                known: Cards::new(),
                 unknown: deck_len,
             },
         };
-        let mut result = Vec::new();
         let targets: Vec<usize> = (0..settings.players_number)
-            .into_iter()
             .filter(|v| *v != player || deck_len > 0)
             .collect();
-        if unique_cards.len() == 1 {
-            if settings.cards_per_type > 2 {
-                for opponents in targets
-                    .iter()
-                    .combinations_with_replacement(settings.cards_per_type - 2)
-                {
-                    let mut game_state = base_game_state.clone();
+        let mut result = vec![base_game_state.clone()];
+        for card in unique_cards.iter() {
+            let held = ordered_cards.iter().filter(|v| *v == card).count();
+            let rest = settings.card_counts.get(*card).saturating_sub(held);
+            if rest == 0 {
+                continue;
+            }
+            let mut next = Vec::new();
+            for game_state in result.iter() {
+                for opponents in targets.iter().combinations_with_replacement(rest) {
+                    let mut game_state = game_state.clone();
                     let mut add = true;
                     for &opponent in opponents {
                         if opponent == player {
@@ -430,82 +682,29 @@ impl GameState {
                                 add = false;
                                 break;
                             }
-                            game_state.deck.replace_any_by_known(unique_cards[0]);
+                            game_state.deck.replace_any_by_known(*card);
                         } else {
                             if !game_state.player_cards[opponent].has_any() {
                                 add = false;
                                 break;
                             }
-                            game_state.player_cards[opponent].replace_any_by_known(unique_cards[0]);
+                            Arc::make_mut(&mut game_state.player_cards[opponent])
+                                .replace_any_by_known(*card);
                         }
                     }
                     if add {
-                        result.push(game_state);
+                        next.push(game_state);
                     }
                 }
             }
-        } else if unique_cards.len() == 2 {
-            if settings.cards_per_type > 1 {
-                for first_opponents in targets
-                    .iter()
-                    .combinations_with_replacement(settings.cards_per_type - 1)
-                {
-                    for second_opponents in targets
-                        .iter()
-                        .combinations_with_replacement(settings.cards_per_type - 1)
-                    {
-                        let mut game_state = base_game_state.clone();
-                        let mut add = true;
-                        for &&opponent in first_opponents.iter() {
-                            if opponent == player {
-                                if !game_state.deck.has_any() {
-                                    add = false;
-                                    break;
-                                }
-                                game_state.deck.replace_any_by_known(unique_cards[0]);
-                            } else {
-                                if !game_state.player_cards[opponent].has_any() {
-                                    add = false;
-                                    break;
-                                }
-                                game_state.player_cards[opponent]
-                                    .replace_any_by_known(unique_cards[0]);
-                            }
-                        }
-                        if !add {
-                            continue;
-                        }
-                        for &opponent in second_opponents {
-                            if opponent == player {
-                                if !game_state.deck.has_any() {
-                                    add = false;
-                                    break;
-                                }
-                                game_state.deck.replace_any_by_known(unique_cards[1]);
-                            } else {
-                                if !game_state.player_cards[opponent].has_any() {
-                                    add = false;
-                                    break;
-                                }
-                                game_state.player_cards[opponent]
-                                    .replace_any_by_known(unique_cards[1]);
-                            }
-                        }
-                        if add {
-                            result.push(game_state);
-                        }
-                    }
-                }
-            }
-        } else {
-            panic!("Unsupported number of unique cards: {:?}", unique_cards);
+            result = next;
         }
         if result.is_empty() {
             result.push(base_game_state);
         }
         for game_state in result.iter_mut() {
             for player in game_state.player_cards.iter_mut() {
-                player.sort();
+                Arc::make_mut(player).sort();
             }
             game_state.deck.sort();
         }
@@ -514,10 +713,37 @@ impl GameState {
         result
     }
 
+    fn from_view(view: &PlayerView) -> Self {
+        Self {
+            valid: true,
+            state_type: view.state_type.clone(),
+            player_coins: view.player_coins.to_vec(),
+            player_hands: view.player_hands.to_vec(),
+            player_cards_counter: view.player_cards.to_vec(),
+            player_cards: (0..view.player_cards.len())
+                .map(|index| {
+                    if index == view.player {
+                        Arc::new(GamePlayerCards::Player(Cards::from_slice(view.cards)))
+                    } else {
+                        Arc::new(GamePlayerCards::Opponent(CardCollection {
+                            known: Cards::new(),
+                            unknown: view.player_cards[index],
+                        }))
+                    }
+                })
+                .collect(),
+            revealed_cards: view.revealed_cards.to_vec(),
+            deck: CardCollection {
+                known: Cards::new(),
+                unknown: view.deck,
+            },
+        }
+    }
+
     fn print(&self) {
         for player in 0..self.player_cards.len() {
             if !self.player_cards[player].is_empty() {
-                match &self.player_cards[player] {
+                match &*self.player_cards[player] {
                     GamePlayerCards::Player(cards) => {
                         print!(" {}={:?}", player, cards);
                     }
@@ -533,42 +759,44 @@ impl GameState {
         );
     }
 
+    fn get_pending_claims(&self) -> Vec<PendingClaim> {
+        get_pending_claims(&self.state_type)
+    }
+
+    fn get_challengeable_claim(&self, player: usize) -> Option<PendingClaim> {
+        self.get_pending_claims()
+            .pop()
+            .filter(|claim| claim.can_challenge && claim.player != player)
+    }
+
     fn is_safe_action_type(
         &self,
         player: usize,
         action_type: &ActionType,
-        last_action: Option<&ActionView>,
-        cards_per_type: usize,
+        card_counts: &CardCounts,
     ) -> bool {
         match action_type {
             ActionType::ForeignAid => {
-                self.count_known(Card::Duke) == cards_per_type
+                self.count_known(Card::Duke) == card_counts.get(Card::Duke)
                     && !self.is_card_hold_by_opponent(player, Card::Duke)
             }
             ActionType::Assassinate(..) => {
-                self.count_known(Card::Duke) == cards_per_type
+                self.count_known(Card::Duke) == card_counts.get(Card::Duke)
                     && !self.is_card_hold_by_opponent(player, Card::Contessa)
             }
             ActionType::Steal(..) => {
-                self.count_known(Card::Ambassador) == cards_per_type
+                self.count_known(Card::Ambassador) == card_counts.get(Card::Ambassador)
                     && self.is_card_hold_by_opponent(player, Card::Ambassador)
-                    && self.count_known(Card::Captain) == cards_per_type
+                    && self.count_known(Card::Captain) == card_counts.get(Card::Captain)
                     && self.is_card_hold_by_opponent(player, Card::Captain)
             }
-            ActionType::Challenge => {
-                let claimed_card = match &last_action.unwrap().action_type {
-                    ActionTypeView::Tax => Card::Duke,
-                    ActionTypeView::Assassinate(..) => Card::Assassin,
-                    ActionTypeView::Exchange => Card::Ambassador,
-                    ActionTypeView::Steal(..) => Card::Captain,
-                    ActionTypeView::BlockForeignAid => Card::Duke,
-                    ActionTypeView::BlockAssassination => Card::Contessa,
-                    ActionTypeView::BlockSteal(card) => *card,
-                    _ => return true,
-                };
-                !self.player_cards[last_action.unwrap().player].contains_known(claimed_card)
-                    && self.count_known(claimed_card) == cards_per_type
-            }
+            ActionType::Challenge => match self.get_challengeable_claim(player) {
+                Some(claim) => {
+                    !self.player_cards[claim.player].contains_known(claim.card)
+                        && self.count_known(claim.card) == card_counts.get(claim.card)
+                }
+                None => true,
+            },
             _ => true,
         }
     }
@@ -588,8 +816,78 @@ impl GameState {
             .any(|(_, opponent)| opponent.contains_known(card))
     }
 
-    fn with_default<F: FnMut(&mut State<GamePlayerCards, CardCollection>) -> Result<(), Error>>(
+    fn get_unknown_cards(&self, card_counts: &CardCounts) -> Option<Vec<Card>> {
+        let mut cards = card_counts.make_deck();
+        let known_cards = self
+            .player_cards
+            .iter()
+            .flat_map(|player| match &**player {
+                GamePlayerCards::Player(cards) => cards.iter(),
+                GamePlayerCards::Opponent(cards) => cards.known.iter(),
+            })
+            .chain(self.deck.known.iter())
+            .chain(self.revealed_cards.iter());
+        for card in known_cards {
+            let index = cards.iter().position(|v| *v == *card)?;
+            cards.remove(index);
+        }
+        let unknown = self.deck.unknown
+            + self
+                .player_cards
+                .iter()
+                .map(|player| match &**player {
+                    GamePlayerCards::Player(..) => 0,
+                    GamePlayerCards::Opponent(cards) => cards.unknown,
+                })
+                .sum::<usize>();
+        if cards.len() != unknown {
+            return None;
+        }
+        Some(cards)
+    }
+
+    fn sample_world<R: Rng>(
+        &self,
+        mut unknown_cards: Vec<Card>,
+        cards_per_player: usize,
+        rules: &Rules,
+        rng: &mut R,
+    ) -> FullGameState {
+        unknown_cards.shuffle(rng);
+        let player_cards = self
+            .player_cards
+            .iter()
+            .map(|player| match &**player {
+                GamePlayerCards::Player(cards) => cards.to_vec(),
+                GamePlayerCards::Opponent(cards) => {
+                    let mut result = cards.known.to_vec();
+                    result.extend(unknown_cards.drain(..cards.unknown));
+                    result.sort();
+                    result
+                }
+            })
+            .collect();
+        let mut deck = self.deck.known.to_vec();
+        deck.append(&mut unknown_cards);
+        deck.shuffle(rng);
+        FullGameState {
+            state_type: self.state_type.clone(),
+            player_coins: self.player_coins.clone(),
+            player_hands: self.player_hands.clone(),
+            player_cards_counter: self.player_cards_counter.clone(),
+            player_cards,
+            revealed_cards: self.revealed_cards.clone(),
+            deck,
+            cards_per_player,
+            rules: *rules,
+        }
+    }
+
+    fn with_default<
+        F: FnMut(&mut State<Arc<GamePlayerCards>, CardCollection>) -> Result<(), Error>,
+    >(
         &mut self,
+        rules: &Rules,
         mut f: F,
     ) {
         let result = f(&mut State {
@@ -600,14 +898,62 @@ impl GameState {
             player_cards: &mut self.player_cards,
             deck: &mut self.deck,
             revealed_cards: &mut self.revealed_cards,
+            rules,
         });
         self.valid = matches!(result, Ok(..));
     }
 
+    fn contains_state(&self, player_cards: &[&[Card]], deck: &[Card]) -> bool {
+        self.player_cards.iter().zip(player_cards.iter()).all(
+            |(cards, true_cards)| match &**cards {
+                GamePlayerCards::Player(cards) => cards.as_slice() == *true_cards,
+                GamePlayerCards::Opponent(cards) => {
+                    cards.len() == true_cards.len() && is_sub_multiset(&cards.known, true_cards)
+                }
+            },
+        ) && self.deck.len() == deck.len()
+            && is_sub_multiset(&self.deck.known, deck)
+    }
+
+    fn play_fast_action(&mut self, action: &Action, rules: &Rules) -> bool {
+        if self.player_hands[action.player] == 0 {
+            return false;
+        }
+        let state_type = match (&self.state_type, &action.action_type) {
+            (StateType::Turn { player }, ActionType::Income)
+                if *player == action.player
+                    && self.player_coins[*player] < rules.forced_coup_coins
+                    && rules.bank_coins.is_none() =>
+            {
+                self.player_coins[*player] += INCOME;
+                StateType::Turn {
+                    player: get_next_player(*player, &self.player_hands),
+                }
+            }
+            (StateType::ForeignAid { player }, ActionType::PassBlock)
+                if *player == action.player && rules.bank_coins.is_none() =>
+            {
+                self.player_coins[*player] += FOREIGN_AID;
+                StateType::Turn {
+                    player: get_next_player(*player, &self.player_hands),
+                }
+            }
+            (source, ActionType::Challenge) => match declare_challenge(source, action.player) {
+                Some(v) => v,
+                None => return false,
+            },
+            _ => return false,
+        };
+        self.state_type = state_type;
+        self.valid = true;
+        true
+    }
+
     fn with_pop_known_from_deck<
-        F: FnMut(&mut State<GamePlayerCards, PopKnownFromDeck>) -> Result<(), Error>,
+        F: FnMut(&mut State<Arc<GamePlayerCards>, PopKnownFromDeck>) -> Result<(), Error>,
     >(
         &mut self,
+        rules: &Rules,
         card: Card,
         mut f: F,
     ) {
@@ -622,14 +968,16 @@ impl GameState {
                 card,
             },
             revealed_cards: &mut self.revealed_cards,
+            rules,
         });
         self.valid = matches!(result, Ok(..));
     }
 
     fn with_pop_unknown_from_deck<
-        F: FnMut(&mut State<GamePlayerCards, PopUnknownFromDeck>) -> Result<(), Error>,
+        F: FnMut(&mut State<Arc<GamePlayerCards>, PopUnknownFromDeck>) -> Result<(), Error>,
     >(
         &mut self,
+        rules: &Rules,
         mut f: F,
     ) {
         let result = f(&mut State {
@@ -642,34 +990,125 @@ impl GameState {
                 deck: &mut self.deck,
             },
             revealed_cards: &mut self.revealed_cards,
+            rules,
         });
         self.valid = matches!(result, Ok(..));
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CardsTracker {
     player: usize,
-    cards_per_type: usize,
+    #[serde(alias = "cards_per_type")]
+    card_counts: CardCounts,
+    #[serde(default = "default_cards_per_player")]
+    cards_per_player: usize,
+    rules: Rules,
     game_states: Vec<GameState>,
-    last_action: Option<ActionView>,
+    #[serde(skip)]
+    pool: GameStatePool,
 }
 
 impl CardsTracker {
     pub fn new(player: usize, hand: &[Card], settings: &Settings) -> Self {
         Self {
             player,
-            cards_per_type: settings.cards_per_type,
+            card_counts: settings.card_counts,
+            cards_per_player: settings.cards_per_player,
+            rules: settings.rules,
             game_states: GameState::initial(player, hand, settings),
-            last_action: None,
+            pool: GameStatePool::default(),
+        }
+    }
+
+    pub fn from_view(view: &PlayerView, settings: &Settings) -> Self {
+        if view.step == 0 {
+            return Self::new(view.player, view.cards, settings);
+        }
+        Self {
+            player: view.player,
+            card_counts: settings.card_counts,
+            cards_per_player: settings.cards_per_player,
+            rules: settings.rules,
+            game_states: vec![GameState::from_view(view)],
+            pool: GameStatePool::default(),
         }
     }
 
+    pub fn pool_stats(&self) -> PoolStats {
+        self.pool.stats
+    }
+
+    pub fn get_accuracy(&self, game: &Game) -> TrackerAccuracy {
+        let player_cards: Vec<&[Card]> = (0..game.get_anonymous_view().player_hands.len())
+            .map(|player| game.get_player_view(player).cards)
+            .collect();
+        let mut result = TrackerAccuracy {
+            steps: 1,
+            hypotheses: self.game_states.len(),
+            max_hypotheses: self.game_states.len(),
+            true_state_found: self
+                .game_states
+                .iter()
+                .any(|v| v.contains_state(&player_cards, game.deck()))
+                as usize,
+            ..Default::default()
+        };
+        for (player, cards) in player_cards.iter().enumerate() {
+            if player == self.player || cards.is_empty() {
+                continue;
+            }
+            let distribution = self.get_hand_distribution(player);
+            let probability = distribution
+                .iter()
+                .find(|(hand, _)| hand.as_slice() == *cards)
+                .map_or(0.0, |(_, probability)| *probability);
+            result.hands += 1;
+            result.true_hand_probability += probability;
+            result.true_hand_rank += 1 + distribution
+                .iter()
+                .filter(|(_, v)| *v > probability)
+                .count();
+        }
+        result
+    }
+
+    pub fn reset(&mut self, view: &PlayerView) {
+        let pool = std::mem::take(&mut self.pool);
+        *self = Self::from_view(
+            view,
+            &Settings {
+                players_number: view.player_hands.len(),
+                card_counts: self.card_counts,
+                cards_per_player: self.cards_per_player,
+                rules: self.rules,
+            },
+        );
+        self.pool = pool;
+    }
+
+    fn compact(&mut self) {
+        self.game_states.sort();
+        let mut kept = 0;
+        for index in 0..self.game_states.len() {
+            if self.game_states[index].valid
+                && (kept == 0 || self.game_states[kept - 1] != self.game_states[index])
+            {
+                self.game_states.swap(kept, index);
+                kept += 1;
+            }
+        }
+        for state in self.game_states.drain(kept..) {
+            self.pool.release(state);
+        }
+        metrics::record_hypotheses(self.game_states.len());
+    }
+
     pub fn after_player_action(&mut self, view: &PlayerView, action: &Action) {
         for game_state in self.game_states.iter_mut() {
             if game_state.deck.len() > view.deck {
                 let card = if let GamePlayerCards::Player(cards) =
-                    &game_state.player_cards[action.player]
+                    &*game_state.player_cards[action.player]
                 {
                     view.cards
                         .iter()
@@ -687,17 +1126,21 @@ impl CardsTracker {
                     game_state.valid = false;
                     continue;
                 }
-                game_state.with_pop_known_from_deck(card, |state| {
+                game_state.with_pop_known_from_deck(&self.rules, card, |state| {
                     play_action(action, state, &mut ConstRng)
                 });
                 continue;
             }
-            game_state.with_default(|state| play_action(action, state, &mut ConstRng));
+            game_state.with_default(&self.rules, |state| {
+                play_action(action, state, &mut ConstRng)
+            });
         }
-        self.game_states.sort();
-        self.game_states.dedup();
-        self.game_states.retain(|game_state| game_state.valid);
-        self.last_action = Some(ActionView::from_action(action));
+        self.compact();
+        tracing::trace!(
+            player = self.player,
+            hypotheses = self.game_states.len(),
+            "cards tracker updated after player action"
+        );
     }
 
     pub fn after_opponent_action(&mut self, view: &PlayerView, action_view: &ActionView) {
@@ -711,7 +1154,11 @@ impl CardsTracker {
                     action_type,
                 };
                 let game_state = &mut self.game_states[i];
-                game_state.with_default(|state| play_action(&action, state, &mut ConstRng));
+                if !game_state.play_fast_action(&action, &self.rules) {
+                    game_state.with_default(&self.rules, |state| {
+                        play_action(&action, state, &mut ConstRng)
+                    });
+                }
                 continue;
             }
             if self.game_states[i].revealed_cards.len() != view.revealed_cards.len() {
@@ -724,7 +1171,9 @@ impl CardsTracker {
                     action_type,
                 };
                 let game_state = &mut self.game_states[i];
-                game_state.with_default(|state| play_action(&action, state, &mut ConstRng));
+                game_state.with_default(&self.rules, |state| {
+                    play_action(&action, state, &mut ConstRng)
+                });
                 continue;
             }
             if self.game_states[i].deck.len() < view.deck {
@@ -740,10 +1189,14 @@ impl CardsTracker {
                         player: action_view.player,
                         action_type,
                     };
-                    let mut game_state = self.game_states[i].clone();
-                    game_state.with_default(|state| play_action(&action, state, &mut ConstRng));
+                    let mut game_state = self.pool.clone_state(&self.game_states[i]);
+                    game_state.with_default(&self.rules, |state| {
+                        play_action(&action, state, &mut ConstRng)
+                    });
                     if game_state.valid {
                         self.game_states.push(game_state);
+                    } else {
+                        self.pool.release(game_state);
                     }
                 }
                 if self.game_states[i].player_cards[action_view.player].has_any() {
@@ -757,7 +1210,9 @@ impl CardsTracker {
                         action_type,
                     };
                     let game_state = &mut self.game_states[i];
-                    game_state.with_default(|state| play_action(&action, state, &mut ConstRng));
+                    game_state.with_default(&self.rules, |state| {
+                        play_action(&action, state, &mut ConstRng)
+                    });
                 } else {
                     self.game_states[i].valid = false;
                 }
@@ -773,12 +1228,16 @@ impl CardsTracker {
                         player: action_view.player,
                         action_type,
                     };
-                    let mut game_state = self.game_states[i].clone();
-                    game_state.with_pop_known_from_deck(game_state.deck.known[card], |state| {
-                        play_action(&action, state, &mut ConstRng)
-                    });
+                    let mut game_state = self.pool.clone_state(&self.game_states[i]);
+                    game_state.with_pop_known_from_deck(
+                        &self.rules,
+                        game_state.deck.known[card],
+                        |state| play_action(&action, state, &mut ConstRng),
+                    );
                     if game_state.valid {
                         self.game_states.push(game_state);
+                    } else {
+                        self.pool.release(game_state);
                     }
                 }
                 if self.game_states[i].deck.has_any() {
@@ -791,7 +1250,7 @@ impl CardsTracker {
                         action_type,
                     };
                     let game_state = &mut self.game_states[i];
-                    game_state.with_pop_unknown_from_deck(|state| {
+                    game_state.with_pop_unknown_from_deck(&self.rules, |state| {
                         play_action(&action, state, &mut ConstRng)
                     });
                 } else {
@@ -801,23 +1260,156 @@ impl CardsTracker {
             }
             panic!("Unrecognized game state change");
         }
-        self.game_states.sort();
-        self.game_states.dedup();
-        self.game_states.retain(|game_state| game_state.valid);
-        self.last_action = Some(action_view.clone());
+        self.compact();
+        tracing::trace!(
+            player = self.player,
+            hypotheses = self.game_states.len(),
+            "cards tracker updated after opponent action"
+        );
     }
 
     pub fn is_safe_action_type(&self, player: usize, action_type: &ActionType) -> bool {
         self.game_states.iter().all(|game_state| {
-            game_state.is_safe_action_type(
-                player,
-                action_type,
-                self.last_action.as_ref(),
-                self.cards_per_type,
-            )
+            game_state.is_safe_action_type(player, action_type, &self.card_counts)
         })
     }
 
+    pub fn sample_world<R: Rng>(&self, rng: &mut R) -> FullGameState {
+        let candidates: Vec<(&GameState, Vec<Card>)> = self
+            .game_states
+            .iter()
+            .filter_map(|game_state| {
+                game_state
+                    .get_unknown_cards(&self.card_counts)
+                    .map(|cards| (game_state, cards))
+            })
+            .collect();
+        let weights =
+            WeightedIndex::new(candidates.iter().map(|(_, cards)| count_completions(cards)))
+                .unwrap();
+        let (game_state, unknown_cards) = &candidates[weights.sample(rng)];
+        game_state.sample_world(
+            unknown_cards.clone(),
+            self.cards_per_player,
+            &self.rules,
+            rng,
+        )
+    }
+
+    pub fn summary(&self) -> TrackerSummary {
+        let players = self.game_states.first().map_or(0, |v| v.player_cards.len());
+        let known_cards = (0..players)
+            .map(|player| {
+                self.game_states
+                    .iter()
+                    .flat_map(|state| match &*state.player_cards[player] {
+                        GamePlayerCards::Player(cards) => cards.iter(),
+                        GamePlayerCards::Opponent(cards) => cards.known.iter(),
+                    })
+                    .cloned()
+                    .sorted()
+                    .dedup()
+                    .collect()
+            })
+            .collect();
+        TrackerSummary {
+            player: self.player,
+            states: self.game_states.len(),
+            known_cards,
+            entropy: (0..players)
+                .map(|player| self.get_hand_entropy(player))
+                .collect(),
+        }
+    }
+
+    pub fn get_hand_entropy(&self, player: usize) -> f64 {
+        get_entropy(
+            self.get_hand_distribution(player)
+                .into_iter()
+                .map(|(_, probability)| probability),
+        )
+    }
+
+    pub fn pending_claims(&self) -> Vec<PendingClaim> {
+        self.game_states
+            .first()
+            .map(|game_state| game_state.get_pending_claims())
+            .unwrap_or_default()
+    }
+
+    pub fn get_expected_information_gain(&self, action: &Action) -> f64 {
+        match &action.action_type {
+            ActionType::Challenge => {
+                let claim = match self.pending_claims().pop() {
+                    Some(v) if v.can_challenge && v.player != self.player => v,
+                    _ => return 0.0,
+                };
+                let probability = self.get_claim_probability(&claim);
+                get_entropy([probability, 1.0 - probability])
+            }
+            ActionType::Coup(target) | ActionType::Assassinate(target)
+                if *target != self.player =>
+            {
+                let mut revealed = [0.0; ALL_CARDS.len()];
+                let mut conditional = 0.0;
+                for (hand, probability) in self.get_hand_distribution(*target) {
+                    for card in hand.iter() {
+                        let index = ALL_CARDS.iter().position(|v| *v == *card).unwrap();
+                        revealed[index] += probability / hand.len() as f64;
+                    }
+                    conditional += probability
+                        * get_entropy(ALL_CARDS.iter().map(|card| {
+                            hand.iter().filter(|v| **v == *card).count() as f64 / hand.len() as f64
+                        }));
+                }
+                get_entropy(revealed) - conditional
+            }
+            _ => 0.0,
+        }
+    }
+
+    pub fn get_claim_probability(&self, claim: &PendingClaim) -> f64 {
+        self.get_any_card_probability(claim.player, &[claim.card])
+    }
+
+    pub fn get_any_card_probability(&self, player: usize, cards: &[Card]) -> f64 {
+        self.get_hand_distribution(player)
+            .into_iter()
+            .filter(|(hand, _)| hand.iter().any(|card| cards.contains(card)))
+            .map(|(_, probability)| probability)
+            .sum()
+    }
+
+    fn get_hand_distribution(&self, player: usize) -> Vec<(Vec<Card>, f64)> {
+        let mut result: Vec<(Vec<Card>, f64)> = Vec::new();
+        let mut total = 0.0;
+        for game_state in self.game_states.iter() {
+            let unknown_cards = match game_state.get_unknown_cards(&self.card_counts) {
+                Some(v) => v,
+                None => continue,
+            };
+            let weight = count_completions(&unknown_cards);
+            let (known, unknown) = match &*game_state.player_cards[player] {
+                GamePlayerCards::Player(cards) => (cards.to_vec(), 0),
+                GamePlayerCards::Opponent(cards) => (cards.known.to_vec(), cards.unknown),
+            };
+            for (drawn, probability) in get_draws(&unknown_cards, unknown) {
+                let mut hand = known.clone();
+                hand.extend(drawn);
+                hand.sort();
+                total += weight * probability;
+                match result.iter_mut().find(|(v, _)| *v == hand) {
+                    Some((_, v)) => *v += weight * probability,
+                    None => result.push((hand, weight * probability)),
+                }
+            }
+        }
+        for (_, probability) in result.iter_mut() {
+            *probability /= total;
+        }
+        result
+    }
+
     pub fn print(&self) {
         println!("player={}: {}", self.player, self.game_states.len());
         for i in 0..self.game_states.len() {
@@ -827,21 +1419,218 @@ impl CardsTracker {
     }
 }
 
+fn count_completions(cards: &[Card]) -> f64 {
+    let mut result = 1.0;
+    let mut total = 0;
+    for card in ALL_CARDS.iter() {
+        for count in 1..=cards.iter().filter(|v| **v == *card).count() {
+            total += 1;
+            result *= total as f64 / count as f64;
+        }
+    }
+    result
+}
+
+fn get_draws(cards: &[Card], size: usize) -> Vec<(Vec<Card>, f64)> {
+    let counts: Vec<usize> = ALL_CARDS
+        .iter()
+        .map(|card| cards.iter().filter(|v| **v == *card).count())
+        .collect();
+    let mut result = Vec::new();
+    add_draws(&counts, size, &mut Vec::new(), 1.0, &mut result);
+    let total = get_binomial(cards.len(), size);
+    for (_, ways) in result.iter_mut() {
+        *ways /= total;
+    }
+    result
+}
+
+fn add_draws(
+    counts: &[usize],
+    left: usize,
+    drawn: &mut Vec<Card>,
+    ways: f64,
+    result: &mut Vec<(Vec<Card>, f64)>,
+) {
+    if left == 0 {
+        result.push((drawn.clone(), ways));
+        return;
+    }
+    if counts.is_empty() {
+        return;
+    }
+    let card = ALL_CARDS[ALL_CARDS.len() - counts.len()];
+    for taken in 0..=counts[0].min(left) {
+        add_draws(
+            &counts[1..],
+            left - taken,
+            drawn,
+            ways * get_binomial(counts[0], taken),
+            result,
+        );
+        drawn.push(card);
+    }
+    drawn.truncate(drawn.len() - counts[0].min(left) - 1);
+}
+
+fn get_binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |result, i| result * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn get_entropy<I: IntoIterator<Item = f64>>(probabilities: I) -> f64 {
+    probabilities
+        .into_iter()
+        .filter(|probability| *probability > 0.0)
+        .map(|probability| probability * (1.0 / probability).log2())
+        .sum()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TrackerState {
+    pub settings: Settings,
+    pub view: GameView,
+    pub tracker: CardsTracker,
+}
+
+impl TrackerState {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read tracker state from {}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("failed to create {}: {}", path, e))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|e| format!("failed to write tracker state to {}: {}", path, e))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrackerSummary {
+    pub player: usize,
+    pub states: usize,
+    pub known_cards: Vec<Vec<Card>>,
+    pub entropy: Vec<f64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BotParams {
+    pub bluff_rate: f64,
+}
+
+impl BotParams {
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "bluff_rate" => {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(format!("bluff_rate should be in [0, 1], got {}", value));
+                }
+                self.bluff_rate = value;
+            }
+            _ => return Err(format!("invalid bot param: {}", name)),
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct HonestCarefulRandomBot {
+    params: BotParams,
     cards_tracker: CardsTracker,
+    history: ActionHistory,
+    match_history: ActionHistory,
+    lineup: Vec<usize>,
+    players_number: usize,
+    tablebase: Tablebase,
     rng: StdRng,
 }
 
 impl HonestCarefulRandomBot {
-    pub fn new(view: &PlayerView, settings: &Settings) -> Self {
+    pub fn new(view: &PlayerView, settings: &Settings, rng: StdRng) -> Self {
         Self {
-            cards_tracker: CardsTracker::new(view.player, view.cards, settings),
-            rng: make_rng_from_cards(view.cards),
+            params: BotParams::default(),
+            cards_tracker: CardsTracker::from_view(view, settings),
+            history: ActionHistory::default(),
+            match_history: ActionHistory::default(),
+            lineup: (0..settings.players_number).collect(),
+            players_number: settings.players_number,
+            tablebase: Tablebase::default(),
+            rng,
+        }
+    }
+
+    pub fn with_cards_tracker(mut self, cards_tracker: CardsTracker) -> Self {
+        self.cards_tracker = cards_tracker;
+        self
+    }
+
+    pub fn with_params(mut self, params: BotParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    fn get_bluff_action(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Option<Action> {
+        if self.params.bluff_rate <= 0.0 || !self.rng.gen_bool(self.params.bluff_rate) {
+            return None;
+        }
+        available_actions
+            .iter()
+            .filter(|action| {
+                is_allowed_action_type(&action.action_type, view.cards)
+                    && !is_honest_action_type(&action.action_type, view.cards)
+            })
+            .collect::<Vec<_>>()
+            .choose(&mut self.rng)
+            .map(|v| (*v).clone())
+    }
+
+    pub fn with_match_context(mut self, context: &MatchContext) -> Self {
+        self.match_history = context
+            .model(context.participant(self.cards_tracker.player))
+            .clone();
+        self.lineup = context.lineup().to_vec();
+        self
+    }
+
+    fn trace_history(&self) {
+        tracing::debug!(actions = self.history.len(), "action history");
+        for player in 0..self.players_number {
+            let counts: Vec<String> = HISTORY_ACTION_TYPES
+                .iter()
+                .filter_map(|action_type| {
+                    let count = self.history.count(player, action_type);
+                    let match_count = self.match_history.count(self.lineup[player], action_type);
+                    (count + match_count > 0).then(|| {
+                        format!(
+                            "{}={}/{}",
+                            get_action_type_name(action_type),
+                            count,
+                            count + match_count
+                        )
+                    })
+                })
+                .collect();
+            tracing::debug!(player, counts = ?counts, "player action history");
         }
     }
 }
 
+const HISTORY_ACTION_TYPES: [ActionType; 8] = [
+    ActionType::Tax,
+    ActionType::Exchange,
+    ActionType::Steal(0),
+    ActionType::Assassinate(0),
+    ActionType::BlockForeignAid,
+    ActionType::BlockAssassination,
+    ActionType::BlockSteal(Card::Unknown),
+    ActionType::Challenge,
+];
+
 impl Bot for HonestCarefulRandomBot {
     fn suggest_actions<'a>(
         &mut self,
@@ -868,11 +1657,14 @@ impl Bot for HonestCarefulRandomBot {
     }
 
     fn get_action(&mut self, view: &PlayerView, available_actions: &[Action]) -> Action {
-        self.suggest_actions(view, available_actions)
-            .choose(&mut self.rng)
-            .copied()
-            .unwrap()
-            .clone()
+        if let Some(action) = self.get_bluff_action(view, available_actions) {
+            return action;
+        }
+        let mut actions = self.suggest_actions(view, available_actions);
+        if actions.is_empty() {
+            actions = available_actions.iter().collect();
+        }
+        actions.choose(&mut self.rng).copied().unwrap().clone()
     }
 
     fn get_optional_action(
@@ -880,6 +1672,9 @@ impl Bot for HonestCarefulRandomBot {
         view: &PlayerView,
         available_actions: &[Action],
     ) -> Option<Action> {
+        if let Some(action) = self.get_bluff_action(view, available_actions) {
+            return Some(action);
+        }
         self.suggest_optional_actions(view, available_actions)
             .choose(&mut self.rng)
             .map(|v| (*v).clone())
@@ -893,8 +1688,235 @@ impl Bot for HonestCarefulRandomBot {
         self.cards_tracker.after_opponent_action(view, action);
     }
 
-    fn query(&self, _: &str) {
-        self.cards_tracker.print();
+    fn score_actions(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Vec<ActionScore> {
+        self.tablebase
+            .probe(view, available_actions)
+            .unwrap_or_default()
+    }
+
+    fn after_forfeit(&mut self, view: &PlayerView, _: usize) {
+        self.cards_tracker.reset(view);
+    }
+
+    fn on_game_start(&mut self, settings: &Settings, _: usize) {
+        self.history = ActionHistory::default();
+        self.players_number = settings.players_number;
+    }
+
+    fn history_mut(&mut self) -> Option<&mut ActionHistory> {
+        Some(&mut self.history)
+    }
+
+    fn cards_tracker(&self) -> Option<&CardsTracker> {
+        Some(&self.cards_tracker)
+    }
+
+    fn query(&self, command: &str) {
+        match command {
+            "history" => self.trace_history(),
+            "tablebase" => tracing::debug!(positions = self.tablebase.len(), "tablebase"),
+            _ => self.cards_tracker.print(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct InfoSeekingBot {
+    inner: HonestCarefulRandomBot,
+}
+
+impl InfoSeekingBot {
+    pub fn new(view: &PlayerView, settings: &Settings, rng: StdRng) -> Self {
+        Self {
+            inner: HonestCarefulRandomBot::new(view, settings, rng),
+        }
+    }
+
+    pub fn with_cards_tracker(self, cards_tracker: CardsTracker) -> Self {
+        Self {
+            inner: self.inner.with_cards_tracker(cards_tracker),
+        }
+    }
+
+    pub fn with_match_context(self, context: &MatchContext) -> Self {
+        Self {
+            inner: self.inner.with_match_context(context),
+        }
+    }
+
+    fn choose_action<'a>(&mut self, actions: &[&'a Action]) -> Option<&'a Action> {
+        let gains: Vec<f64> = actions
+            .iter()
+            .map(|action| {
+                self.inner
+                    .cards_tracker
+                    .get_expected_information_gain(action)
+            })
+            .collect();
+        let max_gain = gains.iter().cloned().fold(0.0, f64::max);
+        let best: Vec<&'a Action> = actions
+            .iter()
+            .zip(gains.iter())
+            .filter(|(_, gain)| **gain >= max_gain - f64::EPSILON)
+            .map(|(action, _)| *action)
+            .collect();
+        best.choose(&mut self.inner.rng).copied()
+    }
+}
+
+impl Bot for InfoSeekingBot {
+    fn suggest_actions<'a>(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        self.inner.suggest_actions(view, available_actions)
+    }
+
+    fn suggest_optional_actions<'a>(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        self.inner.suggest_optional_actions(view, available_actions)
+    }
+
+    fn get_action(&mut self, view: &PlayerView, available_actions: &[Action]) -> Action {
+        let mut actions = self.inner.suggest_actions(view, available_actions);
+        if actions.is_empty() {
+            actions = available_actions.iter().collect();
+        }
+        self.choose_action(&actions).unwrap().clone()
+    }
+
+    fn get_optional_action(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Option<Action> {
+        let actions = self.inner.suggest_optional_actions(view, available_actions);
+        self.choose_action(&actions).cloned()
+    }
+
+    fn after_player_action(&mut self, view: &PlayerView, action: &Action) {
+        self.inner.after_player_action(view, action);
+    }
+
+    fn after_opponent_action(&mut self, view: &PlayerView, action: &ActionView) {
+        self.inner.after_opponent_action(view, action);
+    }
+
+    fn score_actions(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Vec<ActionScore> {
+        self.inner.score_actions(view, available_actions)
+    }
+
+    fn after_forfeit(&mut self, view: &PlayerView, player: usize) {
+        self.inner.after_forfeit(view, player);
+    }
+
+    fn on_game_start(&mut self, settings: &Settings, player: usize) {
+        self.inner.on_game_start(settings, player);
+    }
+
+    fn history_mut(&mut self) -> Option<&mut ActionHistory> {
+        self.inner.history_mut()
+    }
+
+    fn cards_tracker(&self) -> Option<&CardsTracker> {
+        self.inner.cards_tracker()
+    }
+
+    fn query(&self, command: &str) {
+        self.inner.query(command);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimContext {
+    Action,
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PendingClaim {
+    pub player: usize,
+    pub card: Card,
+    pub context: ClaimContext,
+    pub can_challenge: bool,
+}
+
+impl PendingClaim {
+    fn action(player: usize, card: Card, can_challenge: bool) -> Self {
+        Self {
+            player,
+            card,
+            context: ClaimContext::Action,
+            can_challenge,
+        }
+    }
+
+    fn block(player: usize, card: Card) -> Self {
+        Self {
+            player,
+            card,
+            context: ClaimContext::Block,
+            can_challenge: true,
+        }
+    }
+}
+
+pub fn get_pending_claims(state_type: &StateType) -> Vec<PendingClaim> {
+    match state_type {
+        StateType::Tax { player } => vec![PendingClaim::action(*player, Card::Duke, true)],
+        StateType::Exchange { player } => {
+            vec![PendingClaim::action(*player, Card::Ambassador, true)]
+        }
+        StateType::Assassination {
+            player,
+            can_challenge,
+            ..
+        } => vec![PendingClaim::action(
+            *player,
+            Card::Assassin,
+            *can_challenge,
+        )],
+        StateType::Steal {
+            player,
+            can_challenge,
+            ..
+        } => vec![PendingClaim::action(*player, Card::Captain, *can_challenge)],
+        StateType::BlockForeignAid { player, .. } => {
+            vec![PendingClaim::block(*player, Card::Duke)]
+        }
+        StateType::BlockAssassination { player, target } => vec![
+            PendingClaim::action(*target, Card::Assassin, false),
+            PendingClaim::block(*player, Card::Contessa),
+        ],
+        StateType::BlockSteal {
+            player,
+            target,
+            card,
+        } => vec![
+            PendingClaim::action(*target, Card::Captain, false),
+            PendingClaim::block(*player, *card),
+        ],
+        StateType::Challenge { source, .. } => get_pending_claims(source)
+            .into_iter()
+            .map(|claim| PendingClaim {
+                can_challenge: false,
+                ..claim
+            })
+            .collect(),
+        _ => Vec::new(),
     }
 }
 
@@ -993,8 +2015,11 @@ impl<'a> Deck for PopUnknownFromDeck<'a> {
 mod tests {
     use std::rc::Rc;
 
+    use rand::SeedableRng;
+    use smallvec::smallvec;
+
     use crate::fsm::ChallengeState;
-    use crate::game::Game;
+    use crate::game::{get_available_actions, make_deck, Game};
 
     use super::*;
 
@@ -1002,7 +2027,9 @@ mod tests {
     fn initial_game_states_for_hand_with_equal_cards_should_be_valid() {
         let settings = Settings {
             players_number: 6,
-            cards_per_type: 3,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         for target_player in 0..settings.players_number {
             let game_states =
@@ -1024,7 +2051,7 @@ mod tests {
                     if player != target_player {
                         assert!(
                             matches!(
-                                game_state.player_cards[player],
+                                *game_state.player_cards[player],
                                 GamePlayerCards::Opponent(..)
                             ),
                             "{:?}",
@@ -1033,18 +2060,155 @@ mod tests {
                     }
                 }
                 assert_eq!(
-                    game_state.player_cards[target_player],
-                    GamePlayerCards::Player(vec![Card::Captain, Card::Captain])
+                    *game_state.player_cards[target_player],
+                    GamePlayerCards::Player(smallvec![Card::Captain, Card::Captain])
                 );
             }
         }
     }
 
+    #[test]
+    fn initial_game_states_for_three_cards_hand_should_be_valid() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(4),
+            cards_per_player: 3,
+            rules: Rules::default(),
+        };
+        let game_states =
+            GameState::initial(0, &[Card::Captain, Card::Captain, Card::Duke], &settings);
+        assert_eq!(game_states.len(), 50);
+        for game_state in game_states.iter() {
+            assert!(game_state.valid);
+            assert_eq!(game_state.deck.known.len() + game_state.deck.unknown, 11);
+            for player in 0..game_state.player_cards.len() {
+                assert_eq!(game_state.player_hands[player], 3, "{}", player);
+                assert_eq!(game_state.player_cards[player].count(), 3, "{}", player);
+            }
+        }
+    }
+
+    #[test]
+    fn game_state_pool_should_reuse_released_states() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let game_states = GameState::initial(0, &[Card::Duke, Card::Captain], &settings);
+        let mut pool = GameStatePool::default();
+        assert_eq!(pool.clone_state(&game_states[0]), game_states[0]);
+        pool.release(game_states[0].clone());
+        assert_eq!(pool.clone_state(&game_states[1]), game_states[1]);
+        assert_eq!(
+            pool.stats,
+            PoolStats {
+                allocated: 1,
+                reused: 1,
+                released: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn game_state_clone_should_share_unchanged_player_cards() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let game_state = GameState::initial(0, &[Card::Duke, Card::Captain], &settings).remove(0);
+        let mut other = game_state.clone();
+        PlayerCards::add_card(&mut other.player_cards[1], Card::Duke);
+        assert!(Arc::ptr_eq(
+            &game_state.player_cards[0],
+            &other.player_cards[0]
+        ));
+        assert!(!Arc::ptr_eq(
+            &game_state.player_cards[1],
+            &other.player_cards[1]
+        ));
+        assert!(Arc::ptr_eq(
+            &game_state.player_cards[2],
+            &other.player_cards[2]
+        ));
+        assert_eq!(
+            game_state.player_cards[1].count() + 1,
+            other.player_cards[1].count()
+        );
+    }
+
+    #[test]
+    fn play_fast_action_should_match_play_action() {
+        for rules in [
+            Rules::default(),
+            Rules {
+                bank_coins: Some(10),
+                ..Rules::default()
+            },
+        ] {
+            let settings = Settings {
+                players_number: 4,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules,
+            };
+            let mut fast_actions = 0;
+            for seed in 0..20 {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut game = Game::new(settings.clone(), &mut rng);
+                while !game.is_done() {
+                    let game_state = GameState::from_view(&game.get_player_view(0));
+                    let view = game.get_anonymous_view();
+                    let available_actions = get_available_actions(
+                        view.state_type,
+                        view.player_coins,
+                        view.player_hands,
+                        view.rules,
+                    );
+                    let candidates = (0..settings.players_number).flat_map(|player| {
+                        vec![
+                            ActionType::Income,
+                            ActionType::PassBlock,
+                            ActionType::Challenge,
+                        ]
+                        .into_iter()
+                        .map(move |action_type| Action {
+                            player,
+                            action_type,
+                        })
+                    });
+                    for action in candidates {
+                        let mut fast = game_state.clone();
+                        if !fast.play_fast_action(&action, &settings.rules) {
+                            continue;
+                        }
+                        fast_actions += 1;
+                        let mut general = game_state.clone();
+                        general.with_default(&settings.rules, |state| {
+                            play_action(&action, state, &mut ConstRng)
+                        });
+                        assert!(general.valid, "{:?} {:?}", action, game_state.state_type);
+                        assert_eq!(fast, general, "{:?} {:?}", action, rules);
+                    }
+                    let mut actions = available_actions;
+                    actions.shuffle(&mut rng);
+                    assert!(actions.iter().any(|v| game.play(v, &mut rng).is_ok()));
+                }
+            }
+            assert!(fast_actions > 0, "{:?}", rules);
+        }
+    }
+
     #[test]
     fn initial_game_states_for_hand_with_different_cards_should_be_valid() {
         let settings = Settings {
             players_number: 6,
-            cards_per_type: 3,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         for target_player in 0..settings.players_number {
             let game_states =
@@ -1066,7 +2230,7 @@ mod tests {
                     if player != target_player {
                         assert!(
                             matches!(
-                                game_state.player_cards[player],
+                                *game_state.player_cards[player],
                                 GamePlayerCards::Opponent(..)
                             ),
                             "{:?}",
@@ -1075,8 +2239,8 @@ mod tests {
                     }
                 }
                 assert_eq!(
-                    game_state.player_cards[target_player],
-                    GamePlayerCards::Player(vec![Card::Captain, Card::Duke])
+                    *game_state.player_cards[target_player],
+                    GamePlayerCards::Player(smallvec![Card::Captain, Card::Duke])
                 );
             }
         }
@@ -1087,7 +2251,9 @@ mod tests {
         let hand = vec![Card::Assassin, Card::Assassin];
         let settings = Settings {
             players_number: 2,
-            cards_per_type: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         let mut tracker = CardsTracker::new(0, &hand, &settings);
         let mut rng = StdRng::seed_from_u64(42);
@@ -1119,15 +2285,15 @@ mod tests {
                 player_hands: vec![1, 2],
                 player_cards_counter: vec![1, 2],
                 player_cards: vec![
-                    GamePlayerCards::Player(vec![Card::Assassin]),
-                    GamePlayerCards::Opponent(CardCollection {
-                        known: vec![],
+                    Arc::new(GamePlayerCards::Player(smallvec![Card::Assassin])),
+                    Arc::new(GamePlayerCards::Opponent(CardCollection {
+                        known: smallvec![],
                         unknown: 2
-                    }),
+                    })),
                 ],
                 revealed_cards: vec![Card::Assassin],
                 deck: CardCollection {
-                    known: vec![],
+                    known: smallvec![],
                     unknown: 6
                 },
             },]
@@ -1138,7 +2304,9 @@ mod tests {
     fn cards_tracker_should_reveal_opponent_cards() {
         let settings = Settings {
             players_number: 2,
-            cards_per_type: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         let mut rng = StdRng::seed_from_u64(42);
         let mut game = Game::custom(
@@ -1192,15 +2360,15 @@ mod tests {
                 player_hands: vec![2, 1],
                 player_cards_counter: vec![1, 1],
                 player_cards: vec![
-                    GamePlayerCards::Player(vec![Card::Ambassador]),
-                    GamePlayerCards::Opponent(CardCollection {
-                        known: vec![],
+                    Arc::new(GamePlayerCards::Player(smallvec![Card::Ambassador])),
+                    Arc::new(GamePlayerCards::Opponent(CardCollection {
+                        known: smallvec![],
                         unknown: 1
-                    }),
+                    })),
                 ],
                 revealed_cards: vec![Card::Assassin],
                 deck: CardCollection {
-                    known: vec![Card::Ambassador],
+                    known: smallvec![Card::Ambassador],
                     unknown: 6
                 },
             },]
@@ -1211,7 +2379,9 @@ mod tests {
     fn cards_tracker_should_pop_cards_from_deck_for_player() {
         let settings = Settings {
             players_number: 2,
-            cards_per_type: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         let mut rng = StdRng::seed_from_u64(42);
         let mut game = Game::custom(
@@ -1272,15 +2442,18 @@ mod tests {
                 player_hands: vec![2, 1],
                 player_cards_counter: vec![2, 1],
                 player_cards: vec![
-                    GamePlayerCards::Player(vec![Card::Ambassador, Card::Duke]),
-                    GamePlayerCards::Opponent(CardCollection {
-                        known: vec![],
+                    Arc::new(GamePlayerCards::Player(smallvec![
+                        Card::Ambassador,
+                        Card::Duke
+                    ])),
+                    Arc::new(GamePlayerCards::Opponent(CardCollection {
+                        known: smallvec![],
                         unknown: 1
-                    }),
+                    })),
                 ],
                 revealed_cards: vec![Card::Assassin],
                 deck: CardCollection {
-                    known: vec![Card::Ambassador],
+                    known: smallvec![Card::Ambassador],
                     unknown: 5
                 },
             },]
@@ -1291,7 +2464,9 @@ mod tests {
     fn cards_tracker_should_push_cards_to_deck_for_player() {
         let settings = Settings {
             players_number: 2,
-            cards_per_type: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         let mut rng = StdRng::seed_from_u64(42);
         let mut game = Game::custom(
@@ -1365,15 +2540,18 @@ mod tests {
                 player_hands: vec![2, 1],
                 player_cards_counter: vec![2, 1],
                 player_cards: vec![
-                    GamePlayerCards::Player(vec![Card::Captain, Card::Duke]),
-                    GamePlayerCards::Opponent(CardCollection {
-                        known: vec![],
+                    Arc::new(GamePlayerCards::Player(smallvec![
+                        Card::Captain,
+                        Card::Duke
+                    ])),
+                    Arc::new(GamePlayerCards::Opponent(CardCollection {
+                        known: smallvec![],
                         unknown: 1
-                    }),
+                    })),
                 ],
                 revealed_cards: vec![Card::Assassin],
                 deck: CardCollection {
-                    known: vec![Card::Ambassador, Card::Ambassador, Card::Duke],
+                    known: smallvec![Card::Ambassador, Card::Ambassador, Card::Duke],
                     unknown: 3
                 },
             },]
@@ -1384,7 +2562,9 @@ mod tests {
     fn cards_tracker_should_pop_cards_from_deck_for_opponent() {
         let settings = Settings {
             players_number: 2,
-            cards_per_type: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         let mut rng = StdRng::seed_from_u64(42);
         let mut game = Game::custom(
@@ -1463,15 +2643,15 @@ mod tests {
                     player_hands: vec![1, 2],
                     player_cards_counter: vec![1, 2],
                     player_cards: vec![
-                        GamePlayerCards::Player(vec![Card::Assassin]),
-                        GamePlayerCards::Opponent(CardCollection {
-                            known: vec![],
+                        Arc::new(GamePlayerCards::Player(smallvec![Card::Assassin])),
+                        Arc::new(GamePlayerCards::Opponent(CardCollection {
+                            known: smallvec![],
                             unknown: 2
-                        }),
+                        })),
                     ],
                     revealed_cards: vec![Card::Assassin],
                     deck: CardCollection {
-                        known: vec![Card::Ambassador],
+                        known: smallvec![Card::Ambassador],
                         unknown: 5
                     },
                 },
@@ -1485,15 +2665,15 @@ mod tests {
                     player_hands: vec![1, 2],
                     player_cards_counter: vec![1, 2],
                     player_cards: vec![
-                        GamePlayerCards::Player(vec![Card::Assassin]),
-                        GamePlayerCards::Opponent(CardCollection {
-                            known: vec![Card::Ambassador],
+                        Arc::new(GamePlayerCards::Player(smallvec![Card::Assassin])),
+                        Arc::new(GamePlayerCards::Opponent(CardCollection {
+                            known: smallvec![Card::Ambassador],
                             unknown: 1
-                        }),
+                        })),
                     ],
                     revealed_cards: vec![Card::Assassin],
                     deck: CardCollection {
-                        known: vec![],
+                        known: smallvec![],
                         unknown: 6
                     },
                 },
@@ -1505,7 +2685,9 @@ mod tests {
     fn cards_tracker_should_push_cards_to_deck_for_opponent() {
         let settings = Settings {
             players_number: 2,
-            cards_per_type: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
         };
         let mut rng = StdRng::seed_from_u64(42);
         let mut game = Game::custom(
@@ -1591,15 +2773,15 @@ mod tests {
                     player_hands: vec![1, 2],
                     player_cards_counter: vec![1, 2],
                     player_cards: vec![
-                        GamePlayerCards::Player(vec![Card::Assassin]),
-                        GamePlayerCards::Opponent(CardCollection {
-                            known: vec![],
+                        Arc::new(GamePlayerCards::Player(smallvec![Card::Assassin])),
+                        Arc::new(GamePlayerCards::Opponent(CardCollection {
+                            known: smallvec![],
                             unknown: 2
-                        }),
+                        })),
                     ],
                     revealed_cards: vec![Card::Assassin],
                     deck: CardCollection {
-                        known: vec![Card::Ambassador],
+                        known: smallvec![Card::Ambassador],
                         unknown: 5
                     },
                 },
@@ -1610,15 +2792,15 @@ mod tests {
                     player_hands: vec![1, 2],
                     player_cards_counter: vec![1, 2],
                     player_cards: vec![
-                        GamePlayerCards::Player(vec![Card::Assassin]),
-                        GamePlayerCards::Opponent(CardCollection {
-                            known: vec![Card::Ambassador],
+                        Arc::new(GamePlayerCards::Player(smallvec![Card::Assassin])),
+                        Arc::new(GamePlayerCards::Opponent(CardCollection {
+                            known: smallvec![Card::Ambassador],
                             unknown: 1
-                        }),
+                        })),
                     ],
                     revealed_cards: vec![Card::Assassin],
                     deck: CardCollection {
-                        known: vec![],
+                        known: smallvec![],
                         unknown: 6
                     },
                 },
@@ -1626,6 +2808,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cards_tracker_from_mid_game_view_should_track_following_actions() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Ambassador, Card::Duke],
+                vec![Card::Assassin, Card::Assassin],
+            ],
+            vec![
+                Card::Captain,
+                Card::Duke,
+                Card::Contessa,
+                Card::Ambassador,
+                Card::Captain,
+                Card::Contessa,
+            ],
+        );
+        for action in [
+            Action {
+                player: 0,
+                action_type: ActionType::Income,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Tax,
+            },
+        ] {
+            game.play(&action, &mut rng).unwrap();
+        }
+        let mut tracker = CardsTracker::from_view(&game.get_player_view(0), &settings);
+        assert_eq!(
+            tracker.pending_claims(),
+            vec![PendingClaim::action(1, Card::Duke, true)]
+        );
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::RevealCard(Card::Assassin),
+            },
+        ];
+        assert_eq!(
+            play_actions(&actions, &mut game, &mut tracker, &mut rng),
+            Ok(())
+        );
+        assert_eq!(
+            tracker.game_states,
+            vec![GameState {
+                valid: true,
+                state_type: StateType::Turn { player: 0 },
+                player_coins: vec![3, 2],
+                player_hands: vec![2, 1],
+                player_cards_counter: vec![2, 1],
+                player_cards: vec![
+                    Arc::new(GamePlayerCards::Player(smallvec![
+                        Card::Ambassador,
+                        Card::Duke
+                    ])),
+                    Arc::new(GamePlayerCards::Opponent(CardCollection {
+                        known: smallvec![],
+                        unknown: 1
+                    })),
+                ],
+                revealed_cards: vec![Card::Assassin],
+                deck: CardCollection {
+                    known: smallvec![],
+                    unknown: 6
+                },
+            },]
+        );
+    }
+
     fn play_actions<R: Rng>(
         actions: &[Action],
         game: &mut Game,
@@ -1648,4 +2911,309 @@ mod tests {
         game.print();
         Ok(())
     }
+
+    #[test]
+    fn cards_tracker_should_sample_worlds_consistent_with_view() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Ambassador, Card::Duke],
+                vec![Card::Assassin, Card::Assassin],
+            ],
+            vec![
+                Card::Captain,
+                Card::Duke,
+                Card::Contessa,
+                Card::Ambassador,
+                Card::Captain,
+                Card::Contessa,
+            ],
+        );
+        let mut tracker = CardsTracker::new(0, &[Card::Ambassador, Card::Duke], &settings);
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Income,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Tax,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::RevealCard(Card::Assassin),
+            },
+        ];
+        assert_eq!(
+            play_actions(&actions, &mut game, &mut tracker, &mut rng),
+            Ok(())
+        );
+        let view = game.get_player_view(0);
+        let mut opponent_cards = Vec::new();
+        for _ in 0..50 {
+            let world = tracker.sample_world(&mut rng);
+            let mut all_cards: Vec<Card> = world
+                .player_cards
+                .iter()
+                .flatten()
+                .chain(world.deck.iter())
+                .chain(world.revealed_cards.iter())
+                .cloned()
+                .collect();
+            all_cards.sort();
+            assert_eq!(all_cards, make_deck(2));
+            assert_eq!(world.player_cards[0], view.cards);
+            assert_eq!(world.player_cards[1].len(), 1);
+            opponent_cards.push(world.player_cards[1][0]);
+            let sampled = Game::from_full_state(world);
+            let sampled_view = sampled.get_player_view(0);
+            assert_eq!(sampled_view.state_type, view.state_type);
+            assert_eq!(sampled_view.player_coins, view.player_coins);
+            assert_eq!(sampled_view.player_hands, view.player_hands);
+            assert_eq!(sampled_view.deck, view.deck);
+        }
+        opponent_cards.sort();
+        opponent_cards.dedup();
+        assert!(opponent_cards.len() > 1);
+    }
+
+    #[test]
+    fn cards_tracker_should_measure_uncertainty_and_information_gain() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Ambassador, Card::Duke],
+                vec![Card::Assassin, Card::Assassin],
+            ],
+            vec![
+                Card::Captain,
+                Card::Duke,
+                Card::Contessa,
+                Card::Ambassador,
+                Card::Captain,
+                Card::Contessa,
+            ],
+        );
+        let mut tracker = CardsTracker::new(0, &[Card::Ambassador, Card::Duke], &settings);
+        assert_eq!(tracker.get_hand_entropy(0), 0.0);
+        let initial_entropy = tracker.get_hand_entropy(1);
+        assert!(initial_entropy > 0.0);
+        let challenge = Action {
+            player: 0,
+            action_type: ActionType::Challenge,
+        };
+        assert_eq!(tracker.get_expected_information_gain(&challenge), 0.0);
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Income,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Tax,
+            },
+        ];
+        assert_eq!(
+            play_actions(&actions, &mut game, &mut tracker, &mut rng),
+            Ok(())
+        );
+        let gain = tracker.get_expected_information_gain(&challenge);
+        assert!(gain > 0.0 && gain <= 1.0, "{}", gain);
+        let income = Action {
+            player: 0,
+            action_type: ActionType::Income,
+        };
+        assert_eq!(tracker.get_expected_information_gain(&income), 0.0);
+        let coup = Action {
+            player: 0,
+            action_type: ActionType::Coup(1),
+        };
+        let gain = tracker.get_expected_information_gain(&coup);
+        assert!(gain > 0.0 && gain <= initial_entropy, "{}", gain);
+        assert_eq!(tracker.summary().entropy.len(), 2);
+    }
+
+    #[test]
+    fn get_draws_should_sum_to_one() {
+        let cards = [Card::Duke, Card::Duke, Card::Captain, Card::Contessa];
+        let draws = get_draws(&cards, 2);
+        assert_eq!(draws.len(), 4);
+        let total: f64 = draws.iter().map(|(_, probability)| probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        let duke_pair = draws
+            .iter()
+            .find(|(drawn, _)| *drawn == vec![Card::Duke, Card::Duke])
+            .unwrap();
+        assert!((duke_pair.1 - 1.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cards_tracker_should_keep_hypotheses_after_serialization() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Ambassador, Card::Duke],
+                vec![Card::Assassin, Card::Assassin],
+            ],
+            vec![
+                Card::Captain,
+                Card::Duke,
+                Card::Contessa,
+                Card::Ambassador,
+                Card::Captain,
+                Card::Contessa,
+            ],
+        );
+        let mut tracker = CardsTracker::new(0, &[Card::Ambassador, Card::Duke], &settings);
+        let first = [
+            Action {
+                player: 0,
+                action_type: ActionType::Income,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Tax,
+            },
+        ];
+        assert_eq!(
+            play_actions(&first, &mut game, &mut tracker, &mut rng),
+            Ok(())
+        );
+        let mut restored: CardsTracker =
+            serde_json::from_str(&serde_json::to_string(&tracker).unwrap()).unwrap();
+        assert_eq!(restored.game_states, tracker.game_states);
+        let second = [
+            Action {
+                player: 0,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::RevealCard(Card::Assassin),
+            },
+        ];
+        let mut restored_game = game.clone();
+        assert_eq!(
+            play_actions(&second, &mut game, &mut tracker, &mut rng),
+            Ok(())
+        );
+        assert_eq!(
+            play_actions(&second, &mut restored_game, &mut restored, &mut rng),
+            Ok(())
+        );
+        assert_eq!(restored.game_states, tracker.game_states);
+    }
+
+    #[test]
+    fn pending_claims_should_follow_block_challenge_chain() {
+        let steal = StateType::Steal {
+            player: 0,
+            target: 1,
+            can_challenge: false,
+        };
+        assert_eq!(
+            get_pending_claims(&steal),
+            vec![PendingClaim::action(0, Card::Captain, false)]
+        );
+        let block = StateType::BlockSteal {
+            player: 1,
+            target: 0,
+            card: Card::Ambassador,
+        };
+        assert_eq!(
+            get_pending_claims(&block),
+            vec![
+                PendingClaim::action(0, Card::Captain, false),
+                PendingClaim::block(1, Card::Ambassador),
+            ]
+        );
+        let challenge = StateType::Challenge {
+            current_player: 0,
+            source: Rc::new(block.clone()),
+            state: ChallengeState::Initial {
+                initiator: 0,
+                target: 1,
+                card: Card::Ambassador,
+            },
+        };
+        assert!(get_pending_claims(&challenge)
+            .iter()
+            .all(|claim| !claim.can_challenge));
+        let game_state = GameState {
+            state_type: block,
+            ..GameState::initial(
+                0,
+                &[Card::Ambassador, Card::Ambassador],
+                &Settings {
+                    players_number: 2,
+                    card_counts: CardCounts::uniform(2),
+                    cards_per_player: 2,
+                    rules: Rules::default(),
+                },
+            )
+            .remove(0)
+        };
+        assert!(game_state.is_safe_action_type(0, &ActionType::Challenge, &CardCounts::uniform(2)));
+    }
+
+    #[test]
+    fn notify_bot_should_record_actions_in_history() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::new(settings.clone(), &mut rng);
+        let view = game.get_player_view(1);
+        let mut bot = HonestCarefulRandomBot::new(&view, &settings, StdRng::seed_from_u64(42));
+        bot.on_game_start(&settings, 1);
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::ForeignAid,
+            },
+            Action {
+                player: 2,
+                action_type: ActionType::BlockForeignAid,
+            },
+            Action {
+                player: 2,
+                action_type: ActionType::PassChallenge,
+            },
+        ];
+        for action in actions.iter() {
+            game.play(action, &mut rng).unwrap();
+            notify_bot(&mut bot, &game.get_player_view(1), action);
+        }
+        let history = bot.history_mut().unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.count(2, &ActionType::BlockForeignAid), 1);
+        assert_eq!(history.count(0, &ActionType::BlockForeignAid), 0);
+        assert_eq!(history.count(0, &ActionType::ForeignAid), 1);
+        assert_eq!(history.count(2, &ActionType::PassChallenge), 1);
+    }
 }
@@ -0,0 +1,161 @@
+use crate::bots::{get_pending_claims, CardsTracker, PendingClaim};
+use crate::fsm::{StateType, COUP_COST, FOREIGN_AID, MAX_STEAL, TAX};
+use crate::game::PlayerView;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChallengeOdds {
+    pub claim: PendingClaim,
+    pub probability: f64,
+    pub challenge_cost: f64,
+    pub pass_cost: f64,
+    pub break_even: f64,
+}
+
+impl ChallengeOdds {
+    pub fn should_challenge(&self) -> bool {
+        self.probability < self.break_even
+    }
+
+    pub fn print(&self) {
+        println!(
+            "claim: player {} has {:?} ({:?})",
+            self.claim.player, self.claim.card, self.claim.context
+        );
+        println!("probability claim is true: {:.3}", self.probability);
+        println!(
+            "expected cost in influence: challenge={:.3} pass={:.3}",
+            self.challenge_cost, self.pass_cost
+        );
+        println!("break-even probability: {:.3}", self.break_even);
+        println!(
+            "recommendation: {}",
+            if self.should_challenge() {
+                "challenge"
+            } else {
+                "pass"
+            }
+        );
+    }
+}
+
+pub fn get_challenge_odds(tracker: &CardsTracker, view: &PlayerView) -> Option<ChallengeOdds> {
+    let claim = get_pending_claims(view.state_type)
+        .into_iter()
+        .rfind(|claim| claim.can_challenge && claim.player != view.player)?;
+    let probability = tracker.get_claim_probability(&claim);
+    let damage = get_damage(view);
+    let gain = get_claimant_loss(view);
+    Some(ChallengeOdds {
+        claim,
+        probability,
+        challenge_cost: probability * (1.0 + damage) - (1.0 - probability) * gain,
+        pass_cost: damage,
+        break_even: (damage + gain) / (1.0 + damage + gain),
+    })
+}
+
+fn get_damage(view: &PlayerView) -> f64 {
+    let coins = |coins: usize| coins as f64 / COUP_COST as f64;
+    match view.state_type {
+        StateType::Tax { .. } => coins(TAX),
+        StateType::Assassination { target, .. } if *target == view.player => 1.0,
+        StateType::Steal { target, .. } => {
+            let stolen = coins(view.player_coins[*target].min(MAX_STEAL));
+            if *target == view.player {
+                2.0 * stolen
+            } else {
+                stolen
+            }
+        }
+        StateType::BlockForeignAid { target, .. } if *target == view.player => coins(FOREIGN_AID),
+        StateType::BlockAssassination { target, .. } if *target == view.player => 1.0,
+        StateType::BlockSteal { player, target, .. } if *target == view.player => {
+            2.0 * coins(view.player_coins[*player].min(MAX_STEAL))
+        }
+        _ => 0.0,
+    }
+}
+
+fn get_claimant_loss(view: &PlayerView) -> f64 {
+    match view.state_type {
+        StateType::BlockAssassination { player, .. } => view.player_hands[*player].min(2) as f64,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::bots::notify_tracker;
+    use crate::fsm::{Action, ActionType, Card, Rules};
+    use crate::game::{CardCounts, Game, Settings};
+
+    use super::*;
+
+    fn get_odds(player_cards: Vec<Vec<Card>>, deck: Vec<Card>) -> Option<ChallengeOdds> {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::custom(player_cards, deck);
+        let mut tracker = CardsTracker::from_view(&game.get_player_view(0), &settings);
+        for action in [
+            Action {
+                player: 0,
+                action_type: ActionType::Income,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Tax,
+            },
+        ]
+        .iter()
+        {
+            game.play(action, &mut rng).unwrap();
+            notify_tracker(&mut tracker, &game.get_player_view(0), action);
+        }
+        get_challenge_odds(&tracker, &game.get_player_view(0))
+    }
+
+    #[test]
+    fn challenge_odds_should_use_tracker_claim_probability() {
+        let rest = vec![
+            Card::Assassin,
+            Card::Assassin,
+            Card::Ambassador,
+            Card::Ambassador,
+            Card::Captain,
+            Card::Contessa,
+        ];
+        let odds = get_odds(
+            vec![
+                vec![Card::Duke, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+            ],
+            rest.clone(),
+        )
+        .unwrap();
+        assert_eq!(odds.claim.player, 1);
+        assert_eq!(odds.claim.card, Card::Duke);
+        assert_eq!(odds.probability, 0.0);
+        assert_eq!(odds.pass_cost, TAX as f64 / COUP_COST as f64);
+        assert!((odds.challenge_cost + 1.0).abs() < 1e-9);
+        assert!(odds.should_challenge());
+        let odds = get_odds(
+            vec![
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Duke, Card::Duke],
+            ],
+            rest,
+        )
+        .unwrap();
+        assert!(odds.probability > 0.0 && odds.probability < 1.0);
+        let at_break_even = odds.break_even * (1.0 + odds.pass_cost) - (1.0 - odds.break_even);
+        assert!((at_break_even - odds.pass_cost).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::{open_file, InputError, LineReader};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub step: usize,
+    pub player: usize,
+    pub text: String,
+}
+
+pub fn get_chat_path(replay: &Path) -> PathBuf {
+    replay.with_extension("chat.jsonl")
+}
+
+pub fn read_replay_chat(replay: &str) -> Result<Vec<ChatMessage>, InputError> {
+    let path = get_chat_path(Path::new(replay));
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut file = LineReader::new(open_file(&path.to_string_lossy())?);
+    let mut messages = Vec::new();
+    while let Some(message) = file.read()? {
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_replay_chat_should_read_messages_stored_next_to_replay() {
+        let dir = std::env::temp_dir().join(format!("coup-chat-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let replay = dir.join("42.jsonl");
+        assert_eq!(get_chat_path(&replay), dir.join("42.chat.jsonl"));
+        assert_eq!(
+            read_replay_chat(&replay.to_string_lossy()).unwrap(),
+            Vec::new()
+        );
+        let message = ChatMessage {
+            step: 3,
+            player: 1,
+            text: String::from("I have a Duke"),
+        };
+        std::fs::write(
+            get_chat_path(&replay),
+            serde_json::to_string(&message).unwrap() + "\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_replay_chat(&replay.to_string_lossy()).unwrap(),
+            vec![message]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
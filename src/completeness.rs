@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use crate::bots::is_allowed_action_type;
+use crate::encoding::get_action_space_size;
+use crate::fsm::{Action, ConstRng, StateType};
+use crate::game::{available_actions_iter, Game};
+use crate::run::get_action_type_name;
+
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
+pub enum DiscrepancyKind {
+    Missing,
+    Extra,
+}
+
+#[derive(Debug, Clone)]
+pub struct Discrepancy {
+    pub kind: DiscrepancyKind,
+    pub state_type: StateType,
+    pub action: Action,
+}
+
+#[derive(Default)]
+pub struct CompletenessReport {
+    states: usize,
+    actions: usize,
+    discrepancies: BTreeMap<(DiscrepancyKind, &'static str, &'static str), (usize, Discrepancy)>,
+}
+
+impl CompletenessReport {
+    pub fn is_empty(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+
+    pub fn add(&mut self, game: &Game) {
+        let discrepancies = check_available_actions(game);
+        self.states += 1;
+        self.actions += get_action_space_size(&game.get_settings());
+        for discrepancy in discrepancies {
+            let key = (
+                discrepancy.kind,
+                get_state_type_name(&discrepancy.state_type),
+                get_action_type_name(&discrepancy.action.action_type),
+            );
+            self.discrepancies.entry(key).or_insert((0, discrepancy)).0 += 1;
+        }
+    }
+
+    pub fn print(&self) {
+        println!("states: {}", self.states);
+        println!("actions: {}", self.actions);
+        println!(
+            "discrepancies: {}",
+            self.discrepancies.values().map(|(v, _)| *v).sum::<usize>()
+        );
+        for ((kind, state_type, action_type), (count, example)) in self.discrepancies.iter() {
+            println!(
+                "{:?} {} {} {} example: {:?} in {:?}",
+                kind, state_type, action_type, count, example.action, example.state_type
+            );
+        }
+    }
+}
+
+pub fn check_available_actions(game: &Game) -> Vec<Discrepancy> {
+    let settings = game.get_settings();
+    let view = game.get_anonymous_view();
+    let mut available = vec![false; get_action_space_size(&settings)];
+    for action in available_actions_iter(
+        view.state_type,
+        view.player_coins,
+        view.player_hands,
+        view.rules,
+    ) {
+        available[action.to_index(&settings)] = true;
+    }
+    let mut discrepancies = Vec::new();
+    for (index, available) in available.into_iter().enumerate() {
+        let action = Action::from_index(index, &settings).unwrap();
+        let legal = game.clone().play(&action, &mut ConstRng).is_ok();
+        let kind = if legal && !available {
+            DiscrepancyKind::Missing
+        } else if !legal
+            && available
+            && is_allowed_action_type(
+                &action.action_type,
+                game.get_player_view(action.player).cards,
+            )
+        {
+            DiscrepancyKind::Extra
+        } else {
+            continue;
+        };
+        discrepancies.push(Discrepancy {
+            kind,
+            state_type: view.state_type.clone(),
+            action,
+        });
+    }
+    discrepancies
+}
+
+pub fn get_state_type_name(state_type: &StateType) -> &'static str {
+    match state_type {
+        StateType::Turn { .. } => "Turn",
+        StateType::ForeignAid { .. } => "ForeignAid",
+        StateType::Tax { .. } => "Tax",
+        StateType::Exchange { .. } => "Exchange",
+        StateType::Assassination { .. } => "Assassination",
+        StateType::Steal { .. } => "Steal",
+        StateType::BlockForeignAid { .. } => "BlockForeignAid",
+        StateType::BlockSteal { .. } => "BlockSteal",
+        StateType::BlockAssassination { .. } => "BlockAssassination",
+        StateType::Challenge { .. } => "Challenge",
+        StateType::NeedCards { .. } => "NeedCards",
+        StateType::TookCards { .. } => "TookCards",
+        StateType::DroppedCard { .. } => "DroppedCard",
+        StateType::LostInfluence { .. } => "LostInfluence",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    use crate::fsm::Rules;
+    use crate::game::{get_available_actions, CardCounts, Settings};
+
+    use super::*;
+
+    #[test]
+    fn available_actions_should_match_legal_actions_in_random_games() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut report = CompletenessReport::default();
+        for _ in 0..10 {
+            let mut game = Game::new(
+                Settings {
+                    players_number: 3,
+                    card_counts: CardCounts::uniform(2),
+                    cards_per_player: 2,
+                    rules: Rules::default(),
+                },
+                &mut rng,
+            );
+            while !game.is_done() {
+                report.add(&game);
+                let view = game.get_anonymous_view();
+                let mut actions: Vec<Action> = get_available_actions(
+                    view.state_type,
+                    view.player_coins,
+                    view.player_hands,
+                    view.rules,
+                )
+                .into_iter()
+                .filter(|action| {
+                    is_allowed_action_type(
+                        &action.action_type,
+                        game.get_player_view(action.player).cards,
+                    )
+                })
+                .collect();
+                actions.shuffle(&mut rng);
+                assert!(actions
+                    .iter()
+                    .any(|action| game.play(action, &mut rng).is_ok()));
+            }
+        }
+        if !report.is_empty() {
+            report.print();
+        }
+        assert!(report.is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+use std::ffi::OsString;
+
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, Command};
+
+pub fn apply_config(command: Command, args: Vec<OsString>) -> Vec<OsString> {
+    let matches = command
+        .clone()
+        .try_get_matches_from(&args)
+        .unwrap_or_else(|e| e.exit());
+    let path = match matches.get_one::<String>("config") {
+        Some(v) => v.clone(),
+        None => return args,
+    };
+    match load_config_args(&command, &matches, &path) {
+        Ok(config_args) => args.into_iter().chain(config_args).collect(),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn load_config_args(
+    command: &Command,
+    matches: &ArgMatches,
+    path: &str,
+) -> Result<Vec<OsString>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config file {}: {}", path, e))?;
+    let config: toml::value::Table = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse config file {}: {}", path, e))?;
+    let (subcommand_name, subcommand_matches) = matches.subcommand().unwrap();
+    let subcommand = command.find_subcommand(subcommand_name).unwrap();
+    let mut result = Vec::new();
+    for (key, value) in config.iter() {
+        if let toml::Value::Table(table) = value {
+            if command.find_subcommand(key).is_none() {
+                return Err(format!("Unknown config section: {}", key));
+            }
+            if key != subcommand_name {
+                continue;
+            }
+            for (key, value) in table.iter() {
+                if is_global_arg(command, key) {
+                    add_arg(command, matches, key, value, &mut result)
+                } else {
+                    add_arg(subcommand, subcommand_matches, key, value, &mut result)
+                }
+                .map_err(|e| format!("Invalid config section {}: {}", subcommand_name, e))?;
+            }
+        } else {
+            add_arg(command, matches, key, value, &mut result)?;
+        }
+    }
+    Ok(result)
+}
+
+fn is_global_arg(command: &Command, key: &str) -> bool {
+    let id = key.replace('-', "_");
+    command
+        .get_arguments()
+        .any(|arg| arg.get_id() == id.as_str() && arg.is_global_set())
+}
+
+fn add_arg(
+    command: &Command,
+    matches: &ArgMatches,
+    key: &str,
+    value: &toml::Value,
+    result: &mut Vec<OsString>,
+) -> Result<(), String> {
+    let id = key.replace('-', "_");
+    let arg = command
+        .get_arguments()
+        .find(|arg| arg.get_id() == id.as_str() && id != "config")
+        .ok_or_else(|| format!("unknown config key: {}", key))?;
+    let long = arg
+        .get_long()
+        .ok_or_else(|| format!("positional argument can't be configured: {}", key))?;
+    if matches.value_source(&id) == Some(ValueSource::CommandLine) {
+        return Ok(());
+    }
+    if id == "seed" && matches.value_source("seed_phrase") == Some(ValueSource::CommandLine) {
+        return Ok(());
+    }
+    let values = match value {
+        toml::Value::Array(values) => values.iter().collect(),
+        _ => vec![value],
+    };
+    for value in values {
+        let value = match value {
+            toml::Value::String(v) => v.clone(),
+            toml::Value::Integer(v) => v.to_string(),
+            toml::Value::Float(v) => v.to_string(),
+            toml::Value::Boolean(v) => {
+                if !matches!(arg.get_action(), ArgAction::SetTrue) {
+                    return Err(format!("boolean value for non-flag key: {}", key));
+                }
+                if *v {
+                    result.push(OsString::from(format!("--{}", long)));
+                }
+                continue;
+            }
+            _ => return Err(format!("unsupported value type for key: {}", key)),
+        };
+        result.push(OsString::from(format!("--{}", long)));
+        result.push(OsString::from(value));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+
+    use crate::seed::{apply_seed_phrase, hash_seed_phrase};
+    use crate::{Args, Command as ArgsCommand};
+
+    use super::*;
+
+    fn parse_simulate_seed(config: &str, args: &[&str]) -> u64 {
+        let path = std::env::temp_dir().join(format!(
+            "coup-config-{}-{}.toml",
+            std::process::id(),
+            hash_seed_phrase(config)
+        ));
+        std::fs::write(&path, config).unwrap();
+        let args: Vec<OsString> = ["coup", "--config", path.to_str().unwrap(), "simulate"]
+            .iter()
+            .chain(args.iter())
+            .map(OsString::from)
+            .collect();
+        let args = apply_config(Args::command(), args);
+        let args = Args::parse_from(apply_seed_phrase(Args::command(), args));
+        std::fs::remove_file(&path).unwrap();
+        match args.command {
+            ArgsCommand::Simulate(params) => params.seed,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn config_seed_phrase_should_derive_seed() {
+        let seed = hash_seed_phrase("friday");
+        assert_eq!(parse_simulate_seed("seed_phrase = \"friday\"\n", &[]), seed);
+        assert_eq!(
+            parse_simulate_seed("[simulate]\nseed_phrase = \"friday\"\n", &[]),
+            seed
+        );
+        assert_eq!(
+            parse_simulate_seed("[simulate]\nseed = 5\n", &["--seed-phrase", "friday"]),
+            seed
+        );
+        assert_eq!(parse_simulate_seed("[simulate]\nseed = 5\n", &[]), 5);
+    }
+}
@@ -0,0 +1,203 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+
+use crate::fsm::{Action, Card, StateType};
+use crate::game::{Game, Scenario};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConformanceCase {
+    pub rule: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(flatten)]
+    pub scenario: Scenario,
+    pub actions: Vec<Action>,
+    pub expected: Expectation,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    #[serde(default)]
+    pub rejected: Option<usize>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub state_type: Option<StateType>,
+    #[serde(default)]
+    pub player_coins: Option<Vec<usize>>,
+    #[serde(default)]
+    pub player_hands: Option<Vec<usize>>,
+    #[serde(default)]
+    pub player_cards: Option<Vec<Vec<Card>>>,
+    #[serde(default)]
+    pub revealed_cards: Option<Vec<Card>>,
+    #[serde(default)]
+    pub deck: Option<Vec<Card>>,
+    #[serde(default)]
+    pub winner: Option<usize>,
+}
+
+impl ConformanceCase {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read conformance case from {}: {}", path, e))
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        let mut game = Game::from_scenario(&self.scenario)?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut mismatches = Vec::new();
+        match (
+            game.play_all(&self.actions, &mut rng),
+            self.expected.rejected,
+        ) {
+            (Ok(()), None) => (),
+            (Ok(()), Some(index)) => mismatches.push(format!(
+                "action {} {:?} is accepted but should be rejected",
+                index, self.actions[index]
+            )),
+            (Err((index, error)), expected) => {
+                if expected != Some(index) {
+                    mismatches.push(format!(
+                        "action {} {:?} is rejected: {}",
+                        index, self.actions[index], error
+                    ));
+                } else if let Some(expected) = self.expected.error.as_ref() {
+                    if !error.contains(expected.as_str()) {
+                        mismatches.push(format!(
+                            "action {} is rejected with {} instead of {}",
+                            index, error, expected
+                        ));
+                    }
+                }
+            }
+        }
+        let view = game.get_public_view();
+        check_field(
+            "state_type",
+            &self.expected.state_type,
+            view.state_type,
+            &mut mismatches,
+        );
+        check_field(
+            "player_coins",
+            &self.expected.player_coins,
+            &view.player_coins.to_vec(),
+            &mut mismatches,
+        );
+        check_field(
+            "player_hands",
+            &self.expected.player_hands,
+            &view.player_hands.to_vec(),
+            &mut mismatches,
+        );
+        let player_cards: Vec<Vec<Card>> = (0..view.player_hands.len())
+            .map(|player| game.get_player_view(player).cards.to_vec())
+            .collect();
+        check_field(
+            "player_cards",
+            &self.expected.player_cards,
+            &player_cards,
+            &mut mismatches,
+        );
+        check_field(
+            "revealed_cards",
+            &self.expected.revealed_cards,
+            &view.revealed_cards.to_vec(),
+            &mut mismatches,
+        );
+        check_field(
+            "deck",
+            &self.expected.deck,
+            &game.deck().to_vec(),
+            &mut mismatches,
+        );
+        if let Some(winner) = self.expected.winner {
+            if game.get_winner() != Some(winner) {
+                mismatches.push(format!(
+                    "winner is {:?}, expected {}",
+                    game.get_winner(),
+                    winner
+                ));
+            }
+        }
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches.join("; "))
+        }
+    }
+}
+
+fn check_field<T: PartialEq + std::fmt::Debug>(
+    name: &str,
+    expected: &Option<T>,
+    actual: &T,
+    mismatches: &mut Vec<String>,
+) {
+    if let Some(expected) = expected {
+        if expected != actual {
+            mismatches.push(format!("{} is {:?}, expected {:?}", name, actual, expected));
+        }
+    }
+}
+
+pub fn read_conformance_cases(dir: &str) -> Result<Vec<(String, ConformanceCase)>, String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read directory {}: {}", dir, e))?
+        .filter_map(|entry| entry.ok().map(|v| v.path()))
+        .filter(|path| path.is_file() && path.extension() == Some("json".as_ref()))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            ConformanceCase::load(&path.to_string_lossy()).map(|case| (name, case))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conformance_cases_should_pass() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/conformance");
+        let cases = read_conformance_cases(dir).unwrap();
+        assert!(!cases.is_empty());
+        let failed: Vec<String> = cases
+            .iter()
+            .filter_map(|(name, case)| case.check().err().map(|e| format!("{}: {}", name, e)))
+            .collect();
+        assert_eq!(failed, Vec::<String>::new());
+    }
+
+    #[test]
+    fn check_should_report_unexpected_outcome() {
+        let case: ConformanceCase = serde_json::from_str(
+            r#"{
+                "rule": "Income",
+                "player_cards": [["Duke"], ["Contessa"]],
+                "deck": ["Assassin", "Ambassador", "Captain"],
+                "revealed_cards": ["Assassin", "Ambassador", "Captain", "Contessa", "Duke"],
+                "actions": [{"player": 0, "action_type": "Income"}],
+                "expected": {"player_coins": [2, 2], "rejected": 0}
+            }"#,
+        )
+        .unwrap();
+        let error = case.check().unwrap_err();
+        assert!(error.contains("should be rejected"), "{}", error);
+        assert!(error.contains("player_coins is [3, 2]"), "{}", error);
+    }
+}
@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Instant;
+
+use serenity::async_trait;
+use serenity::builder::CreateMessage;
+use serenity::http::Http;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, UserId};
+use serenity::prelude::{Client, Context, EventHandler, GatewayIntents};
+use tokio::sync::oneshot;
+
+use crate::run::BotType;
+use crate::table::{AutoPass, Recipient, Reply, TableManager};
+
+const PREFIX: &str = "!coup";
+
+struct Request {
+    channel: String,
+    user: String,
+    user_name: String,
+    text: String,
+    reply: oneshot::Sender<Vec<Reply>>,
+}
+
+struct Handler {
+    requests: mpsc::Sender<Request>,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn message(&self, ctx: Context, msg: Message) {
+        if msg.author.bot {
+            return;
+        }
+        let text = match msg.content.strip_prefix(PREFIX) {
+            Some(v) => String::from(v.trim()),
+            None => return,
+        };
+        let (sender, receiver) = oneshot::channel();
+        let request = Request {
+            channel: msg.channel_id.to_string(),
+            user: msg.author.id.to_string(),
+            user_name: msg.author.name.clone(),
+            text,
+            reply: sender,
+        };
+        if self.requests.send(request).is_err() {
+            return;
+        }
+        let replies = match receiver.await {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        send_replies(&ctx.http, msg.channel_id, replies).await;
+    }
+}
+
+async fn send_replies(http: &Http, channel: ChannelId, replies: Vec<Reply>) {
+    for reply in replies {
+        let result = match reply.recipient {
+            Recipient::Channel => channel.say(http, &reply.text).await.map(|_| ()),
+            Recipient::User(user) => match user.parse::<u64>() {
+                Ok(id) => UserId::new(id)
+                    .direct_message(http, CreateMessage::new().content(&reply.text))
+                    .await
+                    .map(|_| ()),
+                Err(e) => {
+                    tracing::warn!(user = %user, error = %e, "invalid discord user id");
+                    continue;
+                }
+            },
+        };
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "failed to send discord message");
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_discord_bot(
+    token: &str,
+    cards_per_type: usize,
+    cards_per_player: usize,
+    bot_type: BotType,
+    seed: Option<u64>,
+    spectator_delay: usize,
+    replay_dir: Option<PathBuf>,
+    auto_pass: AutoPass,
+) -> Result<(), String> {
+    let (sender, receiver) = mpsc::channel::<Request>();
+    let (expired_sender, mut expired_receiver) =
+        tokio::sync::mpsc::unbounded_channel::<(String, Vec<Reply>)>();
+    std::thread::spawn(move || {
+        let mut tables = TableManager::new(cards_per_type, bot_type, seed)
+            .with_cards_per_player(cards_per_player)
+            .with_spectator_delay(spectator_delay)
+            .with_replay_dir(replay_dir)
+            .with_auto_pass(auto_pass);
+        loop {
+            // Wakes up at the nearest response window deadline to auto pass on idle tables.
+            let request = match tables.next_deadline() {
+                Some(deadline) => {
+                    match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(v) => v,
+                        Err(RecvTimeoutError::Timeout) => {
+                            for expired in tables.expire(Instant::now()) {
+                                if expired_sender.send(expired).is_err() {
+                                    return;
+                                }
+                            }
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                None => match receiver.recv() {
+                    Ok(v) => v,
+                    Err(_) => return,
+                },
+            };
+            let replies = tables.handle(
+                &request.channel,
+                &request.user,
+                &request.user_name,
+                &request.text,
+            );
+            let _ = request.reply.send(replies);
+        }
+    });
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| format!("failed to start runtime: {}", e))?;
+    runtime.block_on(async {
+        let intents = GatewayIntents::GUILD_MESSAGES
+            | GatewayIntents::DIRECT_MESSAGES
+            | GatewayIntents::MESSAGE_CONTENT;
+        let mut client = Client::builder(token, intents)
+            .event_handler(Handler { requests: sender })
+            .await
+            .map_err(|e| format!("failed to create discord client: {}", e))?;
+        let http = client.http.clone();
+        tokio::spawn(async move {
+            while let Some((channel, replies)) = expired_receiver.recv().await {
+                match channel.parse::<u64>() {
+                    Ok(id) => send_replies(&http, ChannelId::new(id), replies).await,
+                    Err(e) => {
+                        tracing::warn!(channel = %channel, error = %e, "invalid discord channel id")
+                    }
+                }
+            }
+        });
+        client
+            .start()
+            .await
+            .map_err(|e| format!("discord client failed: {}", e))
+    })
+}
@@ -0,0 +1,208 @@
+use crate::fsm::{Action, ActionType, Card};
+use crate::game::Settings;
+
+const CARDS: [Card; 6] = [
+    Card::Unknown,
+    Card::Assassin,
+    Card::Ambassador,
+    Card::Captain,
+    Card::Contessa,
+    Card::Duke,
+];
+
+const SIMPLE_ACTION_TYPES: [ActionType; 11] = [
+    ActionType::Income,
+    ActionType::ForeignAid,
+    ActionType::Tax,
+    ActionType::Exchange,
+    ActionType::BlockForeignAid,
+    ActionType::BlockAssassination,
+    ActionType::PassChallenge,
+    ActionType::PassBlock,
+    ActionType::Challenge,
+    ActionType::TakeCard,
+    ActionType::ShuffleDeck,
+];
+
+const TARGET_ACTION_TYPES: usize = 3;
+const CARD_ACTION_TYPES: usize = 4;
+
+pub fn get_action_space_size(settings: &Settings) -> usize {
+    settings.players_number * get_player_action_space_size(settings.players_number)
+}
+
+fn get_player_action_space_size(players_number: usize) -> usize {
+    SIMPLE_ACTION_TYPES.len()
+        + TARGET_ACTION_TYPES * players_number
+        + CARD_ACTION_TYPES * CARDS.len()
+}
+
+fn get_card_index(card: &Card) -> usize {
+    CARDS.iter().position(|v| v == card).unwrap()
+}
+
+impl Action {
+    pub fn to_index(&self, settings: &Settings) -> usize {
+        let players_number = settings.players_number;
+        assert!(
+            self.player < players_number,
+            "Invalid player {} for {} players",
+            self.player,
+            players_number
+        );
+        if let ActionType::Coup(target)
+        | ActionType::Assassinate(target)
+        | ActionType::Steal(target) = &self.action_type
+        {
+            assert!(
+                *target < players_number,
+                "Invalid target {} for {} players",
+                target,
+                players_number
+            );
+        }
+        let simple = SIMPLE_ACTION_TYPES.len();
+        let cards = simple + TARGET_ACTION_TYPES * players_number;
+        let index = match &self.action_type {
+            ActionType::Coup(target) => simple + *target,
+            ActionType::Assassinate(target) => simple + players_number + *target,
+            ActionType::Steal(target) => simple + 2 * players_number + *target,
+            ActionType::BlockSteal(card) => cards + get_card_index(card),
+            ActionType::ShowCard(card) => cards + CARDS.len() + get_card_index(card),
+            ActionType::RevealCard(card) => cards + 2 * CARDS.len() + get_card_index(card),
+            ActionType::DropCard(card) => cards + 3 * CARDS.len() + get_card_index(card),
+            action_type => SIMPLE_ACTION_TYPES
+                .iter()
+                .position(|v| v == action_type)
+                .unwrap(),
+        };
+        self.player * get_player_action_space_size(players_number) + index
+    }
+
+    pub fn from_index(index: usize, settings: &Settings) -> Option<Self> {
+        let players_number = settings.players_number;
+        let player_size = get_player_action_space_size(players_number);
+        let player = index / player_size;
+        if player >= players_number {
+            return None;
+        }
+        let mut index = index % player_size;
+        if index < SIMPLE_ACTION_TYPES.len() {
+            return Some(Action {
+                player,
+                action_type: SIMPLE_ACTION_TYPES[index].clone(),
+            });
+        }
+        index -= SIMPLE_ACTION_TYPES.len();
+        if index < TARGET_ACTION_TYPES * players_number {
+            let target = index % players_number;
+            let action_type = match index / players_number {
+                0 => ActionType::Coup(target),
+                1 => ActionType::Assassinate(target),
+                _ => ActionType::Steal(target),
+            };
+            return Some(Action {
+                player,
+                action_type,
+            });
+        }
+        index -= TARGET_ACTION_TYPES * players_number;
+        let card = CARDS[index % CARDS.len()];
+        let action_type = match index / CARDS.len() {
+            0 => ActionType::BlockSteal(card),
+            1 => ActionType::ShowCard(card),
+            2 => ActionType::RevealCard(card),
+            _ => ActionType::DropCard(card),
+        };
+        Some(Action {
+            player,
+            action_type,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    fn make_settings(players_number: usize) -> Settings {
+        Settings {
+            players_number,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        }
+    }
+
+    #[test]
+    fn from_index_and_to_index_should_round_trip_whole_action_space() {
+        for players_number in 2..=6 {
+            let settings = make_settings(players_number);
+            let size = get_action_space_size(&settings);
+            for index in 0..size {
+                let action = Action::from_index(index, &settings).unwrap();
+                assert_eq!(action.to_index(&settings), index, "{:?}", action);
+            }
+            assert_eq!(Action::from_index(size, &settings), None);
+        }
+    }
+
+    #[test]
+    fn to_index_and_from_index_should_round_trip_all_targets() {
+        for players_number in 2..=6 {
+            let settings = make_settings(players_number);
+            for player in 0..players_number {
+                for target in 0..players_number {
+                    for action_type in [
+                        ActionType::Coup(target),
+                        ActionType::Assassinate(target),
+                        ActionType::Steal(target),
+                    ] {
+                        let action = Action {
+                            player,
+                            action_type,
+                        };
+                        assert_eq!(
+                            Action::from_index(action.to_index(&settings), &settings),
+                            Some(action.clone()),
+                            "{:?}",
+                            action
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid target 3 for 3 players")]
+    fn to_index_should_reject_out_of_range_target() {
+        Action {
+            player: 0,
+            action_type: ActionType::Coup(3),
+        }
+        .to_index(&make_settings(3));
+    }
+
+    #[test]
+    fn action_space_should_have_unique_actions() {
+        let settings = make_settings(3);
+        let actions: Vec<Action> = (0..get_action_space_size(&settings))
+            .map(|index| Action::from_index(index, &settings).unwrap())
+            .collect();
+        for (index, action) in actions.iter().enumerate() {
+            assert!(!actions[index + 1..].contains(action), "{:?}", action);
+        }
+        assert!(actions.contains(&Action {
+            player: 2,
+            action_type: ActionType::DropCard(Card::Duke),
+        }));
+        assert!(actions.contains(&Action {
+            player: 1,
+            action_type: ActionType::Steal(0),
+        }));
+    }
+}
@@ -0,0 +1,192 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::bots::Bot;
+use crate::fsm::Card;
+use crate::game::{Game, Scenario, Settings};
+use crate::run::{make_bot, make_bots_rng, run_game, BotType, TimeControl};
+use crate::stats::get_wilson_interval;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityStats {
+    pub games: usize,
+    pub wins: usize,
+    pub seat_games: Vec<usize>,
+    pub seat_wins: Vec<usize>,
+}
+
+impl EquityStats {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+}
+
+pub fn estimate_equity(
+    cards: &[Card],
+    bot_type: BotType,
+    field: BotType,
+    games: usize,
+    seed: u64,
+    settings: &Settings,
+) -> Result<EquityStats, String> {
+    let rest = get_rest_deck(cards, settings)?;
+    let mut stats = EquityStats {
+        games: 0,
+        wins: 0,
+        seat_games: vec![0; settings.players_number],
+        seat_wins: vec![0; settings.players_number],
+    };
+    for index in 0..games {
+        let seat = index % settings.players_number;
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(index as u64));
+        let mut deck = rest.clone();
+        deck.shuffle(&mut rng);
+        let player_cards: Vec<Vec<Card>> = (0..settings.players_number)
+            .map(|player| {
+                if player == seat {
+                    cards.to_vec()
+                } else {
+                    deck.split_off(deck.len() - settings.cards_per_player)
+                }
+            })
+            .collect();
+        let mut game = Game::from_scenario(&Scenario {
+            player_cards,
+            deck,
+            player_coins: None,
+            revealed_cards: Vec::new(),
+            state_type: None,
+            cards_per_player: settings.cards_per_player,
+            rules: settings.rules,
+            card_counts: Some(settings.card_counts),
+        })?;
+        let mut bots_rng = make_bots_rng(rng.gen());
+        let mut bots: Vec<Box<dyn Bot>> = (0..settings.players_number)
+            .map(|player| {
+                let player_bot_type = if player == seat { bot_type } else { field };
+                make_bot(
+                    player_bot_type,
+                    &game.get_player_view(player),
+                    settings,
+                    &mut bots_rng,
+                )
+            })
+            .collect();
+        run_game(
+            &mut bots,
+            &mut game,
+            &mut rng,
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        stats.games += 1;
+        stats.seat_games[seat] += 1;
+        if game.get_winner() == Some(seat) {
+            stats.wins += 1;
+            stats.seat_wins[seat] += 1;
+        }
+    }
+    Ok(stats)
+}
+
+fn get_rest_deck(cards: &[Card], settings: &Settings) -> Result<Vec<Card>, String> {
+    if cards.len() != settings.cards_per_player {
+        return Err(format!(
+            "Starting hand should have {} cards, got {:?}",
+            settings.cards_per_player, cards
+        ));
+    }
+    let mut deck = settings.card_counts.make_deck();
+    for card in cards.iter() {
+        match deck.iter().position(|v| v == card) {
+            Some(position) => {
+                deck.remove(position);
+            }
+            None => {
+                return Err(format!(
+                    "Starting hand {:?} has more than {} {:?} cards",
+                    cards,
+                    settings.card_counts.get(*card),
+                    card
+                ))
+            }
+        }
+    }
+    Ok(deck)
+}
+
+pub fn print_equity(stats: &EquityStats, cards: &[Card], bot_type: BotType, field: BotType) {
+    let (low, high) = get_wilson_interval(stats.wins, stats.games);
+    println!(
+        "{:?} {:?} vs {:?}: games={} wins={} win_rate={:.3} 95%_ci=[{:.3}, {:.3}] baseline={:.3}",
+        cards,
+        bot_type,
+        field,
+        stats.games,
+        stats.wins,
+        stats.win_rate(),
+        low,
+        high,
+        1.0 / stats.seat_games.len() as f64
+    );
+    for (seat, games) in stats.seat_games.iter().enumerate() {
+        println!(
+            "seat {} games={} wins={} win_rate={:.3}",
+            seat,
+            games,
+            stats.seat_wins[seat],
+            stats.seat_wins[seat] as f64 / (*games).max(1) as f64
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    #[test]
+    fn estimate_equity_should_rotate_seats_and_validate_hand() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let cards = [Card::Duke, Card::Contessa];
+        let stats =
+            estimate_equity(&cards, BotType::Random, BotType::Random, 7, 42, &settings).unwrap();
+        assert_eq!(stats.games, 7);
+        assert_eq!(stats.seat_games, vec![3, 2, 2]);
+        assert!(stats.wins <= 7);
+        assert_eq!(stats.wins, stats.seat_wins.iter().sum::<usize>());
+        let settings = Settings {
+            card_counts: CardCounts::uniform(1),
+            cards_per_player: 2,
+            ..settings
+        };
+        assert!(estimate_equity(
+            &[Card::Duke, Card::Duke],
+            BotType::Random,
+            BotType::Random,
+            1,
+            42,
+            &settings
+        )
+        .is_err());
+        assert!(estimate_equity(
+            &[Card::Duke],
+            BotType::Random,
+            BotType::Random,
+            1,
+            42,
+            &settings
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use serde::{Deserialize, Serialize};
+
+use crate::fsm::{COUP_COST, MAX_COINS};
+use crate::game::{PlayerView, ALL_CARDS};
+
+pub trait Evaluator {
+    fn evaluate(&self, view: &PlayerView, player: usize) -> f64;
+
+    fn get_win_probabilities(&self, view: &PlayerView) -> Vec<f64> {
+        let scores: Vec<f64> = (0..view.player_hands.len())
+            .map(|player| {
+                if view.player_hands[player] == 0 {
+                    0.0
+                } else {
+                    self.evaluate(view, player).exp()
+                }
+            })
+            .collect();
+        let sum: f64 = scores.iter().sum();
+        scores.iter().map(|v| v / sum).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Features {
+    pub coins: f64,
+    pub influence: f64,
+    pub claim_credibility: f64,
+    pub coup_threat: f64,
+}
+
+impl Features {
+    pub fn new(view: &PlayerView, player: usize) -> Self {
+        let coins = view.player_coins[player];
+        Self {
+            coins: coins.min(MAX_COINS) as f64 / MAX_COINS as f64,
+            influence: view.player_hands[player] as f64,
+            claim_credibility: get_claim_credibility(view, player),
+            coup_threat: coins.min(COUP_COST) as f64 / COUP_COST as f64,
+        }
+    }
+}
+
+fn get_claim_credibility(view: &PlayerView, player: usize) -> f64 {
+    let claims = &view.claims[player];
+    if claims.is_empty() {
+        return 0.0;
+    }
+    let cards_number =
+        view.player_cards.iter().sum::<usize>() + view.revealed_cards.len() + view.deck;
+    let cards_per_type = cards_number / ALL_CARDS.len();
+    let credible = claims
+        .iter()
+        .filter(|card| view.revealed_cards.iter().filter(|v| v == card).count() < cards_per_type)
+        .count();
+    credible as f64 / claims.len() as f64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weights {
+    pub coins: f64,
+    pub influence: f64,
+    pub claim_credibility: f64,
+    pub coup_threat: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            coins: 0.5,
+            influence: 1.0,
+            claim_credibility: 0.25,
+            coup_threat: 0.5,
+        }
+    }
+}
+
+impl Weights {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read eval weights from {}: {}", path, e))
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LinearEvaluator {
+    weights: Weights,
+}
+
+impl LinearEvaluator {
+    pub fn new(weights: Weights) -> Self {
+        Self { weights }
+    }
+}
+
+impl Evaluator for LinearEvaluator {
+    fn evaluate(&self, view: &PlayerView, player: usize) -> f64 {
+        let features = Features::new(view, player);
+        self.weights.coins * features.coins
+            + self.weights.influence * features.influence
+            + self.weights.claim_credibility * features.claim_credibility
+            + self.weights.coup_threat * features.coup_threat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::{Card, Rules, StateType};
+
+    use super::*;
+
+    #[test]
+    fn linear_evaluator_should_prefer_stronger_player() {
+        let claims = vec![vec![Card::Duke], vec![Card::Captain]];
+        let view = PlayerView {
+            step: 10,
+            turn: 5,
+            round: 2,
+            player: 0,
+            coins: 7,
+            cards: &[Card::Duke],
+            state_type: &StateType::Turn { player: 0 },
+            player_coins: &[7, 2],
+            player_hands: &[1, 2],
+            player_cards: &[1, 2],
+            revealed_cards: &[Card::Captain, Card::Captain, Card::Assassin],
+            deck: 4,
+            rules: &Rules::default(),
+            claims: &claims,
+            remaining_time: None,
+        };
+        assert_eq!(
+            Features::new(&view, 0),
+            Features {
+                coins: 0.7,
+                influence: 1.0,
+                claim_credibility: 1.0,
+                coup_threat: 1.0,
+            }
+        );
+        assert_eq!(Features::new(&view, 1).claim_credibility, 0.0);
+        let evaluator = LinearEvaluator::new(Weights {
+            coins: 0.0,
+            influence: 1.0,
+            claim_credibility: 0.0,
+            coup_threat: 0.0,
+        });
+        assert!(evaluator.evaluate(&view, 1) > evaluator.evaluate(&view, 0));
+        let probabilities = LinearEvaluator::default().get_win_probabilities(&view);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let weights: Weights = serde_json::from_str(r#"{"coins": 2.0}"#).unwrap();
+        assert_eq!(weights.coins, 2.0);
+        assert_eq!(weights.influence, Weights::default().influence);
+    }
+}
@@ -0,0 +1,221 @@
+use std::io::Write;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::fsm::Card;
+use crate::game::{Decision, Game, Settings};
+use crate::interactive::to_game_command;
+use crate::run::{run_game_with_bots, BotType, TimeControl};
+
+const RESET: &str = "\x1B[0m";
+const BOLD: &str = "\x1B[1m";
+const DIM: &str = "\x1B[2m";
+const YELLOW: &str = "\x1B[33m";
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+
+#[derive(Debug, Clone)]
+pub struct ExhibitOptions {
+    pub delay: Duration,
+    pub wait_enter: bool,
+    pub color: bool,
+}
+
+pub fn run_exhibit<W: Write>(
+    seed: u64,
+    bot_types: &[BotType],
+    settings: Settings,
+    options: &ExhibitOptions,
+    output: &mut W,
+) -> std::io::Result<Option<usize>> {
+    let result = run_game_with_bots(
+        seed,
+        bot_types,
+        settings.clone(),
+        false,
+        None,
+        0,
+        TimeControl::default(),
+    );
+    let player_names: Vec<String> = (0..bot_types.len()).map(|v| v.to_string()).collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new(settings, &mut rng);
+    render(&game, bot_types, &player_names, None, options, output)?;
+    for decision in result.end.decisions() {
+        pause(options)?;
+        let (player, message) = match decision {
+            Decision::Play(action) => {
+                assert_eq!(game.play(action, &mut rng), Ok(()));
+                (action.player, to_game_command(action, &player_names))
+            }
+            Decision::Forfeit(player) => {
+                game.forfeit(*player);
+                (*player, format!("{} forfeits", player_names[*player]))
+            }
+        };
+        render(
+            &game,
+            bot_types,
+            &player_names,
+            Some((player, &message)),
+            options,
+            output,
+        )?;
+    }
+    Ok(game.get_winner())
+}
+
+fn pause(options: &ExhibitOptions) -> std::io::Result<()> {
+    if options.wait_enter {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+    } else if !options.delay.is_zero() {
+        std::thread::sleep(options.delay);
+    }
+    Ok(())
+}
+
+fn render<W: Write>(
+    game: &Game,
+    bot_types: &[BotType],
+    player_names: &[String],
+    last: Option<(usize, &str)>,
+    options: &ExhibitOptions,
+    output: &mut W,
+) -> std::io::Result<()> {
+    let paint = |style: &str, text: &str| -> String {
+        if options.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            String::from(text)
+        }
+    };
+    if options.color {
+        write!(output, "{}", CLEAR_SCREEN)?;
+    } else {
+        writeln!(output, "----")?;
+    }
+    let view = game.get_anonymous_view();
+    writeln!(
+        output,
+        "round: {} turn: {} step: {}",
+        view.round, view.turn, view.step
+    )?;
+    if let Some((_, message)) = last {
+        writeln!(output, "{}", paint(BOLD, message))?;
+    }
+    writeln!(output, "state_type: {:?}", view.state_type)?;
+    if let Some(bank) = game.bank() {
+        writeln!(output, "bank: {}", bank)?;
+    }
+    writeln!(
+        output,
+        "deck size: {} revealed cards: {}",
+        view.deck,
+        format_cards(view.revealed_cards, options.color)
+    )?;
+    let winner = game.get_winner();
+    writeln!(output, "players:")?;
+    for (player, player_name) in player_names.iter().enumerate() {
+        let marker = if last.map(|(v, _)| v) == Some(player) {
+            "->"
+        } else {
+            "  "
+        };
+        let line = format!(
+            "{} {}) {} {:?} coins={} cards={}",
+            marker,
+            player,
+            player_name,
+            bot_types[player],
+            view.player_coins[player],
+            format_cards(game.get_player_view(player).cards, options.color)
+        );
+        if winner == Some(player) {
+            writeln!(
+                output,
+                "{} {}",
+                paint(YELLOW, &line),
+                paint(YELLOW, "winner")
+            )?;
+        } else if view.player_hands[player] == 0 {
+            writeln!(output, "{}", paint(DIM, &line))?;
+        } else {
+            writeln!(output, "{}", line)?;
+        }
+    }
+    output.flush()
+}
+
+fn format_cards(cards: &[Card], color: bool) -> String {
+    let cards: Vec<String> = cards
+        .iter()
+        .map(|card| {
+            if color {
+                format!("{}{:?}{}", get_card_color(*card), card, RESET)
+            } else {
+                format!("{:?}", card)
+            }
+        })
+        .collect();
+    format!("[{}]", cards.join(", "))
+}
+
+fn get_card_color(card: Card) -> &'static str {
+    match card {
+        Card::Assassin => "\x1B[90m",
+        Card::Ambassador => "\x1B[32m",
+        Card::Captain => "\x1B[34m",
+        Card::Contessa => "\x1B[31m",
+        Card::Duke => "\x1B[35m",
+        Card::Unknown => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    #[test]
+    fn run_exhibit_should_render_every_step_until_winner() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let bot_types = [
+            BotType::Random,
+            BotType::HonestCarefulRandom,
+            BotType::Random,
+        ];
+        let options = ExhibitOptions {
+            delay: Duration::ZERO,
+            wait_enter: false,
+            color: false,
+        };
+        let mut output = Vec::new();
+        let winner = run_exhibit(42, &bot_types, settings.clone(), &options, &mut output).unwrap();
+        let result = run_game_with_bots(
+            42,
+            &bot_types,
+            settings,
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        assert_eq!(winner, result.end.get_winner());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output.matches("----").count(),
+            result.end.decisions().count() + 1
+        );
+        assert!(output.contains(" winner"));
+        assert!(!output.contains('\x1B'));
+    }
+}
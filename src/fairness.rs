@@ -0,0 +1,68 @@
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reveal {
+    pub seed: u64,
+    pub salt: u128,
+}
+
+impl Reveal {
+    pub fn commit(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.to_le_bytes());
+        hasher.update(self.salt.to_le_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|v| format!("{:02x}", v))
+            .collect()
+    }
+
+    pub fn verify(&self, commitment: &str) -> Result<(), String> {
+        let expected = self.commit();
+        if !expected.eq_ignore_ascii_case(commitment) {
+            return Err(format!(
+                "commitment {} doesn't match seed {} and salt {:032x}, expected {}",
+                commitment, self.seed, self.salt, expected
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_salt(value: &str) -> Result<u128, String> {
+    u128::from_str_radix(value, 16).map_err(|e| format!("invalid salt {}: {}", value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_should_match_own_commitment_only() {
+        let reveal = Reveal {
+            seed: 42,
+            salt: 0x0123_4567_89ab_cdef_0123_4567_89ab_cdef,
+        };
+        let commitment = reveal.commit();
+        assert_eq!(commitment.len(), 64);
+        assert_eq!(reveal.verify(&commitment), Ok(()));
+        assert_eq!(reveal.verify(&commitment.to_uppercase()), Ok(()));
+        let other = Reveal {
+            seed: 43,
+            ..reveal.clone()
+        };
+        assert!(other.verify(&commitment).is_err());
+        let other = Reveal {
+            salt: reveal.salt + 1,
+            ..reveal
+        };
+        assert!(other.verify(&commitment).is_err());
+    }
+
+    #[test]
+    fn parse_salt_should_read_hex() {
+        assert_eq!(parse_salt("ff"), Ok(255));
+        assert!(parse_salt("salt").is_err());
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::{BufRead, Read, Result};
+use std::time::Duration;
+
+pub struct Follow<F: BufRead> {
+    inner: F,
+    poll_interval: Duration,
+}
+
+impl<F: BufRead> Follow<F> {
+    pub fn new(inner: F, poll_interval: Duration) -> Self {
+        Self {
+            inner,
+            poll_interval,
+        }
+    }
+}
+
+impl<F: BufRead> Read for Follow<F> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let available = self.fill_buf()?;
+        let size = available.len().min(buf.len());
+        buf[..size].copy_from_slice(&available[..size]);
+        self.consume(size);
+        Ok(size)
+    }
+}
+
+impl<F: BufRead> BufRead for Follow<F> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        while self.inner.fill_buf()?.is_empty() {
+            std::thread::sleep(self.poll_interval);
+        }
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
@@ -5,6 +5,7 @@ use itertools::Itertools;
 use rand::seq::SliceRandom;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 
 pub const CARDS_PER_PLAYER: usize = 2;
 pub const MAX_CARDS_TO_EXCHANGE: usize = 2;
@@ -16,6 +17,61 @@ pub const MAX_STEAL: usize = 2;
 pub const COUP_COST: usize = 7;
 pub const MAX_COINS: usize = 10;
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Rules {
+    pub forced_coup_coins: usize,
+    #[serde(default)]
+    pub response_priority: ResponsePriority,
+    #[serde(default)]
+    pub bank_coins: Option<usize>,
+    #[serde(default = "default_cards_to_exchange")]
+    pub cards_to_exchange: usize,
+}
+
+fn default_cards_to_exchange() -> usize {
+    MAX_CARDS_TO_EXCHANGE
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            forced_coup_coins: MAX_COINS,
+            response_priority: ResponsePriority::default(),
+            bank_coins: None,
+            cards_to_exchange: MAX_CARDS_TO_EXCHANGE,
+        }
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum ResponsePriority {
+    #[default]
+    TurnOrder,
+    Seat,
+    Random,
+}
+
+pub const ALL_RESPONSE_PRIORITIES: [ResponsePriority; 3] = [
+    ResponsePriority::TurnOrder,
+    ResponsePriority::Seat,
+    ResponsePriority::Random,
+];
+
+impl FromStr for ResponsePriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "seat" => Ok(ResponsePriority::Seat),
+            "turn_order" => Ok(ResponsePriority::TurnOrder),
+            "random" => Ok(ResponsePriority::Random),
+            _ => Err(format!("invalid response priority: {}", s)),
+        }
+    }
+}
+
 pub struct ConstRng;
 
 impl rand::RngCore for ConstRng {
@@ -60,13 +116,13 @@ impl FromStr for Card {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Action {
     pub player: usize,
     pub action_type: ActionType,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum ActionType {
     Income,
     ForeignAid,
@@ -98,9 +154,11 @@ pub enum Error {
     NotEnoughCoins,
     TooManyCoins,
     InactivePlayer,
+    EmptyDeck,
+    EmptyBank,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum StateType {
     Turn {
         player: usize,
@@ -160,6 +218,8 @@ pub enum StateType {
     },
 }
 
+pub type Cards = SmallVec<[Card; CARDS_PER_PLAYER + MAX_CARDS_TO_EXCHANGE]>;
+
 pub trait PlayerCards {
     fn has_card(&self, card: Card) -> bool;
     fn count(&self) -> usize;
@@ -191,6 +251,30 @@ impl PlayerCards for Vec<Card> {
     }
 }
 
+impl PlayerCards for Cards {
+    fn has_card(&self, card: Card) -> bool {
+        self.contains(&card)
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn add_card(&mut self, card: Card) {
+        self.push(card);
+        self.sort();
+    }
+
+    fn drop_card(&mut self, card: Card) {
+        let index = self
+            .iter()
+            .find_position(|v| **v == card)
+            .map(|(index, _)| index)
+            .unwrap();
+        self.remove(index);
+    }
+}
+
 pub trait Deck {
     fn count(&self) -> usize;
     fn pop_card(&mut self) -> Card;
@@ -225,6 +309,7 @@ pub struct State<'a, P: PlayerCards + Sized, D: Deck> {
     pub player_cards: &'a mut [P],
     pub deck: &'a mut D,
     pub revealed_cards: &'a mut Vec<Card>,
+    pub rules: &'a Rules,
 }
 
 pub fn play_action<'a, P, D, R>(
@@ -241,17 +326,29 @@ where
         return Err(Error::InactivePlayer);
     }
     let new_state_type = match &state.state_type {
-        StateType::Turn { player } => {
-            on_turn(*player, state.player_coins, state.player_hands, action)
-        }
-        StateType::ForeignAid { player } => {
-            on_foreign_aid(*player, state.player_coins, state.player_hands, action)
-        }
-        StateType::Tax { player } => {
-            on_tax(*player, state.player_coins, state.player_hands, action)
-        }
+        StateType::Turn { player } => on_turn(
+            *player,
+            state.player_coins,
+            state.player_hands,
+            state.rules,
+            action,
+        ),
+        StateType::ForeignAid { player } => on_foreign_aid(
+            *player,
+            state.player_coins,
+            state.player_hands,
+            state.rules,
+            action,
+        ),
+        StateType::Tax { player } => on_tax(
+            *player,
+            state.player_coins,
+            state.player_hands,
+            state.rules,
+            action,
+        ),
         StateType::Exchange { player } => {
-            on_exchange(*player, state.player_hands, state.deck, action)
+            on_exchange(*player, state.player_hands, state.deck, state.rules, action)
         }
         StateType::Assassination {
             player,
@@ -284,6 +381,7 @@ where
             state.player_cards,
             state.deck,
             state.revealed_cards,
+            state.rules,
             action,
             rng,
         ),
@@ -346,17 +444,28 @@ fn on_turn(
     player: usize,
     player_coins: &mut [usize],
     player_hands: &[usize],
+    rules: &Rules,
     action: &Action,
 ) -> Result<StateType, Error> {
     if player != action.player {
         return Err(Error::InvalidPlayer);
     }
-    if player_coins[player] >= MAX_COINS && !matches!(action.action_type, ActionType::Coup(..)) {
+    if player_coins[player] >= rules.forced_coup_coins
+        && !matches!(action.action_type, ActionType::Coup(..))
+    {
         return Err(Error::TooManyCoins);
     }
+    if get_bank(rules, player_coins) == Some(0)
+        && matches!(
+            action.action_type,
+            ActionType::Income | ActionType::ForeignAid | ActionType::Tax
+        )
+    {
+        return Err(Error::EmptyBank);
+    }
     match &action.action_type {
         ActionType::Income => {
-            player_coins[player] += INCOME;
+            player_coins[player] += withdraw(INCOME, player_coins, rules);
             Ok(StateType::Turn {
                 player: get_next_player(player, player_hands),
             })
@@ -409,6 +518,7 @@ fn on_foreign_aid(
     player: usize,
     player_coins: &mut [usize],
     player_hands: &[usize],
+    rules: &Rules,
     action: &Action,
 ) -> Result<StateType, Error> {
     match &action.action_type {
@@ -416,7 +526,7 @@ fn on_foreign_aid(
             if player != action.player {
                 return Err(Error::InvalidPlayer);
             }
-            player_coins[player] += FOREIGN_AID;
+            player_coins[player] += withdraw(FOREIGN_AID, player_coins, rules);
             Ok(StateType::Turn {
                 player: get_next_player(player, player_hands),
             })
@@ -438,6 +548,7 @@ fn on_tax(
     player: usize,
     player_coins: &mut [usize],
     player_hands: &[usize],
+    rules: &Rules,
     action: &Action,
 ) -> Result<StateType, Error> {
     match &action.action_type {
@@ -445,7 +556,7 @@ fn on_tax(
             if player != action.player {
                 return Err(Error::InvalidPlayer);
             }
-            player_coins[player] += TAX;
+            player_coins[player] += withdraw(TAX, player_coins, rules);
             Ok(StateType::Turn {
                 player: get_next_player(player, player_hands),
             })
@@ -472,6 +583,7 @@ fn on_exchange<D: Deck>(
     player: usize,
     player_hands: &[usize],
     deck: &D,
+    rules: &Rules,
     action: &Action,
 ) -> Result<StateType, Error> {
     match &action.action_type {
@@ -479,7 +591,7 @@ fn on_exchange<D: Deck>(
             if player != action.player {
                 return Err(Error::InvalidPlayer);
             }
-            start_exchange(player, player_hands, deck)
+            start_exchange(player, player_hands, deck, rules)
         }
         ActionType::Challenge => {
             if player == action.player {
@@ -651,6 +763,7 @@ fn on_challenge<P, D, R>(
     player_cards: &mut [P],
     deck: &mut D,
     revealed_cards: &mut Vec<Card>,
+    rules: &Rules,
     action: &Action,
     rng: &mut R,
 ) -> Result<StateType, Error>
@@ -671,7 +784,7 @@ where
     )? {
         ChallengeState::TookCard => match &**source {
             StateType::Tax { player } => {
-                player_coins[*player] += TAX;
+                player_coins[*player] += withdraw(TAX, player_coins, rules);
                 Ok(StateType::Turn {
                     player: get_next_player(current_player, player_hands),
                 })
@@ -681,7 +794,7 @@ where
             | StateType::BlockSteal { .. } => Ok(StateType::Turn {
                 player: get_next_player(current_player, player_hands),
             }),
-            StateType::Exchange { player } => start_exchange(*player, player_hands, deck),
+            StateType::Exchange { player } => start_exchange(*player, player_hands, deck, rules),
             StateType::Assassination { player, target, .. } => Ok(StateType::Assassination {
                 player: *player,
                 target: *target,
@@ -775,6 +888,9 @@ where
     }
     match &action.action_type {
         ActionType::TakeCard => {
+            if deck.count() == 0 {
+                return Err(Error::EmptyDeck);
+            }
             player_cards[player].add_card(deck.pop_card());
             player_cards_counter[player] += 1;
             if count == 1 {
@@ -978,8 +1094,9 @@ fn start_exchange<D: Deck>(
     player: usize,
     player_hands: &[usize],
     deck: &D,
+    rules: &Rules,
 ) -> Result<StateType, Error> {
-    match MAX_CARDS_TO_EXCHANGE.min(deck.count()) {
+    match rules.cards_to_exchange.min(deck.count()) {
         0 => Ok(StateType::Turn {
             player: get_next_player(player, player_hands),
         }),
@@ -987,14 +1104,174 @@ fn start_exchange<D: Deck>(
     }
 }
 
-fn get_next_player(mut player: usize, player_hands: &[usize]) -> usize {
+// Moves the game on after the player has left it. A forfeit of the turn player ends the turn,
+// other players count as passed and an action targeting them is cancelled with a refund.
+pub fn on_forfeit(
+    player: usize,
+    turn_player: usize,
+    state_type: &StateType,
+    player_coins: &mut [usize],
+    player_hands: &[usize],
+) -> StateType {
+    let next_turn = || StateType::Turn {
+        player: get_next_player(turn_player, player_hands),
+    };
+    if player == turn_player {
+        return next_turn();
+    }
+    match state_type {
+        StateType::Assassination {
+            player: current_player,
+            target,
+            ..
+        }
+        | StateType::BlockAssassination {
+            player: target,
+            target: current_player,
+        } if *target == player => {
+            player_coins[*current_player] += ASSASSINATION_COST;
+            next_turn()
+        }
+        StateType::Steal { target, .. } | StateType::BlockSteal { player: target, .. }
+            if *target == player =>
+        {
+            next_turn()
+        }
+        StateType::BlockForeignAid {
+            player: blocker,
+            target,
+        } if *blocker == player => StateType::ForeignAid { player: *target },
+        StateType::LostInfluence {
+            player: target,
+            current_player,
+        } if *target == player => StateType::Turn {
+            player: get_next_player(*current_player, player_hands),
+        },
+        StateType::Challenge {
+            current_player,
+            source,
+            state,
+        } => match state {
+            ChallengeState::Initial {
+                initiator, target, ..
+            } if *initiator == player || *target == player => {
+                on_forfeit(player, turn_player, source, player_coins, player_hands)
+            }
+            ChallengeState::ShownCard { target, .. }
+            | ChallengeState::InitiatorRevealedCard { target }
+            | ChallengeState::DeckShuffled { target }
+                if *target == player =>
+            {
+                on_forfeit(player, turn_player, source, player_coins, player_hands)
+            }
+            ChallengeState::ShownCard { initiator, target } if *initiator == player => {
+                StateType::Challenge {
+                    current_player: *current_player,
+                    source: source.clone(),
+                    state: ChallengeState::InitiatorRevealedCard { target: *target },
+                }
+            }
+            _ => state_type.clone(),
+        },
+        _ => state_type.clone(),
+    }
+}
+
+pub fn get_next_player(mut player: usize, player_hands: &[usize]) -> usize {
     while player_hands[(player + 1) % player_hands.len()] == 0 {
         player += 1
     }
     (player + 1) % player_hands.len()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq)]
+pub fn get_bank(rules: &Rules, player_coins: &[usize]) -> Option<usize> {
+    rules
+        .bank_coins
+        .map(|v| v.saturating_sub(player_coins.iter().sum()))
+}
+
+fn withdraw(coins: usize, player_coins: &[usize], rules: &Rules) -> usize {
+    get_bank(rules, player_coins).map_or(coins, |bank| coins.min(bank))
+}
+
+// Picks one of the optional responses collected from all responders. Turn order counts from the
+// player after the claimant, seat prefers the lowest seat index.
+pub fn resolve_responses<'a, R: Rng>(
+    responses: &'a [Action],
+    claimant: usize,
+    players_number: usize,
+    priority: ResponsePriority,
+    rng: &mut R,
+) -> Option<&'a Action> {
+    match priority {
+        ResponsePriority::Seat => responses.iter().min_by_key(|v| v.player),
+        ResponsePriority::TurnOrder => responses
+            .iter()
+            .min_by_key(|v| (v.player + players_number - claimant) % players_number),
+        ResponsePriority::Random => responses.choose(rng),
+    }
+}
+
+// Finds the pass of the player whose claim or action is open for a challenge or block.
+pub fn get_claimant_pass<'a>(
+    state_type: &StateType,
+    available_actions: &'a [Action],
+) -> Option<&'a Action> {
+    let claimant = match state_type {
+        StateType::ForeignAid { player }
+        | StateType::Tax { player }
+        | StateType::Exchange { player }
+        | StateType::Assassination { player, .. }
+        | StateType::Steal { player, .. }
+        | StateType::BlockForeignAid { player, .. }
+        | StateType::BlockAssassination { player, .. }
+        | StateType::BlockSteal { player, .. } => *player,
+        _ => return None,
+    };
+    available_actions.iter().find(|v| {
+        v.player == claimant
+            && matches!(
+                v.action_type,
+                ActionType::PassChallenge | ActionType::PassBlock
+            )
+    })
+}
+
+pub fn get_pending_influence_losses(state_type: &StateType, player_hands: &[usize]) -> Vec<usize> {
+    let mut losses = Vec::new();
+    match state_type {
+        StateType::LostInfluence { player, .. } => losses.push(*player),
+        StateType::Assassination { target, .. } => losses.push(*target),
+        StateType::Challenge { source, state, .. } => match state {
+            ChallengeState::ShownCard { initiator, .. } => {
+                losses.push(*initiator);
+                add_claim_losses(source, true, &mut losses);
+            }
+            ChallengeState::InitiatorRevealedCard { .. }
+            | ChallengeState::DeckShuffled { .. }
+            | ChallengeState::TookCard => add_claim_losses(source, true, &mut losses),
+            ChallengeState::TargetRevealedCard => add_claim_losses(source, false, &mut losses),
+            ChallengeState::Initial { .. } => (),
+        },
+        _ => (),
+    }
+    let mut counts = vec![0; player_hands.len()];
+    losses.retain(|player| {
+        counts[*player] += 1;
+        counts[*player] <= player_hands[*player]
+    });
+    losses
+}
+
+fn add_claim_losses(source: &StateType, is_true: bool, losses: &mut Vec<usize>) {
+    match (source, is_true) {
+        (StateType::Assassination { target, .. }, true) => losses.push(*target),
+        (StateType::BlockAssassination { player, .. }, false) => losses.push(*player),
+        _ => (),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum ChallengeState {
     Initial {
         initiator: usize,
@@ -1177,6 +1454,9 @@ where
     }
     match &action.action_type {
         ActionType::TakeCard => {
+            if deck.count() == 0 {
+                return Err(Error::EmptyDeck);
+            }
             player_cards[target].add_card(deck.pop_card());
             player_cards_counter[target] += 1;
             Ok(ChallengeState::TookCard)
@@ -1198,6 +1478,7 @@ mod tests {
         player_cards: Vec<Vec<Card>>,
         deck: Vec<Card>,
         revealed_cards: Vec<Card>,
+        rules: Rules,
     }
 
     impl TestState {
@@ -1213,6 +1494,7 @@ mod tests {
                 ],
                 deck: vec![Card::Contessa],
                 revealed_cards: Vec::with_capacity(5),
+                rules: Rules::default(),
             }
         }
 
@@ -1230,6 +1512,7 @@ mod tests {
                 ],
                 deck: vec![Card::Duke, Card::Contessa],
                 revealed_cards: Vec::with_capacity(2 * 5),
+                rules: Rules::default(),
             }
         }
 
@@ -1242,6 +1525,7 @@ mod tests {
                 player_cards: &mut self.player_cards,
                 deck: &mut self.deck,
                 revealed_cards: &mut self.revealed_cards,
+                rules: &self.rules,
             }
         }
     }
@@ -1963,6 +2247,330 @@ mod tests {
         assert_eq!(state.state_type, StateType::Turn { player: 0 });
     }
 
+    #[test]
+    fn single_card_exchange_requires_to_drop_one_card() {
+        let mut state = TestState::four_players();
+        state.rules.cards_to_exchange = 1;
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Exchange,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::PassChallenge,
+            },
+        ];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(
+            state.state_type,
+            StateType::NeedCards {
+                player: 0,
+                count: 1
+            }
+        );
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::TakeCard,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::DropCard(Card::Assassin),
+            },
+        ];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(state.state_type, StateType::Turn { player: 1 });
+    }
+
+    #[test]
+    fn shown_card_should_be_taken_back_from_empty_deck() {
+        let mut state = TestState::two_players();
+        state.deck.clear();
+        state.player_cards[0] = vec![Card::Captain, Card::Duke];
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Tax,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::ShowCard(Card::Duke),
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::RevealCard(Card::Ambassador),
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::ShuffleDeck,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::TakeCard,
+            },
+        ];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(state.state_type, StateType::Turn { player: 1 });
+        assert_eq!(state.player_coins, vec![5, 2]);
+        assert_eq!(state.player_cards_counter, vec![2, 1]);
+        assert_eq!(state.player_cards[0], vec![Card::Captain, Card::Duke]);
+        assert!(state.deck.is_empty());
+    }
+
+    #[test]
+    fn take_card_from_empty_deck_should_fail() {
+        let mut state = TestState::two_players();
+        state.deck.clear();
+        state.state_type = StateType::Challenge {
+            current_player: 0,
+            state: ChallengeState::DeckShuffled { target: 0 },
+            source: Rc::new(StateType::Tax { player: 0 }),
+        };
+        let actions = [Action {
+            player: 0,
+            action_type: ActionType::TakeCard,
+        }];
+        assert_eq!(play_actions(&mut state, &actions), Err(Error::EmptyDeck));
+        state.state_type = StateType::NeedCards {
+            player: 0,
+            count: 1,
+        };
+        assert_eq!(play_actions(&mut state, &actions), Err(Error::EmptyDeck));
+    }
+
+    fn failed_assassination_challenge_actions() -> Vec<Action> {
+        [
+            (0, ActionType::Assassinate(1)),
+            (1, ActionType::Challenge),
+            (0, ActionType::ShowCard(Card::Assassin)),
+        ]
+        .iter()
+        .map(|(player, action_type)| Action {
+            player: *player,
+            action_type: action_type.clone(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn failed_challenge_of_assassination_should_lead_to_double_loss() {
+        let mut state = TestState::two_players();
+        state.player_coins[0] = 3;
+        let actions = failed_assassination_challenge_actions();
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(
+            get_pending_influence_losses(&state.state_type, &state.player_hands),
+            vec![1, 1]
+        );
+        let actions = [
+            Action {
+                player: 1,
+                action_type: ActionType::RevealCard(Card::Ambassador),
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::ShuffleDeck,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::TakeCard,
+            },
+        ];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(
+            state.state_type,
+            StateType::Assassination {
+                player: 0,
+                target: 1,
+                can_challenge: false
+            }
+        );
+        assert_eq!(
+            get_pending_influence_losses(&state.state_type, &state.player_hands),
+            vec![1]
+        );
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::PassBlock,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::RevealCard(Card::Duke),
+            },
+        ];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(state.player_hands, vec![2, 0]);
+        assert_eq!(state.player_cards_counter, vec![2, 0]);
+        assert_eq!(state.revealed_cards, vec![Card::Ambassador, Card::Duke]);
+        assert!(get_pending_influence_losses(&state.state_type, &state.player_hands).is_empty());
+    }
+
+    #[test]
+    fn failed_challenge_of_assassination_by_last_card_should_end_before_assassination() {
+        let mut state = TestState::two_players();
+        state.player_coins[0] = 3;
+        state.player_hands[1] = 1;
+        state.player_cards_counter[1] = 1;
+        state.player_cards[1] = vec![Card::Duke];
+        let actions = failed_assassination_challenge_actions();
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(
+            get_pending_influence_losses(&state.state_type, &state.player_hands),
+            vec![1]
+        );
+        let actions = [
+            Action {
+                player: 1,
+                action_type: ActionType::RevealCard(Card::Duke),
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::ShuffleDeck,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::TakeCard,
+            },
+        ];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert!(get_pending_influence_losses(&state.state_type, &state.player_hands).is_empty());
+        let actions = [Action {
+            player: 0,
+            action_type: ActionType::PassBlock,
+        }];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(state.state_type, StateType::Turn { player: 0 });
+        assert_eq!(state.player_hands, vec![2, 0]);
+        assert_eq!(state.player_cards_counter, vec![2, 0]);
+    }
+
+    #[test]
+    fn tax_should_take_only_coins_left_in_bank() {
+        let mut state = TestState::two_players();
+        state.rules.bank_coins = Some(6);
+        state.player_coins = vec![3, 1];
+        assert_eq!(get_bank(&state.rules, &state.player_coins), Some(2));
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Tax,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::PassChallenge,
+            },
+        ];
+        assert_eq!(play_actions(&mut state, &actions), Ok(()));
+        assert_eq!(state.player_coins, vec![5, 1]);
+        assert_eq!(get_bank(&state.rules, &state.player_coins), Some(0));
+        assert_eq!(
+            play_actions(
+                &mut state,
+                &[Action {
+                    player: 1,
+                    action_type: ActionType::Income,
+                }]
+            ),
+            Err(Error::EmptyBank)
+        );
+        state.rules.bank_coins = None;
+        assert_eq!(get_bank(&state.rules, &state.player_coins), None);
+    }
+
+    #[test]
+    fn resolve_responses_should_follow_priority() {
+        let responses = [
+            Action {
+                player: 1,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 3,
+                action_type: ActionType::Challenge,
+            },
+        ];
+        let resolve = |priority| {
+            resolve_responses(&responses, 2, 4, priority, &mut ConstRng).map(|v| v.player)
+        };
+        assert_eq!(resolve(ResponsePriority::Seat), Some(1));
+        assert_eq!(resolve(ResponsePriority::TurnOrder), Some(3));
+        assert!(resolve(ResponsePriority::Random).is_some());
+        assert_eq!(
+            resolve_responses(&[], 2, 4, ResponsePriority::Random, &mut ConstRng),
+            None
+        );
+    }
+
+    #[test]
+    fn get_claimant_pass_should_find_pass_of_state_claimant() {
+        let available_actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 2,
+                action_type: ActionType::PassChallenge,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Challenge,
+            },
+        ];
+        assert_eq!(
+            get_claimant_pass(
+                &StateType::BlockForeignAid {
+                    player: 2,
+                    target: 0,
+                },
+                &available_actions,
+            ),
+            Some(&available_actions[1])
+        );
+        assert_eq!(
+            get_claimant_pass(&StateType::Tax { player: 1 }, &available_actions),
+            None
+        );
+        assert_eq!(
+            get_claimant_pass(&StateType::Turn { player: 2 }, &available_actions),
+            None
+        );
+    }
+
+    #[test]
+    fn rules_hash_should_distinguish_optional_fields() {
+        use std::hash::{Hash, Hasher};
+
+        use crate::seed::FnvHasher;
+
+        let hash = |rules: &Rules| {
+            let mut hasher = FnvHasher::default();
+            rules.hash(&mut hasher);
+            hasher.finish()
+        };
+        let rules = Rules::default();
+        let bank = Rules {
+            bank_coins: Some(2),
+            ..rules
+        };
+        let exchange = Rules {
+            cards_to_exchange: 1,
+            ..rules
+        };
+        let random = Rules {
+            response_priority: ResponsePriority::Random,
+            ..rules
+        };
+        let hashes = [hash(&rules), hash(&bank), hash(&exchange), hash(&random)];
+        assert!(hashes.iter().all_unique(), "{:?}", hashes);
+    }
+
     fn play_actions(state: &mut TestState, actions: &[Action]) -> Result<(), Error> {
         for action in actions {
             println!("Play action={:?} for state={:?}", action, state);
@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::completeness::get_state_type_name;
+use crate::encoding::get_action_space_size;
+use crate::fsm::{
+    Action, Card, ChallengeState, ConstRng, StateType, ASSASSINATION_COST, CARDS_PER_PLAYER,
+    COUP_COST,
+};
+use crate::game::{Game, Scenario};
+use crate::run::get_action_type_name;
+
+const DONE: &str = "Done";
+const COINS_THRESHOLDS: [usize; 2] = [ASSASSINATION_COST, COUP_COST];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            _ => Err(format!("invalid graph format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FsmGraph {
+    pub states: BTreeSet<String>,
+    pub transitions: BTreeMap<(String, String), BTreeSet<&'static str>>,
+}
+
+pub fn collect_fsm_graph() -> FsmGraph {
+    let games = vec![
+        Game::from_scenario(&Scenario {
+            player_cards: vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Ambassador, Card::Duke],
+            ],
+            deck: vec![
+                Card::Ambassador,
+                Card::Assassin,
+                Card::Captain,
+                Card::Contessa,
+            ],
+            player_coins: Some(vec![7, 7, 7]),
+            revealed_cards: Vec::new(),
+            state_type: None,
+            cards_per_player: CARDS_PER_PLAYER,
+            rules: Default::default(),
+            card_counts: None,
+        })
+        .unwrap(),
+        Game::from_scenario(&Scenario {
+            player_cards: vec![vec![Card::Assassin], vec![Card::Captain], vec![Card::Duke]],
+            deck: Vec::new(),
+            player_coins: Some(vec![7, 7, 7]),
+            revealed_cards: vec![
+                Card::Ambassador,
+                Card::Ambassador,
+                Card::Assassin,
+                Card::Captain,
+                Card::Contessa,
+                Card::Contessa,
+                Card::Duke,
+            ],
+            state_type: None,
+            cards_per_player: CARDS_PER_PLAYER,
+            rules: Default::default(),
+            card_counts: None,
+        })
+        .unwrap(),
+    ];
+    let settings = games[0].get_settings();
+    let actions: Vec<Action> = (0..get_action_space_size(&settings))
+        .map(|index| Action::from_index(index, &settings).unwrap())
+        .collect();
+    let mut graph = FsmGraph::default();
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<Game> = games.into_iter().collect();
+    while let Some(game) = queue.pop_front() {
+        let view = game.get_anonymous_view();
+        let source = get_state_name(view.state_type);
+        let coins: Vec<usize> = view
+            .player_coins
+            .iter()
+            .map(|v| {
+                COINS_THRESHOLDS
+                    .iter()
+                    .filter(|threshold| *v >= **threshold)
+                    .count()
+            })
+            .collect();
+        if !visited.insert((source.clone(), view.player_hands.to_vec(), view.deck, coins)) {
+            continue;
+        }
+        graph.states.insert(source.clone());
+        for action in actions.iter() {
+            let mut next = game.clone();
+            if next.play(action, &mut ConstRng).is_err() {
+                continue;
+            }
+            let destination = if next.is_done() {
+                String::from(DONE)
+            } else {
+                get_state_name(next.get_anonymous_view().state_type)
+            };
+            graph.states.insert(destination.clone());
+            graph
+                .transitions
+                .entry((source.clone(), destination))
+                .or_default()
+                .insert(get_action_type_name(&action.action_type));
+            if !next.is_done() {
+                queue.push_back(next);
+            }
+        }
+    }
+    graph
+}
+
+pub fn get_state_name(state_type: &StateType) -> String {
+    match state_type {
+        StateType::Assassination { can_challenge, .. } | StateType::Steal { can_challenge, .. } => {
+            format!(
+                "{}({})",
+                get_state_type_name(state_type),
+                if *can_challenge { "challenge" } else { "block" }
+            )
+        }
+        StateType::Challenge { source, state, .. } => format!(
+            "Challenge({}, {})",
+            get_state_name(source),
+            get_challenge_state_name(state)
+        ),
+        _ => String::from(get_state_type_name(state_type)),
+    }
+}
+
+fn get_challenge_state_name(state: &ChallengeState) -> &'static str {
+    match state {
+        ChallengeState::Initial { .. } => "Initial",
+        ChallengeState::ShownCard { .. } => "ShownCard",
+        ChallengeState::InitiatorRevealedCard { .. } => "InitiatorRevealedCard",
+        ChallengeState::DeckShuffled { .. } => "DeckShuffled",
+        ChallengeState::TookCard => "TookCard",
+        ChallengeState::TargetRevealedCard => "TargetRevealedCard",
+    }
+}
+
+pub fn write_fsm_graph<W: Write>(
+    graph: &FsmGraph,
+    format: GraphFormat,
+    mut output: W,
+) -> std::io::Result<()> {
+    let ids: HashMap<&str, usize> = graph
+        .states
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.as_str(), index))
+        .collect();
+    match format {
+        GraphFormat::Dot => {
+            writeln!(output, "digraph fsm {{")?;
+            for name in graph.states.iter() {
+                writeln!(output, "    s{} [label=\"{}\"];", ids[name.as_str()], name)?;
+            }
+            for ((source, destination), actions) in graph.transitions.iter() {
+                writeln!(
+                    output,
+                    "    s{} -> s{} [label=\"{}\"];",
+                    ids[source.as_str()],
+                    ids[destination.as_str()],
+                    actions.iter().join(", ")
+                )?;
+            }
+            writeln!(output, "}}")?;
+        }
+        GraphFormat::Mermaid => {
+            writeln!(output, "stateDiagram-v2")?;
+            writeln!(output, "    [*] --> s{}", ids["Turn"])?;
+            for name in graph.states.iter() {
+                writeln!(output, "    state \"{}\" as s{}", name, ids[name.as_str()])?;
+            }
+            for ((source, destination), actions) in graph.transitions.iter() {
+                writeln!(
+                    output,
+                    "    s{} --> s{}: {}",
+                    ids[source.as_str()],
+                    ids[destination.as_str()],
+                    actions.iter().join(", ")
+                )?;
+            }
+            if let Some(id) = ids.get(DONE) {
+                writeln!(output, "    s{} --> [*]", id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_fsm_graph_should_find_main_transitions() {
+        let graph = collect_fsm_graph();
+        let has = |source: &str, destination: &str, action: &str| {
+            graph
+                .transitions
+                .get(&(String::from(source), String::from(destination)))
+                .is_some_and(|v| v.contains(action))
+        };
+        assert!(has("Turn", "Turn", "Income"));
+        assert!(has("Turn", "LostInfluence", "Coup"));
+        assert!(has("Tax", "Challenge(Tax, Initial)", "Challenge"));
+        assert!(has(
+            "Challenge(BlockAssassination, Initial)",
+            "Assassination(block)",
+            "RevealCard"
+        ));
+        assert!(has("Exchange", "Turn", "PassChallenge"));
+        assert!(has("TookCards", "Turn", "DropCard"));
+        assert!(graph.states.contains(DONE));
+        assert!(!graph.states.contains("DroppedCard"));
+        let mut dot = Vec::new();
+        write_fsm_graph(&graph, GraphFormat::Dot, &mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.starts_with("digraph fsm {\n"));
+        assert_eq!(dot.matches(" -> ").count(), graph.transitions.len());
+    }
+}
@@ -1,12 +1,23 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter};
+use std::iter::once;
+use std::time::Duration;
+
 use itertools::Itertools;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 use crate::fsm::{
-    play_action, Action, ActionType, Card, ChallengeState, State, StateType, ASSASSINATION_COST,
-    CARDS_PER_PLAYER, COUP_COST, MAX_CARDS_TO_EXCHANGE, MAX_COINS,
+    get_bank, get_pending_influence_losses, on_forfeit, play_action, Action, ActionType, Card,
+    Cards, ChallengeState, Rules, State, StateType, ASSASSINATION_COST, CARDS_PER_PLAYER,
+    COUP_COST, MAX_CARDS_TO_EXCHANGE,
 };
+use crate::seed::FnvHasher;
 
 pub const ALL_CARDS: [Card; 5] = [
     Card::Assassin,
@@ -32,6 +43,103 @@ pub struct PlayerView<'a> {
     pub player_cards: &'a [usize],
     pub revealed_cards: &'a [Card],
     pub deck: usize,
+    pub rules: &'a Rules,
+    pub claims: &'a [Vec<Card>],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_time: Option<Duration>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameView {
+    pub step: usize,
+    pub turn: usize,
+    pub round: usize,
+    pub player: usize,
+    pub coins: usize,
+    pub cards: Vec<Card>,
+    pub state_type: StateType,
+    pub player_coins: Vec<usize>,
+    pub player_hands: Vec<usize>,
+    pub player_cards: Vec<usize>,
+    pub revealed_cards: Vec<Card>,
+    pub deck: usize,
+    #[serde(default)]
+    pub rules: Rules,
+    #[serde(default)]
+    pub claims: Vec<Vec<Card>>,
+    #[serde(default)]
+    pub remaining_time: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bank: Option<usize>,
+}
+
+impl PlayerView<'_> {
+    pub fn bank(&self) -> Option<usize> {
+        get_bank(self.rules, self.player_coins)
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        self.step.hash(&mut hasher);
+        self.turn.hash(&mut hasher);
+        self.round.hash(&mut hasher);
+        self.player.hash(&mut hasher);
+        self.coins.hash(&mut hasher);
+        self.cards.hash(&mut hasher);
+        self.state_type.hash(&mut hasher);
+        self.player_coins.hash(&mut hasher);
+        self.player_hands.hash(&mut hasher);
+        self.player_cards.hash(&mut hasher);
+        self.revealed_cards.hash(&mut hasher);
+        self.deck.hash(&mut hasher);
+        self.rules.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl GameView {
+    pub fn player_view(&self) -> PlayerView<'_> {
+        PlayerView {
+            step: self.step,
+            turn: self.turn,
+            round: self.round,
+            player: self.player,
+            coins: self.coins,
+            cards: &self.cards,
+            state_type: &self.state_type,
+            player_coins: &self.player_coins,
+            player_hands: &self.player_hands,
+            player_cards: &self.player_cards,
+            revealed_cards: &self.revealed_cards,
+            deck: self.deck,
+            rules: &self.rules,
+            claims: &self.claims,
+            remaining_time: self.remaining_time,
+        }
+    }
+}
+
+impl From<&PlayerView<'_>> for GameView {
+    fn from(view: &PlayerView) -> Self {
+        Self {
+            step: view.step,
+            turn: view.turn,
+            round: view.round,
+            player: view.player,
+            coins: view.coins,
+            cards: view.cards.to_vec(),
+            state_type: view.state_type.clone(),
+            player_coins: view.player_coins.to_vec(),
+            player_hands: view.player_hands.to_vec(),
+            player_cards: view.player_cards.to_vec(),
+            revealed_cards: view.revealed_cards.to_vec(),
+            deck: view.deck,
+            rules: *view.rules,
+            claims: view.claims.to_vec(),
+            remaining_time: view.remaining_time,
+            bank: view.bank(),
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -46,301 +154,462 @@ pub struct AnonymousView<'a> {
     pub player_cards: &'a [usize],
     pub revealed_cards: &'a [Card],
     pub deck: usize,
+    pub rules: &'a Rules,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpectatorView<'a> {
+    pub step: usize,
+    pub turn: usize,
+    pub round: usize,
+    pub player: usize,
+    pub state_type: &'a StateType,
+    pub player_coins: &'a [usize],
+    pub player_hands: &'a [usize],
+    pub player_cards: &'a [usize],
+    pub revealed_cards: &'a [Card],
+    pub deck: usize,
+    pub rules: &'a Rules,
+    pub claims: &'a [Vec<Card>],
+    pub history: &'a [HistoryEntry],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicView<'a> {
+    pub step: usize,
+    pub turn: usize,
+    pub round: usize,
+    pub state_type: &'a StateType,
+    pub player_coins: &'a [usize],
+    pub player_hands: &'a [usize],
+    pub player_cards: &'a [usize],
+    pub revealed_cards: &'a [Card],
+    pub deck: usize,
+    pub rules: &'a Rules,
+    pub claims: &'a [Vec<Card>],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllViews<'a> {
+    pub spectator: SpectatorView<'a>,
+    pub players: Vec<GameView>,
 }
 
 pub fn get_available_actions(
     state_type: &StateType,
     player_coins: &[usize],
     player_hands: &[usize],
+    rules: &Rules,
 ) -> Vec<Action> {
+    available_actions_iter(state_type, player_coins, player_hands, rules).collect()
+}
+
+pub fn available_actions_for<'a>(
+    view: &PlayerView<'a>,
+    player: usize,
+) -> impl Iterator<Item = Action> + 'a {
+    available_actions_iter(
+        view.state_type,
+        view.player_coins,
+        view.player_hands,
+        view.rules,
+    )
+    .of_player(player)
+}
+
+pub fn available_actions_iter<'a>(
+    state_type: &'a StateType,
+    player_coins: &'a [usize],
+    player_hands: &'a [usize],
+    rules: &'a Rules,
+) -> Box<dyn Iterator<Item = Action> + 'a> {
     match state_type {
-        StateType::Turn { player } => {
-            get_turn_available_actions(*player, player_coins, player_hands)
-        }
-        StateType::ForeignAid { player } => {
-            get_foreign_aid_available_actions(*player, player_hands)
-        }
+        StateType::Turn { player } => turn_actions(*player, player_coins, player_hands, rules),
+        StateType::ForeignAid { player } => Box::new(
+            others_actions(*player, player_hands, ActionType::BlockForeignAid)
+                .chain(once(make_action(*player, ActionType::PassBlock))),
+        ),
         StateType::Tax { player }
         | StateType::Exchange { player }
         | StateType::BlockForeignAid { player, .. }
         | StateType::BlockSteal { player, .. }
         | StateType::BlockAssassination { player, .. } => {
-            get_non_blocking_available_actions(*player, player_hands)
+            Box::new(challenge_actions(*player, player_hands))
         }
         StateType::Assassination {
             player,
             target,
             can_challenge,
-        } => get_assassination_available_actions(*player, *target, *can_challenge, player_hands),
+        } => {
+            if *can_challenge {
+                Box::new(challenge_actions(*player, player_hands))
+            } else {
+                let target = *target;
+                Box::new(
+                    (player_hands[target] > 0)
+                        .then(|| make_action(target, ActionType::BlockAssassination))
+                        .into_iter()
+                        .chain(once(make_action(*player, ActionType::PassBlock))),
+                )
+            }
+        }
         StateType::Steal {
             player,
             target,
             can_challenge,
-        } => get_steal_available_actions(*player, *target, *can_challenge, player_hands),
-        StateType::Challenge { state, .. } => get_challenge_available_actions(state),
-        StateType::NeedCards { player, .. } => get_need_cards_available_actions(*player),
-        StateType::TookCards { player, .. } | StateType::DroppedCard { player, .. } => {
-            get_drop_card_actions(*player)
-        }
-        StateType::LostInfluence { player, .. } => get_lost_influence_available_actions(*player),
-    }
-}
-
-pub fn get_turn_available_actions(
-    player: usize,
-    player_coins: &[usize],
-    player_hands: &[usize],
-) -> Vec<Action> {
-    if player_coins[player] >= MAX_COINS {
-        let mut actions: Vec<Action> = Vec::with_capacity(player_hands.len());
-        for (other_player, other_player_hand) in player_hands.iter().enumerate() {
-            if other_player != player && *other_player_hand > 0 {
-                actions.push(Action {
-                    player,
-                    action_type: ActionType::Coup(other_player),
-                });
+        } => {
+            if *can_challenge {
+                Box::new(challenge_actions(*player, player_hands))
+            } else {
+                let target = *target;
+                Box::new(
+                    [Card::Ambassador, Card::Captain]
+                        .iter()
+                        .filter(move |_| player_hands[target] > 0)
+                        .map(move |card| make_action(target, ActionType::BlockSteal(*card)))
+                        .chain(once(make_action(*player, ActionType::PassBlock))),
+                )
             }
         }
-        return actions;
-    }
-    let action_types = [
-        ActionType::Income,
-        ActionType::ForeignAid,
-        ActionType::Tax,
-        ActionType::Exchange,
-    ];
-    let mut actions: Vec<Action> =
-        Vec::with_capacity(action_types.len() + 3 * (player_hands.len() - 1));
-    for action_type in action_types.iter().cloned() {
-        actions.push(Action {
-            player,
-            action_type,
-        });
-    }
-    for (other_player, other_player_hand) in player_hands.iter().enumerate() {
-        if other_player != player && *other_player_hand > 0 {
-            actions.push(Action {
-                player,
-                action_type: ActionType::Steal(other_player),
-            });
-            if player_coins[player] >= ASSASSINATION_COST {
-                actions.push(Action {
-                    player,
-                    action_type: ActionType::Assassinate(other_player),
-                });
+        StateType::Challenge { state, .. } => match state {
+            ChallengeState::Initial { target, card, .. } => Box::new(
+                once(make_action(*target, ActionType::ShowCard(*card)))
+                    .chain(cards_actions(*target, ActionType::RevealCard)),
+            ),
+            ChallengeState::ShownCard { initiator, .. } => {
+                Box::new(cards_actions(*initiator, ActionType::RevealCard))
             }
-            if player_coins[player] >= COUP_COST {
-                actions.push(Action {
-                    player,
-                    action_type: ActionType::Coup(other_player),
-                });
+            ChallengeState::InitiatorRevealedCard { target } => {
+                Box::new(once(make_action(*target, ActionType::ShuffleDeck)))
+            }
+            ChallengeState::DeckShuffled { target } => {
+                Box::new(once(make_action(*target, ActionType::TakeCard)))
             }
+            _ => Box::new(std::iter::empty()),
+        },
+        StateType::NeedCards { player, .. } => {
+            Box::new(once(make_action(*player, ActionType::TakeCard)))
+        }
+        StateType::TookCards { player, .. } | StateType::DroppedCard { player, .. } => {
+            Box::new(cards_actions(*player, ActionType::DropCard))
+        }
+        StateType::LostInfluence { player, .. } => {
+            Box::new(cards_actions(*player, ActionType::RevealCard))
         }
     }
-    actions
 }
 
-pub fn get_foreign_aid_available_actions(player: usize, player_hands: &[usize]) -> Vec<Action> {
-    let mut actions: Vec<Action> = Vec::with_capacity(player_hands.len());
-    fill_actions(
-        &ActionType::BlockForeignAid,
-        player,
-        player_hands,
-        &mut actions,
-    );
-    actions.push(Action {
-        player,
-        action_type: ActionType::PassBlock,
-    });
-    actions
-}
+pub trait ActionsIterator: Iterator<Item = Action> + Sized {
+    fn of_player(self, player: usize) -> impl Iterator<Item = Action> {
+        self.filter(move |action| action.player == player)
+    }
 
-pub fn get_non_blocking_available_actions(player: usize, player_hands: &[usize]) -> Vec<Action> {
-    let mut actions: Vec<Action> = Vec::with_capacity(player_hands.len());
-    fill_challenge_actions(player, player_hands, &mut actions);
-    actions.push(Action {
-        player,
-        action_type: ActionType::PassChallenge,
-    });
-    actions
+    fn of_kind(self, action_type: &ActionType) -> impl Iterator<Item = Action> {
+        let kind = std::mem::discriminant(action_type);
+        self.filter(move |action| std::mem::discriminant(&action.action_type) == kind)
+    }
 }
 
-pub fn get_assassination_available_actions(
+impl<I: Iterator<Item = Action>> ActionsIterator for I {}
+
+fn turn_actions<'a>(
     player: usize,
-    target: usize,
-    can_challenge: bool,
-    player_hands: &[usize],
-) -> Vec<Action> {
-    if can_challenge {
-        let mut actions: Vec<Action> = Vec::with_capacity(player_hands.len());
-        fill_challenge_actions(player, player_hands, &mut actions);
-        actions.push(Action {
-            player,
-            action_type: ActionType::PassChallenge,
-        });
-        actions
-    } else {
-        let mut actions = if player_hands[target] > 0 {
-            let mut actions: Vec<Action> = Vec::with_capacity(2);
-            actions.push(Action {
-                player: target,
-                action_type: ActionType::BlockAssassination,
-            });
-            actions
-        } else {
-            Vec::with_capacity(1)
-        };
-        actions.push(Action {
-            player,
-            action_type: ActionType::PassBlock,
-        });
-        actions
+    player_coins: &'a [usize],
+    player_hands: &'a [usize],
+    rules: &'a Rules,
+) -> Box<dyn Iterator<Item = Action> + 'a> {
+    let targets = (0..player_hands.len())
+        .filter(move |other_player| *other_player != player && player_hands[*other_player] > 0);
+    let coins = player_coins[player];
+    if coins >= rules.forced_coup_coins {
+        return Box::new(targets.map(move |target| make_action(player, ActionType::Coup(target))));
     }
+    let empty_bank = get_bank(rules, player_coins) == Some(0);
+    Box::new(
+        [
+            ActionType::Income,
+            ActionType::ForeignAid,
+            ActionType::Tax,
+            ActionType::Exchange,
+        ]
+        .iter()
+        .filter(move |action_type| !empty_bank || **action_type == ActionType::Exchange)
+        .map(move |action_type| make_action(player, action_type.clone()))
+        .chain(targets.flat_map(move |target| {
+            once(ActionType::Steal(target))
+                .chain((coins >= ASSASSINATION_COST).then_some(ActionType::Assassinate(target)))
+                .chain((coins >= COUP_COST).then_some(ActionType::Coup(target)))
+                .map(move |action_type| make_action(player, action_type))
+        })),
+    )
 }
 
-pub fn get_steal_available_actions(
-    player: usize,
+fn challenge_actions(player: usize, player_hands: &[usize]) -> impl Iterator<Item = Action> + '_ {
+    others_actions(player, player_hands, ActionType::Challenge)
+        .chain(once(make_action(player, ActionType::PassChallenge)))
+}
+
+fn others_actions(
     target: usize,
-    can_challenge: bool,
     player_hands: &[usize],
-) -> Vec<Action> {
-    if can_challenge {
-        let mut actions: Vec<Action> = Vec::with_capacity(player_hands.len());
-        fill_challenge_actions(player, player_hands, &mut actions);
-        actions.push(Action {
-            player,
-            action_type: ActionType::PassChallenge,
-        });
-        actions
-    } else {
-        let mut actions = if player_hands[target] > 0 {
-            let mut actions: Vec<Action> = Vec::with_capacity(3);
-            actions.push(Action {
-                player: target,
-                action_type: ActionType::BlockSteal(Card::Ambassador),
-            });
-            actions.push(Action {
-                player: target,
-                action_type: ActionType::BlockSteal(Card::Captain),
-            });
-            actions
-        } else {
-            Vec::with_capacity(1)
-        };
-        actions.push(Action {
-            player,
-            action_type: ActionType::PassBlock,
-        });
-        actions
+    action_type: ActionType,
+) -> impl Iterator<Item = Action> + '_ {
+    (target + 1..player_hands.len())
+        .chain(0..target)
+        .filter(move |player| player_hands[*player] > 0)
+        .map(move |player| make_action(player, action_type.clone()))
+}
+
+fn cards_actions(player: usize, f: fn(Card) -> ActionType) -> impl Iterator<Item = Action> {
+    ALL_CARDS
+        .iter()
+        .map(move |card| make_action(player, f(*card)))
+}
+
+fn make_action(player: usize, action_type: ActionType) -> Action {
+    Action {
+        player,
+        action_type,
     }
 }
 
-pub fn get_challenge_available_actions(state: &ChallengeState) -> Vec<Action> {
-    match state {
-        ChallengeState::Initial { target, card, .. } => {
-            let mut actions: Vec<Action> = Vec::with_capacity(ALL_CARDS.len() + 1);
-            actions.push(Action {
-                player: *target,
-                action_type: ActionType::ShowCard(*card),
-            });
-            for other_card in &ALL_CARDS {
-                actions.push(Action {
-                    player: *target,
-                    action_type: ActionType::RevealCard(*other_card),
-                });
-            }
-            actions
-        }
-        ChallengeState::ShownCard { initiator, .. } => {
-            let mut actions: Vec<Action> = Vec::with_capacity(ALL_CARDS.len());
-            for card in &ALL_CARDS {
-                actions.push(Action {
-                    player: *initiator,
-                    action_type: ActionType::RevealCard(*card),
-                });
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "CardCountsValue", into = "BTreeMap<Card, usize>")]
+pub struct CardCounts([usize; ALL_CARDS.len()]);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CardCountsValue {
+    PerType(usize),
+    PerCard(BTreeMap<Card, usize>),
+}
+
+impl From<CardCountsValue> for CardCounts {
+    fn from(value: CardCountsValue) -> Self {
+        match value {
+            CardCountsValue::PerType(count) => Self::uniform(count),
+            CardCountsValue::PerCard(counts) => {
+                let mut result = Self::uniform(0);
+                for (card, count) in counts {
+                    result.set(card, count);
+                }
+                result
             }
-            actions
         }
-        ChallengeState::InitiatorRevealedCard { target } => {
-            vec![Action {
-                player: *target,
-                action_type: ActionType::ShuffleDeck,
-            }]
-        }
-        ChallengeState::DeckShuffled { target } => {
-            vec![Action {
-                player: *target,
-                action_type: ActionType::TakeCard,
-            }]
-        }
-        _ => Vec::new(),
     }
 }
 
-fn get_need_cards_available_actions(player: usize) -> Vec<Action> {
-    vec![Action {
-        player,
-        action_type: ActionType::TakeCard,
-    }]
+impl From<CardCounts> for BTreeMap<Card, usize> {
+    fn from(value: CardCounts) -> Self {
+        ALL_CARDS
+            .iter()
+            .map(|card| (*card, value.get(*card)))
+            .collect()
+    }
 }
 
-fn get_drop_card_actions(player: usize) -> Vec<Action> {
-    let mut actions: Vec<Action> = Vec::with_capacity(ALL_CARDS.len());
-    for card in &ALL_CARDS {
-        actions.push(Action {
-            player,
-            action_type: ActionType::DropCard(*card),
-        });
+impl CardCounts {
+    pub fn uniform(count: usize) -> Self {
+        Self([count; ALL_CARDS.len()])
     }
-    actions
-}
 
-fn get_lost_influence_available_actions(player: usize) -> Vec<Action> {
-    let mut actions: Vec<Action> = Vec::with_capacity(ALL_CARDS.len());
-    for card in &ALL_CARDS {
-        actions.push(Action {
-            player,
-            action_type: ActionType::RevealCard(*card),
-        });
+    pub fn from_cards<'a, I: IntoIterator<Item = &'a Card>>(cards: I) -> Self {
+        let mut result = Self::uniform(0);
+        for card in cards {
+            result.set(*card, result.get(*card) + 1);
+        }
+        result
     }
-    actions
-}
 
-fn fill_challenge_actions(target: usize, player_hands: &[usize], actions: &mut Vec<Action>) {
-    fill_actions(&ActionType::Challenge, target, player_hands, actions);
-}
+    pub fn get(&self, card: Card) -> usize {
+        ALL_CARDS
+            .iter()
+            .position(|v| *v == card)
+            .map_or(0, |index| self.0[index])
+    }
 
-fn fill_actions(
-    action_type: &ActionType,
-    target: usize,
-    player_hands: &[usize],
-    actions: &mut Vec<Action>,
-) {
-    for (player, player_hand) in player_hands.iter().enumerate().skip(target + 1) {
-        if *player_hand > 0 {
-            actions.push(Action {
-                player,
-                action_type: action_type.clone(),
-            });
+    pub fn set(&mut self, card: Card, count: usize) {
+        if let Some(index) = ALL_CARDS.iter().position(|v| *v == card) {
+            self.0[index] = count;
         }
     }
-    for (player, player_hand) in player_hands.iter().enumerate().take(target) {
-        if *player_hand > 0 {
-            actions.push(Action {
-                player,
-                action_type: action_type.clone(),
-            });
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+
+    pub fn max(&self) -> usize {
+        self.0.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn make_deck(&self) -> Vec<Card> {
+        let mut deck = Vec::with_capacity(self.total());
+        for card in &ALL_CARDS {
+            for _ in 0..self.get(*card) {
+                deck.push(*card);
+            }
         }
+        deck
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Display for CardCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, card) in ALL_CARDS.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:?}={}", card, self.get(*card))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Settings {
     pub players_number: usize,
-    pub cards_per_type: usize,
+    #[serde(alias = "cards_per_type")]
+    pub card_counts: CardCounts,
+    #[serde(default = "default_cards_per_player")]
+    pub cards_per_player: usize,
+    #[serde(default)]
+    pub rules: Rules,
+}
+
+pub fn default_cards_per_player() -> usize {
+    CARDS_PER_PLAYER
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.players_number < 2 {
+            return Err(format!(
+                "Need at least 2 players, got {}",
+                self.players_number
+            ));
+        }
+        if self.cards_per_player == 0 {
+            return Err(String::from("Need at least 1 card per player"));
+        }
+        if self.card_counts.total() < self.players_number * self.cards_per_player {
+            return Err(format!(
+                "Not enough cards for {} players: {} give {} cards in total, need at least {} cards",
+                self.players_number,
+                self.card_counts,
+                self.card_counts.total(),
+                self.players_number * self.cards_per_player
+            ));
+        }
+        if self.rules.forced_coup_coins < COUP_COST {
+            return Err(format!(
+                "Forced coup threshold {} is less than coup cost {}",
+                self.rules.forced_coup_coins, COUP_COST
+            ));
+        }
+        if !(1..=MAX_CARDS_TO_EXCHANGE).contains(&self.rules.cards_to_exchange) {
+            return Err(format!(
+                "Cards to exchange should be from 1 to {}, got {}",
+                MAX_CARDS_TO_EXCHANGE, self.rules.cards_to_exchange
+            ));
+        }
+        if let Some(bank_coins) = self.rules.bank_coins {
+            if bank_coins < self.players_number * INITIAL_COINS {
+                return Err(format!(
+                    "Bank of {} coins can't pay initial {} coins to {} players",
+                    bank_coins, INITIAL_COINS, self.players_number
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub player_cards: Vec<Vec<Card>>,
+    pub deck: Vec<Card>,
+    #[serde(default)]
+    pub player_coins: Option<Vec<usize>>,
+    #[serde(default)]
+    pub revealed_cards: Vec<Card>,
+    #[serde(default)]
+    pub state_type: Option<StateType>,
+    #[serde(default = "default_cards_per_player")]
+    pub cards_per_player: usize,
+    #[serde(default)]
+    pub rules: Rules,
+    #[serde(default)]
+    pub card_counts: Option<CardCounts>,
+}
+
+impl Scenario {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read scenario from {}: {}", path, e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FullGameState {
+    pub state_type: StateType,
+    pub player_coins: Vec<usize>,
+    pub player_hands: Vec<usize>,
+    pub player_cards_counter: Vec<usize>,
+    pub player_cards: Vec<Vec<Card>>,
+    pub revealed_cards: Vec<Card>,
+    pub deck: Vec<Card>,
+    #[serde(default = "default_cards_per_player")]
+    pub cards_per_player: usize,
+    pub rules: Rules,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Decision {
+    Play(Action),
+    Forfeit(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub decision: Decision,
+    pub hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    InvalidSettings(String),
+    IllegalAction {
+        index: usize,
+        step: usize,
+        action: Action,
+        error: String,
+    },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::InvalidSettings(error) => write!(f, "Invalid settings: {}", error),
+            ReplayError::IllegalAction {
+                index,
+                step,
+                action,
+                error,
+            } => write!(
+                f,
+                "Illegal action {} at step {}: {:?}: {}",
+                index, step, action, error
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Game {
     step: usize,
     turn: usize,
@@ -350,9 +619,44 @@ pub struct Game {
     player_coins: Vec<usize>,
     player_hands: Vec<usize>,
     player_cards_counter: Vec<usize>,
-    player_cards: Vec<Vec<Card>>,
+    player_cards: Vec<Cards>,
     revealed_cards: Vec<Card>,
     deck: Vec<Card>,
+    #[serde(default = "default_cards_per_player")]
+    cards_per_player: usize,
+    #[serde(default)]
+    rules: Rules,
+    #[serde(default)]
+    player_claims: Vec<Vec<Card>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    history: Option<Vec<HistoryEntry>>,
+}
+
+pub fn get_claimed_card(action_type: &ActionType) -> Option<Card> {
+    match action_type {
+        ActionType::Tax | ActionType::BlockForeignAid => Some(Card::Duke),
+        ActionType::Exchange => Some(Card::Ambassador),
+        ActionType::Assassinate(..) => Some(Card::Assassin),
+        ActionType::Steal(..) => Some(Card::Captain),
+        ActionType::BlockAssassination => Some(Card::Contessa),
+        ActionType::BlockSteal(card) => Some(*card),
+        _ => None,
+    }
+}
+
+pub fn update_claims(claims: &mut Vec<Vec<Card>>, player_hands: &[usize], action: &Action) {
+    claims.resize_with(player_hands.len(), Vec::new);
+    if let Some(card) = get_claimed_card(&action.action_type) {
+        let cards = &mut claims[action.player];
+        if let Err(index) = cards.binary_search(&card) {
+            cards.insert(index, card);
+        }
+    }
+    for (cards, hand) in claims.iter_mut().zip(player_hands.iter()) {
+        if *hand == 0 {
+            cards.clear();
+        }
+    }
 }
 
 pub fn make_deck(cards_per_type: usize) -> Vec<Card> {
@@ -367,15 +671,16 @@ pub fn make_deck(cards_per_type: usize) -> Vec<Card> {
 
 impl Game {
     pub fn new<R: Rng>(settings: Settings, rng: &mut R) -> Self {
-        let mut deck = make_deck(settings.cards_per_type);
+        let mut deck = settings.card_counts.make_deck();
         deck.shuffle(rng);
-        let deck_size = deck.len() - CARDS_PER_PLAYER * settings.players_number;
-        let max_player_cards = CARDS_PER_PLAYER + MAX_CARDS_TO_EXCHANGE.min(deck_size);
-        let mut player_cards: Vec<Vec<Card>> = (0..settings.players_number)
-            .map(|_| Vec::with_capacity(max_player_cards))
+        let deck_size = deck.len() - settings.cards_per_player * settings.players_number;
+        let max_player_cards =
+            settings.cards_per_player + settings.rules.cards_to_exchange.min(deck_size);
+        let mut player_cards: Vec<Cards> = (0..settings.players_number)
+            .map(|_| Cards::with_capacity(max_player_cards))
             .take(settings.players_number)
             .collect();
-        for _ in 0..CARDS_PER_PLAYER {
+        for _ in 0..settings.cards_per_player {
             for player_cards in player_cards.iter_mut() {
                 player_cards.push(deck.pop().unwrap());
             }
@@ -392,15 +697,19 @@ impl Game {
             player_coins: std::iter::repeat(INITIAL_COINS)
                 .take(settings.players_number)
                 .collect(),
-            player_hands: std::iter::repeat(CARDS_PER_PLAYER)
+            player_hands: std::iter::repeat(settings.cards_per_player)
                 .take(settings.players_number)
                 .collect(),
-            player_cards_counter: std::iter::repeat(CARDS_PER_PLAYER)
+            player_cards_counter: std::iter::repeat(settings.cards_per_player)
                 .take(settings.players_number)
                 .collect(),
             player_cards,
-            revealed_cards: Vec::with_capacity(settings.cards_per_type * ALL_CARDS.len()),
+            revealed_cards: Vec::with_capacity(settings.card_counts.total()),
             deck,
+            cards_per_player: settings.cards_per_player,
+            rules: settings.rules,
+            player_claims: vec![Vec::new(); settings.players_number],
+            history: None,
         }
     }
 
@@ -425,11 +734,221 @@ impl Game {
                 .take(player_cards.len())
                 .collect(),
             revealed_cards: Vec::with_capacity(CARDS_PER_PLAYER * player_cards.len() + deck.len()),
-            player_cards,
+            player_claims: vec![Vec::new(); player_cards.len()],
+            player_cards: player_cards.into_iter().map(Cards::from_vec).collect(),
             deck,
+            cards_per_player: CARDS_PER_PLAYER,
+            rules: Rules::default(),
+            history: None,
+        }
+    }
+
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, String> {
+        let players_number = scenario.player_cards.len();
+        if players_number < 2 {
+            return Err(format!(
+                "Scenario should have at least 2 players, got {}",
+                players_number
+            ));
+        }
+        let player_coins = match &scenario.player_coins {
+            Some(v) => v.clone(),
+            None => std::iter::repeat_n(INITIAL_COINS, players_number).collect(),
+        };
+        if player_coins.len() != players_number {
+            return Err(format!(
+                "Scenario has coins for {} players but cards for {}",
+                player_coins.len(),
+                players_number
+            ));
+        }
+        if let Some(bank_coins) = scenario.rules.bank_coins {
+            if player_coins.iter().sum::<usize>() > bank_coins {
+                return Err(format!(
+                    "Scenario players have more coins than the bank of {} coins",
+                    bank_coins
+                ));
+            }
+        }
+        let mut player_cards = scenario.player_cards.clone();
+        for cards in player_cards.iter_mut() {
+            if cards.len() > scenario.cards_per_player {
+                return Err(format!(
+                    "Player can't have more than {} cards at scenario start: {:?}",
+                    scenario.cards_per_player, cards
+                ));
+            }
+            cards.sort();
+        }
+        let all_cards = player_cards
+            .iter()
+            .flatten()
+            .chain(scenario.deck.iter())
+            .chain(scenario.revealed_cards.iter());
+        if all_cards.clone().any(|card| matches!(card, Card::Unknown)) {
+            return Err(String::from("Scenario can't have unknown cards"));
+        }
+        let card_counts = CardCounts::from_cards(all_cards);
+        match &scenario.card_counts {
+            Some(expected) => {
+                for card in ALL_CARDS.iter() {
+                    if card_counts.get(*card) != expected.get(*card) {
+                        return Err(format!(
+                            "Scenario should have {} {:?} cards, got {}",
+                            expected.get(*card),
+                            card,
+                            card_counts.get(*card)
+                        ));
+                    }
+                }
+            }
+            None => {
+                if ALL_CARDS
+                    .iter()
+                    .any(|card| card_counts.get(*card) != card_counts.max())
+                {
+                    return Err(format!(
+                        "Scenario without card counts should have the same number of cards of each type, got {}",
+                        card_counts
+                    ));
+                }
+            }
+        }
+        let player_hands: Vec<usize> = player_cards.iter().map(|v| v.len()).collect();
+        let state_type = scenario.state_type.clone().unwrap_or(StateType::Turn {
+            player: player_hands.iter().position(|hand| *hand > 0).unwrap_or(0),
+        });
+        let player = match &state_type {
+            StateType::Turn { player } => *player,
+            _ => 0,
+        };
+        if player >= players_number || player_hands[player] == 0 {
+            return Err(format!("Invalid scenario current player: {}", player));
+        }
+        Ok(Self {
+            step: 0,
+            turn: 0,
+            round: 0,
+            player,
+            state_type,
+            player_coins,
+            player_cards_counter: player_hands.clone(),
+            player_hands,
+            player_cards: player_cards.into_iter().map(Cards::from_vec).collect(),
+            revealed_cards: scenario.revealed_cards.clone(),
+            deck: scenario.deck.clone(),
+            cards_per_player: scenario.cards_per_player,
+            rules: scenario.rules,
+            player_claims: vec![Vec::new(); players_number],
+            history: None,
+        })
+    }
+
+    pub fn from_history(
+        settings: Settings,
+        seed: u64,
+        actions: &[Action],
+    ) -> Result<Self, ReplayError> {
+        settings.validate().map_err(ReplayError::InvalidSettings)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game = Self::new(settings, &mut rng);
+        game.record_history();
+        game.play_all(actions, &mut rng)
+            .map_err(|(index, error)| ReplayError::IllegalAction {
+                index,
+                step: game.step,
+                action: actions[index].clone(),
+                error,
+            })?;
+        Ok(game)
+    }
+
+    pub fn from_history_with<F: FnMut(&Game, Option<&Action>)>(
+        settings: Settings,
+        seed: u64,
+        actions: &[Action],
+        mut f: F,
+    ) -> Result<Self, ReplayError> {
+        settings.validate().map_err(ReplayError::InvalidSettings)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game = Self::new(settings, &mut rng);
+        game.record_history();
+        f(&game, None);
+        for (index, action) in actions.iter().enumerate() {
+            let step = game.step;
+            game.play(action, &mut rng)
+                .map_err(|error| ReplayError::IllegalAction {
+                    index,
+                    step,
+                    action: action.clone(),
+                    error,
+                })?;
+            f(&game, Some(action));
+        }
+        Ok(game)
+    }
+
+    pub fn from_full_state(state: FullGameState) -> Self {
+        let player = match &state.state_type {
+            StateType::Turn { player } => *player,
+            _ => 0,
+        };
+        Self {
+            step: 0,
+            turn: 0,
+            round: 0,
+            player,
+            player_claims: vec![Vec::new(); state.player_hands.len()],
+            state_type: state.state_type,
+            player_coins: state.player_coins,
+            player_hands: state.player_hands,
+            player_cards_counter: state.player_cards_counter,
+            player_cards: state
+                .player_cards
+                .into_iter()
+                .map(Cards::from_vec)
+                .collect(),
+            revealed_cards: state.revealed_cards,
+            deck: state.deck,
+            cards_per_player: state.cards_per_player,
+            rules: state.rules,
+            history: None,
+        }
+    }
+
+    pub fn with_progress(mut self, step: usize, turn: usize, round: usize) -> Self {
+        self.step = step;
+        self.turn = turn;
+        self.round = round;
+        self
+    }
+
+    pub fn get_settings(&self) -> Settings {
+        let cards = self
+            .deck
+            .iter()
+            .chain(self.revealed_cards.iter())
+            .chain(self.player_cards.iter().flatten());
+        Settings {
+            players_number: self.player_cards.len(),
+            card_counts: CardCounts::from_cards(cards),
+            cards_per_player: self.cards_per_player,
+            rules: self.rules,
         }
     }
 
+    pub fn save_snapshot(&self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("failed to create {}: {}", path, e))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|e| format!("failed to write snapshot to {}: {}", path, e))
+    }
+
+    pub fn load_snapshot(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read snapshot from {}: {}", path, e))
+    }
+
     pub fn step(&self) -> usize {
         self.step
     }
@@ -442,6 +961,66 @@ impl Game {
         self.round
     }
 
+    pub fn deck(&self) -> &[Card] {
+        &self.deck
+    }
+
+    pub fn normalized(&self) -> Self {
+        let mut game = self.clone();
+        game.step = 0;
+        game.turn = 0;
+        game.round = 0;
+        game.player = 0;
+        for cards in game.player_cards.iter_mut() {
+            cards.sort();
+        }
+        game.revealed_cards.sort();
+        game.deck.sort();
+        game.player_claims.iter_mut().for_each(Vec::clear);
+        game.history = None;
+        game
+    }
+
+    pub fn record_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(Vec::new());
+        }
+    }
+
+    pub fn history(&self) -> &[HistoryEntry] {
+        self.history.as_deref().unwrap_or(&[])
+    }
+
+    pub fn decisions(&self) -> impl Iterator<Item = &Decision> {
+        self.history().iter().map(|entry| &entry.decision)
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        self.step.hash(&mut hasher);
+        self.turn.hash(&mut hasher);
+        self.round.hash(&mut hasher);
+        self.player.hash(&mut hasher);
+        self.state_type.hash(&mut hasher);
+        self.player_coins.hash(&mut hasher);
+        self.player_hands.hash(&mut hasher);
+        self.player_cards_counter.hash(&mut hasher);
+        self.player_cards.hash(&mut hasher);
+        self.revealed_cards.hash(&mut hasher);
+        self.deck.hash(&mut hasher);
+        self.rules.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn add_history(&mut self, decision: Decision) {
+        if self.history.is_some() {
+            let hash = self.state_hash();
+            if let Some(history) = self.history.as_mut() {
+                history.push(HistoryEntry { decision, hash });
+            }
+        }
+    }
+
     pub fn get_anonymous_view(&self) -> AnonymousView {
         AnonymousView {
             step: self.step,
@@ -453,6 +1032,52 @@ impl Game {
             player_cards: &self.player_cards_counter,
             revealed_cards: &self.revealed_cards,
             deck: self.deck.len(),
+            rules: &self.rules,
+        }
+    }
+
+    pub fn get_spectator_view(&self) -> SpectatorView<'_> {
+        SpectatorView {
+            step: self.step,
+            turn: self.turn,
+            round: self.round,
+            player: self.player,
+            state_type: &self.state_type,
+            player_coins: &self.player_coins,
+            player_hands: &self.player_hands,
+            player_cards: &self.player_cards_counter,
+            revealed_cards: &self.revealed_cards,
+            deck: self.deck.len(),
+            rules: &self.rules,
+            claims: &self.player_claims,
+            history: self.history(),
+            bank: self.bank(),
+        }
+    }
+
+    pub fn get_public_view(&self) -> PublicView<'_> {
+        PublicView {
+            step: self.step,
+            turn: self.turn,
+            round: self.round,
+            state_type: &self.state_type,
+            player_coins: &self.player_coins,
+            player_hands: &self.player_hands,
+            player_cards: &self.player_cards_counter,
+            revealed_cards: &self.revealed_cards,
+            deck: self.deck.len(),
+            rules: &self.rules,
+            claims: &self.player_claims,
+            bank: self.bank(),
+        }
+    }
+
+    pub fn get_all_views(&self) -> AllViews<'_> {
+        AllViews {
+            spectator: self.get_spectator_view(),
+            players: (0..self.player_cards.len())
+                .map(|player| GameView::from(&self.get_player_view(player)))
+                .collect(),
         }
     }
 
@@ -470,9 +1095,40 @@ impl Game {
             player_cards: &self.player_cards_counter,
             revealed_cards: &self.revealed_cards,
             deck: self.deck.len(),
+            rules: &self.rules,
+            claims: &self.player_claims,
+            remaining_time: None,
         }
     }
 
+    pub fn pending_influence_losses(&self) -> Vec<usize> {
+        get_pending_influence_losses(&self.state_type, &self.player_hands)
+    }
+
+    pub fn check_influence(&self) -> Result<(), String> {
+        for player in 0..self.player_hands.len() {
+            if self.player_cards[player].len() != self.player_cards_counter[player] {
+                return Err(format!(
+                    "Player {} has {} cards but cards counter is {}",
+                    player,
+                    self.player_cards[player].len(),
+                    self.player_cards_counter[player]
+                ));
+            }
+            if self.player_hands[player] == 0 && self.player_cards_counter[player] != 0 {
+                return Err(format!(
+                    "Eliminated player {} still has {} cards",
+                    player, self.player_cards_counter[player]
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn bank(&self) -> Option<usize> {
+        get_bank(&self.rules, &self.player_coins)
+    }
+
     pub fn is_player_active(&self, index: usize) -> bool {
         self.player_hands[index] > 0
     }
@@ -501,18 +1157,78 @@ impl Game {
             player_cards: &mut self.player_cards,
             deck: &mut self.deck,
             revealed_cards: &mut self.revealed_cards,
+            rules: &self.rules,
         };
         if let Err(e) = play_action(action, &mut state, rng) {
             return Err(format!("State machine check is failed: {:?}", e));
         }
+        update_claims(&mut self.player_claims, &self.player_hands, action);
+        self.step += 1;
+        if let StateType::Turn { player } = &self.state_type {
+            self.turn += 1;
+            if self.player >= *player {
+                self.round += 1;
+            }
+            self.player = *player;
+        }
+        self.add_history(Decision::Play(action.clone()));
+        Ok(())
+    }
+
+    pub fn play_all<R: Rng>(
+        &mut self,
+        actions: &[Action],
+        rng: &mut R,
+    ) -> Result<(), (usize, String)> {
+        for (index, action) in actions.iter().enumerate() {
+            self.play(action, rng).map_err(|e| (index, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn forfeit(&mut self, player: usize) {
+        let mut cards = std::mem::take(&mut self.player_cards[player]);
+        let hand = self.player_hands[player].min(cards.len());
+        for card in cards.drain(hand..) {
+            self.deck.insert(0, card);
+        }
+        self.revealed_cards.extend(cards);
+        self.player_hands[player] = 0;
+        self.player_cards_counter[player] = 0;
+        if let Some(cards) = self.player_claims.get_mut(player) {
+            cards.clear();
+        }
         self.step += 1;
-        if let StateType::Turn { player } = &self.state_type {
-            self.turn += 1;
-            if self.player >= *player {
-                self.round += 1;
+        if !self.is_done() {
+            self.state_type = on_forfeit(
+                player,
+                self.player,
+                &self.state_type,
+                &mut self.player_coins,
+                &self.player_hands,
+            );
+            match &self.state_type {
+                StateType::Turn { player } if *player != self.player => {
+                    self.turn += 1;
+                    if self.player >= *player {
+                        self.round += 1;
+                    }
+                    self.player = *player;
+                }
+                _ => (),
             }
-            self.player = *player;
         }
+        self.add_history(Decision::Forfeit(player));
+    }
+
+    pub fn put_deck_card_on_top(&mut self, card: Card) -> Result<(), String> {
+        let index = self
+            .deck
+            .iter()
+            .rposition(|v| *v == card)
+            .ok_or_else(|| format!("Deck has no {:?} card", card))?;
+        let card = self.deck.remove(index);
+        self.deck.push(card);
         Ok(())
     }
 
@@ -549,12 +1265,33 @@ impl Game {
         }
         println!("State: {:?}", self.state_type);
     }
+
+    pub fn trace(&self) {
+        tracing::info!(
+            round = self.round,
+            turn = self.turn,
+            step = self.step,
+            done = self.is_done(),
+            winner = ?self.get_winner(),
+            player = self.player,
+            deck = ?self.deck,
+            bank = ?self.bank(),
+            player_coins = ?self.player_coins,
+            player_hands = ?self.player_hands,
+            player_cards = ?self.player_cards,
+            state = ?self.state_type,
+            pending_influence_losses = ?self.pending_influence_losses(),
+            "state"
+        );
+    }
 }
 
 pub fn get_example_settings() -> Settings {
     Settings {
         players_number: 6,
-        cards_per_type: 3,
+        card_counts: CardCounts::uniform(3),
+        cards_per_player: CARDS_PER_PLAYER,
+        rules: Rules::default(),
     }
 }
 
@@ -1095,6 +1832,7 @@ pub fn get_example_actions() -> Vec<Action> {
 mod tests {
     use rand::rngs::StdRng;
     use rand::SeedableRng;
+    use smallvec::smallvec;
 
     use super::*;
 
@@ -1104,7 +1842,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1127,7 +1867,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1152,7 +1894,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1181,7 +1925,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1220,7 +1966,10 @@ mod tests {
         assert_eq!(game.player_coins, vec![2, 2]);
         assert_eq!(
             game.player_cards,
-            vec![vec![Card::Contessa], vec![Card::Assassin, Card::Captain],]
+            vec![
+                smallvec![Card::Contessa],
+                smallvec![Card::Assassin, Card::Captain],
+            ] as Vec<Cards>
         );
         assert_eq!(game.revealed_cards, vec![Card::Ambassador]);
     }
@@ -1231,7 +1980,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1262,7 +2013,10 @@ mod tests {
         assert_eq!(game.player_coins, vec![4, 2]);
         assert_eq!(
             game.player_cards,
-            vec![vec![Card::Ambassador, Card::Contessa], vec![Card::Duke],]
+            vec![
+                smallvec![Card::Ambassador, Card::Contessa],
+                smallvec![Card::Duke],
+            ] as Vec<Cards>
         );
         assert_eq!(game.revealed_cards, vec![Card::Captain]);
     }
@@ -1273,7 +2027,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1298,7 +2054,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1329,7 +2087,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1355,7 +2115,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1390,7 +2152,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 3,
-                cards_per_type: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1428,7 +2192,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1479,7 +2245,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1509,7 +2277,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1539,7 +2309,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1586,7 +2358,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1630,7 +2404,7 @@ mod tests {
         assert_eq!(game.player_coins[1], 0);
         assert_eq!(game.player_hands[1], 0);
         assert_eq!(game.player_cards_counter[1], 0);
-        assert_eq!(game.player_cards[1], vec![]);
+        assert!(game.player_cards[1].is_empty());
     }
 
     #[test]
@@ -1639,7 +2413,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1663,7 +2439,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 3,
-                cards_per_type: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1694,7 +2472,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 3,
-                cards_per_type: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1739,7 +2519,7 @@ mod tests {
         assert_eq!(game.player_coins[0], 0);
         assert_eq!(game.player_hands[1], 0);
         assert_eq!(game.player_cards_counter[1], 0);
-        assert_eq!(game.player_cards[1], vec![]);
+        assert!(game.player_cards[1].is_empty());
         assert_eq!(game.revealed_cards, vec![Card::Ambassador, Card::Duke]);
     }
 
@@ -1750,7 +2530,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 3,
-                cards_per_type: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1794,7 +2576,7 @@ mod tests {
         assert_eq!(game.player_coins[0], 0);
         assert_eq!(game.player_hands[1], 0);
         assert_eq!(game.player_cards_counter[1], 0);
-        assert_eq!(game.player_cards[1], vec![]);
+        assert!(game.player_cards[1].is_empty());
         assert_eq!(game.revealed_cards, vec![Card::Ambassador]);
     }
 
@@ -1804,7 +2586,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 3,
-                cards_per_type: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1838,7 +2622,7 @@ mod tests {
         ];
         assert_eq!(play_actions(&actions, &mut game, &mut rng), Ok(()));
         assert_eq!(game.state_type, StateType::Turn { player: 1 });
-        assert_eq!(game.player_cards[0], vec![Card::Captain, Card::Duke]);
+        assert_eq!(*game.player_cards[0], [Card::Captain, Card::Duke]);
     }
 
     #[test]
@@ -1847,7 +2631,9 @@ mod tests {
         let mut game = Game::new(
             Settings {
                 players_number: 2,
-                cards_per_type: 1,
+                card_counts: CardCounts::uniform(1),
+                cards_per_player: 2,
+                rules: Rules::default(),
             },
             &mut rng,
         );
@@ -1882,6 +2668,671 @@ mod tests {
         assert_eq!(game.round(), 9);
     }
 
+    #[test]
+    fn play_full_game_with_three_cards_per_player_should_set_a_winner() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let settings = Settings {
+            players_number: 4,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 3,
+            rules: Rules::default(),
+        };
+        assert_eq!(settings.validate(), Ok(()));
+        let mut game = Game::new(settings, &mut rng);
+        assert_eq!(game.deck().len(), 3);
+        for player in 0..4 {
+            assert_eq!(game.get_player_view(player).cards.len(), 3);
+        }
+        while !game.is_done() {
+            let view = game.get_anonymous_view();
+            let mut actions = get_available_actions(
+                view.state_type,
+                view.player_coins,
+                view.player_hands,
+                view.rules,
+            );
+            actions.shuffle(&mut rng);
+            assert!(actions
+                .iter()
+                .any(|action| game.play(action, &mut rng).is_ok()));
+        }
+        assert!(game.get_winner().is_some());
+    }
+
+    #[test]
+    fn settings_card_counts_should_define_deck_composition() {
+        let settings: Settings =
+            serde_json::from_str(r#"{"players_number": 2, "cards_per_type": 2}"#).unwrap();
+        assert_eq!(settings.card_counts, CardCounts::uniform(2));
+        let mut card_counts = CardCounts::uniform(1);
+        card_counts.set(Card::Duke, 4);
+        card_counts.set(Card::Contessa, 0);
+        let settings = Settings {
+            players_number: 3,
+            card_counts,
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), settings);
+        assert_eq!(settings.validate(), Ok(()));
+        let game = Game::new(settings.clone(), &mut StdRng::seed_from_u64(42));
+        assert_eq!(game.get_settings().card_counts, card_counts);
+        assert!(Settings {
+            card_counts: CardCounts::uniform(0),
+            ..settings
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn game_from_scenario_should_start_from_given_position() {
+        let scenario = Scenario {
+            player_cards: vec![
+                vec![Card::Assassin],
+                vec![Card::Contessa, Card::Duke],
+                vec![],
+            ],
+            deck: vec![
+                Card::Ambassador,
+                Card::Ambassador,
+                Card::Captain,
+                Card::Captain,
+            ],
+            player_coins: Some(vec![3, 1, 0]),
+            revealed_cards: vec![Card::Assassin, Card::Contessa, Card::Duke],
+            state_type: None,
+            cards_per_player: 2,
+            rules: Rules::default(),
+            card_counts: None,
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::from_scenario(&scenario).unwrap();
+        assert_eq!(game.get_settings().players_number, 3);
+        assert_eq!(game.get_settings().card_counts, CardCounts::uniform(2));
+        assert_eq!(
+            game.play(
+                &Action {
+                    player: 0,
+                    action_type: ActionType::Assassinate(1),
+                },
+                &mut rng
+            ),
+            Ok(())
+        );
+        assert_eq!(game.get_player_view(0).coins, 0);
+        assert_eq!(
+            game.play(
+                &Action {
+                    player: 0,
+                    action_type: ActionType::Assassinate(2),
+                },
+                &mut rng
+            ),
+            Err(String::from("State machine check is failed: InvalidAction"))
+        );
+    }
+
+    #[test]
+    fn game_from_scenario_should_reject_inconsistent_cards() {
+        let scenario = Scenario {
+            player_cards: vec![vec![Card::Duke, Card::Duke], vec![Card::Duke]],
+            deck: vec![],
+            player_coins: None,
+            revealed_cards: vec![],
+            state_type: None,
+            cards_per_player: 2,
+            rules: Rules::default(),
+            card_counts: None,
+        };
+        assert!(Game::from_scenario(&scenario).is_err());
+    }
+
+    #[test]
+    fn game_from_scenario_should_check_cards_against_custom_deck() {
+        let mut card_counts = CardCounts::uniform(1);
+        card_counts.set(Card::Duke, 3);
+        card_counts.set(Card::Contessa, 0);
+        let mut scenario = Scenario {
+            player_cards: vec![
+                vec![Card::Duke, Card::Duke],
+                vec![Card::Assassin, Card::Duke],
+            ],
+            deck: vec![Card::Ambassador, Card::Captain],
+            player_coins: None,
+            revealed_cards: vec![],
+            state_type: None,
+            cards_per_player: 2,
+            rules: Rules::default(),
+            card_counts: Some(card_counts),
+        };
+        let game = Game::from_scenario(&scenario).unwrap();
+        assert_eq!(game.get_settings().card_counts, card_counts);
+        scenario.card_counts = None;
+        assert!(Game::from_scenario(&scenario).is_err());
+        card_counts.set(Card::Contessa, 1);
+        scenario.card_counts = Some(card_counts);
+        assert_eq!(
+            Game::from_scenario(&scenario).err(),
+            Some(String::from("Scenario should have 1 Contessa cards, got 0"))
+        );
+    }
+
+    #[test]
+    fn player_with_more_than_forced_coup_coins_after_steal_should_only_coup() {
+        let actions = [
+            Action {
+                player: 0,
+                action_type: ActionType::Steal(1),
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::PassChallenge,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::PassBlock,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Income,
+            },
+        ];
+        let play = |rules: Rules| {
+            let scenario = Scenario {
+                player_cards: vec![
+                    vec![Card::Captain, Card::Duke],
+                    vec![Card::Assassin, Card::Contessa],
+                ],
+                deck: vec![
+                    Card::Ambassador,
+                    Card::Ambassador,
+                    Card::Assassin,
+                    Card::Captain,
+                    Card::Contessa,
+                    Card::Duke,
+                ],
+                player_coins: Some(vec![9, 2]),
+                revealed_cards: vec![],
+                state_type: None,
+                cards_per_player: 2,
+                rules,
+                card_counts: None,
+            };
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut game = Game::from_scenario(&scenario).unwrap();
+            assert_eq!(play_actions(&actions, &mut game, &mut rng), Ok(()));
+            assert_eq!(game.get_player_view(0).coins, 11);
+            game
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = play(Rules::default());
+        let view = game.get_anonymous_view();
+        let available_actions = get_available_actions(
+            view.state_type,
+            view.player_coins,
+            view.player_hands,
+            view.rules,
+        );
+        assert_eq!(
+            available_actions,
+            vec![Action {
+                player: 0,
+                action_type: ActionType::Coup(1),
+            }]
+        );
+        assert!(game
+            .play(
+                &Action {
+                    player: 0,
+                    action_type: ActionType::Income,
+                },
+                &mut rng,
+            )
+            .is_err());
+        let mut game = play(Rules {
+            forced_coup_coins: 12,
+            ..Default::default()
+        });
+        let view = game.get_anonymous_view();
+        let available_actions = get_available_actions(
+            view.state_type,
+            view.player_coins,
+            view.player_hands,
+            view.rules,
+        );
+        let income = Action {
+            player: 0,
+            action_type: ActionType::Income,
+        };
+        assert!(available_actions.contains(&income));
+        assert_eq!(game.play(&income, &mut rng), Ok(()));
+    }
+
+    #[test]
+    fn forfeit_should_reveal_player_cards_and_pass_turn() {
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Ambassador, Card::Duke],
+            ],
+            vec![Card::Ambassador, Card::Captain],
+        );
+        game.forfeit(0);
+        assert!(!game.is_player_active(0));
+        assert_eq!(
+            game.get_anonymous_view().state_type,
+            &StateType::Turn { player: 1 }
+        );
+        assert_eq!(
+            game.get_anonymous_view().revealed_cards,
+            &[Card::Assassin, Card::Duke]
+        );
+        game.forfeit(2);
+        assert!(game.is_done());
+        assert_eq!(game.get_winner(), Some(1));
+    }
+
+    fn make_assassination_game() -> Game {
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Ambassador, Card::Duke],
+                vec![Card::Assassin, Card::Captain],
+            ],
+            vec![Card::Ambassador, Card::Contessa],
+        );
+        game.player_coins = vec![3; 4];
+        let assassinate = Action {
+            player: 0,
+            action_type: ActionType::Assassinate(1),
+        };
+        assert_eq!(
+            game.play(&assassinate, &mut StdRng::seed_from_u64(42)),
+            Ok(())
+        );
+        game
+    }
+
+    #[test]
+    fn forfeit_of_responder_should_keep_pending_action() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = make_assassination_game();
+        game.forfeit(3);
+        assert!(!game.is_player_active(3));
+        assert_eq!(
+            game.get_anonymous_view().state_type,
+            &StateType::Assassination {
+                player: 0,
+                target: 1,
+                can_challenge: true,
+            }
+        );
+        assert_eq!(game.get_anonymous_view().player_coins, &[0, 3, 3, 3]);
+        let pass = Action {
+            player: 0,
+            action_type: ActionType::PassChallenge,
+        };
+        assert_eq!(game.play(&pass, &mut rng), Ok(()));
+        let pass = Action {
+            player: 0,
+            action_type: ActionType::PassBlock,
+        };
+        assert_eq!(game.play(&pass, &mut rng), Ok(()));
+        assert_eq!(
+            game.get_anonymous_view().state_type,
+            &StateType::LostInfluence {
+                player: 1,
+                current_player: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn forfeit_of_target_should_cancel_action_and_refund_coins() {
+        let mut game = make_assassination_game();
+        game.forfeit(1);
+        assert!(!game.is_player_active(1));
+        assert_eq!(
+            game.get_anonymous_view().state_type,
+            &StateType::Turn { player: 2 }
+        );
+        assert_eq!(game.get_anonymous_view().player_coins, &[3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn forfeit_of_challenge_initiator_should_restore_challenged_state() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = make_assassination_game();
+        let challenge = Action {
+            player: 2,
+            action_type: ActionType::Challenge,
+        };
+        assert_eq!(game.play(&challenge, &mut rng), Ok(()));
+        game.forfeit(2);
+        assert_eq!(
+            game.get_anonymous_view().state_type,
+            &StateType::Assassination {
+                player: 0,
+                target: 1,
+                can_challenge: true,
+            }
+        );
+    }
+
+    #[test]
+    fn available_actions_for_should_filter_player_and_kind() {
+        let game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Ambassador, Card::Duke],
+            ],
+            vec![Card::Ambassador, Card::Captain],
+        );
+        let view = game.get_player_view(0);
+        assert_eq!(
+            available_actions_for(&view, 0).collect::<Vec<_>>(),
+            get_available_actions(
+                view.state_type,
+                view.player_coins,
+                view.player_hands,
+                view.rules
+            )
+        );
+        assert_eq!(available_actions_for(&view, 1).count(), 0);
+        assert_eq!(
+            available_actions_for(&view, 0)
+                .of_kind(&ActionType::Steal(0))
+                .collect::<Vec<_>>(),
+            vec![
+                Action {
+                    player: 0,
+                    action_type: ActionType::Steal(1),
+                },
+                Action {
+                    player: 0,
+                    action_type: ActionType::Steal(2),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn put_deck_card_on_top_should_make_it_next_to_take() {
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+            ],
+            vec![Card::Ambassador, Card::Captain, Card::Duke],
+        );
+        assert_eq!(
+            game.put_deck_card_on_top(Card::Contessa),
+            Err(String::from("Deck has no Contessa card"))
+        );
+        assert_eq!(game.put_deck_card_on_top(Card::Ambassador), Ok(()));
+        assert_eq!(game.deck, vec![Card::Captain, Card::Duke, Card::Ambassador]);
+    }
+
+    #[test]
+    fn play_should_record_claims_per_player() {
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+            ],
+            vec![Card::Ambassador, Card::Captain, Card::Duke],
+        );
+        let mut rng = StdRng::seed_from_u64(42);
+        for (player, action_type) in [
+            (0, ActionType::Tax),
+            (0, ActionType::PassChallenge),
+            (1, ActionType::Steal(0)),
+            (1, ActionType::PassChallenge),
+            (0, ActionType::BlockSteal(Card::Captain)),
+            (0, ActionType::PassChallenge),
+        ] {
+            game.play(
+                &Action {
+                    player,
+                    action_type,
+                },
+                &mut rng,
+            )
+            .unwrap();
+        }
+        let view = game.get_player_view(1);
+        assert_eq!(
+            view.claims,
+            &[vec![Card::Captain, Card::Duke], vec![Card::Captain]]
+        );
+        assert!(game.normalized().player_claims.iter().all(Vec::is_empty));
+        game.forfeit(0);
+        assert!(game.get_player_view(1).claims[0].is_empty());
+    }
+
+    #[test]
+    fn history_should_record_decisions_with_state_hashes() {
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Ambassador, Card::Duke],
+            ],
+            vec![Card::Ambassador, Card::Captain],
+        );
+        let mut rng = StdRng::seed_from_u64(42);
+        let income = Action {
+            player: 0,
+            action_type: ActionType::Income,
+        };
+        game.play(&income, &mut rng).unwrap();
+        assert!(game.history().is_empty());
+        game.record_history();
+        game.play(
+            &Action {
+                player: 1,
+                action_type: ActionType::Income,
+            },
+            &mut rng,
+        )
+        .unwrap();
+        let hash = game.state_hash();
+        game.forfeit(2);
+        assert_eq!(
+            game.decisions().collect::<Vec<_>>(),
+            vec![
+                &Decision::Play(Action {
+                    player: 1,
+                    action_type: ActionType::Income,
+                }),
+                &Decision::Forfeit(2),
+            ]
+        );
+        assert_eq!(game.history()[0].hash, hash);
+        assert_eq!(game.history()[1].hash, game.state_hash());
+        let restored: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(restored, game);
+        assert!(game.normalized().history().is_empty());
+    }
+
+    #[test]
+    fn state_hash_should_be_stable() {
+        let game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Ambassador, Card::Duke],
+            ],
+            vec![Card::Ambassador, Card::Captain],
+        );
+        assert_eq!(game.state_hash(), 18259179484053293162);
+        assert_eq!(game.get_player_view(0).state_hash(), 15151877175546457226);
+    }
+
+    #[test]
+    fn spectator_view_should_not_include_hidden_cards() {
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Assassin, Card::Duke],
+                vec![Card::Captain, Card::Contessa],
+            ],
+            vec![Card::Ambassador, Card::Captain],
+        );
+        game.record_history();
+        game.play(
+            &Action {
+                player: 0,
+                action_type: ActionType::Tax,
+            },
+            &mut StdRng::seed_from_u64(42),
+        )
+        .unwrap();
+        let view = serde_json::to_value(game.get_spectator_view()).unwrap();
+        assert!(view.get("cards").is_none());
+        assert_eq!(view["player_cards"], serde_json::json!([2, 2]));
+        assert_eq!(view["claims"], serde_json::json!([["Duke"], []]));
+        assert_eq!(view["history"].as_array().unwrap().len(), 1);
+        assert_eq!(view["deck"], serde_json::json!(2));
+        let public = serde_json::to_value(game.get_public_view()).unwrap();
+        assert!(public.get("history").is_none());
+        assert_eq!(public["claims"], view["claims"]);
+    }
+
+    #[test]
+    fn get_all_views_should_match_individual_views() {
+        let game = Game::new(
+            Settings {
+                players_number: 3,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+            &mut StdRng::seed_from_u64(42),
+        );
+        let views = game.get_all_views();
+        assert_eq!(views.players.len(), 3);
+        for (player, view) in views.players.iter().enumerate() {
+            assert_eq!(
+                serde_json::to_value(view.player_view()).unwrap(),
+                serde_json::to_value(game.get_player_view(player)).unwrap()
+            );
+        }
+        assert_eq!(
+            serde_json::to_value(&views.spectator).unwrap(),
+            serde_json::to_value(game.get_spectator_view()).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_history_should_report_first_illegal_action() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut actions = vec![
+            Action {
+                player: 0,
+                action_type: ActionType::Income,
+            },
+            Action {
+                player: 1,
+                action_type: ActionType::Income,
+            },
+        ];
+        let game = Game::from_history(settings.clone(), 42, &actions).unwrap();
+        assert_eq!(game.step(), 2);
+        assert_eq!(game.decisions().count(), 2);
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut expected = Game::new(settings.clone(), &mut rng);
+        assert_eq!(expected.play_all(&actions, &mut rng), Ok(()));
+        assert_eq!(game.state_hash(), expected.state_hash());
+        actions.push(actions[0].clone());
+        actions.push(actions[0].clone());
+        assert_eq!(
+            Game::from_history(settings, 42, &actions),
+            Err(ReplayError::IllegalAction {
+                index: 3,
+                step: 3,
+                action: actions[0].clone(),
+                error: String::from("State machine check is failed: InvalidPlayer"),
+            })
+        );
+    }
+
+    #[test]
+    fn play_all_should_stop_at_first_failed_action() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let income = |player| Action {
+            player,
+            action_type: ActionType::Income,
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::new(settings, &mut rng);
+        assert_eq!(
+            game.play_all(&[income(0), income(1), income(1), income(0)], &mut rng),
+            Err((
+                2,
+                String::from("State machine check is failed: InvalidPlayer")
+            ))
+        );
+        assert_eq!(game.step(), 2);
+    }
+
+    #[test]
+    fn settings_validate_should_check_players_and_cards_number() {
+        let valid = |players_number, cards_per_type| {
+            Settings {
+                players_number,
+                card_counts: CardCounts::uniform(cards_per_type),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            }
+            .validate()
+            .is_ok()
+        };
+        assert!(valid(2, 1));
+        assert!(valid(6, 3));
+        assert!(valid(7, 3));
+        assert!(!valid(1, 3));
+        assert!(!valid(6, 2));
+        assert!(!valid(8, 3));
+        assert!(!valid(2, 0));
+        assert!(Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(1),
+            cards_per_player: 2,
+            rules: Rules {
+                forced_coup_coins: COUP_COST - 1,
+                ..Default::default()
+            },
+        }
+        .validate()
+        .is_err());
+        assert!(Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(1),
+            cards_per_player: 2,
+            rules: Rules {
+                cards_to_exchange: MAX_CARDS_TO_EXCHANGE + 1,
+                ..Default::default()
+            },
+        }
+        .validate()
+        .is_err());
+    }
+
     fn play_actions<R: Rng>(
         actions: &[Action],
         game: &mut Game,
@@ -1889,8 +3340,12 @@ mod tests {
     ) -> Result<(), String> {
         for (i, action) in actions.iter().enumerate() {
             let view = game.get_player_view(action.player);
-            let available_actions =
-                get_available_actions(view.state_type, view.player_coins, view.player_hands);
+            let available_actions = get_available_actions(
+                view.state_type,
+                view.player_coins,
+                view.player_hands,
+                view.rules,
+            );
             game.print();
             println!("Play {:?}", action);
             match game.play(action, rng) {
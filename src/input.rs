@@ -0,0 +1,324 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+
+use serde::Deserialize;
+
+#[derive(Debug)]
+pub enum InputError {
+    Open {
+        path: String,
+        error: std::io::Error,
+    },
+    Read {
+        line: usize,
+        error: std::io::Error,
+    },
+    Parse {
+        line: usize,
+        content: String,
+        error: serde_json::Error,
+    },
+    Invalid {
+        line: usize,
+        content: String,
+        error: String,
+    },
+    Play {
+        line: usize,
+        content: String,
+        error: String,
+    },
+    UnexpectedEnd {
+        line: usize,
+        expected: &'static str,
+    },
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Open { path, error } => write!(f, "Failed to open {}: {}", path, error),
+            InputError::Read { line, error } => {
+                write!(f, "Failed to read line {}: {}", line, error)
+            }
+            InputError::Parse {
+                line,
+                content,
+                error,
+            } => write!(
+                f,
+                "Failed to parse line {}: {}\n    {}",
+                line, error, content
+            ),
+            InputError::Invalid {
+                line,
+                content,
+                error,
+            } => write!(
+                f,
+                "Invalid content at line {}: {}\n    {}",
+                line, error, content
+            ),
+            InputError::Play {
+                line,
+                content,
+                error,
+            } => write!(
+                f,
+                "Failed to play action from line {}: {}\n    {}",
+                line, error, content
+            ),
+            InputError::UnexpectedEnd { line, expected } => {
+                write!(
+                    f,
+                    "Unexpected end of input after line {}: expected {}",
+                    line, expected
+                )
+            }
+        }
+    }
+}
+
+pub fn open_file(path: &str) -> Result<BufReader<File>, InputError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|error| InputError::Open {
+            path: String::from(path),
+            error,
+        })
+}
+
+pub struct Record<T> {
+    pub line: usize,
+    content: Range<usize>,
+    pub value: T,
+}
+
+pub struct LineReader<F: BufRead> {
+    inner: F,
+    line: usize,
+    content: Vec<u8>,
+    block: Vec<u8>,
+    block_lines: Vec<Range<usize>>,
+    block_first_line: usize,
+}
+
+fn get_content(line: &[u8]) -> String {
+    String::from_utf8_lossy(line.trim_ascii_end()).into_owned()
+}
+
+impl<F: BufRead> LineReader<F> {
+    pub fn new(inner: F) -> Self {
+        Self {
+            inner,
+            line: 0,
+            content: Vec::new(),
+            block: Vec::new(),
+            block_lines: Vec::new(),
+            block_first_line: 0,
+        }
+    }
+
+    pub fn read<'a, T: Deserialize<'a>>(&'a mut self) -> Result<Option<T>, InputError> {
+        self.content.clear();
+        let size = self
+            .inner
+            .read_until(b'\n', &mut self.content)
+            .map_err(|error| InputError::Read {
+                line: self.line + 1,
+                error,
+            })?;
+        if size == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+        serde_json::from_slice(&self.content)
+            .map(Some)
+            .map_err(|error| InputError::Parse {
+                line: self.line,
+                content: get_content(&self.content),
+                error,
+            })
+    }
+
+    pub fn read_required<'a, T: Deserialize<'a>>(
+        &'a mut self,
+        expected: &'static str,
+    ) -> Result<T, InputError> {
+        let line = self.line;
+        match self.read()? {
+            Some(v) => Ok(v),
+            None => Err(InputError::UnexpectedEnd { line, expected }),
+        }
+    }
+
+    // Reads the rest of the input into a reused buffer line by line to let records borrow from it.
+    pub fn read_lines(&mut self) -> Result<(), InputError> {
+        self.content.clear();
+        self.block.clear();
+        self.block_lines.clear();
+        self.block_first_line = self.line + 1;
+        loop {
+            let start = self.block.len();
+            let size = self
+                .inner
+                .read_until(b'\n', &mut self.block)
+                .map_err(|error| InputError::Read {
+                    line: self.line + 1,
+                    error,
+                })?;
+            if size == 0 {
+                return Ok(());
+            }
+            self.line += 1;
+            self.block_lines.push(start..self.block.len());
+        }
+    }
+
+    pub fn records<'a, T: Deserialize<'a>>(&'a self) -> Result<Vec<Record<T>>, InputError> {
+        self.block_lines
+            .iter()
+            .enumerate()
+            .map(|(index, content)| {
+                let line = self.block_first_line + index;
+                let value =
+                    serde_json::from_slice(&self.block[content.clone()]).map_err(|error| {
+                        InputError::Parse {
+                            line,
+                            content: get_content(&self.block[content.clone()]),
+                            error,
+                        }
+                    })?;
+                Ok(Record {
+                    line,
+                    content: content.clone(),
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    pub fn invalid_error(&self, error: String) -> InputError {
+        InputError::Invalid {
+            line: self.line,
+            content: get_content(&self.content),
+            error,
+        }
+    }
+
+    pub fn play_error<T>(&self, record: &Record<T>, error: String) -> InputError {
+        InputError::Play {
+            line: record.line,
+            content: get_content(&self.block[record.content.clone()]),
+            error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_should_keep_line_numbers_and_content() {
+        let mut file = LineReader::new(&b"{\"a\":1}\n[1]\n[2, 3]  \n[4]"[..]);
+        let header: serde_json::Value = file.read_required("header").unwrap();
+        assert_eq!(header["a"], 1);
+        file.read_lines().unwrap();
+        let records: Vec<Record<Vec<usize>>> = file.records().unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|v| (v.line, v.value.clone()))
+                .collect::<Vec<_>>(),
+            vec![(2, vec![1]), (3, vec![2, 3]), (4, vec![4])]
+        );
+        assert_eq!(
+            file.play_error(&records[1], String::from("error"))
+                .to_string(),
+            "Failed to play action from line 3: error\n    [2, 3]"
+        );
+        let mut file = LineReader::new(&b"[1]\nx\n"[..]);
+        file.read_lines().unwrap();
+        assert!(matches!(
+            file.records::<Vec<usize>>(),
+            Err(InputError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn read_should_report_parse_error_with_line_and_content() {
+        let mut file = LineReader::new(&b"[1]\n{\"a\": }  \n"[..]);
+        assert_eq!(file.read::<Vec<usize>>().unwrap(), Some(vec![1]));
+        let error = file.read::<Vec<usize>>().unwrap_err();
+        assert!(
+            matches!(&error, InputError::Parse { line: 2, content, .. } if content == "{\"a\": }")
+        );
+        assert!(error.to_string().starts_with("Failed to parse line 2: "));
+        assert!(error.to_string().ends_with("\n    {\"a\": }"));
+    }
+
+    #[test]
+    fn read_should_report_type_mismatch_as_parse_error() {
+        let mut file = LineReader::new(&b"{\"a\":1}\n"[..]);
+        assert!(matches!(
+            file.read::<Vec<usize>>(),
+            Err(InputError::Parse { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn read_required_should_report_unexpected_end() {
+        let mut file = LineReader::new(&b"[1]\n"[..]);
+        let _: Vec<usize> = file.read_required("header").unwrap();
+        let error = file.read_required::<Vec<usize>>("game params").unwrap_err();
+        assert!(matches!(
+            error,
+            InputError::UnexpectedEnd {
+                line: 1,
+                expected: "game params"
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Unexpected end of input after line 1: expected game params"
+        );
+    }
+
+    #[test]
+    fn invalid_error_should_point_to_last_read_line() {
+        let mut file = LineReader::new(&b"[1]\n[2]\n"[..]);
+        let _: Vec<usize> = file.read_required("first").unwrap();
+        let _: Vec<usize> = file.read_required("second").unwrap();
+        assert_eq!(
+            file.invalid_error(String::from("error")).to_string(),
+            "Invalid content at line 2: error\n    [2]"
+        );
+    }
+
+    #[test]
+    fn open_file_should_report_path() {
+        let error = open_file("/nonexistent/coup.log").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Failed to open /nonexistent/coup.log: "));
+    }
+
+    #[test]
+    fn records_should_borrow_strings_from_input() {
+        #[derive(Deserialize)]
+        struct Named<'a> {
+            name: &'a str,
+        }
+
+        let mut file = LineReader::new(&b"{\"name\":\"a\"}\n{\"name\":\"b\"}\n"[..]);
+        file.read_lines().unwrap();
+        let records: Vec<Record<Named>> = file.records().unwrap();
+        assert_eq!(
+            records.iter().map(|v| v.value.name).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+    }
+}
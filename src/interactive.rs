@@ -3,22 +3,32 @@ use std::io::{BufRead, Write};
 use std::str::FromStr;
 
 use itertools::Itertools;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use scan_fmt::parse::ScanError;
 
-use crate::bots::{ActionView, Bot, HonestCarefulRandomBot, RandomBot};
+use crate::bots::{notify_bot, ActionView, Bot, HonestCarefulRandomBot, InfoSeekingBot, RandomBot};
+use crate::challenge::get_challenge_odds;
 use crate::fsm::{
-    play_action, Action, ActionType, Card, ConstRng, Deck, PlayerCards, State, StateType,
-    CARDS_PER_PLAYER,
+    get_claimant_pass, play_action, Action, ActionType, Card, ConstRng, Deck, PlayerCards, Rules,
+    State, StateType, CARDS_PER_PLAYER,
 };
-use crate::game::{get_available_actions, PlayerView, Settings, ALL_CARDS, INITIAL_COINS};
+use crate::game::{
+    available_actions_for, get_available_actions, update_claims, ActionsIterator, CardCounts, Game,
+    PlayerView, Settings, INITIAL_COINS,
+};
+use crate::locale::{format_cards, get_lang, tr, Message};
 use crate::run::BotType;
+use crate::seed::hash_seed_phrase;
+use crate::state_class::StateClass;
+use crate::target::rank_targets;
 
 #[derive(Debug)]
 enum Command {
     Help,
     Quit,
     Set(SetCommand),
+    Deck(DeckCommand),
     NamePlayer { index: usize, name: String },
     Add(Card),
     Remove(Card),
@@ -28,14 +38,29 @@ enum Command {
     State,
     Available,
     Bot(BotCommand),
+    Hint,
+    HintChallenge,
+    HintTarget,
+    Branch(BranchCommand),
+    Skip,
 }
 
 #[derive(Debug)]
 enum SetCommand {
     PlayersNumber(usize),
     CardsPerType(usize),
+    CardsPerPlayer(usize),
     BotType(BotType),
     PlayerIndex(usize),
+    Seed(u64),
+    AutoPass(bool),
+}
+
+#[derive(Debug)]
+enum DeckCommand {
+    Set(Card, usize),
+    Add(Card, usize),
+    Remove(Card, usize),
 }
 
 #[derive(Debug)]
@@ -45,6 +70,13 @@ enum BotCommand {
     Custom(String),
 }
 
+#[derive(Debug)]
+enum BranchCommand {
+    Start,
+    Drop,
+    Keep,
+}
+
 #[derive(Debug)]
 struct GameAction {
     player: String,
@@ -74,47 +106,125 @@ pub enum GameActionType {
 pub fn run_interactive_game() {
     let mut settings = Settings {
         players_number: 6,
-        cards_per_type: 2,
+        card_counts: CardCounts::uniform(3),
+        cards_per_player: CARDS_PER_PLAYER,
+        rules: Rules::default(),
     };
     let mut bot_type = BotType::HonestCarefulRandom;
     let mut player_index = 0;
+    let mut seed = 42;
+    let mut auto_pass = false;
     let mut player_cards = Vec::with_capacity(2);
     let mut custom_player_names: HashMap<usize, String> = HashMap::new();
-    println!("Use default settings:");
+    println!("{}", tr(Message::UseDefaultSettings));
     println!("players_number: {}", settings.players_number);
-    println!("cards_per_type: {}", settings.cards_per_type);
+    println!("card_counts: {}", settings.card_counts);
+    println!("cards_per_player: {}", settings.cards_per_player);
     println!("player_index: {}", player_index);
     println!("bot_type: {:?}", bot_type);
+    println!("seed: {}", seed);
+    println!("auto_pass: {}", auto_pass);
     loop {
         match read_command() {
-            Command::Help => println!("{}", HELP),
+            Command::Help => println!("{}", get_lang().help()),
             Command::Quit => break,
-            Command::Set(set) => match set {
-                SetCommand::PlayersNumber(v) => settings.players_number = v,
-                SetCommand::CardsPerType(v) => settings.cards_per_type = v,
-                SetCommand::BotType(v) => bot_type = v,
-                SetCommand::PlayerIndex(v) => player_index = v,
-            },
+            Command::Set(set) => {
+                let mut new_settings = settings.clone();
+                match set {
+                    SetCommand::PlayersNumber(v) => new_settings.players_number = v,
+                    SetCommand::CardsPerType(v) => {
+                        new_settings.card_counts = CardCounts::uniform(v)
+                    }
+                    SetCommand::CardsPerPlayer(v) => new_settings.cards_per_player = v,
+                    SetCommand::BotType(v) => bot_type = v,
+                    SetCommand::PlayerIndex(v) => player_index = v,
+                    SetCommand::Seed(v) => {
+                        seed = v;
+                        println!("seed: {}", seed);
+                    }
+                    SetCommand::AutoPass(v) => {
+                        auto_pass = v;
+                        println!("auto_pass: {}", auto_pass);
+                    }
+                }
+                if let Err(e) = new_settings.validate() {
+                    println!("{}", e);
+                    continue;
+                }
+                settings = new_settings;
+                if let Err(e) = validate_setup(&settings, player_index, &player_cards) {
+                    println!("{}", tr(Message::Warning(&e)));
+                }
+            }
+            Command::Deck(deck) => {
+                let (card, count) = match deck {
+                    DeckCommand::Set(card, count) => (card, count),
+                    DeckCommand::Add(card, count) => (card, settings.card_counts.get(card) + count),
+                    DeckCommand::Remove(card, count) => {
+                        match settings.card_counts.get(card).checked_sub(count) {
+                            Some(v) => (card, v),
+                            None => {
+                                println!(
+                                    "{}",
+                                    tr(Message::CantRemoveCards {
+                                        count,
+                                        card,
+                                        available: settings.card_counts.get(card),
+                                    })
+                                );
+                                continue;
+                            }
+                        }
+                    }
+                };
+                if matches!(card, Card::Unknown) {
+                    println!("{}", tr(Message::DeckCantHaveUnknownCards));
+                    continue;
+                }
+                let mut new_settings = settings.clone();
+                new_settings.card_counts.set(card, count);
+                if let Err(e) = new_settings.validate() {
+                    println!("{}", e);
+                    continue;
+                }
+                settings = new_settings;
+                println!("card_counts: {}", settings.card_counts);
+                if let Err(e) = validate_setup(&settings, player_index, &player_cards) {
+                    println!("{}", tr(Message::Warning(&e)));
+                }
+            }
             Command::NamePlayer { index, name } => {
                 if index >= settings.players_number {
                     println!(
-                        "Player index is not applicable for current number of players: {}",
-                        settings.players_number
+                        "{}",
+                        tr(Message::PlayerIndexNotApplicable(settings.players_number))
                     );
                     continue;
                 }
                 custom_player_names.insert(index, name);
             }
             Command::Add(card) => {
-                if player_cards.len() >= CARDS_PER_PLAYER {
-                    println!("Can't add more than {} cards", CARDS_PER_PLAYER);
+                if player_cards.len() >= settings.cards_per_player {
+                    println!(
+                        "{}",
+                        tr(Message::CantAddMoreCards(settings.cards_per_player))
+                    );
+                    continue;
+                }
+                let mut new_player_cards = player_cards.clone();
+                new_player_cards.push(card);
+                if let Err(e) = validate_hand(&settings, &new_player_cards) {
+                    println!("{}", e);
                     continue;
                 }
-                player_cards.push(card);
+                player_cards = new_player_cards;
             }
             Command::Remove(card) => {
                 if player_cards.is_empty() {
-                    println!("Can't add more than {} cards", CARDS_PER_PLAYER);
+                    println!(
+                        "{}",
+                        tr(Message::CantAddMoreCards(settings.cards_per_player))
+                    );
                     continue;
                 }
                 let index = player_cards
@@ -124,62 +234,121 @@ pub fn run_interactive_game() {
                 if let Some(index) = index {
                     player_cards.remove(index);
                 } else {
-                    println!("Don't have {:?} card", card);
+                    println!("{}", tr(Message::DontHaveCard(card)));
                 }
             }
             Command::Start => {
-                if player_cards.len() != CARDS_PER_PLAYER {
+                if player_cards.len() < settings.cards_per_player {
                     println!(
-                        "Need to add {} more card(s)",
-                        CARDS_PER_PLAYER - player_cards.len()
+                        "{}",
+                        tr(Message::NeedToAddCards(
+                            settings.cards_per_player - player_cards.len()
+                        ))
                     );
                     continue;
                 }
-                if settings.cards_per_type * ALL_CARDS.len()
-                    < settings.players_number * CARDS_PER_PLAYER
-                {
+                if player_cards.len() > settings.cards_per_player {
                     println!(
-                        "Not enough cards for all players: need at least {} cards per type for {} players",
-                        (settings.players_number * CARDS_PER_PLAYER) / ALL_CARDS.len(),
-                        settings.players_number
+                        "{}",
+                        tr(Message::NeedToRemoveCards(
+                            player_cards.len() - settings.cards_per_player
+                        ))
                     );
                     continue;
                 }
+                if let Err(e) = validate_setup(&settings, player_index, &player_cards) {
+                    println!("{}", e);
+                    continue;
+                }
                 let game_state =
                     make_initial_game_state(&settings, player_index, player_cards.clone());
-                let player_names: Vec<String> = (0..settings.players_number)
-                    .map(|index| {
-                        if let Some(v) = custom_player_names.get(&index) {
-                            v.clone()
-                        } else if index == player_index {
-                            String::from("me")
-                        } else {
-                            format!("{}", index)
-                        }
-                    })
-                    .collect();
-                println!("Start game with initial state:");
+                let player_names = get_player_names(
+                    settings.players_number,
+                    &custom_player_names,
+                    Some(player_index),
+                );
+                println!("{}", tr(Message::StartGame));
                 println!("players_number: {}", settings.players_number);
-                println!("cards_per_type: {}", settings.cards_per_type);
+                println!("card_counts: {}", settings.card_counts);
+                println!("cards_per_player: {}", settings.cards_per_player);
                 println!("bot_type: {:?}", bot_type);
+                println!("seed: {}", seed);
+                println!("auto_pass: {}", auto_pass);
                 print_state(&game_state, &player_names);
+                let rng = StdRng::seed_from_u64(seed);
                 match bot_type {
                     BotType::Random => {
-                        let bot = RandomBot::new(&game_state.player_view());
-                        interactive_with_bot(&player_names, game_state, bot);
+                        let mut bot = RandomBot::new(rng);
+                        bot.on_game_start(&settings, game_state.player);
+                        interactive_with_bot(&player_names, game_state, bot, auto_pass);
                     }
                     BotType::HonestCarefulRandom => {
-                        let bot = HonestCarefulRandomBot::new(&game_state.player_view(), &settings);
-                        interactive_with_bot(&player_names, game_state, bot);
+                        let mut bot =
+                            HonestCarefulRandomBot::new(&game_state.player_view(), &settings, rng);
+                        bot.on_game_start(&settings, game_state.player);
+                        interactive_with_bot(&player_names, game_state, bot, auto_pass);
+                    }
+                    BotType::InfoSeeking => {
+                        let mut bot =
+                            InfoSeekingBot::new(&game_state.player_view(), &settings, rng);
+                        bot.on_game_start(&settings, game_state.player);
+                        interactive_with_bot(&player_names, game_state, bot, auto_pass);
                     }
                 }
                 break;
             }
-            _ => println!("Invalid command"),
+            _ => println!("{}", tr(Message::InvalidCommand)),
         }
     }
 }
 
+pub fn get_player_names(
+    players_number: usize,
+    custom_player_names: &HashMap<usize, String>,
+    me: Option<usize>,
+) -> Vec<String> {
+    (0..players_number)
+        .map(|index| {
+            if let Some(v) = custom_player_names.get(&index) {
+                v.clone()
+            } else if Some(index) == me {
+                tr(Message::Me)
+            } else {
+                format!("{}", index)
+            }
+        })
+        .collect()
+}
+
+fn validate_setup(settings: &Settings, player_index: usize, cards: &[Card]) -> Result<(), String> {
+    settings.validate()?;
+    if player_index >= settings.players_number {
+        return Err(format!(
+            "Player index {} is not applicable for current number of players: {}",
+            player_index, settings.players_number
+        ));
+    }
+    validate_hand(settings, cards)
+}
+
+fn validate_hand(settings: &Settings, cards: &[Card]) -> Result<(), String> {
+    for card in cards.iter() {
+        if matches!(card, Card::Unknown) {
+            return Err(String::from("Player hand can't have unknown card"));
+        }
+        let count = cards.iter().filter(|v| **v == *card).count();
+        if count > settings.card_counts.get(*card) {
+            return Err(format!(
+                "Can't have {} {:?} cards with {} of them in the deck",
+                count,
+                card,
+                settings.card_counts.get(*card)
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn read_command() -> Command {
     loop {
         print!("> ");
@@ -213,13 +382,35 @@ fn parse_command(line: &str) -> Result<Command, ScanError> {
                 "cards_per_type" => {
                     SetCommand::CardsPerType(scan_fmt!(line, "set cards_per_type {d}", usize)?)
                 }
+                "cards_per_player" => {
+                    SetCommand::CardsPerPlayer(scan_fmt!(line, "set cards_per_player {d}", usize)?)
+                }
                 "bot_type" => {
                     SetCommand::BotType(scan(scan_fmt!(line, "set bot_type {}", String)?)?)
                 }
                 "player" => SetCommand::PlayerIndex(scan_fmt!(line, "set player {}", usize)?),
+                "seed" => SetCommand::Seed(scan_fmt!(line, "set seed {d}", u64)?),
+                "auto_pass" => SetCommand::AutoPass(scan_fmt!(line, "set auto_pass {}", bool)?),
+                "seed_phrase" => {
+                    let phrase = line.split_once("seed_phrase").map_or("", |v| v.1).trim();
+                    if phrase.is_empty() {
+                        return Err(ScanError(String::from("empty seed phrase")));
+                    }
+                    SetCommand::Seed(hash_seed_phrase(phrase))
+                }
                 v => return Err(ScanError(format!("invalid set command param: {}", v))),
             },
         )),
+        "deck" => {
+            let (command, card, count) = scan_fmt!(line, "deck {} {} {d}", String, String, usize)?;
+            let card = scan(card)?;
+            Ok(Command::Deck(match command.as_str() {
+                "set" => DeckCommand::Set(card, count),
+                "add" => DeckCommand::Add(card, count),
+                "remove" | "rm" => DeckCommand::Remove(card, count),
+                v => return Err(ScanError(format!("invalid deck command: {}", v))),
+            }))
+        }
         "name" => {
             let (index, name) = scan_fmt!(line, "name {d} {}", usize, String)?;
             Ok(Command::NamePlayer { index, name })
@@ -230,34 +421,14 @@ fn parse_command(line: &str) -> Result<Command, ScanError> {
         "play" => {
             let player = scan_fmt!(line, "play {}", String)?;
             let sub = get_tail(player.len(), get_tail(4, line));
-            let action_type = match scan_fmt!(sub, "{}", String)?.as_str() {
-                "income" => GameActionType::Income,
-                "coup" => GameActionType::Coup(scan_fmt!(sub, "coup {}", String)?),
-                "foreign_aid" | "aid" => GameActionType::ForeignAid,
-                "tax" => GameActionType::Tax,
-                "assassinate" => {
-                    GameActionType::Assassinate(scan_fmt!(sub, "assassinate {}", String)?)
-                }
-                "kill" => GameActionType::Assassinate(scan_fmt!(sub, "kill {}", String)?),
-                "exchange" => GameActionType::Exchange,
-                "steal" => GameActionType::Steal(scan_fmt!(sub, "steal {}", String)?),
-                "block" => GameActionType::Block(scan(scan_fmt!(sub, "block {}", String)?)?),
-                "pass_challenge" | "pass_c" => GameActionType::PassChallenge,
-                "pass_block" | "pass_b" => GameActionType::PassBlock,
-                "challenge" => GameActionType::Challenge,
-                "show" => GameActionType::ShowCard(scan(scan_fmt!(sub, "show {}", String)?)?),
-                "reveal" => GameActionType::RevealCard(scan(scan_fmt!(sub, "reveal {}", String)?)?),
-                "drop" => GameActionType::DropCard(scan(scan_fmt!(sub, "drop {}", String)?)?),
-                "take" => GameActionType::TakeCard(scan(scan_fmt!(sub, "take {}", String)?)?),
-                "shuffle" => GameActionType::ShuffleDeck,
-                v => return Err(ScanError(format!("invalid action type: {}", v))),
-            };
+            let action_type = parse_game_action_type(sub)?;
             Ok(Command::Play(GameAction {
                 player,
                 action_type,
             }))
         }
         "undo" => Ok(Command::Undo),
+        "skip" => Ok(Command::Skip),
         "state" => Ok(Command::State),
         "available" => Ok(Command::Available),
         "bot" => Ok(Command::Bot(
@@ -268,10 +439,49 @@ fn parse_command(line: &str) -> Result<Command, ScanError> {
                 v => return Err(ScanError(format!("invalid bot command: {}", v))),
             },
         )),
+        "hint" => match scan_fmt!(line, "hint {}", String) {
+            Ok(v) if v == "challenge" => Ok(Command::HintChallenge),
+            Ok(v) if v == "target" => Ok(Command::HintTarget),
+            Ok(v) => Err(ScanError(format!("invalid hint command: {}", v))),
+            Err(..) => Ok(Command::Hint),
+        },
+        "branch" => Ok(Command::Branch(
+            match scan_fmt!(line, "branch {}", String) {
+                Ok(v) => match v.as_str() {
+                    "drop" => BranchCommand::Drop,
+                    "keep" => BranchCommand::Keep,
+                    v => return Err(ScanError(format!("invalid branch command: {}", v))),
+                },
+                Err(..) => BranchCommand::Start,
+            },
+        )),
         v => Err(ScanError(format!("invalid command name: {}", v))),
     }
 }
 
+pub fn parse_game_action_type(sub: &str) -> Result<GameActionType, ScanError> {
+    Ok(match scan_fmt!(sub, "{}", String)?.as_str() {
+        "income" => GameActionType::Income,
+        "coup" => GameActionType::Coup(scan_fmt!(sub, "coup {}", String)?),
+        "foreign_aid" | "aid" => GameActionType::ForeignAid,
+        "tax" => GameActionType::Tax,
+        "assassinate" => GameActionType::Assassinate(scan_fmt!(sub, "assassinate {}", String)?),
+        "kill" => GameActionType::Assassinate(scan_fmt!(sub, "kill {}", String)?),
+        "exchange" => GameActionType::Exchange,
+        "steal" => GameActionType::Steal(scan_fmt!(sub, "steal {}", String)?),
+        "block" => GameActionType::Block(scan(scan_fmt!(sub, "block {}", String)?)?),
+        "pass_challenge" | "pass_c" => GameActionType::PassChallenge,
+        "pass_block" | "pass_b" => GameActionType::PassBlock,
+        "challenge" => GameActionType::Challenge,
+        "show" => GameActionType::ShowCard(scan(scan_fmt!(sub, "show {}", String)?)?),
+        "reveal" => GameActionType::RevealCard(scan(scan_fmt!(sub, "reveal {}", String)?)?),
+        "drop" => GameActionType::DropCard(scan(scan_fmt!(sub, "drop {}", String)?)?),
+        "take" => GameActionType::TakeCard(scan(scan_fmt!(sub, "take {}", String)?)?),
+        "shuffle" => GameActionType::ShuffleDeck,
+        v => return Err(ScanError(format!("invalid action type: {}", v))),
+    })
+}
+
 fn get_tail(skip: usize, line: &str) -> &str {
     let spaces = line
         .bytes()
@@ -284,7 +494,7 @@ fn get_tail(skip: usize, line: &str) -> &str {
 
 fn make_initial_game_state(settings: &Settings, player: usize, cards: Vec<Card>) -> GameState {
     let mut player_cards: Vec<GamePlayerCards> = (0..settings.players_number)
-        .map(|_| GamePlayerCards::Opponent(CARDS_PER_PLAYER))
+        .map(|_| GamePlayerCards::Opponent(settings.cards_per_player))
         .collect();
     player_cards[player] = GamePlayerCards::Player(cards);
     GameState {
@@ -296,18 +506,20 @@ fn make_initial_game_state(settings: &Settings, player: usize, cards: Vec<Card>)
         player_coins: std::iter::repeat(INITIAL_COINS)
             .take(settings.players_number)
             .collect(),
-        player_hands: std::iter::repeat(CARDS_PER_PLAYER)
+        player_hands: std::iter::repeat(settings.cards_per_player)
             .take(settings.players_number)
             .collect(),
-        player_cards_counter: std::iter::repeat(CARDS_PER_PLAYER)
+        player_cards_counter: std::iter::repeat(settings.cards_per_player)
             .take(settings.players_number)
             .collect(),
         player_cards,
-        revealed_cards: Vec::with_capacity(settings.cards_per_type * ALL_CARDS.len()),
+        revealed_cards: Vec::with_capacity(settings.card_counts.total()),
         deck: GameDeck {
-            size: settings.cards_per_type * ALL_CARDS.len()
-                - CARDS_PER_PLAYER * settings.players_number,
+            size: settings.card_counts.total()
+                - settings.cards_per_player * settings.players_number,
         },
+        rules: settings.rules,
+        player_claims: vec![Vec::new(); settings.players_number],
     }
 }
 
@@ -324,6 +536,8 @@ struct GameState {
     player_cards: Vec<GamePlayerCards>,
     revealed_cards: Vec<Card>,
     deck: GameDeck,
+    rules: Rules,
+    player_claims: Vec<Vec<Card>>,
 }
 
 impl GameState {
@@ -348,6 +562,9 @@ impl GameState {
             player_cards: &self.player_cards_counter,
             revealed_cards: &self.revealed_cards,
             deck: self.deck.size,
+            rules: &self.rules,
+            claims: &self.player_claims,
+            remaining_time: None,
         }
     }
 
@@ -363,6 +580,7 @@ impl GameState {
             player_cards: &mut self.player_cards,
             deck: &mut self.deck,
             revealed_cards: &mut self.revealed_cards,
+            rules: &self.rules,
         })?;
         self.advance();
         Ok(())
@@ -383,6 +601,7 @@ impl GameState {
                 card,
             },
             revealed_cards: &mut self.revealed_cards,
+            rules: &self.rules,
         })?;
         self.advance();
         Ok(())
@@ -485,27 +704,38 @@ fn interactive_with_bot<B: Bot + Sized + Clone>(
     player_names: &[String],
     mut game_state: GameState,
     mut bot: B,
+    auto_pass: bool,
 ) {
     let mut history: Vec<(GameState, B)> = Vec::new();
+    let mut branch: Option<(GameState, B, usize)> = None;
     loop {
         match read_command() {
-            Command::Help => println!("{}", HELP),
+            Command::Help => println!("{}", get_lang().help()),
             Command::Quit => break,
             Command::Play(game_action) => {
                 history.push((game_state.clone(), bot.clone()));
-                if let Err(e) =
-                    handle_game_action(&game_action, player_names, &mut game_state, &mut bot)
-                {
+                let result =
+                    handle_game_action(&game_action, player_names, &mut game_state, &mut bot);
+                let result = match result {
+                    Err(e) if auto_pass => {
+                        play_after_auto_pass(&game_action, player_names, &mut game_state, &mut bot)
+                            .ok_or(e)
+                    }
+                    v => v,
+                };
+                if let Err(e) = result {
                     println!("{}", e);
                     continue;
                 }
             }
             Command::Undo => {
-                if let Some((prev_game_state, prev_bot)) = history.pop() {
+                if matches!(&branch, Some((_, _, length)) if *length == history.len()) {
+                    println!("{}", tr(Message::NothingToUndoInBranch));
+                } else if let Some((prev_game_state, prev_bot)) = history.pop() {
                     game_state = prev_game_state;
                     bot = prev_bot;
                 } else {
-                    println!("Nothing to undo");
+                    println!("{}", tr(Message::NothingToUndo));
                 }
             }
             Command::State => print_state(&game_state, player_names),
@@ -514,20 +744,15 @@ fn interactive_with_bot<B: Bot + Sized + Clone>(
                     &game_state.state_type,
                     &game_state.player_coins,
                     &game_state.player_hands,
+                    &game_state.rules,
                 );
                 for action in available_actions {
                     println!("{}", to_game_command(&action, player_names));
                 }
             }
             Command::Bot(bot_command) => {
-                let available_actions: Vec<Action> = get_available_actions(
-                    &game_state.state_type,
-                    &game_state.player_coins,
-                    &game_state.player_hands,
-                )
-                .into_iter()
-                .filter(|action| action.player == game_state.player)
-                .collect();
+                let available_actions: Vec<Action> =
+                    available_actions_for(&game_state.player_view(), game_state.player).collect();
                 match bot_command {
                     BotCommand::SuggestActions => {
                         for action in
@@ -543,11 +768,421 @@ fn interactive_with_bot<B: Bot + Sized + Clone>(
                     BotCommand::Custom(command) => bot.query(&command),
                 }
             }
+            Command::Hint => print_hint(&mut bot, &game_state, player_names),
+            Command::HintChallenge => match bot.cards_tracker() {
+                Some(tracker) => match get_challenge_odds(tracker, &game_state.player_view()) {
+                    Some(odds) => odds.print(),
+                    None => println!("{}", tr(Message::NoPendingClaim)),
+                },
+                None => println!("{}", tr(Message::BotDoesntTrackCards)),
+            },
+            Command::HintTarget => match bot.cards_tracker() {
+                Some(tracker) => {
+                    for advice in rank_targets(tracker, &game_state.player_view()) {
+                        println!(
+                            "{} score={:.3} block_probability={:.3} coins={} threat={:.3}",
+                            to_game_command(
+                                &Action {
+                                    player: game_state.player,
+                                    action_type: advice.action_type,
+                                },
+                                player_names
+                            ),
+                            advice.score,
+                            advice.block_probability,
+                            advice.coins,
+                            advice.threat
+                        );
+                    }
+                }
+                None => println!("{}", tr(Message::BotDoesntTrackCards)),
+            },
+            Command::Branch(BranchCommand::Start) => {
+                if branch.is_some() {
+                    println!("{}", tr(Message::AlreadyInBranch));
+                    continue;
+                }
+                branch = Some((game_state.clone(), bot.clone(), history.len()));
+                println!("{}", tr(Message::BranchStarted));
+            }
+            Command::Branch(BranchCommand::Drop) => {
+                if let Some((main_game_state, main_bot, length)) = branch.take() {
+                    game_state = main_game_state;
+                    bot = main_bot;
+                    history.truncate(length);
+                    println!("{}", tr(Message::BranchDropped));
+                } else {
+                    println!("{}", tr(Message::NotInBranch));
+                }
+            }
+            Command::Branch(BranchCommand::Keep) => {
+                if branch.take().is_some() {
+                    println!("{}", tr(Message::BranchKept));
+                } else {
+                    println!("{}", tr(Message::NotInBranch));
+                }
+            }
             _ => (),
         }
     }
 }
 
+fn print_hint<B: Bot>(bot: &mut B, game_state: &GameState, player_names: &[String]) {
+    let view = game_state.player_view();
+    let available_actions: Vec<Action> = available_actions_for(&view, game_state.player).collect();
+    let actions: Vec<Action> = bot
+        .suggest_actions(&view, &available_actions)
+        .into_iter()
+        .cloned()
+        .collect();
+    let mut scores = bot.score_actions(&view, &actions);
+    if scores.is_empty() {
+        for action in actions.iter() {
+            println!("{}", to_game_command(action, player_names));
+        }
+        return;
+    }
+    scores.sort_by(|l, r| r.win_probability.total_cmp(&l.win_probability));
+    for score in scores.iter() {
+        println!(
+            "{} win_probability={:.3} simulations={}",
+            to_game_command(&score.action, player_names),
+            score.win_probability,
+            score.simulations
+        );
+    }
+}
+
+pub fn run_referee_game<R: Rng>(
+    mut game: Game,
+    player_names: &[String],
+    auto_pass: bool,
+    rng: &mut R,
+) {
+    println!("{}", tr(Message::RefereeGame));
+    game.print();
+    let mut history: Vec<Game> = Vec::new();
+    while !game.is_done() {
+        match read_command() {
+            Command::Help => println!("{}", get_lang().help()),
+            Command::Quit => return,
+            Command::Play(game_action) => {
+                let prev = game.clone();
+                let mut result = handle_referee_action(&game_action, player_names, &mut game, rng);
+                if result.is_err() && auto_pass {
+                    let view = prev.get_anonymous_view();
+                    let available_actions = get_available_actions(
+                        view.state_type,
+                        view.player_coins,
+                        view.player_hands,
+                        view.rules,
+                    );
+                    if let Some(pass) = get_claimant_pass(view.state_type, &available_actions) {
+                        let mut next = prev.clone();
+                        if next.play(pass, rng).is_ok()
+                            && handle_referee_action(&game_action, player_names, &mut next, rng)
+                                .is_ok()
+                        {
+                            println!(
+                                "{}",
+                                tr(Message::AutoPass(&to_game_command(pass, player_names)))
+                            );
+                            game = next;
+                            result = Ok(());
+                        }
+                    }
+                }
+                if let Err(e) = result {
+                    println!("{}", tr(Message::InvalidClaim(&e)));
+                    game = prev;
+                    continue;
+                }
+                history.push(prev);
+            }
+            Command::Undo => {
+                if let Some(prev) = history.pop() {
+                    game = prev;
+                } else {
+                    println!("{}", tr(Message::NothingToUndo));
+                }
+            }
+            Command::State => game.print(),
+            Command::Available => {
+                let view = game.get_anonymous_view();
+                let available_actions = get_available_actions(
+                    view.state_type,
+                    view.player_coins,
+                    view.player_hands,
+                    view.rules,
+                );
+                for action in available_actions {
+                    println!("{}", to_game_command(&action, player_names));
+                }
+            }
+            _ => println!("{}", tr(Message::InvalidCommand)),
+        }
+    }
+    if let Some(winner) = game.get_winner() {
+        println!("{}", tr(Message::Wins(&player_names[winner])));
+    }
+}
+
+fn handle_referee_action<R: Rng>(
+    game_action: &GameAction,
+    player_names: &[String],
+    game: &mut Game,
+    rng: &mut R,
+) -> Result<(), String> {
+    let player = get_player_index(&game_action.player, player_names)?;
+    if let GameActionType::TakeCard(card) = &game_action.action_type {
+        if !matches!(card, Card::Unknown) {
+            game.put_deck_card_on_top(*card)?;
+        }
+    }
+    let action = Action {
+        player,
+        action_type: get_action_type(&game_action.action_type, player_names)?,
+    };
+    game.play(&action, rng)
+}
+
+pub struct HumanBot {
+    player_names: Vec<String>,
+    hot_seat: bool,
+    log: Vec<String>,
+}
+
+impl HumanBot {
+    pub fn new(players_number: usize) -> Self {
+        Self {
+            player_names: (0..players_number).map(|v| format!("{}", v)).collect(),
+            hot_seat: false,
+            log: Vec::new(),
+        }
+    }
+
+    pub fn hot_seat(player_names: Vec<String>) -> Self {
+        Self {
+            player_names,
+            hot_seat: true,
+            log: Vec::new(),
+        }
+    }
+
+    fn read_action(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+        optional: bool,
+    ) -> Option<Action> {
+        if !optional && available_actions.len() == 1 {
+            let action = available_actions[0].clone();
+            println!("{}", to_game_command(&action, &self.player_names));
+            return Some(action);
+        }
+        if self.hot_seat {
+            clear_screen();
+            println!(
+                "{}",
+                tr(Message::PassTerminal(&self.player_names[view.player]))
+            );
+            let mut line = String::new();
+            if std::io::stdin().lock().read_line(&mut line).unwrap_or(0) == 0 {
+                std::process::exit(0);
+            }
+            println!("{}", tr(Message::SinceYourLastMove));
+            for entry in self.log.drain(..) {
+                println!("{}", entry);
+            }
+        }
+        let action = self.read_visible_action(view, available_actions, optional);
+        if self.hot_seat {
+            clear_screen();
+        }
+        action
+    }
+
+    fn read_visible_action(
+        &self,
+        view: &PlayerView,
+        available_actions: &[Action],
+        optional: bool,
+    ) -> Option<Action> {
+        print_player_view(view, &self.player_names);
+        println!("{}", tr(Message::AvailableActions));
+        for action in available_actions.iter() {
+            println!("{}", to_game_command(action, &self.player_names));
+        }
+        if optional {
+            println!("skip");
+        }
+        loop {
+            match read_command() {
+                Command::Help => println!("{}", get_lang().help()),
+                Command::Quit => std::process::exit(0),
+                Command::State => print_player_view(view, &self.player_names),
+                Command::Skip if optional => return None,
+                Command::Play(game_action) => {
+                    let action = match get_player_index(&game_action.player, &self.player_names)
+                        .and_then(|player| {
+                            Ok(Action {
+                                player,
+                                action_type: get_action_type(
+                                    &game_action.action_type,
+                                    &self.player_names,
+                                )?,
+                            })
+                        }) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            println!("{}", e);
+                            continue;
+                        }
+                    };
+                    if available_actions.contains(&action) {
+                        return Some(action);
+                    }
+                    println!(
+                        "{}",
+                        tr(Message::ActionNotAvailable(&format!("{:?}", action)))
+                    );
+                    for similar in available_actions
+                        .iter()
+                        .cloned()
+                        .of_kind(&action.action_type)
+                    {
+                        println!("{}", to_game_command(&similar, &self.player_names));
+                    }
+                }
+                _ => println!("{}", tr(Message::InvalidCommand)),
+            }
+        }
+    }
+}
+
+impl Bot for HumanBot {
+    fn suggest_actions<'a>(
+        &mut self,
+        _: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        available_actions.iter().collect()
+    }
+
+    fn suggest_optional_actions<'a>(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        self.suggest_actions(view, available_actions)
+    }
+
+    fn get_action(&mut self, view: &PlayerView, available_actions: &[Action]) -> Action {
+        self.read_action(view, available_actions, false).unwrap()
+    }
+
+    fn get_optional_action(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Option<Action> {
+        self.read_action(view, available_actions, true)
+    }
+
+    fn after_player_action(&mut self, _: &PlayerView, action: &Action) {
+        if self.hot_seat {
+            self.log.push(to_game_command(action, &self.player_names));
+        }
+    }
+
+    fn after_opponent_action(&mut self, _: &PlayerView, action: &ActionView) {
+        if self.hot_seat {
+            self.log.push(format!("{:?}", action));
+        } else {
+            println!("{:?}", action);
+        }
+    }
+
+    fn after_forfeit(&mut self, _: &PlayerView, player: usize) {
+        println!(
+            "{}",
+            tr(Message::PlayerForfeits(&self.player_names[player]))
+        );
+    }
+
+    fn query(&self, _: &str) {}
+}
+
+fn clear_screen() {
+    print!("\x1B[2J\x1B[H");
+    std::io::stdout().flush().unwrap();
+}
+
+fn print_player_view(view: &PlayerView, player_names: &[String]) {
+    println!("step: {:?}", view.step);
+    println!("turn: {:?}", view.turn);
+    println!("round: {:?}", view.round);
+    println!("state_type: {:?}", view.state_type);
+    println!("player_index: {}", view.player);
+    println!("deck size: {}", view.deck);
+    println!("revealed cards: {}", format_cards(view.revealed_cards));
+    println!("players:");
+    for (i, player_name) in player_names.iter().enumerate() {
+        print!("{}) {} coins={} ", i, player_name, view.player_coins[i]);
+        if i == view.player {
+            println!("cards={}", format_cards(view.cards));
+        } else {
+            println!("cards={}", view.player_cards[i]);
+        }
+    }
+    std::io::stdout().flush().unwrap();
+}
+
+fn play_after_auto_pass<B: Bot + Clone>(
+    game_action: &GameAction,
+    player_names: &[String],
+    game_state: &mut GameState,
+    bot: &mut B,
+) -> Option<()> {
+    let available_actions = get_available_actions(
+        &game_state.state_type,
+        &game_state.player_coins,
+        &game_state.player_hands,
+        &game_state.rules,
+    );
+    let pass = get_claimant_pass(&game_state.state_type, &available_actions)?;
+    let pass_action = GameAction {
+        player: player_names[pass.player].clone(),
+        action_type: match pass.action_type {
+            ActionType::PassBlock => GameActionType::PassBlock,
+            _ => GameActionType::PassChallenge,
+        },
+    };
+    let mut next_game_state = game_state.clone();
+    let mut next_bot = bot.clone();
+    handle_game_action(
+        &pass_action,
+        player_names,
+        &mut next_game_state,
+        &mut next_bot,
+    )
+    .ok()?;
+    handle_game_action(
+        game_action,
+        player_names,
+        &mut next_game_state,
+        &mut next_bot,
+    )
+    .ok()?;
+    println!(
+        "{}",
+        tr(Message::AutoPass(&to_game_command(pass, player_names)))
+    );
+    *game_state = next_game_state;
+    *bot = next_bot;
+    Some(())
+}
+
 fn handle_game_action<B: Bot>(
     game_action: &GameAction,
     player_names: &[String],
@@ -555,7 +1190,37 @@ fn handle_game_action<B: Bot>(
     bot: &mut B,
 ) -> Result<(), String> {
     let player = get_player_index(&game_action.player, player_names)?;
-    let action_type = match &game_action.action_type {
+    if let GameActionType::TakeCard(card) = &game_action.action_type {
+        if player == game_state.player && matches!(card, Card::Unknown) {
+            return Err(String::from("Player can't take unknown card"));
+        }
+        let action = Action {
+            player,
+            action_type: ActionType::TakeCard,
+        };
+        game_state.with_pop_deck(*card, |state| play(&action, state))?;
+        notify_bot(bot, &game_state.player_view(), &action);
+        return Ok(());
+    }
+    let action = Action {
+        player,
+        action_type: get_action_type(&game_action.action_type, player_names)?,
+    };
+    game_state.with_default(|state| play(&action, state))?;
+    update_claims(
+        &mut game_state.player_claims,
+        &game_state.player_hands,
+        &action,
+    );
+    notify_bot(bot, &game_state.player_view(), &action);
+    Ok(())
+}
+
+pub fn get_action_type(
+    game_action_type: &GameActionType,
+    player_names: &[String],
+) -> Result<ActionType, String> {
+    Ok(match game_action_type {
         GameActionType::Income => ActionType::Income,
         GameActionType::ForeignAid => ActionType::ForeignAid,
         GameActionType::Coup(target) => ActionType::Coup(get_player_index(target, player_names)?),
@@ -578,37 +1243,8 @@ fn handle_game_action<B: Bot>(
         GameActionType::RevealCard(card) => ActionType::RevealCard(*card),
         GameActionType::DropCard(card) => ActionType::DropCard(*card),
         GameActionType::ShuffleDeck => ActionType::ShuffleDeck,
-        GameActionType::TakeCard(card) => {
-            if player == game_state.player && matches!(card, Card::Unknown) {
-                return Err(String::from("Player can't take unknown card"));
-            }
-            let action = Action {
-                player,
-                action_type: ActionType::TakeCard,
-            };
-            game_state.with_pop_deck(*card, |state| play(&action, state))?;
-            if game_state.player == action.player {
-                bot.after_player_action(&game_state.player_view(), &action);
-            } else {
-                bot.after_opponent_action(
-                    &game_state.player_view(),
-                    &ActionView::from_action(&action),
-                );
-            }
-            return Ok(());
-        }
-    };
-    let action = Action {
-        player,
-        action_type,
-    };
-    game_state.with_default(|state| play(&action, state))?;
-    if game_state.player == action.player {
-        bot.after_player_action(&game_state.player_view(), &action);
-    } else {
-        bot.after_opponent_action(&game_state.player_view(), &ActionView::from_action(&action));
-    }
-    Ok(())
+        GameActionType::TakeCard(..) => ActionType::TakeCard,
+    })
 }
 
 fn play<'a, P: PlayerCards + Sized, D: Deck>(
@@ -630,7 +1266,7 @@ fn get_player_index(name: &String, player_names: &[String]) -> Result<usize, Str
         .unwrap_or_else(|| Err(format!("invalid player name: {}", name)))
 }
 
-fn to_game_command(action: &Action, player_names: &[String]) -> String {
+pub fn to_game_command(action: &Action, player_names: &[String]) -> String {
     match &action.action_type {
         ActionType::Income => format!("play {} income", player_names[action.player]),
         ActionType::ForeignAid => format!("play {} foreign_aid", player_names[action.player]),
@@ -691,7 +1327,14 @@ fn print_state(game_state: &GameState, player_names: &[String]) {
     println!("round: {:?}", game_state.round);
     println!("state_type: {:?}", game_state.state_type);
     println!("player_index: {}", game_state.player);
+    println!(
+        "state_class: {}",
+        StateClass::classify(&game_state.player_view())
+    );
     println!("deck size: {}", game_state.deck.size);
+    if let Some(bank) = game_state.player_view().bank() {
+        println!("bank: {}", bank);
+    }
     println!("players:");
     for (i, player_name) in player_names.iter().enumerate() {
         print!(
@@ -699,55 +1342,9 @@ fn print_state(game_state: &GameState, player_names: &[String]) {
             i, player_name, game_state.player_coins[i]
         );
         match &game_state.player_cards[i] {
-            GamePlayerCards::Player(cards) => println!("cards={:?}", cards),
+            GamePlayerCards::Player(cards) => println!("cards={}", format_cards(cards)),
             GamePlayerCards::Opponent(count) => println!("cards={}", count),
         }
     }
     std::io::stdout().flush().unwrap();
 }
-
-const HELP: &str = r#"
-Commands:
-help - show this message
-quit - stop the game and exit the process
-set players_number <number> - set number of players before the game starts
-set cards_per_type <number> - set how much of each card is present before the game starts
-set bot_type <name> - set a bot type with given name before the game starts
-set player <index> - set which player you are going to play before the game starts
-name <index> <string> - set custom name for given player before the game starts
-add <name> - add a card with given name to the player hand before the game starts
-rm <name> - remove a card with given name from the player hand before the game starts
-start - start the game with current settings
-play <player_name> income - given player takes one coin at the game turn start
-play <player_name> coup <target> - given player pays 7 coins and performs a coup for the target player at the game turn start
-play <player_name> foreign_aid|aid - given player starts foreign aid at the game turn start
-play <player_name> tax - given player claims Duke and starts to get a tax at the game turn start
-play <player_name> assassinate|kill <target> - given player pays 3 coins and starts an assassination for the target player at the game turn start
-play <player_name> steal <target> - given player starts stealing for the target player at the game turn start
-play <player_name> challenge - given player starts a challenge against a claim of other player, allowed only after any action or block that require a claim
-play <player_name> pass_challenge|pass_c - given player considers that none challenges are started after it claimed a card
-play <player_name> show <card> - given player shows a card to prove a claim when challenged and puts the card into a deck
-play <player_name> reveal <card> - given player reveals the card to lose the influence, now the card is visible to everyone
-play <player_name> block <card> - given player blocks any action in progress claiming to have given card, allowed only after passed challenge or when unchallangeable action is in progress
-play <player_name> pass_block|pass_b - given player considers that no more blocks are going to happen for its current action
-play <player_name> shuffle - given player shuffles a deck before taking a card after showing a card
-play <player_name> take <card> - given player takes the card from a deck to get a new card instead of showed one or when does exchange
-play <player_name> drop <card> - given player puts the card into a deck to finish the exchange action
-undo - undo last game action
-state - print current game state
-avaialble - print all avaialble actions for all players at the current game state
-bot suggest - print all suggested actions by current bot at the current game state
-bot get - print action that would be used by a bot at the current game state
-bot custom <query> - send a custom query to a bot, implementation depends on the bot type
-
-Cards:
-Unknown|unknown - use for opponents take and drop actions, indicates that only that player can see the card
-Assassin|assassin - can assassinate other players to reduce influence by forcing to reveal a card
-Ambassador|ambassador - can exchange at most 2 cards from deck and block stealing
-Captain|captain - can steal 2 coins at most from other players and block stealing
-Contessa|contessa - can block assassination
-Duke|duke - can get tax and block foreign aid
-
-Target:
-Only other players can be targeted. Players with no cards can't be targeted.
-"#;
@@ -0,0 +1,262 @@
+use std::cell::Cell;
+use std::io::Write;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::archive::ArchivedGame;
+use crate::bots::Bot;
+use crate::game::{Game, Settings};
+use crate::run::{make_bots, run_game, BotType, RunResult, TimeControl};
+use crate::stats::Stats;
+
+pub const ISOLATED_GAME_COMMAND: &str = "isolated-game";
+
+#[derive(Clone)]
+pub struct Isolation {
+    pub executable: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl Isolation {
+    pub fn current(args: Vec<String>) -> Result<Self, String> {
+        Ok(Self {
+            executable: std::env::current_exe()
+                .map_err(|e| format!("failed to get current executable: {}", e))?,
+            args,
+        })
+    }
+
+    pub fn run(&self, request: &IsolatedGame) -> IsolatedResult {
+        match self.spawn(request) {
+            Ok(result) => result,
+            Err(error) => IsolatedResult::Failed {
+                player: None,
+                error,
+            },
+        }
+    }
+
+    fn spawn(&self, request: &IsolatedGame) -> Result<IsolatedResult, String> {
+        let mut child = Command::new(&self.executable)
+            .args(self.args.iter())
+            .arg(ISOLATED_GAME_COMMAND)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to spawn {}: {}", self.executable.display(), e))?;
+        serde_json::to_writer(child.stdin.take().unwrap(), request)
+            .map_err(|e| format!("failed to write isolated game request: {}", e))?;
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("failed to wait for isolated game: {}", e))?;
+        match serde_json::from_slice(&output.stdout) {
+            Ok(result) => Ok(result),
+            Err(e) if output.status.success() => {
+                Err(format!("failed to read isolated game result: {}", e))
+            }
+            Err(..) => Err(format!("isolated game failed: {}", output.status)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct IsolatedGame {
+    pub seed: u64,
+    pub bot_types: Vec<BotType>,
+    pub settings: Settings,
+    pub time_control: TimeControl,
+    pub record: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum IsolatedResult {
+    Finished {
+        stats: Box<Stats>,
+        game: Option<Box<ArchivedGame>>,
+    },
+    Failed {
+        player: Option<usize>,
+        error: String,
+    },
+}
+
+impl IsolatedResult {
+    pub fn into_stats(self, bot_types: &[BotType]) -> (Stats, Option<ArchivedGame>) {
+        match self {
+            IsolatedResult::Finished { stats, game } => (*stats, game.map(|v| *v)),
+            IsolatedResult::Failed { player, error } => {
+                tracing::warn!(?bot_types, player, error = %error, "isolated game failed");
+                let mut stats = Stats::default();
+                stats.add_failure(bot_types, player);
+                (stats, None)
+            }
+        }
+    }
+}
+
+struct TrackedBot {
+    index: usize,
+    active: Rc<Cell<Option<usize>>>,
+    bot: Box<dyn Bot>,
+}
+
+impl AsMut<dyn Bot> for TrackedBot {
+    fn as_mut(&mut self) -> &mut (dyn Bot + 'static) {
+        self.active.set(Some(self.index));
+        self.bot.as_mut()
+    }
+}
+
+pub fn play_isolated_game(request: &IsolatedGame) -> IsolatedResult {
+    let mut rng = StdRng::seed_from_u64(request.seed);
+    let mut game = Game::new(request.settings.clone(), &mut rng);
+    let bots = make_bots(request.seed, &request.bot_types, &game, None);
+    play_tracked_game(request, bots, &mut game, &mut rng)
+}
+
+fn play_tracked_game(
+    request: &IsolatedGame,
+    bots: Vec<Box<dyn Bot>>,
+    game: &mut Game,
+    rng: &mut StdRng,
+) -> IsolatedResult {
+    let begin = game.clone();
+    let active = Rc::new(Cell::new(None));
+    let mut bots: Vec<TrackedBot> = bots
+        .into_iter()
+        .enumerate()
+        .map(|(index, bot)| TrackedBot {
+            index,
+            active: active.clone(),
+            bot,
+        })
+        .collect();
+    let log = match catch_unwind(AssertUnwindSafe(|| {
+        run_game(&mut bots, game, rng, false, None, 0, request.time_control)
+    })) {
+        Ok(log) => log,
+        Err(error) => {
+            return IsolatedResult::Failed {
+                player: active.get(),
+                error: get_panic_message(error.as_ref()),
+            }
+        }
+    };
+    let result = RunResult {
+        begin,
+        end: game.clone(),
+        log,
+    };
+    let mut stats = Stats::default();
+    stats.add(&request.bot_types, &result);
+    IsolatedResult::Finished {
+        stats: Box::new(stats),
+        game: if request.record {
+            Some(Box::new(ArchivedGame::new(
+                request.seed,
+                &request.bot_types,
+                &result,
+            )))
+        } else {
+            None
+        },
+    }
+}
+
+fn get_panic_message(error: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = error.downcast_ref::<&str>() {
+        String::from(*message)
+    } else if let Some(message) = error.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+pub fn serve_isolated_game() -> Result<(), String> {
+    let request: IsolatedGame = serde_json::from_reader(std::io::stdin().lock())
+        .map_err(|e| format!("failed to read isolated game request: {}", e))?;
+    let result = play_isolated_game(&request);
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, &result)
+        .map_err(|e| format!("failed to write isolated game result: {}", e))?;
+    stdout
+        .flush()
+        .map_err(|e| format!("failed to write isolated game result: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bots::ActionView;
+    use crate::fsm::{Action, Rules};
+    use crate::game::{CardCounts, PlayerView};
+
+    use super::*;
+
+    struct PanicBot;
+
+    impl Bot for PanicBot {
+        fn suggest_actions<'a>(&mut self, _: &PlayerView, _: &'a [Action]) -> Vec<&'a Action> {
+            Vec::new()
+        }
+
+        fn suggest_optional_actions<'a>(
+            &mut self,
+            _: &PlayerView,
+            _: &'a [Action],
+        ) -> Vec<&'a Action> {
+            Vec::new()
+        }
+
+        fn get_action(&mut self, _: &PlayerView, _: &[Action]) -> Action {
+            panic!("get_action")
+        }
+
+        fn get_optional_action(&mut self, _: &PlayerView, _: &[Action]) -> Option<Action> {
+            panic!("get_optional_action")
+        }
+
+        fn after_player_action(&mut self, _: &PlayerView, _: &Action) {}
+
+        fn after_opponent_action(&mut self, _: &PlayerView, _: &ActionView) {}
+
+        fn query(&self, _: &str) {}
+    }
+
+    #[test]
+    fn play_tracked_game_should_report_panicking_player() {
+        let request = IsolatedGame {
+            seed: 42,
+            bot_types: vec![BotType::Random, BotType::Random],
+            settings: Settings {
+                players_number: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+            time_control: TimeControl::default(),
+            record: true,
+        };
+        assert!(matches!(
+            play_isolated_game(&request),
+            IsolatedResult::Finished { game: Some(..), .. }
+        ));
+        let mut rng = StdRng::seed_from_u64(request.seed);
+        let mut game = Game::new(request.settings.clone(), &mut rng);
+        let mut bots = make_bots(request.seed, &request.bot_types, &game, None);
+        bots[1] = Box::new(PanicBot);
+        match play_tracked_game(&request, bots, &mut game, &mut rng) {
+            IsolatedResult::Failed { player, error } => {
+                assert_eq!(player, Some(1));
+                assert!(error.starts_with("get_"), "{}", error);
+            }
+            IsolatedResult::Finished { .. } => panic!("game should fail"),
+        }
+    }
+}
@@ -0,0 +1,600 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::fsm::Card;
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Ru,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Lang::En),
+            "ru" => Ok(Lang::Ru),
+            _ => Err(format!("invalid language: {}", s)),
+        }
+    }
+}
+
+pub enum Message<'a> {
+    Me,
+    UseDefaultSettings,
+    StartGame,
+    RefereeGame,
+    InvalidCommand,
+    Warning(&'a str),
+    CantRemoveCards {
+        count: usize,
+        card: Card,
+        available: usize,
+    },
+    DeckCantHaveUnknownCards,
+    PlayerIndexNotApplicable(usize),
+    CantAddMoreCards(usize),
+    DontHaveCard(Card),
+    NeedToAddCards(usize),
+    NeedToRemoveCards(usize),
+    NothingToUndo,
+    NothingToUndoInBranch,
+    NoPendingClaim,
+    BotDoesntTrackCards,
+    AlreadyInBranch,
+    BranchStarted,
+    BranchDropped,
+    BranchKept,
+    NotInBranch,
+    PassTerminal(&'a str),
+    SinceYourLastMove,
+    AvailableActions,
+    ActionNotAvailable(&'a str),
+    PlayerForfeits(&'a str),
+    AutoPass(&'a str),
+    InvalidClaim(&'a str),
+    Wins(&'a str),
+    Round {
+        round: usize,
+        clauses: &'a str,
+    },
+    TakesIncome(&'a str),
+    TakesForeignAid(&'a str),
+    Coup {
+        player: &'a str,
+        target: &'a str,
+        coins: usize,
+    },
+    Tax(&'a str),
+    Assassinate {
+        player: &'a str,
+        target: &'a str,
+        coins: usize,
+    },
+    Exchange(&'a str),
+    Steal {
+        player: &'a str,
+        target: &'a str,
+    },
+    Block {
+        player: &'a str,
+        card: Card,
+    },
+    ChallengeClaim {
+        player: &'a str,
+        target: &'a str,
+        card: Card,
+    },
+    Challenge(&'a str),
+    ShowCard {
+        player: &'a str,
+        card: Card,
+    },
+    RevealBluff {
+        player: &'a str,
+        claimed: Card,
+        card: Card,
+    },
+    RevealLostChallenge {
+        player: &'a str,
+        card: Card,
+    },
+    RevealCard {
+        player: &'a str,
+        card: Card,
+    },
+    TakeCard(&'a str),
+    DropCard(&'a str),
+    OutOfGame(&'a str),
+}
+
+pub fn set_lang(lang: Lang) {
+    LANG.get_or_init(|| lang);
+}
+
+pub fn get_lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+pub fn tr(message: Message) -> String {
+    get_lang().format(&message)
+}
+
+pub fn card_name(card: Card) -> &'static str {
+    get_lang().card_name(card)
+}
+
+pub fn format_cards(cards: &[Card]) -> String {
+    format!(
+        "[{}]",
+        cards
+            .iter()
+            .map(|v| card_name(*v))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+impl Lang {
+    pub fn card_name(self, card: Card) -> &'static str {
+        match self {
+            Lang::En => match card {
+                Card::Unknown => "Unknown",
+                Card::Assassin => "Assassin",
+                Card::Ambassador => "Ambassador",
+                Card::Captain => "Captain",
+                Card::Contessa => "Contessa",
+                Card::Duke => "Duke",
+            },
+            Lang::Ru => match card {
+                Card::Unknown => "Неизвестная",
+                Card::Assassin => "Ассасин",
+                Card::Ambassador => "Посол",
+                Card::Captain => "Капитан",
+                Card::Contessa => "Графиня",
+                Card::Duke => "Герцог",
+            },
+        }
+    }
+
+    pub fn help(self) -> &'static str {
+        match self {
+            Lang::En => HELP_EN,
+            Lang::Ru => HELP_RU,
+        }
+    }
+
+    pub fn format(self, message: &Message) -> String {
+        match self {
+            Lang::En => self.format_en(message),
+            Lang::Ru => self.format_ru(message),
+        }
+    }
+
+    fn format_en(self, message: &Message) -> String {
+        let card = |v: Card| self.card_name(v);
+        match message {
+            Message::Me => String::from("me"),
+            Message::UseDefaultSettings => String::from("Use default settings:"),
+            Message::StartGame => String::from("Start game with initial state:"),
+            Message::RefereeGame => String::from("Referee game with initial state:"),
+            Message::InvalidCommand => String::from("Invalid command"),
+            Message::Warning(e) => format!("Warning: {}", e),
+            Message::CantRemoveCards {
+                count,
+                card: v,
+                available,
+            } => format!(
+                "Can't remove {} {} cards, deck has only {}",
+                count,
+                card(*v),
+                available
+            ),
+            Message::DeckCantHaveUnknownCards => String::from("Deck can't have unknown cards"),
+            Message::PlayerIndexNotApplicable(players_number) => format!(
+                "Player index is not applicable for current number of players: {}",
+                players_number
+            ),
+            Message::CantAddMoreCards(count) => format!("Can't add more than {} cards", count),
+            Message::DontHaveCard(v) => format!("Don't have {} card", card(*v)),
+            Message::NeedToAddCards(count) => format!("Need to add {} more card(s)", count),
+            Message::NeedToRemoveCards(count) => format!("Need to remove {} card(s)", count),
+            Message::NothingToUndo => String::from("Nothing to undo"),
+            Message::NothingToUndoInBranch => String::from("Nothing to undo in branch"),
+            Message::NoPendingClaim => String::from("No pending claim to challenge"),
+            Message::BotDoesntTrackCards => String::from("Bot doesn't track cards"),
+            Message::AlreadyInBranch => String::from("Already in a branch"),
+            Message::BranchStarted => {
+                String::from("Branch started, use branch drop or branch keep to finish it")
+            }
+            Message::BranchDropped => String::from("Branch dropped"),
+            Message::BranchKept => String::from("Branch kept"),
+            Message::NotInBranch => String::from("Not in a branch"),
+            Message::PassTerminal(player) => {
+                format!("Pass the terminal to {} and press Enter", player)
+            }
+            Message::SinceYourLastMove => String::from("since your last move:"),
+            Message::AvailableActions => String::from("available actions:"),
+            Message::ActionNotAvailable(action) => format!("Action is not available: {}", action),
+            Message::PlayerForfeits(player) => format!("Player {} forfeits", player),
+            Message::AutoPass(action) => format!("Auto pass: {}", action),
+            Message::InvalidClaim(e) => format!("Invalid claim: {}", e),
+            Message::Wins(player) => format!("{} wins the game", player),
+            Message::Round { round, clauses } => format!("Round {}: {}.", round, clauses),
+            Message::TakesIncome(player) => format!("{} takes income", player),
+            Message::TakesForeignAid(player) => format!("{} reaches for foreign aid", player),
+            Message::Coup {
+                player,
+                target,
+                coins,
+            } => format!(
+                "{} pays {} coins to launch a coup against {}",
+                player, coins, target
+            ),
+            Message::Tax(player) => format!("{} claims {} and takes tax", player, card(Card::Duke)),
+            Message::Assassinate {
+                player,
+                target,
+                coins,
+            } => format!(
+                "{} claims {} and pays {} coins to assassinate {}",
+                player,
+                card(Card::Assassin),
+                coins,
+                target
+            ),
+            Message::Exchange(player) => format!(
+                "{} claims {} to exchange cards with the deck",
+                player,
+                card(Card::Ambassador)
+            ),
+            Message::Steal { player, target } => format!(
+                "{} claims {} to steal from {}",
+                player,
+                card(Card::Captain),
+                target
+            ),
+            Message::Block { player, card: v } => {
+                format!("{} blocks it claiming {}", player, card(*v))
+            }
+            Message::ChallengeClaim {
+                player,
+                target,
+                card: v,
+            } => format!(
+                "{} doesn't buy it and challenges {}'s {}",
+                player,
+                target,
+                card(*v)
+            ),
+            Message::Challenge(player) => format!("{} challenges", player),
+            Message::ShowCard { player, card: v } => {
+                format!("{} shows {} and wins the challenge", player, card(*v))
+            }
+            Message::RevealBluff {
+                player,
+                claimed,
+                card: v,
+            } => format!(
+                "{} can't show {} and reveals {}",
+                player,
+                card(*claimed),
+                card(*v)
+            ),
+            Message::RevealLostChallenge { player, card: v } => {
+                format!("{} loses the challenge and reveals {}", player, card(*v))
+            }
+            Message::RevealCard { player, card: v } => {
+                format!("{} loses influence and reveals {}", player, card(*v))
+            }
+            Message::TakeCard(player) => format!("{} takes a card from the deck", player),
+            Message::DropCard(player) => format!("{} returns a card to the deck", player),
+            Message::OutOfGame(player) => format!("{} is out of the game", player),
+        }
+    }
+
+    fn format_ru(self, message: &Message) -> String {
+        let card = |v: Card| self.card_name(v);
+        match message {
+            Message::Me => String::from("я"),
+            Message::UseDefaultSettings => String::from("Настройки по умолчанию:"),
+            Message::StartGame => String::from("Игра начинается с состоянием:"),
+            Message::RefereeGame => String::from("Судейство игры с состоянием:"),
+            Message::InvalidCommand => String::from("Неверная команда"),
+            Message::Warning(e) => format!("Предупреждение: {}", e),
+            Message::CantRemoveCards {
+                count,
+                card: v,
+                available,
+            } => format!(
+                "Нельзя убрать {} карт(ы) {}, в колоде только {}",
+                count,
+                card(*v),
+                available
+            ),
+            Message::DeckCantHaveUnknownCards => {
+                String::from("В колоде не может быть неизвестных карт")
+            }
+            Message::PlayerIndexNotApplicable(players_number) => format!(
+                "Номер игрока не подходит для текущего числа игроков: {}",
+                players_number
+            ),
+            Message::CantAddMoreCards(count) => format!("Нельзя добавить больше {} карт", count),
+            Message::DontHaveCard(v) => format!("Нет карты {}", card(*v)),
+            Message::NeedToAddCards(count) => format!("Нужно добавить ещё карт: {}", count),
+            Message::NeedToRemoveCards(count) => format!("Нужно убрать карт: {}", count),
+            Message::NothingToUndo => String::from("Нечего отменять"),
+            Message::NothingToUndoInBranch => String::from("В ветке нечего отменять"),
+            Message::NoPendingClaim => String::from("Нет заявления, которое можно оспорить"),
+            Message::BotDoesntTrackCards => String::from("Бот не отслеживает карты"),
+            Message::AlreadyInBranch => String::from("Ветка уже открыта"),
+            Message::BranchStarted => {
+                String::from("Ветка открыта, завершите её командой branch drop или branch keep")
+            }
+            Message::BranchDropped => String::from("Ветка отброшена"),
+            Message::BranchKept => String::from("Ветка сохранена"),
+            Message::NotInBranch => String::from("Ветка не открыта"),
+            Message::PassTerminal(player) => {
+                format!("Передайте терминал игроку {} и нажмите Enter", player)
+            }
+            Message::SinceYourLastMove => String::from("с вашего последнего хода:"),
+            Message::AvailableActions => String::from("доступные действия:"),
+            Message::ActionNotAvailable(action) => format!("Действие недоступно: {}", action),
+            Message::PlayerForfeits(player) => format!("Игрок {} сдаётся", player),
+            Message::AutoPass(action) => format!("Автопропуск: {}", action),
+            Message::InvalidClaim(e) => format!("Неверное заявление: {}", e),
+            Message::Wins(player) => format!("{} побеждает в игре", player),
+            Message::Round { round, clauses } => format!("Раунд {}: {}.", round, clauses),
+            Message::TakesIncome(player) => format!("{} берёт доход", player),
+            Message::TakesForeignAid(player) => format!("{} просит иностранную помощь", player),
+            Message::Coup {
+                player,
+                target,
+                coins,
+            } => format!(
+                "{} платит {} монет за переворот против игрока {}",
+                player, coins, target
+            ),
+            Message::Tax(player) => format!(
+                "{} заявляет карту {} и берёт налог",
+                player,
+                card(Card::Duke)
+            ),
+            Message::Assassinate {
+                player,
+                target,
+                coins,
+            } => format!(
+                "{} заявляет карту {} и платит {} монеты за убийство игрока {}",
+                player,
+                card(Card::Assassin),
+                coins,
+                target
+            ),
+            Message::Exchange(player) => format!(
+                "{} заявляет карту {}, чтобы обменять карты с колодой",
+                player,
+                card(Card::Ambassador)
+            ),
+            Message::Steal { player, target } => format!(
+                "{} заявляет карту {}, чтобы украсть у игрока {}",
+                player,
+                card(Card::Captain),
+                target
+            ),
+            Message::Block { player, card: v } => {
+                format!("{} блокирует, заявляя карту {}", player, card(*v))
+            }
+            Message::ChallengeClaim {
+                player,
+                target,
+                card: v,
+            } => format!(
+                "{} не верит и оспаривает карту {} игрока {}",
+                player,
+                card(*v),
+                target
+            ),
+            Message::Challenge(player) => format!("{} оспаривает", player),
+            Message::ShowCard { player, card: v } => {
+                format!("{} показывает карту {} и выигрывает спор", player, card(*v))
+            }
+            Message::RevealBluff {
+                player,
+                claimed,
+                card: v,
+            } => format!(
+                "{} не может показать карту {} и раскрывает карту {}",
+                player,
+                card(*claimed),
+                card(*v)
+            ),
+            Message::RevealLostChallenge { player, card: v } => {
+                format!(
+                    "{} проигрывает спор и раскрывает карту {}",
+                    player,
+                    card(*v)
+                )
+            }
+            Message::RevealCard { player, card: v } => {
+                format!("{} теряет влияние и раскрывает карту {}", player, card(*v))
+            }
+            Message::TakeCard(player) => format!("{} берёт карту из колоды", player),
+            Message::DropCard(player) => format!("{} возвращает карту в колоду", player),
+            Message::OutOfGame(player) => format!("{} выбывает из игры", player),
+        }
+    }
+}
+
+const HELP_EN: &str = r#"
+Commands:
+help - show this message
+quit - stop the game and exit the process
+set players_number <number> - set number of players before the game starts
+set cards_per_type <number> - set how much of each card is present before the game starts
+deck set|add|remove <card> <number> - set, add or remove given number of cards with given name in the deck before the game starts
+set cards_per_player <number> - set how many cards each player is dealt before the game starts
+set bot_type <name> - set a bot type with given name before the game starts
+set player <index> - set which player you are going to play before the game starts
+set seed <number> - set a seed for the bot random number generator before the game starts
+set seed_phrase <text> - set a seed derived from given text before the game starts
+set auto_pass true|false - when an action is not possible during a challenge or block window, play the pending pass_challenge or pass_block first
+name <index> <string> - set custom name for given player before the game starts
+add <name> - add a card with given name to the player hand before the game starts
+rm <name> - remove a card with given name from the player hand before the game starts
+start - start the game with current settings
+play <player_name> income - given player takes one coin at the game turn start
+play <player_name> coup <target> - given player pays 7 coins and performs a coup for the target player at the game turn start
+play <player_name> foreign_aid|aid - given player starts foreign aid at the game turn start
+play <player_name> tax - given player claims Duke and starts to get a tax at the game turn start
+play <player_name> assassinate|kill <target> - given player pays 3 coins and starts an assassination for the target player at the game turn start
+play <player_name> steal <target> - given player starts stealing for the target player at the game turn start
+play <player_name> challenge - given player starts a challenge against a claim of other player, allowed only after any action or block that require a claim
+play <player_name> pass_challenge|pass_c - given player considers that none challenges are started after it claimed a card
+play <player_name> show <card> - given player shows a card to prove a claim when challenged and puts the card into a deck
+play <player_name> reveal <card> - given player reveals the card to lose the influence, now the card is visible to everyone
+play <player_name> block <card> - given player blocks any action in progress claiming to have given card, allowed only after passed challenge or when unchallangeable action is in progress
+play <player_name> pass_block|pass_b - given player considers that no more blocks are going to happen for its current action
+play <player_name> shuffle - given player shuffles a deck before taking a card after showing a card
+play <player_name> take <card> - given player takes the card from a deck to get a new card instead of showed one or when does exchange
+play <player_name> drop <card> - given player puts the card into a deck to finish the exchange action
+undo - undo last game action
+skip - do nothing when asked for an optional action (challenge or block) in simulation with a human player
+state - print current game state
+avaialble - print all avaialble actions for all players at the current game state
+bot suggest - print all suggested actions by current bot at the current game state
+bot get - print action that would be used by a bot at the current game state
+bot custom <query> - send a custom query to a bot, implementation depends on the bot type
+hint - print actions suggested by a bot at the current game state with their estimated win probability when available
+hint target - rank opponents as targets for steal, assassinate and coup by likely blocks, coins and threat
+hint challenge - print probability that a pending claim is true, expected cost of challenging and passing and the break-even probability
+branch - fork the current game state and bot into a sandbox to try hypothetical actions
+branch drop - discard the sandbox and return to the game state where the branch started
+branch keep - adopt the sandbox line as the game
+
+Referee:
+In referee mode all hands and the deck are known, every show, reveal, drop and take is checked against them.
+play <player_name> take <card> - moves given card from the deck to the player, fails if the deck doesn't have it
+play <player_name> take unknown - given player takes the top card from the deck
+
+Cards:
+Unknown|unknown - use for opponents take and drop actions, indicates that only that player can see the card
+Assassin|assassin - can assassinate other players to reduce influence by forcing to reveal a card
+Ambassador|ambassador - can exchange at most 2 cards from deck and block stealing
+Captain|captain - can steal 2 coins at most from other players and block stealing
+Contessa|contessa - can block assassination
+Duke|duke - can get tax and block foreign aid
+
+Target:
+Only other players can be targeted. Players with no cards can't be targeted.
+"#;
+
+const HELP_RU: &str = r#"
+Команды:
+help - показать это сообщение
+quit - остановить игру и выйти из процесса
+set players_number <number> - задать число игроков до начала игры
+set cards_per_type <number> - задать, сколько карт каждого типа в колоде до начала игры
+deck set|add|remove <card> <number> - задать, добавить или убрать заданное число карт с указанным именем в колоде до начала игры
+set cards_per_player <number> - задать, сколько карт раздаётся каждому игроку до начала игры
+set bot_type <name> - задать тип бота с указанным именем до начала игры
+set player <index> - задать, за какого игрока вы играете, до начала игры
+set seed <number> - задать зерно генератора случайных чисел бота до начала игры
+set seed_phrase <text> - задать зерно, полученное из указанного текста, до начала игры
+set auto_pass true|false - если действие невозможно во время окна спора или блока, сначала сыграть ожидаемый pass_challenge или pass_block
+name <index> <string> - задать имя указанного игрока до начала игры
+add <name> - добавить карту с указанным именем в руку игрока до начала игры
+rm <name> - убрать карту с указанным именем из руки игрока до начала игры
+start - начать игру с текущими настройками
+play <player_name> income - игрок берёт одну монету в начале хода
+play <player_name> coup <target> - игрок платит 7 монет и устраивает переворот против цели в начале хода
+play <player_name> foreign_aid|aid - игрок просит иностранную помощь в начале хода
+play <player_name> tax - игрок заявляет Герцога и берёт налог в начале хода
+play <player_name> assassinate|kill <target> - игрок платит 3 монеты и начинает убийство цели в начале хода
+play <player_name> steal <target> - игрок начинает кражу у цели в начале хода
+play <player_name> challenge - игрок оспаривает заявление другого игрока, разрешено только после действия или блока, требующего заявления
+play <player_name> pass_challenge|pass_c - игрок считает, что его заявление никто не оспаривает
+play <player_name> show <card> - оспоренный игрок показывает карту, подтверждая заявление, и кладёт её в колоду
+play <player_name> reveal <card> - игрок раскрывает карту и теряет влияние, теперь карта видна всем
+play <player_name> block <card> - игрок блокирует текущее действие, заявляя указанную карту, разрешено только после пропущенного спора или при неоспариваемом действии
+play <player_name> pass_block|pass_b - игрок считает, что его текущее действие больше никто не заблокирует
+play <player_name> shuffle - игрок перемешивает колоду перед тем, как взять карту после показа
+play <player_name> take <card> - игрок берёт карту из колоды вместо показанной или при обмене
+play <player_name> drop <card> - игрок кладёт карту в колоду, завершая обмен
+undo - отменить последнее действие
+skip - ничего не делать, когда предлагается необязательное действие (спор или блок) в игре с человеком
+state - показать текущее состояние игры
+avaialble - показать все доступные действия всех игроков в текущем состоянии
+bot suggest - показать все действия, предлагаемые текущим ботом в текущем состоянии
+bot get - показать действие, которое выбрал бы бот в текущем состоянии
+bot custom <query> - отправить боту произвольный запрос, результат зависит от типа бота
+hint - показать действия, предлагаемые ботом в текущем состоянии, с оценкой вероятности победы, если она есть
+hint target - упорядочить соперников как цели для кражи, убийства и переворота по вероятности блока, монетам и угрозе
+hint challenge - показать вероятность того, что ожидающее заявление правдиво, ожидаемую цену спора и пропуска и точку безубыточности
+branch - ответвить текущее состояние игры и бота в песочницу, чтобы пробовать гипотетические действия
+branch drop - отбросить песочницу и вернуться к состоянию игры, где началась ветка
+branch keep - принять линию песочницы как игру
+
+Судья:
+В режиме судьи известны все руки и колода, каждый показ, раскрытие, сброс и взятие карты проверяются по ним.
+play <player_name> take <card> - перемещает указанную карту из колоды к игроку, ошибка, если в колоде её нет
+play <player_name> take unknown - игрок берёт верхнюю карту из колоды
+
+Карты (в командах используются английские имена):
+Unknown|unknown (Неизвестная) - для действий take и drop соперников, означает, что карту видит только этот игрок
+Assassin|assassin (Ассасин) - может убивать других игроков, заставляя раскрыть карту и потерять влияние
+Ambassador|ambassador (Посол) - может обменять не более 2 карт из колоды и блокировать кражу
+Captain|captain (Капитан) - может украсть не более 2 монет у других игроков и блокировать кражу
+Contessa|contessa (Графиня) - может блокировать убийство
+Duke|duke (Герцог) - может брать налог и блокировать иностранную помощь
+
+Цель:
+Целью могут быть только другие игроки. Игроков без карт нельзя выбрать целью.
+"#;
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::game::ALL_CARDS;
+
+    use super::*;
+
+    #[test]
+    fn lang_should_be_parsed_from_code() {
+        assert_eq!(Lang::from_str("en"), Ok(Lang::En));
+        assert_eq!(Lang::from_str("ru"), Ok(Lang::Ru));
+        assert!(Lang::from_str("xx").is_err());
+    }
+
+    #[test]
+    fn card_names_should_be_unique_in_every_lang() {
+        for lang in [Lang::En, Lang::Ru] {
+            assert!(ALL_CARDS.iter().map(|v| lang.card_name(*v)).all_unique());
+        }
+        for card in ALL_CARDS.iter() {
+            assert_eq!(Lang::En.card_name(*card), format!("{:?}", card));
+        }
+    }
+
+    #[test]
+    fn format_should_use_lang_card_names() {
+        let message = Message::Block {
+            player: "Alice",
+            card: Card::Contessa,
+        };
+        assert_eq!(
+            Lang::En.format(&message),
+            "Alice blocks it claiming Contessa"
+        );
+        assert_eq!(
+            Lang::Ru.format(&message),
+            "Alice блокирует, заявляя карту Графиня"
+        );
+    }
+}
@@ -1,35 +1,144 @@
 #[macro_use]
 extern crate scan_fmt;
 
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, IsTerminal};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use tracing_subscriber::filter::LevelFilter;
 
+use crate::alloc::allocations;
+use crate::analyze::{
+    collect_agreement_stats, collect_win_probabilities, print_agreement_stats,
+    print_win_probabilities, AnalyzeMode, Rollouts, WinProbabilityFormat,
+};
+use crate::archive::{read_game, read_index, Archive, ArchiveQuery};
+use crate::arena::ArenaDb;
+use crate::async_bot::play_async_games;
+use crate::audit::{audit_game, print_audit_report, AuditReport};
+use crate::batch::suggest_batch;
+use crate::book::{build_opening_book, BookParams};
 use crate::bots::{
-    is_allowed_action_type, ActionView, Bot, CardsTracker, HonestCarefulRandomBot, RandomBot,
+    is_allowed_action_type, notify_bot, notify_tracker, ActionView, Bot, CardsTracker,
+    HonestCarefulRandomBot, InfoSeekingBot, PoolStats, RandomBot, TrackerState,
+};
+use crate::challenge::get_challenge_odds;
+use crate::chat::{read_replay_chat, ChatMessage};
+use crate::completeness::CompletenessReport;
+use crate::config::apply_config;
+use crate::conformance::read_conformance_cases;
+use crate::equity::{estimate_equity, print_equity};
+use crate::eval::{LinearEvaluator, Weights};
+use crate::exhibit::{run_exhibit, ExhibitOptions};
+use crate::fairness::{parse_salt, Reveal};
+use crate::follow::Follow;
+use crate::fsm::{
+    Action, Card, ResponsePriority, Rules, ALL_RESPONSE_PRIORITIES, CARDS_PER_PLAYER,
+    MAX_CARDS_TO_EXCHANGE, MAX_COINS,
 };
-use crate::fsm::{Action, Card, StateType};
+use crate::fsm_graph::{collect_fsm_graph, write_fsm_graph, GraphFormat};
 use crate::game::{
-    get_available_actions, get_example_actions, get_example_settings, Game, PlayerView, Settings,
+    get_available_actions, get_example_actions, get_example_settings, CardCounts, Decision, Game,
+    GameView, ReplayError, Scenario, Settings, ALL_CARDS, INITIAL_COINS,
+};
+use crate::input::{open_file, InputError, LineReader, Record};
+use crate::interactive::{get_player_names, run_interactive_game, run_referee_game, HumanBot};
+use crate::isolate::{serve_isolated_game, Isolation};
+use crate::locale::{get_lang, set_lang, Lang};
+use crate::narrate::{narrate_game, PlayerName};
+use crate::perft::{get_branching_factor, perft};
+use crate::profile::collect_strategy_profile;
+use crate::regression::{check_regression, RegressionRecord, DEFAULT_WIN_RATE};
+use crate::run::{
+    make_bot, make_bots, make_bots_rng, make_lineup, run_game, run_game_with_bots, verify_replay,
+    BotType, Clock, MoveTimeout, SeatSpec, TimeControl, TimeoutPolicy, ALL_BOT_TYPES,
+};
+use crate::seed::{apply_seed_phrase, check_seed_phrase, hash_seed_phrase};
+use crate::session::{
+    make_suggestion, SessionManager, SessionRequest, SessionResponse, Suggestion,
 };
-use crate::interactive::run_interactive_game;
-use crate::run::{run_game_with_bots, BotType};
-use crate::stats::{collect_random_games_stats, print_stats};
+use crate::stats::{
+    collect_duel_stats, collect_random_games_stats, print_duel_stats, print_stats,
+    write_action_heatmap, CheckpointOutput, HeatmapFormat, StatsCheckpoint,
+};
+use crate::sweep::{run_sweep, write_csv, ParamRange};
+use crate::timeline::TrackerTimeline;
 
+mod alloc;
+mod analyze;
+mod archive;
+mod arena;
+mod async_bot;
+mod audit;
+mod batch;
+mod book;
 mod bots;
+mod challenge;
+mod chat;
+mod completeness;
+mod config;
+mod conformance;
+#[cfg(feature = "discord")]
+mod discord;
+mod encoding;
+mod equity;
+mod eval;
+mod exhibit;
+mod fairness;
+mod follow;
 mod fsm;
+mod fsm_graph;
 mod game;
+mod input;
 mod interactive;
+mod isolate;
+mod locale;
+mod metrics;
+mod narrate;
+mod perft;
+mod profile;
+mod regression;
 mod run;
+mod seed;
+mod session;
+mod state_class;
 mod stats;
+mod sweep;
+mod symmetry;
+#[cfg(feature = "discord")]
+mod table;
+mod tablebase;
+mod target;
+mod timeline;
+
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: alloc::CountingAllocator = alloc::CountingAllocator;
 
 #[derive(Parser)]
 struct Args {
+    #[arg(long, global = true, default_value = "info")]
+    log_level: LevelFilter,
+    #[arg(long, global = true)]
+    log_json: bool,
+    #[arg(long, global = true)]
+    metrics_address: Option<String>,
+    #[arg(long, global = true)]
+    config: Option<String>,
+    #[arg(long, global = true)]
+    book: Option<String>,
+    #[arg(long, global = true)]
+    seed_phrase: Option<String>,
+    #[arg(long, global = true, default_value = "en")]
+    lang: Lang,
     #[command(subcommand)]
     command: Command,
 }
@@ -44,22 +153,240 @@ enum Command {
     Suggest(SuggestParams),
     Fuzzy(FuzzyParams),
     Interactive,
+    Scenario(ScenarioParams),
+    Arena(ArenaParams),
+    Duel(DuelParams),
+    Analyze(AnalyzeParams),
+    HotSeat(HotSeatParams),
+    Referee(RefereeParams),
+    Profile(ProfileParams),
+    Archive(ArchiveParams),
+    BuildBook(BuildBookParams),
+    Perft(PerftParams),
+    Bench(BenchParams),
+    Regression(RegressionParams),
+    Sweep(SweepParams),
+    Equity(EquityParams),
+    ChallengeOdds(ChallengeOddsParams),
+    Validate(ValidateParams),
+    Conformance(ConformanceParams),
+    FsmGraph(FsmGraphParams),
+    Audit(AuditParams),
+    Exhibit(ExhibitParams),
+    #[command(hide = true)]
+    IsolatedGame,
+    #[cfg(feature = "discord")]
+    Discord(DiscordParams),
+}
+
+#[derive(clap::Args, Debug)]
+struct SettingsArgs {
+    #[arg(long, default_value = "3")]
+    cards_per_type: usize,
+    #[arg(long, default_value_t = CARDS_PER_PLAYER)]
+    cards_per_player: usize,
+    #[arg(long, default_value_t = MAX_COINS)]
+    forced_coup_coins: usize,
+    #[arg(long, default_value = "turn_order")]
+    response_priority: ResponsePriority,
+    #[arg(long)]
+    bank_coins: Option<usize>,
+    #[arg(long, default_value_t = MAX_CARDS_TO_EXCHANGE)]
+    cards_to_exchange: usize,
+}
+
+impl SettingsArgs {
+    fn to_settings(&self, players_number: usize) -> Settings {
+        Settings {
+            players_number,
+            card_counts: CardCounts::uniform(self.cards_per_type),
+            cards_per_player: self.cards_per_player,
+            rules: Rules {
+                forced_coup_coins: self.forced_coup_coins,
+                response_priority: self.response_priority,
+                bank_coins: self.bank_coins,
+                cards_to_exchange: self.cards_to_exchange,
+            },
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
 struct SimulateParams {
     #[arg(long)]
     bot_types: Vec<BotType>,
+    #[arg(long)]
+    seat: Vec<SeatSpec>,
+    #[arg(long, default_value = "random")]
+    default_bot_type: BotType,
+    #[arg(long)]
+    cycle_bot_types: bool,
+    #[arg(long)]
+    move_timeout_ms: Option<u64>,
+    #[arg(long, default_value = "fallback")]
+    timeout_policy: TimeoutPolicy,
+    #[arg(long)]
+    clock_ms: Option<u64>,
+    #[arg(long, default_value = "0")]
+    clock_increment_ms: u64,
     #[arg(long, default_value = "42")]
     seed: u64,
     #[arg(long, default_value = "0")]
     max_steps: usize,
     #[arg(long, default_value = "6")]
     players_number: usize,
-    #[arg(long, default_value = "3")]
-    cards_per_type: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+    #[arg(long)]
+    write_player: Option<usize>,
+    #[arg(long)]
+    human: Option<usize>,
+    #[arg(
+        long,
+        conflicts_with_all = ["human", "resume", "save_snapshot", "write_player"]
+    )]
+    seed_range: Option<SeedRange>,
+    #[arg(long)]
+    filter: Vec<GameFilter>,
+    #[arg(long)]
+    save_snapshot: Option<String>,
+    #[arg(long)]
+    resume: Option<String>,
+    #[arg(long)]
+    verify: bool,
+    #[arg(
+        long,
+        requires = "seed_range",
+        conflicts_with_all = ["verify", "filter", "move_timeout_ms", "clock_ms", "max_steps"]
+    )]
+    async_bots: bool,
+}
+
+#[derive(Debug, Clone)]
+struct SeedRange {
+    start: u64,
+    end: u64,
+}
+
+impl FromStr for SeedRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| format!("invalid seed range, expected <start>..<end>: {}", s))?;
+        let start = start
+            .parse::<u64>()
+            .map_err(|e| format!("invalid seed range start {}: {}", start, e))?;
+        let end = end
+            .parse::<u64>()
+            .map_err(|e| format!("invalid seed range end {}: {}", end, e))?;
+        if start > end {
+            return Err(format!("seed range start is greater than end: {}", s));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum GameFilterField {
+    Winner,
+    Rounds,
+    Turns,
+    Steps,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum GameFilterOperator {
+    Equal,
+    Less,
+    Greater,
+}
+
+#[derive(Debug, Clone)]
+struct GameFilter {
+    field: GameFilterField,
+    operator: GameFilterOperator,
+    value: usize,
+}
+
+impl GameFilter {
+    fn matches(&self, game: &Game) -> bool {
+        let value = match self.field {
+            GameFilterField::Winner => match game.get_winner() {
+                Some(v) => v,
+                None => return false,
+            },
+            GameFilterField::Rounds => game.round(),
+            GameFilterField::Turns => game.turn(),
+            GameFilterField::Steps => game.step(),
+        };
+        match self.operator {
+            GameFilterOperator::Equal => value == self.value,
+            GameFilterOperator::Less => value < self.value,
+            GameFilterOperator::Greater => value > self.value,
+        }
+    }
+}
+
+impl FromStr for GameFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, operator) = s
+            .char_indices()
+            .find_map(|(i, c)| match c {
+                '=' => Some((i, GameFilterOperator::Equal)),
+                '<' => Some((i, GameFilterOperator::Less)),
+                '>' => Some((i, GameFilterOperator::Greater)),
+                _ => None,
+            })
+            .ok_or_else(|| format!("invalid filter, expected <field>(=|<|>)<value>: {}", s))?;
+        let field = match &s[..position] {
+            "winner" => GameFilterField::Winner,
+            "rounds" => GameFilterField::Rounds,
+            "turns" => GameFilterField::Turns,
+            "steps" => GameFilterField::Steps,
+            v => return Err(format!("invalid filter field: {}", v)),
+        };
+        let value = s[position + 1..]
+            .parse::<usize>()
+            .map_err(|e| format!("invalid filter value {}: {}", &s[position + 1..], e))?;
+        Ok(Self {
+            field,
+            operator,
+            value,
+        })
+    }
+}
+
+#[derive(Parser)]
+struct ScenarioParams {
+    #[arg(long)]
+    bot_types: Vec<BotType>,
+    #[arg(long)]
+    seat: Vec<SeatSpec>,
+    #[arg(long, default_value = "random")]
+    default_bot_type: BotType,
+    #[arg(long)]
+    cycle_bot_types: bool,
+    #[arg(long)]
+    move_timeout_ms: Option<u64>,
+    #[arg(long, default_value = "fallback")]
+    timeout_policy: TimeoutPolicy,
+    #[arg(long)]
+    clock_ms: Option<u64>,
+    #[arg(long, default_value = "0")]
+    clock_increment_ms: u64,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "0")]
+    max_steps: usize,
     #[arg(long)]
     write_player: Option<usize>,
+    #[arg(long)]
+    human: Option<usize>,
+    file: String,
 }
 
 #[derive(Parser)]
@@ -72,330 +399,1957 @@ struct StatsParams {
     seed: u64,
     #[arg(long)]
     bot_types: Vec<BotType>,
+    #[arg(long)]
+    seat: Vec<SeatSpec>,
+    #[arg(long, default_value = "random")]
+    default_bot_type: BotType,
+    #[arg(long)]
+    cycle_bot_types: bool,
+    #[arg(long)]
+    random_lineups: bool,
+    #[arg(long)]
+    checkpoint: Option<String>,
+    #[arg(long, default_value = "1000")]
+    checkpoint_interval: usize,
+    #[arg(long)]
+    resume_from: Option<String>,
+    #[arg(long)]
+    archive: Option<String>,
+    #[arg(long)]
+    compress_archive: bool,
+    #[arg(long)]
+    move_timeout_ms: Option<u64>,
+    #[arg(long, default_value = "fallback")]
+    timeout_policy: TimeoutPolicy,
+    #[arg(long)]
+    clock_ms: Option<u64>,
+    #[arg(long, default_value = "0")]
+    clock_increment_ms: u64,
+    #[arg(long)]
+    isolate: bool,
+    #[arg(long, conflicts_with = "isolate")]
+    tracker_accuracy: bool,
+    #[arg(long)]
+    heatmap: Option<String>,
+    #[arg(long, default_value = "csv")]
+    heatmap_format: HeatmapFormat,
+    #[arg(long, default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct ArenaParams {
+    #[arg(long)]
+    db: String,
+    #[arg(long)]
+    bot_types: Vec<BotType>,
+    #[arg(long, default_value = "0")]
+    games: usize,
+    #[arg(long, default_value = "100")]
+    save_interval: usize,
+    #[arg(long)]
+    resume_from: Option<String>,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long)]
+    move_timeout_ms: Option<u64>,
+    #[arg(long, default_value = "fallback")]
+    timeout_policy: TimeoutPolicy,
+    #[arg(long)]
+    clock_ms: Option<u64>,
+    #[arg(long, default_value = "0")]
+    clock_increment_ms: u64,
+    #[arg(long, default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct DuelParams {
+    #[arg(long)]
+    a: BotType,
+    #[arg(long)]
+    b: BotType,
+    #[arg(long, default_value = "1000")]
+    games: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long)]
+    move_timeout_ms: Option<u64>,
+    #[arg(long, default_value = "fallback")]
+    timeout_policy: TimeoutPolicy,
+    #[arg(long)]
+    clock_ms: Option<u64>,
+    #[arg(long, default_value = "0")]
+    clock_increment_ms: u64,
+    #[arg(long, default_value = "2")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct ProfileParams {
+    #[arg(long, default_value = "1000")]
+    games: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long)]
+    bot_types: Vec<BotType>,
+    #[arg(long)]
+    seat: Vec<SeatSpec>,
+    #[arg(long, default_value = "random")]
+    default_bot_type: BotType,
+    #[arg(long)]
+    cycle_bot_types: bool,
+    #[arg(long, default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct BenchParams {
+    #[arg(long, default_value = "100")]
+    games: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "honest_careful_random")]
+    bot_type: BotType,
+    #[arg(long, default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+    #[arg(long)]
+    parse_actions: Option<usize>,
+}
+
+#[derive(Parser, Debug)]
+struct PerftParams {
+    #[arg(long)]
+    depth: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "2")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+    #[arg(long)]
+    scenario: Option<String>,
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Parser, Debug)]
+struct RegressionParams {
+    #[arg(long)]
+    baseline: BotType,
+    #[arg(long)]
+    candidate: BotType,
+    #[arg(long, default_value = "1000")]
+    games: usize,
+    #[arg(long, default_value = "0.02")]
+    threshold: f64,
+    #[arg(long)]
+    record: Option<String>,
+    #[arg(long)]
+    update_record: bool,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "2")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct ChallengeOddsParams {
+    #[arg(long)]
+    load_state: Option<String>,
+    file: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ValidateParams {
+    #[arg(long)]
+    commitment: String,
+    #[arg(long)]
+    seed: u64,
+    #[arg(long, value_parser = parse_salt)]
+    salt: u128,
+    file: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ExhibitParams {
+    #[arg(long)]
+    bot_types: Vec<BotType>,
+    #[arg(long)]
+    seat: Vec<SeatSpec>,
+    #[arg(long, default_value = "random")]
+    default_bot_type: BotType,
+    #[arg(long)]
+    cycle_bot_types: bool,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "800")]
+    delay_ms: u64,
+    #[arg(long)]
+    wait_enter: bool,
+    #[arg(long)]
+    no_color: bool,
+    #[arg(long, default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct AuditParams {
+    file: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ConformanceParams {
+    #[arg(long, default_value = "conformance")]
+    dir: String,
+    #[arg(long)]
+    show_notes: bool,
+}
+
+#[derive(Parser, Debug)]
+struct FsmGraphParams {
+    #[arg(long, default_value = "dot")]
+    format: GraphFormat,
+}
+
+#[derive(Parser, Debug)]
+struct EquityParams {
+    #[arg(long, value_delimiter = ',', required = true)]
+    cards: Vec<Card>,
+    #[arg(long, default_value = "honest_careful_random")]
+    bot_type: BotType,
+    #[arg(long, default_value = "random")]
+    field: BotType,
+    #[arg(long, default_value = "10000")]
+    games: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, alias = "players", default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct SweepParams {
+    #[arg(long)]
+    param: ParamRange,
+    #[arg(long, default_value = "random")]
+    opponent: BotType,
+    #[arg(long, default_value = "100")]
+    pairs: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "2")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct BuildBookParams {
+    #[arg(long)]
+    output: String,
+    #[arg(long, default_value = "10000")]
+    games: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "2")]
+    rounds: usize,
+    #[arg(long, default_value = "10")]
+    min_samples: usize,
+    #[arg(long)]
+    bot_types: Vec<BotType>,
+    #[arg(long)]
+    seat: Vec<SeatSpec>,
+    #[arg(long, default_value = "honest_careful_random")]
+    default_bot_type: BotType,
+    #[arg(long)]
+    cycle_bot_types: bool,
     #[arg(long, default_value = "6")]
     players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct ArchiveParams {
+    #[command(subcommand)]
+    command: ArchiveCommand,
+}
+
+#[derive(Parser, Debug)]
+enum ArchiveCommand {
+    Get(ArchiveGetParams),
+    Query(ArchiveQueryParams),
+}
+
+#[derive(Parser, Debug)]
+struct ArchiveQueryParams {
+    #[arg(long)]
+    dir: String,
+    #[arg(long)]
+    records: bool,
+    query: ArchiveQuery,
+}
+
+#[derive(Parser, Debug)]
+struct ArchiveGetParams {
+    #[arg(long)]
+    dir: String,
+    id: usize,
+}
+
+#[derive(Parser, Debug)]
+struct AnalyzeParams {
+    #[arg(long, default_value = "agreement")]
+    mode: AnalyzeMode,
+    #[arg(long)]
+    bot_types: Vec<BotType>,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "random")]
+    rollout_bot_type: BotType,
+    #[arg(long, default_value = "32")]
+    rollouts: usize,
+    #[arg(long, default_value = "0")]
+    rollout_max_steps: usize,
+    #[arg(long)]
+    eval_weights: Option<String>,
+    #[arg(long, default_value = "sparkline")]
+    format: WinProbabilityFormat,
+    #[arg(long, default_value = "3")]
+    critical_moments: usize,
+    dir: String,
+}
+
+#[cfg(feature = "discord")]
+#[derive(Parser, Debug)]
+struct DiscordParams {
+    #[arg(long)]
+    token: Option<String>,
+    #[arg(long, default_value = "honest_careful_random")]
+    bot_type: BotType,
     #[arg(long, default_value = "3")]
     cards_per_type: usize,
+    #[arg(long, default_value_t = CARDS_PER_PLAYER)]
+    cards_per_player: usize,
+    #[arg(long, hide = true)]
+    seed: Option<u64>,
+    #[arg(long, default_value = "3")]
+    spectator_delay: usize,
+    #[arg(long)]
+    replay_dir: Option<String>,
+    #[arg(long)]
+    auto_pass_all_passed: bool,
+    #[arg(long)]
+    auto_pass_timeout_ms: Option<u64>,
+}
+
+#[derive(Parser, Debug)]
+struct HotSeatParams {
+    #[arg(long)]
+    name: Vec<String>,
+    #[arg(long)]
+    bot_types: Vec<BotType>,
+    #[arg(long)]
+    seed: Option<u64>,
+    #[command(flatten)]
+    settings: SettingsArgs,
+}
+
+#[derive(Parser, Debug)]
+struct RefereeParams {
+    #[arg(long)]
+    name: Vec<String>,
+    #[arg(long, default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(long)]
+    scenario: Option<String>,
+    #[arg(long)]
+    auto_pass: bool,
+}
+
+#[derive(Parser)]
+struct ReplayParams {
+    #[arg(long)]
+    verbose: bool,
+    #[arg(long)]
+    write_player: Option<usize>,
+    #[arg(long, conflicts_with = "write_player")]
+    write_spectator: bool,
+    #[arg(long, conflicts_with_all = ["verbose", "write_player", "write_spectator"])]
+    export_public: bool,
+    #[arg(long, conflicts_with_all = ["verbose", "write_player", "write_spectator", "export_public"])]
+    narrate: bool,
+    #[arg(long = "name", requires = "narrate")]
+    names: Vec<PlayerName>,
+    file: Option<String>,
+}
+
+#[derive(Parser)]
+struct TrackerParams {
+    #[arg(long)]
+    follow: bool,
+    #[arg(long, default_value = "100")]
+    poll_interval_ms: u64,
+    #[arg(long, default_value = "0")]
+    sample_worlds: usize,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long)]
+    dot: Option<String>,
+    #[arg(long)]
+    json: bool,
+    #[arg(long)]
+    load_state: Option<String>,
+    #[arg(long)]
+    save_state: Option<String>,
+    file: Option<String>,
+}
+
+struct TrackOptions {
+    follow: bool,
+    json: bool,
+    sample_worlds: usize,
+}
+
+#[derive(Parser)]
+struct SuggestParams {
+    #[arg(long)]
+    bot_type: BotType,
+    #[arg(long)]
+    follow: bool,
+    #[arg(long, default_value = "100")]
+    poll_interval_ms: u64,
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long)]
+    multi: bool,
+    #[arg(long)]
+    batch: Option<String>,
+    #[arg(long, default_value = "1")]
+    workers: usize,
+    #[arg(long)]
+    load_state: Option<String>,
+    #[arg(long)]
+    save_state: Option<String>,
+    file: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct FuzzyParams {
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    #[arg(long, default_value = "10000")]
+    max_games: usize,
+    #[arg(long, default_value = "6")]
+    players_number: usize,
+    #[command(flatten)]
+    settings: SettingsArgs,
+    #[arg(long)]
+    vary_settings: bool,
+    #[arg(long)]
+    check_actions: bool,
+}
+
+fn main() {
+    let args = apply_config(Args::command(), std::env::args_os().collect());
+    let args: Args = Args::parse_from(apply_seed_phrase(Args::command(), args));
+    if let Some(phrase) = args.seed_phrase.as_ref() {
+        eprintln!(
+            "seed phrase {:?} => seed {}",
+            phrase,
+            hash_seed_phrase(phrase)
+        );
+    }
+    init_logging(args.log_level, args.log_json);
+    set_lang(args.lang);
+    if let Some(address) = args.metrics_address.as_ref() {
+        unwrap_or_exit(metrics::serve(address));
+    }
+    if let Some(path) = args.book.as_ref() {
+        unwrap_or_exit(book::load(path));
+    }
+    match args.command {
+        Command::Simulate(params) => simulate(params),
+        Command::Replay(params) => replay(params),
+        Command::Stats(params) => stats(params, args.book.as_deref()),
+        Command::Example => example(),
+        Command::Track(params) => track(params),
+        Command::Suggest(params) => suggest(params),
+        Command::Fuzzy(params) => fuzzy(params),
+        Command::Interactive => run_interactive_game(),
+        Command::Scenario(params) => scenario(params),
+        Command::Arena(params) => arena(params),
+        Command::Duel(params) => duel(params),
+        Command::Analyze(params) => analyze(params),
+        Command::HotSeat(params) => hot_seat(params),
+        Command::Referee(params) => referee(params),
+        Command::Profile(params) => profile(params),
+        Command::Archive(params) => archive(params),
+        Command::BuildBook(params) => build_book(params),
+        Command::Perft(params) => perft_command(params),
+        Command::Bench(params) => bench(params),
+        Command::Regression(params) => regression(params),
+        Command::Sweep(params) => sweep(params),
+        Command::Equity(params) => equity(params),
+        Command::ChallengeOdds(params) => challenge_odds(params),
+        Command::Validate(params) => validate(params),
+        Command::Conformance(params) => conformance(params),
+        Command::FsmGraph(params) => fsm_graph(params),
+        Command::Audit(params) => audit(params),
+        Command::Exhibit(params) => exhibit(params),
+        Command::IsolatedGame => unwrap_or_exit(serve_isolated_game()),
+        #[cfg(feature = "discord")]
+        Command::Discord(params) => discord(params),
+    }
+}
+
+fn init_logging(log_level: LevelFilter, log_json: bool) {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(log_level)
+        .with_target(false)
+        .with_ansi(std::io::stdout().is_terminal())
+        .without_time();
+    if log_json {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
+}
+
+fn simulate(params: SimulateParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let time_control = get_time_control(
+        params.move_timeout_ms,
+        params.timeout_policy,
+        params.clock_ms,
+        params.clock_increment_ms,
+    );
+    if params.verify
+        && matches!(time_control.move_timeout, Some(v) if v.policy == TimeoutPolicy::Fallback)
+    {
+        eprintln!("--verify can't be used with fallback timeout policy because fallback actions are not reproducible");
+        std::process::exit(1);
+    }
+    if let Some(seed_range) = params.seed_range.as_ref() {
+        let bot_types = get_lineup(
+            params.players_number,
+            &params.bot_types,
+            &params.seat,
+            params.default_bot_type,
+            params.cycle_bot_types,
+        );
+        if params.async_bots {
+            let seeds: Vec<u64> = (seed_range.start..seed_range.end).collect();
+            let games = play_async_games(&seeds, &bot_types, &settings);
+            for (seed, game) in seeds.iter().zip(games.iter()) {
+                print_seed_result(*seed, &bot_types, game);
+            }
+            return;
+        }
+        simulate_seed_range(
+            seed_range,
+            &params.filter,
+            &bot_types,
+            settings,
+            time_control,
+            params.max_steps,
+            params.verify,
+        );
+        return;
+    }
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let game = if let Some(path) = params.resume.as_ref() {
+        unwrap_or_exit(Game::load_snapshot(path))
+    } else {
+        Game::new(settings, &mut rng)
+    };
+    let players_number = game.get_settings().players_number;
+    if let Some(human) = params.human.filter(|v| *v >= players_number) {
+        eprintln!(
+            "Human player {} is not applicable for {} players",
+            human, players_number
+        );
+        std::process::exit(1);
+    }
+    let bot_types = get_lineup(
+        players_number,
+        &params.bot_types,
+        &params.seat,
+        params.default_bot_type,
+        params.cycle_bot_types,
+    );
+    let begin = (game.clone(), rng.clone());
+    let game = play_game(
+        game,
+        params.seed,
+        &mut rng,
+        &bot_types,
+        params.human,
+        params.write_player,
+        params.max_steps,
+        time_control,
+    );
+    if params.verify {
+        let (begin_game, mut begin_rng) = begin;
+        unwrap_or_exit(verify_replay(begin_game, &mut begin_rng, &game));
+        tracing::info!(seed = params.seed, "replay verified");
+    }
+    if let Some(path) = params.save_snapshot.as_ref() {
+        unwrap_or_exit(game.save_snapshot(path));
+    }
+}
+
+fn simulate_seed_range(
+    seed_range: &SeedRange,
+    filters: &[GameFilter],
+    bot_types: &[BotType],
+    settings: Settings,
+    time_control: TimeControl,
+    max_steps: usize,
+    verify: bool,
+) {
+    for seed in seed_range.start..seed_range.end {
+        let result = run_game_with_bots(
+            seed,
+            bot_types,
+            settings.clone(),
+            false,
+            None,
+            max_steps,
+            time_control,
+        );
+        print_seed_result(seed, bot_types, &result.end);
+        if verify {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let game = Game::new(settings.clone(), &mut rng);
+            if game != result.begin {
+                eprintln!("Initial game state doesn't match for seed {}", seed);
+                std::process::exit(1);
+            }
+            unwrap_or_exit(verify_replay(game, &mut rng, &result.end));
+        }
+        if !filters.is_empty() && filters.iter().all(|filter| filter.matches(&result.end)) {
+            run_game_with_bots(
+                seed,
+                bot_types,
+                settings.clone(),
+                true,
+                None,
+                max_steps,
+                time_control,
+            );
+        }
+    }
+}
+
+fn print_seed_result(seed: u64, bot_types: &[BotType], game: &Game) {
+    println!("{}", format_seed_result(seed, bot_types, game));
+}
+
+fn format_seed_result(seed: u64, bot_types: &[BotType], game: &Game) -> String {
+    let winner = match game.get_winner() {
+        Some(v) => format!("winner={} winner_bot_type={:?}", v, bot_types[v]),
+        None => String::from("winner=none"),
+    };
+    format!(
+        "seed={} {} rounds={} turns={} steps={}",
+        seed,
+        winner,
+        game.round(),
+        game.turn(),
+        game.step()
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_game(
+    mut game: Game,
+    seed: u64,
+    rng: &mut StdRng,
+    bot_types: &[BotType],
+    human: Option<usize>,
+    write_player: Option<usize>,
+    max_steps: usize,
+    time_control: TimeControl,
+) -> Game {
+    let _span = tracing::info_span!("game", seed).entered();
+    let settings = game.get_settings();
+    let mut bots_rng = make_bots_rng(seed);
+    let mut bots: Vec<Box<dyn Bot>> = bot_types
+        .iter()
+        .enumerate()
+        .map(|(index, bot_type)| -> Box<dyn Bot> {
+            if Some(index) == human {
+                Box::new(HumanBot::new(settings.players_number))
+            } else {
+                make_bot(
+                    *bot_type,
+                    &game.get_player_view(index),
+                    &settings,
+                    &mut bots_rng,
+                )
+            }
+        })
+        .collect();
+    run_game(
+        &mut bots,
+        &mut game,
+        rng,
+        human.is_none(),
+        write_player,
+        max_steps,
+        time_control,
+    );
+    if human.is_some() {
+        game.print();
+    }
+    game
+}
+
+fn scenario(params: ScenarioParams) {
+    let scenario = unwrap_or_exit(Scenario::load(&params.file));
+    let game = unwrap_or_exit(Game::from_scenario(&scenario));
+    let bot_types = get_lineup(
+        game.get_settings().players_number,
+        &params.bot_types,
+        &params.seat,
+        params.default_bot_type,
+        params.cycle_bot_types,
+    );
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    play_game(
+        game,
+        params.seed,
+        &mut rng,
+        &bot_types,
+        params.human,
+        params.write_player,
+        params.max_steps,
+        get_time_control(
+            params.move_timeout_ms,
+            params.timeout_policy,
+            params.clock_ms,
+            params.clock_increment_ms,
+        ),
+    );
+}
+
+fn get_time_control(
+    move_timeout_ms: Option<u64>,
+    timeout_policy: TimeoutPolicy,
+    clock_ms: Option<u64>,
+    clock_increment_ms: u64,
+) -> TimeControl {
+    TimeControl {
+        move_timeout: move_timeout_ms.map(|ms| MoveTimeout {
+            duration: Duration::from_millis(ms),
+            policy: timeout_policy,
+        }),
+        clock: clock_ms.map(|ms| Clock {
+            total: Duration::from_millis(ms),
+            increment: Duration::from_millis(clock_increment_ms),
+        }),
+    }
+}
+
+fn unwrap_or_exit<T>(result: Result<T, String>) -> T {
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn get_lineup(
+    players_number: usize,
+    bot_types: &[BotType],
+    seats: &[SeatSpec],
+    default_bot_type: BotType,
+    cycle_bot_types: bool,
+) -> Vec<BotType> {
+    unwrap_or_exit(make_lineup(
+        players_number,
+        bot_types,
+        seats,
+        default_bot_type,
+        cycle_bot_types,
+    ))
+}
+
+fn replay(params: ReplayParams) {
+    let result = if let Some(path) = params.file.as_ref() {
+        read_replay_chat(path).and_then(|chat| {
+            open_file(path).and_then(|file| replay_from_file(LineReader::new(file), &chat, &params))
+        })
+    } else {
+        replay_from_file(LineReader::new(std::io::stdin().lock()), &[], &params)
+    };
+    unwrap_input_or_exit(result);
+}
+
+#[derive(Serialize, Deserialize)]
+struct GameParams {
+    seed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed_phrase: Option<String>,
+    settings: Settings,
+}
+
+impl GameParams {
+    fn validate(&self) -> Result<(), String> {
+        check_seed_phrase(self.seed, self.seed_phrase.as_deref())?;
+        self.settings.validate()
+    }
+}
+
+fn replay_from_file<F: BufRead>(
+    mut file: LineReader<F>,
+    chat: &[ChatMessage],
+    params: &ReplayParams,
+) -> Result<(), InputError> {
+    let game_params: GameParams = file.read_required("game params")?;
+    game_params.validate().map_err(|e| file.invalid_error(e))?;
+    let players_number = game_params.settings.players_number;
+    let mut custom_player_names = HashMap::new();
+    for player_name in params.names.iter() {
+        if player_name.index >= players_number {
+            return Err(file.invalid_error(format!(
+                "player {} named {} is out of range for {} players",
+                player_name.index, player_name.name, players_number
+            )));
+        }
+        custom_player_names.insert(player_name.index, player_name.name.clone());
+    }
+    file.read_lines()?;
+    let records: Vec<Record<Action>> = file.records()?;
+    let actions: Vec<Action> = records.iter().map(|v| v.value.clone()).collect();
+    if params.narrate {
+        let player_names = get_player_names(players_number, &custom_player_names, None);
+        let paragraphs = narrate_game(
+            game_params.settings,
+            game_params.seed,
+            &actions,
+            &player_names,
+            get_lang(),
+        )
+        .map_err(|e| match &e {
+            ReplayError::InvalidSettings(error) => file.invalid_error(error.clone()),
+            ReplayError::IllegalAction { index, .. } => {
+                file.play_error(&records[*index], e.to_string())
+            }
+        })?;
+        for paragraph in paragraphs.iter() {
+            println!("{}", paragraph);
+        }
+        return Ok(());
+    }
+    let replay_error = |e: ReplayError| match &e {
+        ReplayError::InvalidSettings(error) => file.invalid_error(error.clone()),
+        ReplayError::IllegalAction { index, .. } => {
+            file.play_error(&records[*index], e.to_string())
+        }
+    };
+    if params.export_public {
+        let lines = export_public_replay(game_params.settings, game_params.seed, &actions)
+            .map_err(replay_error)?;
+        for line in lines.iter() {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+    if params.write_player.is_some() || params.write_spectator {
+        println!("{}", serde_json::to_string(&game_params.settings).unwrap());
+    } else if !params.verbose {
+        Game::from_history(game_params.settings, game_params.seed, &actions)
+            .map_err(replay_error)?;
+        return Ok(());
+    }
+    Game::from_history_with(
+        game_params.settings,
+        game_params.seed,
+        &actions,
+        |game, action| {
+            if let Some(action) = action {
+                if params.verbose {
+                    println!("[{}] play {:?}", game.step() - 1, action);
+                }
+                if params.write_player.is_some() {
+                    println!("{}", serde_json::to_string(action).unwrap());
+                }
+            }
+            if params.verbose {
+                game.print();
+                for message in chat.iter().filter(|v| v.step == game.step()) {
+                    println!(
+                        "[{}] player {} says: {}",
+                        message.step, message.player, message.text
+                    );
+                }
+            }
+            if let Some(player) = params.write_player {
+                println!(
+                    "{}",
+                    serde_json::to_string(&game.get_player_view(player)).unwrap()
+                );
+            }
+            if params.write_spectator {
+                println!(
+                    "{}",
+                    serde_json::to_string(&game.get_spectator_view()).unwrap()
+                );
+            }
+        },
+    )
+    .map_err(replay_error)?;
+    Ok(())
+}
+
+fn export_public_replay(
+    settings: Settings,
+    seed: u64,
+    actions: &[Action],
+) -> Result<Vec<String>, ReplayError> {
+    let mut lines = vec![serde_json::to_string(&settings).unwrap()];
+    Game::from_history_with(settings, seed, actions, |game, action| {
+        if let Some(action) = action {
+            lines.push(serde_json::to_string(&ActionView::from_action(action)).unwrap());
+        }
+        lines.push(serde_json::to_string(&game.get_public_view()).unwrap());
+    })?;
+    Ok(lines)
+}
+
+fn stats(params: StatsParams, book: Option<&str>) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let bot_types = get_lineup(
+        params.players_number,
+        &params.bot_types,
+        &params.seat,
+        params.default_bot_type,
+        params.cycle_bot_types,
+    );
+    let mut checkpoint =
+        StatsCheckpoint::new(params.seed, settings, bot_types, params.random_lineups)
+            .with_tracker_accuracy(params.tracker_accuracy);
+    if let Some(path) = params.resume_from.as_ref() {
+        checkpoint = unwrap_or_exit(checkpoint.resume(path));
+        tracing::info!(games = checkpoint.games(), path = %path, "resume stats");
+    }
+    let mut archive = params
+        .archive
+        .as_ref()
+        .map(|path| unwrap_or_exit(Archive::open(path, params.compress_archive)));
+    let stats = unwrap_or_exit(collect_random_games_stats(
+        checkpoint,
+        params.games,
+        params.workers,
+        get_time_control(
+            params.move_timeout_ms,
+            params.timeout_policy,
+            params.clock_ms,
+            params.clock_increment_ms,
+        ),
+        params.checkpoint.as_ref().map(|path| CheckpointOutput {
+            path,
+            interval: params.checkpoint_interval,
+        }),
+        archive.as_mut(),
+        if params.isolate {
+            Some(unwrap_or_exit(make_isolation(book)))
+        } else {
+            None
+        },
+    ));
+    print_stats(&stats);
+    if let Some(path) = params.heatmap.as_ref() {
+        unwrap_or_exit(
+            File::create(path)
+                .and_then(|file| write_action_heatmap(&stats, params.heatmap_format, file))
+                .map_err(|e| format!("failed to write heatmap to {}: {}", path, e)),
+        );
+    }
+}
+
+fn make_isolation(book: Option<&str>) -> Result<Isolation, String> {
+    let mut args = vec![String::from("--log-level"), String::from("off")];
+    if let Some(path) = book {
+        args.push(String::from("--book"));
+        args.push(String::from(path));
+    }
+    Isolation::current(args)
+}
+
+fn arena(params: ArenaParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let mut db = match params.resume_from.as_ref() {
+        Some(path) => unwrap_or_exit(ArenaDb::resume(path, settings)),
+        None => unwrap_or_exit(ArenaDb::load_or_new(&params.db, params.seed, settings)),
+    };
+    let bot_types = if params.bot_types.is_empty() && db.games() == 0 {
+        ALL_BOT_TYPES.to_vec()
+    } else {
+        params.bot_types
+    };
+    for bot_type in bot_types {
+        db.register(bot_type);
+    }
+    let time_control = get_time_control(
+        params.move_timeout_ms,
+        params.timeout_policy,
+        params.clock_ms,
+        params.clock_increment_ms,
+    );
+    let save_interval = params.save_interval.max(1);
+    let mut games = 0;
+    while params.games == 0 || games < params.games {
+        db.play_match(time_control);
+        games += 1;
+        if games % save_interval == 0 {
+            unwrap_or_exit(db.save(&params.db));
+            db.print_leaderboard();
+            println!();
+        }
+    }
+    if games % save_interval != 0 {
+        unwrap_or_exit(db.save(&params.db));
+        db.print_leaderboard();
+    }
+}
+
+fn duel(params: DuelParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let bot_types = [params.a, params.b];
+    print_duel_stats(
+        &collect_duel_stats(
+            params.seed,
+            params.games,
+            bot_types,
+            settings,
+            get_time_control(
+                params.move_timeout_ms,
+                params.timeout_policy,
+                params.clock_ms,
+                params.clock_increment_ms,
+            ),
+        ),
+        bot_types,
+    );
+}
+
+fn analyze(params: AnalyzeParams) {
+    let bot_types = if params.bot_types.is_empty() {
+        ALL_BOT_TYPES.to_vec()
+    } else {
+        params.bot_types
+    };
+    match params.mode {
+        AnalyzeMode::Agreement => print_agreement_stats(
+            &unwrap_or_exit(collect_agreement_stats(
+                &params.dir,
+                params.seed,
+                &bot_types,
+            )),
+            &bot_types,
+        ),
+        AnalyzeMode::WinProbability => print_win_probabilities(
+            &unwrap_or_exit(collect_win_probabilities(
+                &params.dir,
+                params.seed,
+                &Rollouts {
+                    bot_type: params.rollout_bot_type,
+                    number: params.rollouts,
+                    max_steps: params.rollout_max_steps,
+                    evaluator: Box::new(LinearEvaluator::new(match params.eval_weights.as_ref() {
+                        Some(path) => unwrap_or_exit(Weights::load(path)),
+                        None => Weights::default(),
+                    })),
+                },
+                params.critical_moments,
+            )),
+            params.format,
+        ),
+    }
+}
+
+fn profile(params: ProfileParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let bot_types = get_lineup(
+        params.players_number,
+        &params.bot_types,
+        &params.seat,
+        params.default_bot_type,
+        params.cycle_bot_types,
+    );
+    let profile = unwrap_or_exit(collect_strategy_profile(
+        params.seed,
+        params.games,
+        &bot_types,
+        settings,
+    ));
+    tracing::info!(games = profile.games(), "strategy profile collected");
+    for entry in profile.entries() {
+        println!("{}", serde_json::to_string(&entry).unwrap());
+    }
+}
+
+fn build_book(params: BuildBookParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let bot_types = get_lineup(
+        params.players_number,
+        &params.bot_types,
+        &params.seat,
+        params.default_bot_type,
+        params.cycle_bot_types,
+    );
+    let book = unwrap_or_exit(build_opening_book(
+        &BookParams {
+            seed: params.seed,
+            games: params.games,
+            rounds: params.rounds,
+            min_samples: params.min_samples,
+        },
+        &bot_types,
+        settings,
+    ));
+    unwrap_or_exit(book.save(&params.output));
+    println!("moves: {}", book.len());
+}
+
+fn perft_command(params: PerftParams) {
+    let game = match &params.scenario {
+        Some(path) => unwrap_or_exit(Game::from_scenario(&unwrap_or_exit(Scenario::load(path)))),
+        None => {
+            let settings = params.settings.to_settings(params.players_number);
+            unwrap_or_exit(settings.validate());
+            Game::new(settings, &mut StdRng::seed_from_u64(params.seed))
+        }
+    };
+    let result = perft(&game, params.depth);
+    for value in result.iter() {
+        if params.json {
+            println!("{}", serde_json::to_string(value).unwrap());
+            continue;
+        }
+        print!(
+            "depth={} sequences={} states={} finished={}",
+            value.depth, value.sequences, value.states, value.finished
+        );
+        match get_branching_factor(&result, value.depth) {
+            Some(v) => println!(" branching={:.3}", v),
+            None => println!(),
+        }
+    }
+}
+
+fn bench(params: BenchParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    if let Some(actions) = params.parse_actions {
+        return bench_parse(actions, params.seed, settings);
+    }
+    let bot_types = vec![params.bot_type; params.players_number];
+    let mut steps = 0;
+    let mut pool = PoolStats::default();
+    let start_allocations = allocations();
+    let start = Instant::now();
+    for seed in params.seed..params.seed + params.games as u64 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game = Game::new(settings.clone(), &mut rng);
+        let mut bots = make_bots(seed, &bot_types, &game, None);
+        run_game(
+            &mut bots,
+            &mut game,
+            &mut rng,
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        steps += game.step();
+        for bot in bots.iter() {
+            if let Some(tracker) = bot.cards_tracker() {
+                pool.add(&tracker.pool_stats());
+            }
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "games={} steps={} elapsed={:.3}s steps_per_second={:.1}",
+        params.games,
+        steps,
+        elapsed,
+        steps as f64 / elapsed
+    );
+    if let (Some(start_allocations), Some(end_allocations)) = (start_allocations, allocations()) {
+        let allocations = end_allocations - start_allocations;
+        println!(
+            "allocations={} allocations_per_step={:.1}",
+            allocations,
+            allocations as f64 / steps as f64
+        );
+    }
+    println!(
+        "tracker_states allocated={} reused={} released={}",
+        pool.allocated, pool.reused, pool.released
+    );
+}
+
+fn bench_parse(actions_number: usize, seed: u64, settings: Settings) {
+    let game_params = GameParams {
+        seed,
+        seed_phrase: None,
+        settings: settings.clone(),
+    };
+    let mut input = serde_json::to_string(&game_params).unwrap() + "\n";
+    let bot_types = vec![BotType::Random; settings.players_number];
+    let mut actions = 0;
+    for seed in seed.. {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game = Game::new(settings.clone(), &mut rng);
+        let mut bots = make_bots(seed, &bot_types, &game, None);
+        game.record_history();
+        run_game(
+            &mut bots,
+            &mut game,
+            &mut rng,
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        for decision in game.decisions() {
+            if let Decision::Play(action) = decision {
+                input += &serde_json::to_string(action).unwrap();
+                input += "\n";
+                actions += 1;
+                if actions == actions_number {
+                    break;
+                }
+            }
+        }
+        if actions == actions_number {
+            break;
+        }
+    }
+    let start_allocations = allocations();
+    let start = Instant::now();
+    let mut file = LineReader::new(input.as_bytes());
+    let result = file
+        .read_required::<GameParams>("game params")
+        .and_then(|_| file.read_lines());
+    unwrap_input_or_exit(result);
+    let records = unwrap_input_or_exit(file.records::<Action>());
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "actions={} bytes={} elapsed={:.3}s actions_per_second={:.1} mb_per_second={:.1}",
+        records.len(),
+        input.len(),
+        elapsed,
+        records.len() as f64 / elapsed,
+        input.len() as f64 / elapsed / 1e6
+    );
+    if let (Some(start_allocations), Some(end_allocations)) = (start_allocations, allocations()) {
+        println!("allocations={}", end_allocations - start_allocations);
+    }
 }
 
-#[derive(Parser)]
-struct ReplayParams {
-    #[arg(long)]
-    verbose: bool,
-    #[arg(long)]
-    write_player: Option<usize>,
-    file: Option<String>,
+fn regression(params: RegressionParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let stats = collect_duel_stats(
+        params.seed,
+        params.games,
+        [params.candidate, params.baseline],
+        settings.clone(),
+        TimeControl::default(),
+    );
+    let result = RegressionRecord::new(params.baseline, params.candidate, settings, &stats);
+    let reference = match params.record.as_ref() {
+        Some(path) if !params.update_record && Path::new(path).exists() => {
+            let reference = unwrap_or_exit(RegressionRecord::load(path));
+            unwrap_or_exit(result.check(&reference));
+            Some(reference)
+        }
+        _ => None,
+    };
+    let reference_win_rate = reference.as_ref().map_or(DEFAULT_WIN_RATE, |v| v.win_rate);
+    println!(
+        "games={} candidate={:?} baseline={:?} win_rate={:.3} reference={:.3}",
+        result.games, result.candidate, result.baseline, result.win_rate, reference_win_rate
+    );
+    if let (Some(path), None) = (params.record.as_ref(), reference.as_ref()) {
+        unwrap_or_exit(result.save(path));
+    }
+    unwrap_or_exit(check_regression(
+        result.win_rate,
+        reference_win_rate,
+        params.threshold,
+    ));
 }
 
-#[derive(Parser)]
-struct TrackerParams {
-    file: Option<String>,
+fn challenge_odds(params: ChallengeOddsParams) {
+    let state = match params.load_state.as_ref() {
+        Some(path) => unwrap_or_exit(TrackerState::load(path)),
+        None => match unwrap_input_or_exit(
+            open_input(params.file, false, 0).and_then(read_tracker_state),
+        ) {
+            Some(v) => v,
+            None => {
+                eprintln!("No player view to track");
+                std::process::exit(1);
+            }
+        },
+    };
+    match get_challenge_odds(&state.tracker, &state.view.player_view()) {
+        Some(odds) => odds.print(),
+        None => {
+            eprintln!("No pending claim to challenge");
+            std::process::exit(1);
+        }
+    }
 }
 
-#[derive(Parser)]
-struct SuggestParams {
-    #[arg(long)]
-    bot_type: BotType,
-    file: Option<String>,
+fn conformance(params: ConformanceParams) {
+    let cases = unwrap_or_exit(read_conformance_cases(&params.dir));
+    let mut failed = 0;
+    for (name, case) in cases.iter() {
+        match case.check() {
+            Ok(()) => println!("PASS {}", name),
+            Err(e) => {
+                println!("FAIL {}: {}", name, e);
+                failed += 1;
+            }
+        }
+        if params.show_notes {
+            println!("    rule: {}", case.rule);
+            if let Some(note) = case.note.as_ref() {
+                println!("    note: {}", note);
+            }
+        }
+    }
+    println!(
+        "passed={} failed={} documented={}",
+        cases.len() - failed,
+        failed,
+        cases.iter().filter(|(_, case)| case.note.is_some()).count()
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
 }
 
-#[derive(Parser, Debug)]
-struct FuzzyParams {
-    #[arg(long, default_value = "42")]
-    seed: u64,
-    #[arg(long, default_value = "10000")]
-    max_games: usize,
-    #[arg(long, default_value = "6")]
-    players_number: usize,
-    #[arg(long, default_value = "3")]
-    cards_per_type: usize,
+fn fsm_graph(params: FsmGraphParams) {
+    unwrap_or_exit(
+        write_fsm_graph(
+            &collect_fsm_graph(),
+            params.format,
+            std::io::stdout().lock(),
+        )
+        .map_err(|e| format!("failed to write graph: {}", e)),
+    );
 }
 
-fn main() {
-    let args: Args = Args::parse();
-    match args.command {
-        Command::Simulate(params) => simulate(params),
-        Command::Replay(params) => replay(params),
-        Command::Stats(params) => stats(params),
-        Command::Example => example(),
-        Command::Track(params) => track(params),
-        Command::Suggest(params) => suggest(params),
-        Command::Fuzzy(params) => fuzzy(params),
-        Command::Interactive => run_interactive_game(),
+fn validate(params: ValidateParams) {
+    let reveal = Reveal {
+        seed: params.seed,
+        salt: params.salt,
+    };
+    unwrap_or_exit(reveal.verify(&params.commitment));
+    if let Some(path) = params.file.as_ref() {
+        unwrap_input_or_exit(
+            open_file(path).and_then(|file| validate_replay(LineReader::new(file), params.seed)),
+        );
     }
+    println!("commitment is valid");
 }
 
-fn simulate(params: SimulateParams) {
-    let settings = Settings {
-        players_number: params.players_number,
-        cards_per_type: params.cards_per_type,
-    };
-    run_game_with_bots(
-        params.seed,
+fn validate_replay<F: BufRead>(mut file: LineReader<F>, seed: u64) -> Result<(), InputError> {
+    let game_params: GameParams = file.read_required("game params")?;
+    game_params.validate().map_err(|e| file.invalid_error(e))?;
+    if game_params.seed != seed {
+        return Err(file.invalid_error(format!(
+            "replay seed {} doesn't match revealed seed {}",
+            game_params.seed, seed
+        )));
+    }
+    file.read_lines()?;
+    let records: Vec<Record<Action>> = file.records()?;
+    let actions: Vec<Action> = records.iter().map(|v| v.value.clone()).collect();
+    Game::from_history(game_params.settings, seed, &actions).map_err(|e| match &e {
+        ReplayError::InvalidSettings(error) => file.invalid_error(error.clone()),
+        ReplayError::IllegalAction { index, .. } => {
+            file.play_error(&records[*index], e.to_string())
+        }
+    })?;
+    Ok(())
+}
+
+fn exhibit(params: ExhibitParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let bot_types = get_lineup(
+        params.players_number,
         &params.bot_types,
-        settings,
-        true,
-        params.write_player,
+        &params.seat,
+        params.default_bot_type,
+        params.cycle_bot_types,
+    );
+    let options = ExhibitOptions {
+        delay: Duration::from_millis(params.delay_ms),
+        wait_enter: params.wait_enter,
+        color: !params.no_color && std::io::stdout().is_terminal(),
+    };
+    unwrap_or_exit(
+        run_exhibit(
+            params.seed,
+            &bot_types,
+            settings,
+            &options,
+            &mut std::io::stdout().lock(),
+        )
+        .map_err(|e| format!("failed to run exhibit: {}", e)),
     );
 }
 
-fn replay(params: ReplayParams) {
-    if let Some(path) = params.file {
-        replay_from_file(
-            BufReader::new(File::open(path).unwrap()),
-            params.verbose,
-            params.write_player,
-        );
+fn audit(params: AuditParams) {
+    let result = if let Some(path) = params.file.as_ref() {
+        open_file(path).and_then(|file| audit_from_file(LineReader::new(file)))
     } else {
-        replay_from_file(std::io::stdin().lock(), params.verbose, params.write_player);
+        audit_from_file(LineReader::new(std::io::stdin().lock()))
+    };
+    let report = unwrap_input_or_exit(result);
+    print_audit_report(&report);
+    if !report.inconsistencies.is_empty() {
+        std::process::exit(1);
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct GameParams {
-    seed: u64,
-    settings: Settings,
+fn audit_from_file<F: BufRead>(mut file: LineReader<F>) -> Result<AuditReport, InputError> {
+    let game_params: GameParams = file.read_required("game params")?;
+    game_params.validate().map_err(|e| file.invalid_error(e))?;
+    file.read_lines()?;
+    let records: Vec<Record<Action>> = file.records()?;
+    let actions: Vec<Action> = records.iter().map(|v| v.value.clone()).collect();
+    audit_game(game_params.settings, game_params.seed, &actions).map_err(|e| match &e {
+        ReplayError::InvalidSettings(error) => file.invalid_error(error.clone()),
+        ReplayError::IllegalAction { index, .. } => {
+            file.play_error(&records[*index], e.to_string())
+        }
+    })
 }
 
-fn replay_from_file<F: BufRead>(mut file: F, verbose: bool, write_player: Option<usize>) {
-    let mut line = String::new();
-    file.read_line(&mut line).unwrap();
-    let params: GameParams = serde_json::from_str(&line).unwrap();
-    let mut rng = StdRng::seed_from_u64(params.seed);
-    let mut game = Game::new(params.settings.clone(), &mut rng);
-    if let Some(player) = write_player {
-        println!("{}", serde_json::to_string(&params.settings).unwrap());
-        println!(
-            "{}",
-            serde_json::to_string(&game.get_player_view(player)).unwrap()
-        );
+fn read_tracker_state<F: BufRead>(
+    mut file: LineReader<F>,
+) -> Result<Option<TrackerState>, InputError> {
+    let settings: Settings = file.read_required("settings")?;
+    settings.validate().map_err(|e| file.invalid_error(e))?;
+    let mut view = match file.read::<GameView>()? {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let mut tracker = CardsTracker::from_view(&view.player_view(), &settings);
+    while let Some(action) = file.read::<Action>()? {
+        view = file.read_required("player view")?;
+        notify_tracker(&mut tracker, &view.player_view(), &action);
     }
-    loop {
-        let mut line = String::new();
-        file.read_line(&mut line).unwrap();
-        if line.is_empty() {
-            break;
-        }
-        if verbose {
-            game.print();
-        }
-        let action: Action = serde_json::from_str(&line).unwrap();
-        if verbose {
-            println!("[{}] play {:?}", game.step(), action);
-        }
-        if write_player.is_some() {
-            println!("{}", serde_json::to_string(&action).unwrap());
+    Ok(Some(TrackerState {
+        settings,
+        view,
+        tracker,
+    }))
+}
+
+fn equity(params: EquityParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let stats = unwrap_or_exit(estimate_equity(
+        &params.cards,
+        params.bot_type,
+        params.field,
+        params.games,
+        params.seed,
+        &settings,
+    ));
+    print_equity(&stats, &params.cards, params.bot_type, params.field);
+}
+
+fn sweep(params: SweepParams) {
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let points = unwrap_or_exit(run_sweep(
+        &params.param,
+        params.opponent,
+        params.pairs,
+        params.seed,
+        &settings,
+    ));
+    unwrap_or_exit(
+        write_csv(&params.param.name, &points, std::io::stdout().lock())
+            .map_err(|e| format!("failed to write csv: {}", e)),
+    );
+}
+
+fn archive(params: ArchiveParams) {
+    match params.command {
+        ArchiveCommand::Get(params) => {
+            let entries = unwrap_or_exit(read_index(&params.dir));
+            let entry = match entries.iter().find(|v| v.id == params.id) {
+                Some(v) => v,
+                None => {
+                    eprintln!("Game {} is not found in archive {}", params.id, params.dir);
+                    std::process::exit(1);
+                }
+            };
+            let record = unwrap_or_exit(read_game(&params.dir, entry));
+            println!("{}", serde_json::to_string(&record).unwrap());
         }
-        assert_eq!(game.play(&action, &mut rng), Ok(()));
-        if let Some(player) = write_player {
-            println!(
-                "{}",
-                serde_json::to_string(&game.get_player_view(player)).unwrap()
-            );
+        ArchiveCommand::Query(params) => {
+            let entries = unwrap_or_exit(read_index(&params.dir));
+            for entry in entries.iter().filter(|v| params.query.matches(v)) {
+                if params.records {
+                    let record = unwrap_or_exit(read_game(&params.dir, entry));
+                    println!("{}", serde_json::to_string(&record).unwrap());
+                } else {
+                    println!("{}", entry.id);
+                }
+            }
         }
     }
-    if verbose {
-        game.print();
-    }
 }
 
-fn stats(params: StatsParams) {
-    let settings = Settings {
-        players_number: params.players_number,
-        cards_per_type: params.cards_per_type,
+#[cfg(feature = "discord")]
+fn discord(params: DiscordParams) {
+    let token = match params.token.or_else(|| std::env::var("DISCORD_TOKEN").ok()) {
+        Some(v) => v,
+        None => {
+            eprintln!("Discord token is not set, use --token or DISCORD_TOKEN");
+            std::process::exit(1);
+        }
     };
-    print_stats(&collect_random_games_stats(
+    unwrap_or_exit(discord::run_discord_bot(
+        &token,
+        params.cards_per_type,
+        params.cards_per_player,
+        params.bot_type,
         params.seed,
-        params.games,
-        params.workers,
-        params.bot_types,
-        settings,
+        params.spectator_delay,
+        params.replay_dir.map(std::path::PathBuf::from),
+        crate::table::AutoPass {
+            all_passed: params.auto_pass_all_passed,
+            timeout: params.auto_pass_timeout_ms.map(Duration::from_millis),
+        },
     ));
 }
 
+fn hot_seat(params: HotSeatParams) {
+    let humans = params.name.len();
+    let settings = params.settings.to_settings(humans + params.bot_types.len());
+    if humans == 0 {
+        eprintln!("At least one player name is required, use --name");
+        std::process::exit(1);
+    }
+    unwrap_or_exit(settings.validate());
+    let seed = params.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new(settings.clone(), &mut rng);
+    let player_names: Vec<String> = params
+        .name
+        .iter()
+        .cloned()
+        .chain((0..params.bot_types.len()).map(|v| format!("bot{}", humans + v)))
+        .collect();
+    let mut bots_rng = make_bots_rng(seed);
+    let mut bots: Vec<Box<dyn Bot>> = (0..settings.players_number)
+        .map(|index| -> Box<dyn Bot> {
+            if index < humans {
+                Box::new(HumanBot::hot_seat(player_names.clone()))
+            } else {
+                make_bot(
+                    params.bot_types[index - humans],
+                    &game.get_player_view(index),
+                    &settings,
+                    &mut bots_rng,
+                )
+            }
+        })
+        .collect();
+    run_game(
+        &mut bots,
+        &mut game,
+        &mut rng,
+        false,
+        None,
+        0,
+        TimeControl::default(),
+    );
+    if let Some(winner) = game.get_winner() {
+        println!("{} wins the game (seed={})", player_names[winner], seed);
+    }
+}
+
+fn referee(params: RefereeParams) {
+    let seed = params.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let game = if let Some(path) = params.scenario.as_ref() {
+        let scenario = unwrap_or_exit(Scenario::load(path));
+        unwrap_or_exit(Game::from_scenario(&scenario))
+    } else {
+        let settings = params.settings.to_settings(params.players_number);
+        unwrap_or_exit(settings.validate());
+        Game::new(settings, &mut rng)
+    };
+    let players_number = game.get_settings().players_number;
+    if params.name.len() > players_number {
+        eprintln!(
+            "Too many player names for {} players: {}",
+            players_number,
+            params.name.len()
+        );
+        std::process::exit(1);
+    }
+    let player_names: Vec<String> = (0..players_number)
+        .map(|index| {
+            params
+                .name
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| format!("{}", index))
+        })
+        .collect();
+    println!("seed: {}", seed);
+    run_referee_game(game, &player_names, params.auto_pass, &mut rng);
+}
+
 fn example() {
     let settings = get_example_settings();
     println!(
         "{}",
-        serde_json::to_string(&GameParams { seed: 42, settings }).unwrap()
+        serde_json::to_string(&GameParams {
+            seed: 42,
+            seed_phrase: None,
+            settings,
+        })
+        .unwrap()
     );
     for action in get_example_actions() {
         println!("{}", serde_json::to_string(&action).unwrap());
     }
 }
 
-fn track(params: TrackerParams) {
-    if let Some(path) = params.file {
-        track_from_file(BufReader::new(File::open(path).unwrap()));
+fn open_input(
+    file: Option<String>,
+    follow: bool,
+    poll_interval_ms: u64,
+) -> Result<LineReader<Box<dyn BufRead>>, InputError> {
+    let input: Box<dyn BufRead> = if let Some(path) = file {
+        Box::new(open_file(&path)?)
     } else {
-        track_from_file(std::io::stdin().lock());
+        Box::new(BufReader::new(std::io::stdin()))
+    };
+    if follow {
+        Ok(LineReader::new(Box::new(Follow::new(
+            input,
+            Duration::from_millis(poll_interval_ms),
+        ))))
+    } else {
+        Ok(LineReader::new(input))
+    }
+}
+
+fn unwrap_input_or_exit<T>(result: Result<T, InputError>) -> T {
+    unwrap_or_exit(result.map_err(|e| e.to_string()))
+}
+
+fn track(params: TrackerParams) {
+    let options = TrackOptions {
+        follow: params.follow,
+        json: params.json,
+        sample_worlds: params.sample_worlds,
+    };
+    let state = params
+        .load_state
+        .as_ref()
+        .map(|path| unwrap_or_exit(TrackerState::load(path)));
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut timeline = TrackerTimeline::default();
+    let state = unwrap_input_or_exit(
+        open_input(params.file, options.follow, params.poll_interval_ms)
+            .and_then(|file| track_from_file(file, &options, &mut rng, &mut timeline, state)),
+    );
+    if let Some(path) = params.dot.as_ref() {
+        unwrap_or_exit(timeline.save_dot(path));
+    }
+    if let (Some(path), Some(state)) = (params.save_state.as_ref(), state) {
+        unwrap_or_exit(state.save(path));
     }
 }
 
-fn track_from_file<F: BufRead>(mut file: F) {
-    let mut line = String::new();
-    file.read_line(&mut line).unwrap();
-    let settings: Settings = serde_json::from_str(&line).unwrap();
-    if let Some(view) = read_game_view(&mut file) {
+fn track_from_file<F: BufRead, R: Rng>(
+    mut file: LineReader<F>,
+    options: &TrackOptions,
+    rng: &mut R,
+    timeline: &mut TrackerTimeline,
+    state: Option<TrackerState>,
+) -> Result<Option<TrackerState>, InputError> {
+    let (settings, view, mut tracker) = match state {
+        Some(state) => (state.settings, state.view, state.tracker),
+        None => {
+            let settings: Settings = file.read_required("settings")?;
+            settings.validate().map_err(|e| file.invalid_error(e))?;
+            let view = match file.read::<GameView>()? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            let tracker = CardsTracker::from_view(&view.player_view(), &settings);
+            (settings, view, tracker)
+        }
+    };
+    if !options.json {
         println!("[{}] View {:?}", view.step, view);
-        let mut tracker = CardsTracker::new(view.player, &view.cards, &settings);
-        while let Some(action) = read_action(&mut file) {
-            println!("[{}] Play {:?}", view.step, action);
-            if let Some(view) = read_game_view(&mut file) {
+    }
+    let entry = timeline.add(view.step, None, &tracker);
+    if options.json {
+        println!("{}", serde_json::to_string(entry).unwrap());
+    }
+    let mut last_view = view;
+    while let Some(action) = file.read::<Action>()? {
+        if !options.json {
+            println!("[{}] Play {:?}", last_view.step, action);
+        }
+        if let Some(view) = file.read::<GameView>()? {
+            if !options.json {
                 println!("[{}] View {:?}", view.step, view);
-                if view.player == action.player {
-                    tracker.after_player_action(&view.player_view(), &action);
-                } else {
-                    tracker.after_opponent_action(
-                        &view.player_view(),
-                        &ActionView::from_action(&action),
-                    );
-                }
-            } else {
-                break;
             }
+            notify_tracker(&mut tracker, &view.player_view(), &action);
+            let entry = timeline.add(view.step, Some(&action), &tracker);
+            if options.json {
+                println!("{}", serde_json::to_string(entry).unwrap());
+            } else if options.follow {
+                print!("[{}] Track ", view.step);
+                tracker.print();
+            }
+            last_view = view;
+        } else {
+            break;
         }
-        print!("[{}] Track ", view.step);
+    }
+    if !options.json {
+        print!("[{}] Track ", last_view.step);
         tracker.print();
     }
+    for _ in 0..options.sample_worlds {
+        let world =
+            serde_json::to_string(&Game::from_full_state(tracker.sample_world(rng))).unwrap();
+        if options.json {
+            println!("{}", world);
+        } else {
+            println!("[{}] World {}", last_view.step, world);
+        }
+    }
+    Ok(Some(TrackerState {
+        settings,
+        view: last_view,
+        tracker,
+    }))
 }
 
 fn suggest(params: SuggestParams) {
-    if let Some(path) = params.file {
-        suggest_from_file(params.bot_type, BufReader::new(File::open(path).unwrap()));
-    } else {
-        suggest_from_file(params.bot_type, std::io::stdin().lock());
+    let follow = params.follow;
+    let bot_type = params.bot_type;
+    let seed = params.seed;
+    if let Some(dir) = params.batch.as_ref() {
+        unwrap_or_exit(suggest_batch(dir, bot_type, seed, params.workers));
+        return;
+    }
+    if params.multi {
+        unwrap_input_or_exit(
+            open_input(params.file, follow, params.poll_interval_ms)
+                .and_then(|file| suggest_multi_from_file(bot_type, seed, file)),
+        );
+        return;
+    }
+    let state = params
+        .load_state
+        .as_ref()
+        .map(|path| unwrap_or_exit(TrackerState::load(path)));
+    let state = unwrap_input_or_exit(
+        open_input(params.file, follow, params.poll_interval_ms)
+            .and_then(|file| suggest_from_file(bot_type, seed, file, follow, state)),
+    );
+    if let Some(path) = params.save_state.as_ref() {
+        match state {
+            Some(state) => unwrap_or_exit(state.save(path)),
+            None => {
+                eprintln!("Bot {:?} has no cards tracker state to save", bot_type);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn suggest_multi_from_file<F: BufRead>(
+    bot_type: BotType,
+    seed: u64,
+    mut file: LineReader<F>,
+) -> Result<(), InputError> {
+    let mut sessions = SessionManager::new(bot_type, seed);
+    loop {
+        let response = match file.read::<SessionRequest>() {
+            Ok(Some(request)) => sessions.handle(request),
+            Ok(None) => break,
+            Err(e @ InputError::Parse { .. }) => SessionResponse::error(None, e.to_string()),
+            Err(e) => return Err(e),
+        };
+        println!("{}", serde_json::to_string(&response).unwrap());
     }
+    Ok(())
 }
 
-fn suggest_from_file<F: BufRead>(bot_type: BotType, mut file: F) {
-    let mut line = String::new();
-    file.read_line(&mut line).unwrap();
-    let settings: Settings = serde_json::from_str(&line).unwrap();
-    if let Some(view) = read_game_view(&mut file) {
-        match bot_type {
-            BotType::Random => {
-                let bot = RandomBot::new(&view.player_view());
-                suggest_from_file_with_bot(view, file, bot);
+fn suggest_from_file<F: BufRead>(
+    bot_type: BotType,
+    seed: u64,
+    mut file: LineReader<F>,
+    follow: bool,
+    state: Option<TrackerState>,
+) -> Result<Option<TrackerState>, InputError> {
+    let (settings, view, tracker) = match state {
+        Some(state) => (state.settings, state.view, Some(state.tracker)),
+        None => {
+            let settings: Settings = file.read_required("settings")?;
+            settings.validate().map_err(|e| file.invalid_error(e))?;
+            match file.read::<GameView>()? {
+                Some(view) => (settings, view, None),
+                None => return Ok(None),
+            }
+        }
+    };
+    let rng = StdRng::seed_from_u64(seed);
+    match bot_type {
+        BotType::Random => {
+            let mut bot = RandomBot::new(rng);
+            bot.on_game_start(&settings, view.player);
+            suggest_from_file_with_bot(settings, view, file, bot, follow)
+        }
+        BotType::HonestCarefulRandom => {
+            let mut bot = HonestCarefulRandomBot::new(&view.player_view(), &settings, rng);
+            bot.on_game_start(&settings, view.player);
+            if let Some(tracker) = tracker {
+                bot = bot.with_cards_tracker(tracker);
             }
-            BotType::HonestCarefulRandom => {
-                let bot = HonestCarefulRandomBot::new(&view.player_view(), &settings);
-                suggest_from_file_with_bot(view, file, bot)
+            suggest_from_file_with_bot(settings, view, file, bot, follow)
+        }
+        BotType::InfoSeeking => {
+            let mut bot = InfoSeekingBot::new(&view.player_view(), &settings, rng);
+            bot.on_game_start(&settings, view.player);
+            if let Some(tracker) = tracker {
+                bot = bot.with_cards_tracker(tracker);
             }
+            suggest_from_file_with_bot(settings, view, file, bot, follow)
         }
     }
 }
 
-fn suggest_from_file_with_bot<F: BufRead, B: Bot>(initial_view: GameView, mut file: F, mut bot: B) {
-    let initial_player_view = initial_view.player_view();
-    let available_actions: Vec<Action> = get_available_actions(
-        initial_player_view.state_type,
-        initial_player_view.player_coins,
-        initial_player_view.player_hands,
-    )
-    .into_iter()
-    .filter(|action| action.player == initial_view.player)
-    .collect();
-    let mut suggested_actions: Vec<Action> = bot
-        .suggest_actions(&initial_player_view, &available_actions)
-        .iter()
-        .map(|v| (*v).clone())
-        .collect();
+fn suggest_from_file_with_bot<F: BufRead, B: Bot>(
+    settings: Settings,
+    initial_view: GameView,
+    mut file: LineReader<F>,
+    mut bot: B,
+    follow: bool,
+) -> Result<Option<TrackerState>, InputError> {
+    let mut suggestion = make_suggestion(&mut bot, &initial_view);
+    if follow {
+        print_suggestion(&initial_view, &suggestion);
+    }
     let mut last_view = initial_view;
-    while let Some(action) = read_action(&mut file) {
-        if let Some(view) = read_game_view(&mut file) {
-            if view.player == action.player {
-                bot.after_player_action(&view.player_view(), &action);
-            } else {
-                bot.after_opponent_action(&view.player_view(), &ActionView::from_action(&action));
+    while let Some(action) = file.read::<Action>()? {
+        if let Some(view) = file.read::<GameView>()? {
+            notify_bot(&mut bot, &view.player_view(), &action);
+            suggestion = make_suggestion(&mut bot, &view);
+            if follow {
+                print_suggestion(&view, &suggestion);
             }
-            let available_actions: Vec<Action> =
-                get_available_actions(&view.state_type, &view.player_coins, &view.player_hands)
-                    .into_iter()
-                    .filter(|action| action.player == view.player)
-                    .collect();
-            suggested_actions = bot
-                .suggest_actions(&view.player_view(), &available_actions)
-                .iter()
-                .map(|v| (*v).clone())
-                .collect();
             last_view = view;
         } else {
             break;
         }
     }
-    println!("[{}] {:?}", last_view.step, last_view);
-    for action in suggested_actions {
-        println!("{}", serde_json::to_string(&action).unwrap());
-    }
-}
-
-fn read_action<F: BufRead>(file: &mut F) -> Option<Action> {
-    let mut line = String::new();
-    file.read_line(&mut line).unwrap();
-    if line.is_empty() {
-        return None;
-    }
-    Some(serde_json::from_str(&line).unwrap())
-}
-
-#[derive(Debug, Deserialize)]
-struct GameView {
-    step: usize,
-    turn: usize,
-    round: usize,
-    player: usize,
-    coins: usize,
-    cards: Vec<Card>,
-    state_type: StateType,
-    player_coins: Vec<usize>,
-    player_hands: Vec<usize>,
-    player_cards: Vec<usize>,
-    revealed_cards: Vec<Card>,
-    deck: usize,
+    print_suggestion(&last_view, &suggestion);
+    Ok(bot.cards_tracker().cloned().map(|tracker| TrackerState {
+        settings,
+        view: last_view,
+        tracker,
+    }))
 }
 
-impl GameView {
-    fn player_view(&self) -> PlayerView {
-        PlayerView {
-            step: self.step,
-            turn: self.turn,
-            round: self.round,
-            player: self.player,
-            coins: self.coins,
-            cards: &self.cards,
-            state_type: &self.state_type,
-            player_coins: &self.player_coins,
-            player_hands: &self.player_hands,
-            player_cards: &self.player_cards,
-            revealed_cards: &self.revealed_cards,
-            deck: self.deck,
+fn print_suggestion(view: &GameView, suggestion: &Suggestion) {
+    println!("[{}] {:?}", view.step, view);
+    if suggestion.scores.is_empty() {
+        for action in suggestion.actions.iter() {
+            println!("{}", serde_json::to_string(action).unwrap());
+        }
+    } else {
+        for score in suggestion.scores.iter() {
+            println!("{}", serde_json::to_string(score).unwrap());
         }
     }
-}
-
-fn read_game_view<F: BufRead>(file: &mut F) -> Option<GameView> {
-    let mut line = String::new();
-    file.read_line(&mut line).unwrap();
-    if line.is_empty() {
-        return None;
+    for target in suggestion.targets.iter() {
+        println!("{}", serde_json::to_string(target).unwrap());
     }
-    Some(serde_json::from_str(&line).unwrap())
 }
 
 fn fuzzy(params: FuzzyParams) {
     let mut rng = StdRng::seed_from_u64(params.seed);
-    let settings = Settings {
-        players_number: params.players_number,
-        cards_per_type: params.cards_per_type,
-    };
+    let settings = params.settings.to_settings(params.players_number);
+    unwrap_or_exit(settings.validate());
+    let mut report = CompletenessReport::default();
     for _ in 0..params.max_games {
         let mut record: Vec<(Game, Action)> = Vec::new();
-        let mut game = Game::new(settings.clone(), &mut rng);
+        let game_settings = if params.vary_settings {
+            get_random_settings(&settings, &mut rng)
+        } else {
+            settings.clone()
+        };
+        let mut game = Game::new(game_settings, &mut rng);
         while !game.is_done() {
+            if params.check_actions {
+                report.add(&game);
+            }
             let view = game.get_anonymous_view();
-            let available_actions =
-                get_available_actions(view.state_type, view.player_coins, view.player_hands);
+            let available_actions = get_available_actions(
+                view.state_type,
+                view.player_coins,
+                view.player_hands,
+                view.rules,
+            );
             let mut allowed_actions: Vec<Action> = available_actions
                 .iter()
                 .filter(|action| {
@@ -444,9 +2398,207 @@ fn fuzzy(params: FuzzyParams) {
                     }
                 } else {
                     record.push((game_copy, action));
+                    if let Err(e) = game.check_influence() {
+                        for (game, action) in record {
+                            game.print();
+                            println!("Play {:?}", action);
+                        }
+                        game.print();
+                        panic!("Influence bookkeeping is broken: {}", e);
+                    }
                     break;
                 }
             }
         }
     }
+    if params.check_actions {
+        report.print();
+        if !report.is_empty() {
+            std::process::exit(1);
+        }
+    }
+}
+
+fn get_random_settings<R: Rng>(max_settings: &Settings, rng: &mut R) -> Settings {
+    let players_number = rng.gen_range(2..=max_settings.players_number);
+    let min_cards_per_type =
+        (players_number * max_settings.cards_per_player).div_ceil(ALL_CARDS.len());
+    Settings {
+        players_number,
+        card_counts: CardCounts::uniform(rng.gen_range(
+            min_cards_per_type..=max_settings.card_counts.max().max(min_cards_per_type),
+        )),
+        cards_per_player: max_settings.cards_per_player,
+        rules: Rules {
+            bank_coins: rng.gen_bool(0.5).then(|| {
+                rng.gen_range(players_number * INITIAL_COINS..=players_number * MAX_COINS)
+            }),
+            cards_to_exchange: rng.gen_range(1..=max_settings.rules.cards_to_exchange),
+            response_priority: *ALL_RESPONSE_PRIORITIES.choose(rng).unwrap(),
+            ..max_settings.rules
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::run::for_each_played_action;
+
+    use super::*;
+
+    fn parse_simulate(args: &[&str]) -> Result<SimulateParams, clap::Error> {
+        let args = Args::try_parse_from(["coup", "simulate"].iter().chain(args.iter()))?;
+        match args.command {
+            Command::Simulate(params) => Ok(params),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn settings_args_should_build_settings() {
+        let params = parse_simulate(&[]).unwrap();
+        assert_eq!(
+            params.settings.to_settings(params.players_number),
+            Settings {
+                players_number: 6,
+                card_counts: CardCounts::uniform(3),
+                cards_per_player: CARDS_PER_PLAYER,
+                rules: Rules::default(),
+            }
+        );
+        let params = parse_simulate(&[
+            "--players-number",
+            "3",
+            "--cards-per-type",
+            "4",
+            "--cards-per-player",
+            "3",
+            "--forced-coup-coins",
+            "8",
+            "--response-priority",
+            "random",
+            "--bank-coins",
+            "20",
+            "--cards-to-exchange",
+            "1",
+        ])
+        .unwrap();
+        assert_eq!(
+            params.settings.to_settings(params.players_number),
+            Settings {
+                players_number: 3,
+                card_counts: CardCounts::uniform(4),
+                cards_per_player: 3,
+                rules: Rules {
+                    forced_coup_coins: 8,
+                    response_priority: ResponsePriority::Random,
+                    bank_coins: Some(20),
+                    cards_to_exchange: 1,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn simulate_seed_range_should_reject_single_game_options() {
+        assert!(parse_simulate(&["--seed-range", "0..2"]).is_ok());
+        for args in [
+            ["--human", "0"],
+            ["--resume", "snapshot.json"],
+            ["--save-snapshot", "snapshot.json"],
+            ["--write-player", "0"],
+        ] {
+            assert!(
+                parse_simulate(&["--seed-range", "0..2", args[0], args[1]]).is_err(),
+                "{:?}",
+                args
+            );
+        }
+        assert!(
+            parse_simulate(&["--seed-range", "0..2", "--async-bots", "--max-steps", "3"]).is_err()
+        );
+        assert!(parse_simulate(&["--async-bots"]).is_err());
+    }
+
+    #[test]
+    fn format_seed_result_should_handle_game_without_winner() {
+        let bot_types = [BotType::Random; 3];
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let result = run_game_with_bots(
+            42,
+            &bot_types,
+            settings.clone(),
+            false,
+            None,
+            3,
+            TimeControl::default(),
+        );
+        assert_eq!(
+            format_seed_result(42, &bot_types, &result.end),
+            "seed=42 winner=none rounds=0 turns=1 steps=3"
+        );
+        let result = run_game_with_bots(
+            42,
+            &bot_types,
+            settings,
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        assert!(
+            format_seed_result(42, &bot_types, &result.end).contains(" winner_bot_type=Random ")
+        );
+    }
+
+    #[test]
+    fn export_public_replay_should_not_include_hidden_information() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut dropped_cards = 0;
+        for seed in 0..10 {
+            let result = run_game_with_bots(
+                seed,
+                &[BotType::Random; 3],
+                settings.clone(),
+                false,
+                None,
+                0,
+                TimeControl::default(),
+            );
+            let mut actions = Vec::new();
+            for_each_played_action(seed, &result, |_, action| actions.push(action.clone()))
+                .unwrap();
+            let lines = export_public_replay(settings.clone(), seed, &actions).unwrap();
+            assert_eq!(lines.len(), 2 + 2 * actions.len());
+            let value: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+            assert!(value.get("seed").is_none());
+            for (index, line) in lines[1..].iter().enumerate() {
+                assert!(!line.contains("DropCard\":"), "{}", line);
+                let value: serde_json::Value = serde_json::from_str(line).unwrap();
+                if index % 2 == 0 {
+                    assert!(value.get("cards").is_none(), "{}", line);
+                    assert!(value.get("history").is_none(), "{}", line);
+                    assert!(value["deck"].is_u64(), "{}", line);
+                    assert!(value["player_cards"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .all(|v| v.is_u64()));
+                } else if value["action_type"] == "DropCard" {
+                    dropped_cards += 1;
+                }
+            }
+        }
+        assert!(dropped_cards > 0);
+    }
 }
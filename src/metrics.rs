@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::bots::{ActionHistory, ActionScore, ActionView, Bot, CardsTracker};
+use crate::fsm::Action;
+use crate::game::{PlayerView, Settings};
+use crate::run::BotType;
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+struct Metrics {
+    start: Instant,
+    games: AtomicU64,
+    moves: AtomicU64,
+    hypotheses: AtomicU64,
+    hypotheses_sum: AtomicU64,
+    hypotheses_count: AtomicU64,
+    hypotheses_max: AtomicU64,
+    move_latency: Mutex<BTreeMap<BotType, Latency>>,
+}
+
+#[derive(Default)]
+struct Latency {
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+pub fn serve(address: &str) -> Result<(), String> {
+    let listener = TcpListener::bind(address)
+        .map_err(|e| format!("Failed to bind metrics address {}: {}", address, e))?;
+    METRICS.get_or_init(|| Metrics {
+        start: Instant::now(),
+        games: AtomicU64::new(0),
+        moves: AtomicU64::new(0),
+        hypotheses: AtomicU64::new(0),
+        hypotheses_sum: AtomicU64::new(0),
+        hypotheses_count: AtomicU64::new(0),
+        hypotheses_max: AtomicU64::new(0),
+        move_latency: Mutex::new(BTreeMap::new()),
+    });
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = respond(stream) {
+                tracing::warn!(error = %e, "failed to serve metrics");
+            }
+        }
+    });
+    Ok(())
+}
+
+pub fn is_enabled() -> bool {
+    METRICS.get().is_some()
+}
+
+pub fn record_game() {
+    if let Some(metrics) = METRICS.get() {
+        metrics.games.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn record_move() {
+    if let Some(metrics) = METRICS.get() {
+        metrics.moves.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn record_hypotheses(number: usize) {
+    if let Some(metrics) = METRICS.get() {
+        let number = number as u64;
+        metrics.hypotheses.store(number, Ordering::Relaxed);
+        metrics.hypotheses_sum.fetch_add(number, Ordering::Relaxed);
+        metrics.hypotheses_count.fetch_add(1, Ordering::Relaxed);
+        metrics.hypotheses_max.fetch_max(number, Ordering::Relaxed);
+    }
+}
+
+fn record_move_latency(bot_type: BotType, duration: Duration) {
+    if let Some(metrics) = METRICS.get() {
+        let mut move_latency = metrics.move_latency.lock().unwrap();
+        let latency = move_latency.entry(bot_type).or_default();
+        latency.count += 1;
+        latency.total += duration;
+        latency.max = latency.max.max(duration);
+    }
+}
+
+fn respond(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && line != "\r\n" && line != "\n" {
+        line.clear();
+    }
+    let body = format_metrics();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+fn format_metrics() -> String {
+    let mut result = String::new();
+    let metrics = match METRICS.get() {
+        Some(v) => v,
+        None => return result,
+    };
+    let uptime = metrics.start.elapsed().as_secs_f64();
+    let games = metrics.games.load(Ordering::Relaxed);
+    let moves = metrics.moves.load(Ordering::Relaxed);
+    let hypotheses_count = metrics.hypotheses_count.load(Ordering::Relaxed);
+    writeln!(result, "# TYPE coup_uptime_seconds gauge").unwrap();
+    writeln!(result, "coup_uptime_seconds {}", uptime).unwrap();
+    writeln!(result, "# TYPE coup_games_total counter").unwrap();
+    writeln!(result, "coup_games_total {}", games).unwrap();
+    writeln!(result, "# TYPE coup_moves_total counter").unwrap();
+    writeln!(result, "coup_moves_total {}", moves).unwrap();
+    writeln!(result, "# TYPE coup_moves_per_second gauge").unwrap();
+    writeln!(
+        result,
+        "coup_moves_per_second {}",
+        if uptime > 0.0 {
+            moves as f64 / uptime
+        } else {
+            0.0
+        }
+    )
+    .unwrap();
+    writeln!(result, "# TYPE coup_tracker_hypotheses gauge").unwrap();
+    writeln!(
+        result,
+        "coup_tracker_hypotheses {}",
+        metrics.hypotheses.load(Ordering::Relaxed)
+    )
+    .unwrap();
+    writeln!(result, "# TYPE coup_tracker_hypotheses_max gauge").unwrap();
+    writeln!(
+        result,
+        "coup_tracker_hypotheses_max {}",
+        metrics.hypotheses_max.load(Ordering::Relaxed)
+    )
+    .unwrap();
+    writeln!(result, "# TYPE coup_tracker_hypotheses_observed summary").unwrap();
+    writeln!(
+        result,
+        "coup_tracker_hypotheses_observed_sum {}",
+        metrics.hypotheses_sum.load(Ordering::Relaxed)
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "coup_tracker_hypotheses_observed_count {}",
+        hypotheses_count
+    )
+    .unwrap();
+    writeln!(result, "# TYPE coup_move_latency_seconds summary").unwrap();
+    writeln!(result, "# TYPE coup_move_latency_seconds_max gauge").unwrap();
+    for (bot_type, latency) in metrics.move_latency.lock().unwrap().iter() {
+        writeln!(
+            result,
+            "coup_move_latency_seconds_sum{{bot_type=\"{:?}\"}} {}",
+            bot_type,
+            latency.total.as_secs_f64()
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "coup_move_latency_seconds_count{{bot_type=\"{:?}\"}} {}",
+            bot_type, latency.count
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "coup_move_latency_seconds_max{{bot_type=\"{:?}\"}} {}",
+            bot_type,
+            latency.max.as_secs_f64()
+        )
+        .unwrap();
+    }
+    result
+}
+
+pub struct TimedBot {
+    bot_type: BotType,
+    inner: Box<dyn Bot>,
+}
+
+impl TimedBot {
+    pub fn new(bot_type: BotType, inner: Box<dyn Bot>) -> Self {
+        Self { bot_type, inner }
+    }
+}
+
+impl Bot for TimedBot {
+    fn suggest_actions<'a>(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        self.inner.suggest_actions(view, available_actions)
+    }
+
+    fn suggest_optional_actions<'a>(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &'a [Action],
+    ) -> Vec<&'a Action> {
+        self.inner.suggest_optional_actions(view, available_actions)
+    }
+
+    fn get_action(&mut self, view: &PlayerView, available_actions: &[Action]) -> Action {
+        let start = Instant::now();
+        let result = self.inner.get_action(view, available_actions);
+        record_move_latency(self.bot_type, start.elapsed());
+        result
+    }
+
+    fn get_optional_action(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Option<Action> {
+        let start = Instant::now();
+        let result = self.inner.get_optional_action(view, available_actions);
+        record_move_latency(self.bot_type, start.elapsed());
+        result
+    }
+
+    fn after_player_action(&mut self, view: &PlayerView, action: &Action) {
+        self.inner.after_player_action(view, action);
+    }
+
+    fn after_opponent_action(&mut self, view: &PlayerView, action: &ActionView) {
+        self.inner.after_opponent_action(view, action);
+    }
+
+    fn score_actions(
+        &mut self,
+        view: &PlayerView,
+        available_actions: &[Action],
+    ) -> Vec<ActionScore> {
+        self.inner.score_actions(view, available_actions)
+    }
+
+    fn after_forfeit(&mut self, view: &PlayerView, player: usize) {
+        self.inner.after_forfeit(view, player);
+    }
+
+    fn on_game_start(&mut self, settings: &Settings, player: usize) {
+        self.inner.on_game_start(settings, player);
+    }
+
+    fn history_mut(&mut self) -> Option<&mut ActionHistory> {
+        self.inner.history_mut()
+    }
+
+    fn cards_tracker(&self) -> Option<&CardsTracker> {
+        self.inner.cards_tracker()
+    }
+
+    fn query(&self, query: &str) {
+        self.inner.query(query);
+    }
+}
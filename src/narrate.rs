@@ -0,0 +1,243 @@
+use std::str::FromStr;
+
+use crate::fsm::{
+    Action, ActionType, Card, ChallengeState, StateType, ASSASSINATION_COST, COUP_COST,
+};
+use crate::game::{Game, ReplayError, Settings};
+use crate::locale::{Lang, Message};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerName {
+    pub index: usize,
+    pub name: String,
+}
+
+impl FromStr for PlayerName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, name) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid player name, expected <index>=<name>: {}", s))?;
+        let index = index
+            .parse::<usize>()
+            .map_err(|e| format!("invalid player index {}: {}", index, e))?;
+        if name.is_empty() {
+            return Err(format!("empty name for player {}", index));
+        }
+        Ok(Self {
+            index,
+            name: String::from(name),
+        })
+    }
+}
+
+struct Narrator<'a> {
+    player_names: &'a [String],
+    lang: Lang,
+    state_type: Option<StateType>,
+    player_hands: Vec<usize>,
+    round: usize,
+    clauses: Vec<String>,
+    paragraphs: Vec<String>,
+}
+
+pub fn narrate_game(
+    settings: Settings,
+    seed: u64,
+    actions: &[Action],
+    player_names: &[String],
+    lang: Lang,
+) -> Result<Vec<String>, ReplayError> {
+    let mut narrator = Narrator {
+        player_names,
+        lang,
+        state_type: None,
+        player_hands: Vec::new(),
+        round: 0,
+        clauses: Vec::new(),
+        paragraphs: Vec::new(),
+    };
+    let game = Game::from_history_with(settings, seed, actions, |game, action| {
+        if let Some(action) = action {
+            narrator.narrate(game, action);
+        }
+        narrator.update(game);
+    })?;
+    narrator.flush();
+    if let Some(winner) = game.get_winner() {
+        let message = lang.format(&Message::Wins(&player_names[winner]));
+        narrator.paragraphs.push(format!("{}.", message));
+    }
+    Ok(narrator.paragraphs)
+}
+
+impl Narrator<'_> {
+    fn narrate(&mut self, game: &Game, action: &Action) {
+        let view = game.get_anonymous_view();
+        if matches!(self.state_type, Some(StateType::Turn { .. })) {
+            self.flush();
+            self.round = view.round;
+        }
+        let player = self.player_names[action.player].as_str();
+        let message = match &action.action_type {
+            ActionType::Income => Message::TakesIncome(player),
+            ActionType::ForeignAid => Message::TakesForeignAid(player),
+            ActionType::Coup(target) => Message::Coup {
+                player,
+                target: &self.player_names[*target],
+                coins: COUP_COST,
+            },
+            ActionType::Tax => Message::Tax(player),
+            ActionType::Assassinate(target) => Message::Assassinate {
+                player,
+                target: &self.player_names[*target],
+                coins: ASSASSINATION_COST,
+            },
+            ActionType::Exchange => Message::Exchange(player),
+            ActionType::Steal(target) => Message::Steal {
+                player,
+                target: &self.player_names[*target],
+            },
+            ActionType::BlockForeignAid => Message::Block {
+                player,
+                card: Card::Duke,
+            },
+            ActionType::BlockAssassination => Message::Block {
+                player,
+                card: Card::Contessa,
+            },
+            ActionType::BlockSteal(card) => Message::Block {
+                player,
+                card: *card,
+            },
+            ActionType::Challenge => match view.state_type {
+                StateType::Challenge {
+                    state: ChallengeState::Initial { target, card, .. },
+                    ..
+                } => Message::ChallengeClaim {
+                    player,
+                    target: &self.player_names[*target],
+                    card: *card,
+                },
+                _ => Message::Challenge(player),
+            },
+            ActionType::ShowCard(card) => Message::ShowCard {
+                player,
+                card: *card,
+            },
+            ActionType::RevealCard(card) => match self.state_type.as_ref() {
+                Some(StateType::Challenge {
+                    state: ChallengeState::Initial { card: claimed, .. },
+                    ..
+                }) => Message::RevealBluff {
+                    player,
+                    claimed: *claimed,
+                    card: *card,
+                },
+                Some(StateType::Challenge {
+                    state: ChallengeState::ShownCard { .. },
+                    ..
+                }) => Message::RevealLostChallenge {
+                    player,
+                    card: *card,
+                },
+                _ => Message::RevealCard {
+                    player,
+                    card: *card,
+                },
+            },
+            ActionType::TakeCard => Message::TakeCard(player),
+            ActionType::DropCard(..) => Message::DropCard(player),
+            ActionType::PassChallenge | ActionType::PassBlock | ActionType::ShuffleDeck => {
+                return;
+            }
+        };
+        let mut clauses = vec![self.lang.format(&message)];
+        if self.player_hands[action.player] > 0 && view.player_hands[action.player] == 0 {
+            clauses.push(self.lang.format(&Message::OutOfGame(player)));
+        }
+        self.clauses.extend(clauses);
+    }
+
+    fn update(&mut self, game: &Game) {
+        let view = game.get_anonymous_view();
+        self.state_type = Some(view.state_type.clone());
+        self.player_hands = view.player_hands.to_vec();
+    }
+
+    fn flush(&mut self) {
+        if self.clauses.is_empty() {
+            return;
+        }
+        self.paragraphs.push(self.lang.format(&Message::Round {
+            round: self.round + 1,
+            clauses: &self.clauses.join("; "),
+        }));
+        self.clauses.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::game::{get_example_actions, get_example_settings};
+    use crate::interactive::get_player_names;
+
+    use super::*;
+
+    #[test]
+    fn narrate_example_game_should_use_player_names() {
+        let custom_player_names =
+            HashMap::from([(0, String::from("Alice")), (1, String::from("Bob"))]);
+        let player_names = get_player_names(6, &custom_player_names, None);
+        let paragraphs = narrate_game(
+            get_example_settings(),
+            42,
+            &get_example_actions(),
+            &player_names,
+            Lang::En,
+        )
+        .unwrap();
+        assert_eq!(paragraphs[0], "Round 1: Alice takes income.");
+        assert!(paragraphs[1]
+            .starts_with("Round 1: Bob reaches for foreign aid; Alice blocks it claiming Duke"));
+        assert!(paragraphs
+            .iter()
+            .any(|v| v.contains("doesn't buy it and challenges")));
+        assert!(paragraphs.iter().any(|v| v.contains("is out of the game")));
+        assert!(paragraphs[paragraphs.len() - 2].starts_with("Round 9: "));
+        assert!(paragraphs.last().unwrap().ends_with("wins the game."));
+    }
+
+    #[test]
+    fn narrate_example_game_should_use_given_language() {
+        let player_names = get_player_names(6, &HashMap::new(), None);
+        let paragraphs = narrate_game(
+            get_example_settings(),
+            42,
+            &get_example_actions(),
+            &player_names,
+            Lang::Ru,
+        )
+        .unwrap();
+        assert_eq!(paragraphs[0], "Раунд 1: 0 берёт доход.");
+        assert!(paragraphs[1].contains("блокирует, заявляя карту Герцог"));
+        assert!(paragraphs.last().unwrap().ends_with("побеждает в игре."));
+    }
+
+    #[test]
+    fn player_name_should_be_parsed_from_index_and_name() {
+        assert_eq!(
+            PlayerName::from_str("1=Bob"),
+            Ok(PlayerName {
+                index: 1,
+                name: String::from("Bob")
+            })
+        );
+        assert!(PlayerName::from_str("Bob").is_err());
+        assert!(PlayerName::from_str("x=Bob").is_err());
+        assert!(PlayerName::from_str("1=").is_err());
+    }
+}
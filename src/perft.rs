@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::fsm::ConstRng;
+use crate::game::{get_available_actions, Game};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct PerftDepth {
+    pub depth: usize,
+    pub sequences: usize,
+    pub states: usize,
+    pub finished: usize,
+}
+
+pub fn perft(game: &Game, depth: usize) -> Vec<PerftDepth> {
+    let mut result: Vec<PerftDepth> = (0..=depth)
+        .map(|depth| PerftDepth {
+            depth,
+            ..Default::default()
+        })
+        .collect();
+    let mut states: Vec<HashSet<Game>> = vec![HashSet::new(); depth + 1];
+    visit(game, 0, &mut result, &mut states);
+    for (value, states) in result.iter_mut().zip(states.iter()) {
+        value.states = states.len();
+    }
+    result
+}
+
+fn visit(game: &Game, depth: usize, result: &mut [PerftDepth], states: &mut [HashSet<Game>]) {
+    result[depth].sequences += 1;
+    states[depth].insert(game.normalized());
+    if game.is_done() {
+        result[depth].finished += 1;
+        return;
+    }
+    if depth + 1 >= result.len() {
+        return;
+    }
+    let view = game.get_anonymous_view();
+    let available_actions = get_available_actions(
+        view.state_type,
+        view.player_coins,
+        view.player_hands,
+        view.rules,
+    );
+    for action in available_actions.iter() {
+        let mut next = game.clone();
+        if next.play(action, &mut ConstRng).is_ok() {
+            visit(&next, depth + 1, result, states);
+        }
+    }
+}
+
+pub fn get_branching_factor(result: &[PerftDepth], depth: usize) -> Option<f64> {
+    if depth == 0 {
+        return None;
+    }
+    let previous = &result[depth - 1];
+    let expanded = previous.sequences - previous.finished;
+    if expanded == 0 {
+        return None;
+    }
+    Some(result[depth].sequences as f64 / expanded as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::fsm::Rules;
+    use crate::game::{CardCounts, Settings};
+
+    use super::*;
+
+    #[test]
+    fn perft_should_count_sequences_and_states_by_depth() {
+        let game = Game::new(
+            Settings {
+                players_number: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+            &mut StdRng::seed_from_u64(42),
+        );
+        let result = perft(&game, 3);
+        assert_eq!(result.len(), 4);
+        assert_eq!(result[0].sequences, 1);
+        assert_eq!(result[0].states, 1);
+        assert_eq!(result[1].sequences, 5);
+        assert_eq!(get_branching_factor(&result, 1), Some(5.0));
+        for value in result.iter() {
+            assert!(value.states <= value.sequences);
+        }
+        assert_eq!(perft(&game, 3), result);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::game::Settings;
+use crate::run::{
+    for_each_played_action, get_action_type_name, run_game_with_bots, BotType, RunResult,
+    TimeControl,
+};
+use crate::state_class::StateClass;
+
+#[derive(Debug, Serialize)]
+pub struct ProfileEntry {
+    pub bot_type: BotType,
+    pub state_class: StateClass,
+    pub samples: usize,
+    pub actions: BTreeMap<&'static str, f64>,
+}
+
+#[derive(Default)]
+pub struct StrategyProfile {
+    games: usize,
+    classes: BTreeMap<(BotType, StateClass), BTreeMap<&'static str, usize>>,
+}
+
+impl StrategyProfile {
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    pub fn add_game(
+        &mut self,
+        seed: u64,
+        bot_types: &[BotType],
+        result: &RunResult,
+    ) -> Result<(), String> {
+        let classes = &mut self.classes;
+        for_each_played_action(seed, result, |game, action| {
+            let state_class = StateClass::classify(&game.get_player_view(action.player));
+            *classes
+                .entry((bot_types[action.player], state_class))
+                .or_default()
+                .entry(get_action_type_name(&action.action_type))
+                .or_insert(0) += 1;
+        })?;
+        self.games += 1;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> Vec<ProfileEntry> {
+        self.classes
+            .iter()
+            .map(|((bot_type, state_class), actions)| {
+                let samples: usize = actions.values().sum();
+                ProfileEntry {
+                    bot_type: *bot_type,
+                    state_class: state_class.clone(),
+                    samples,
+                    actions: actions
+                        .iter()
+                        .map(|(name, count)| (*name, *count as f64 / samples as f64))
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+pub fn collect_strategy_profile(
+    seed: u64,
+    games: usize,
+    bot_types: &[BotType],
+    settings: Settings,
+) -> Result<StrategyProfile, String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut profile = StrategyProfile::default();
+    for _ in 0..games {
+        let game_seed = rng.gen::<u64>();
+        let result = run_game_with_bots(
+            game_seed,
+            bot_types,
+            settings.clone(),
+            false,
+            None,
+            0,
+            TimeControl::default(),
+        );
+        profile.add_game(game_seed, bot_types, &result)?;
+    }
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::{Card, Rules};
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    #[test]
+    fn strategy_profile_should_have_distribution_for_each_class() {
+        let bot_types = [BotType::Random, BotType::HonestCarefulRandom];
+        let profile = collect_strategy_profile(
+            42,
+            5,
+            &bot_types,
+            Settings {
+                players_number: 2,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+        )
+        .unwrap();
+        assert_eq!(profile.games(), 5);
+        let entries = profile.entries();
+        assert!(entries
+            .iter()
+            .any(|v| v.bot_type == BotType::HonestCarefulRandom));
+        for entry in entries.iter() {
+            assert!(entry.samples > 0);
+            let total: f64 = entry.actions.values().sum();
+            assert!((total - 1.0).abs() < 1e-9, "{:?}", entry);
+        }
+        assert!(!entries
+            .iter()
+            .any(|v| v.bot_type == BotType::HonestCarefulRandom
+                && v.state_class.state_type == "Turn"
+                && !v.state_class.cards.contains(&Card::Duke)
+                && v.actions.contains_key("Tax")));
+    }
+}
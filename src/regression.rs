@@ -0,0 +1,85 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Settings;
+use crate::run::BotType;
+use crate::stats::DuelStats;
+
+pub const DEFAULT_WIN_RATE: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionRecord {
+    pub baseline: BotType,
+    pub candidate: BotType,
+    pub settings: Settings,
+    pub games: usize,
+    pub win_rate: f64,
+}
+
+impl RegressionRecord {
+    pub fn new(
+        baseline: BotType,
+        candidate: BotType,
+        settings: Settings,
+        stats: &DuelStats,
+    ) -> Self {
+        Self {
+            baseline,
+            candidate,
+            settings,
+            games: stats.games(),
+            win_rate: stats.win_rate(0),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read regression record from {}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("failed to create {}: {}", path, e))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|e| format!("failed to write regression record to {}: {}", path, e))
+    }
+
+    pub fn check(&self, reference: &RegressionRecord) -> Result<(), String> {
+        if self.baseline != reference.baseline || self.settings != reference.settings {
+            return Err(format!(
+                "Regression record is made for baseline {:?} with settings {:?}",
+                reference.baseline, reference.settings
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn check_regression(win_rate: f64, reference: f64, threshold: f64) -> Result<(), String> {
+    if reference - win_rate > threshold {
+        return Err(format!(
+            "Candidate win rate {:.3} dropped by {:.3} from {:.3} which is more than threshold {:.3}",
+            win_rate,
+            reference - win_rate,
+            reference,
+            threshold
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_regression_should_fail_only_when_drop_exceeds_threshold() {
+        assert!(check_regression(0.5, DEFAULT_WIN_RATE, 0.02).is_ok());
+        assert!(check_regression(0.49, DEFAULT_WIN_RATE, 0.02).is_ok());
+        assert!(check_regression(0.7, 0.6, 0.02).is_ok());
+        assert!(check_regression(0.47, DEFAULT_WIN_RATE, 0.02).is_err());
+        assert!(check_regression(0.55, 0.6, 0.02).is_err());
+    }
+}
@@ -1,19 +1,38 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use crate::bots::{ActionView, Bot, HonestCarefulRandomBot, RandomBot};
-use crate::fsm::Action;
-use crate::game::{get_available_actions, Game, Settings};
+use crate::book::{self, BookBot};
+use crate::bots::{
+    is_allowed_action_type, notify_bot, ActionHistory, Bot, HonestCarefulRandomBot, InfoSeekingBot,
+    RandomBot,
+};
+use crate::fsm::{resolve_responses, Action, ActionType, Card, ChallengeState, StateType};
+use crate::game::{
+    get_available_actions, ActionsIterator, Decision, Game, HistoryEntry, PlayerView, Settings,
+};
+use crate::metrics::{self, TimedBot};
 
-#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BotType {
     Random,
     HonestCarefulRandom,
+    InfoSeeking,
 }
 
-pub const ALL_BOT_TYPES: [BotType; 2] = [BotType::Random, BotType::HonestCarefulRandom];
+pub const ALL_BOT_TYPES: [BotType; 3] = [
+    BotType::Random,
+    BotType::HonestCarefulRandom,
+    BotType::InfoSeeking,
+];
+
+const BOTS_SEED_MASK: u64 = 0x9e37_79b9_7f4a_7c15;
 
 impl FromStr for BotType {
     type Err = String;
@@ -22,14 +41,223 @@ impl FromStr for BotType {
         match s {
             "random" => Ok(BotType::Random),
             "honest_careful_random" => Ok(BotType::HonestCarefulRandom),
+            "info_seeking" => Ok(BotType::InfoSeeking),
             _ => Err(format!("invalid bot type: {}", s)),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct SeatSpec {
+    pub seat: usize,
+    pub bot_type: BotType,
+}
+
+impl FromStr for SeatSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (seat, bot) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid seat spec, expected <seat>=<bot_type>: {}", s))?;
+        let seat = seat
+            .parse::<usize>()
+            .map_err(|e| format!("invalid seat index {}: {}", seat, e))?;
+        let (name, params) = match bot.split_once(':') {
+            Some((name, params)) => (name, Some(params)),
+            None => (bot, None),
+        };
+        let bot_type = BotType::from_str(name)?;
+        if let Some(params) = params {
+            return Err(format!(
+                "bot type {} doesn't support parameters: {}",
+                name, params
+            ));
+        }
+        Ok(Self { seat, bot_type })
+    }
+}
+
+pub fn make_lineup(
+    players_number: usize,
+    bot_types: &[BotType],
+    seats: &[SeatSpec],
+    default_bot_type: BotType,
+    cycle_bot_types: bool,
+) -> Result<Vec<BotType>, String> {
+    if bot_types.len() > players_number {
+        return Err(format!(
+            "Too many bot types for {} players: {}, expected at most {}",
+            players_number,
+            bot_types.len(),
+            players_number
+        ));
+    }
+    let mut lineup: Vec<BotType> = if cycle_bot_types && !bot_types.is_empty() {
+        bot_types
+            .iter()
+            .cycle()
+            .take(players_number)
+            .copied()
+            .collect()
+    } else {
+        bot_types.to_vec()
+    };
+    lineup.resize(players_number, default_bot_type);
+    let mut assigned: Vec<usize> = Vec::with_capacity(seats.len());
+    for spec in seats.iter() {
+        if spec.seat >= players_number {
+            return Err(format!(
+                "Seat {} is not applicable for {} players",
+                spec.seat, players_number
+            ));
+        }
+        if assigned.contains(&spec.seat) {
+            return Err(format!("Seat {} is specified more than once", spec.seat));
+        }
+        assigned.push(spec.seat);
+        lineup[spec.seat] = spec.bot_type;
+    }
+    Ok(lineup)
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TimeoutPolicy {
+    Fallback,
+    Forfeit,
+}
+
+impl FromStr for TimeoutPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fallback" => Ok(TimeoutPolicy::Fallback),
+            "forfeit" => Ok(TimeoutPolicy::Forfeit),
+            _ => Err(format!("invalid timeout policy: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MoveTimeout {
+    pub duration: Duration,
+    pub policy: TimeoutPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Clock {
+    pub total: Duration,
+    pub increment: Duration,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub move_timeout: Option<MoveTimeout>,
+    pub clock: Option<Clock>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Clocks {
+    increment: Duration,
+    remaining: Vec<Duration>,
+    flag_fallen: Vec<bool>,
+}
+
+impl Clocks {
+    pub fn new(clock: &Clock, players_number: usize) -> Self {
+        Self {
+            increment: clock.increment,
+            remaining: vec![clock.total; players_number],
+            flag_fallen: vec![false; players_number],
+        }
+    }
+
+    pub fn remaining(&self, player: usize) -> Duration {
+        self.remaining[player]
+    }
+
+    pub fn is_flag_fallen(&self, player: usize) -> bool {
+        self.flag_fallen[player]
+    }
+
+    pub fn charge(&mut self, player: usize, elapsed: Duration) -> bool {
+        if elapsed >= self.remaining[player] {
+            self.remaining[player] = Duration::ZERO;
+            self.flag_fallen[player] = true;
+            return false;
+        }
+        self.remaining[player] = self.remaining[player] - elapsed + self.increment;
+        true
+    }
+}
+
 pub struct RunResult {
     pub begin: Game,
     pub end: Game,
+    pub log: GameLog,
+}
+
+pub struct GameLog {
+    pub timeouts: Vec<usize>,
+    pub coins: BTreeMap<&'static str, CoinsFlow>,
+    pub player_coins: Vec<BTreeMap<&'static str, CoinsFlow>>,
+    pub eliminations: Vec<Elimination>,
+    pub revealed_cards: Vec<(Card, EliminationCause)>,
+    pub action_rounds: BTreeMap<(usize, usize, &'static str), usize>,
+}
+
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum EliminationCause {
+    Coup,
+    Assassination,
+    Bluff,
+    FailedChallenge,
+    Forfeit,
+    FlagFall,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Elimination {
+    pub player: usize,
+    pub killer: Option<usize>,
+    pub cause: EliminationCause,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct CoinsFlow {
+    pub earned: usize,
+    pub spent: usize,
+}
+
+impl CoinsFlow {
+    pub fn add(&mut self, other: &CoinsFlow) {
+        self.earned += other.earned;
+        self.spent += other.spent;
+    }
+}
+
+pub fn get_action_type_name(action_type: &ActionType) -> &'static str {
+    match action_type {
+        ActionType::Income => "Income",
+        ActionType::ForeignAid => "ForeignAid",
+        ActionType::Coup(..) => "Coup",
+        ActionType::Tax => "Tax",
+        ActionType::Assassinate(..) => "Assassinate",
+        ActionType::Exchange => "Exchange",
+        ActionType::Steal(..) => "Steal",
+        ActionType::BlockForeignAid => "BlockForeignAid",
+        ActionType::BlockAssassination => "BlockAssassination",
+        ActionType::BlockSteal(..) => "BlockSteal",
+        ActionType::PassChallenge => "PassChallenge",
+        ActionType::PassBlock => "PassBlock",
+        ActionType::Challenge => "Challenge",
+        ActionType::ShowCard(..) => "ShowCard",
+        ActionType::RevealCard(..) => "RevealCard",
+        ActionType::TakeCard => "TakeCard",
+        ActionType::ShuffleDeck => "ShuffleDeck",
+        ActionType::DropCard(..) => "DropCard",
+    }
 }
 
 pub fn run_game_with_bots(
@@ -38,25 +266,186 @@ pub fn run_game_with_bots(
     settings: Settings,
     verbose: bool,
     write_player: Option<usize>,
+    max_steps: usize,
+    time_control: TimeControl,
 ) -> RunResult {
+    assert_eq!(
+        bot_types.len(),
+        settings.players_number,
+        "Number of bot types doesn't match number of players"
+    );
+    let _span = tracing::info_span!("game", seed).entered();
     let mut rng = StdRng::seed_from_u64(seed);
     let mut game = Game::new(settings.clone(), &mut rng);
     let begin = game.clone();
-    let mut bots: Vec<Box<dyn Bot>> = bot_types
+    let mut bots = make_bots(seed, bot_types, &game, None);
+    let log = run_game(
+        &mut bots,
+        &mut game,
+        &mut rng,
+        verbose,
+        write_player,
+        max_steps,
+        time_control,
+    );
+    RunResult {
+        begin,
+        end: game,
+        log,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchContext {
+    games: usize,
+    lineup: Vec<usize>,
+    models: Vec<ActionHistory>,
+}
+
+impl MatchContext {
+    pub fn new(participants: usize) -> Self {
+        Self {
+            games: 0,
+            lineup: Vec::new(),
+            models: vec![ActionHistory::default(); participants],
+        }
+    }
+
+    pub fn lineup(&self) -> &[usize] {
+        &self.lineup
+    }
+
+    pub fn participant(&self, seat: usize) -> usize {
+        self.lineup[seat]
+    }
+
+    pub fn model(&self, participant: usize) -> &ActionHistory {
+        &self.models[participant]
+    }
+
+    fn finish_game(&mut self, bots: &mut [Box<dyn Bot>]) {
+        for (participant, model) in self.models.iter_mut().enumerate() {
+            let seat = match self.lineup.iter().position(|v| *v == participant) {
+                Some(v) => v,
+                None => continue,
+            };
+            if let Some(history) = bots[seat].history_mut() {
+                model.extend(history.relabeled(&self.lineup));
+            }
+        }
+        self.games += 1;
+    }
+}
+
+pub fn run_match_game(
+    seed: u64,
+    bot_types: &[BotType],
+    lineup: Vec<usize>,
+    settings: Settings,
+    time_control: TimeControl,
+    context: &mut MatchContext,
+) -> RunResult {
+    assert_eq!(
+        lineup.len(),
+        settings.players_number,
+        "Lineup size doesn't match number of players"
+    );
+    let _span = tracing::info_span!("game", seed, match_game = context.games).entered();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new(settings, &mut rng);
+    let begin = game.clone();
+    context.lineup = lineup;
+    let seat_bot_types: Vec<BotType> = context
+        .lineup
+        .iter()
+        .map(|participant| bot_types[*participant])
+        .collect();
+    let mut bots = make_bots(seed, &seat_bot_types, &game, Some(context));
+    let log = run_game(&mut bots, &mut game, &mut rng, false, None, 0, time_control);
+    context.finish_game(&mut bots);
+    RunResult {
+        begin,
+        end: game,
+        log,
+    }
+}
+
+pub fn make_bots(
+    seed: u64,
+    bot_types: &[BotType],
+    game: &Game,
+    context: Option<&MatchContext>,
+) -> Vec<Box<dyn Bot>> {
+    let settings = game.get_settings();
+    let mut bots_rng = make_bots_rng(seed);
+    bot_types
         .iter()
         .enumerate()
-        .map(|(index, bot_type)| -> Box<dyn Bot> {
-            match bot_type {
-                BotType::Random => Box::new(RandomBot::new(&game.get_player_view(index))),
-                BotType::HonestCarefulRandom => Box::new(HonestCarefulRandomBot::new(
-                    &game.get_player_view(index),
-                    &settings,
-                )),
-            }
+        .map(|(index, bot_type)| {
+            make_match_bot(
+                *bot_type,
+                &game.get_player_view(index),
+                &settings,
+                &mut bots_rng,
+                context,
+            )
         })
-        .collect();
-    run_game(&mut bots, &mut game, &mut rng, verbose, write_player);
-    RunResult { begin, end: game }
+        .collect()
+}
+
+pub fn make_bots_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed ^ BOTS_SEED_MASK)
+}
+
+pub fn make_bot<R: Rng>(
+    bot_type: BotType,
+    view: &PlayerView,
+    settings: &Settings,
+    rng: &mut R,
+) -> Box<dyn Bot> {
+    make_match_bot(bot_type, view, settings, rng, None)
+}
+
+fn make_match_bot<R: Rng>(
+    bot_type: BotType,
+    view: &PlayerView,
+    settings: &Settings,
+    rng: &mut R,
+    context: Option<&MatchContext>,
+) -> Box<dyn Bot> {
+    let bot_rng = StdRng::seed_from_u64(rng.gen());
+    let mut bot: Box<dyn Bot> = match bot_type {
+        BotType::Random => Box::new(RandomBot::new(bot_rng)),
+        BotType::HonestCarefulRandom => {
+            let bot = HonestCarefulRandomBot::new(view, settings, bot_rng);
+            match context {
+                Some(context) => Box::new(bot.with_match_context(context)),
+                None => Box::new(bot),
+            }
+        }
+        BotType::InfoSeeking => {
+            let bot = InfoSeekingBot::new(view, settings, bot_rng);
+            match context {
+                Some(context) => Box::new(bot.with_match_context(context)),
+                None => Box::new(bot),
+            }
+        }
+    };
+    if let Some(opening_book) = book::get().filter(|v| v.settings() == settings) {
+        bot = Box::new(BookBot::new(opening_book, bot));
+    }
+    if metrics::is_enabled() {
+        Box::new(TimedBot::new(bot_type, bot))
+    } else {
+        bot
+    }
+}
+
+pub fn has_cards_tracker(bot_type: BotType) -> bool {
+    matches!(
+        bot_type,
+        BotType::HonestCarefulRandom | BotType::InfoSeeking
+    )
 }
 
 pub fn run_game<B: AsMut<dyn Bot>, R: Rng>(
@@ -65,9 +454,24 @@ pub fn run_game<B: AsMut<dyn Bot>, R: Rng>(
     rng: &mut R,
     verbose: bool,
     write_player: Option<usize>,
-) {
+    max_steps: usize,
+    time_control: TimeControl,
+) -> GameLog {
+    let mut timers = Timers::new(&time_control, bots.len());
+    let mut coins: BTreeMap<&'static str, CoinsFlow> = BTreeMap::new();
+    let mut player_coins: Vec<BTreeMap<&'static str, CoinsFlow>> =
+        vec![BTreeMap::new(); bots.len()];
+    let mut eliminations = Vec::new();
+    let mut revealed_cards = Vec::new();
+    let mut action_rounds = BTreeMap::new();
+    let mut turn_action_type = None;
+    let settings = game.get_settings();
+    game.record_history();
+    for (player, bot) in bots.iter_mut().enumerate() {
+        bot.as_mut().on_game_start(&settings, player);
+    }
     if verbose {
-        game.print();
+        game.trace();
     }
     if let Some(player) = write_player {
         println!(
@@ -75,74 +479,458 @@ pub fn run_game<B: AsMut<dyn Bot>, R: Rng>(
             serde_json::to_string(&game.get_player_view(player)).unwrap()
         );
     }
-    while !game.is_done() {
+    while !game.is_done() && (max_steps == 0 || game.step() < max_steps) {
         let view = game.get_anonymous_view();
-        let available_actions =
-            get_available_actions(view.state_type, view.player_coins, view.player_hands);
-        let action = get_action(&available_actions, bots, game);
+        let available_actions = get_available_actions(
+            view.state_type,
+            view.player_coins,
+            view.player_hands,
+            view.rules,
+        );
+        let decision = get_action(&available_actions, bots, game, &mut timers, rng);
+        let action = match decision {
+            Decision::Play(action) => action,
+            Decision::Forfeit(player) => {
+                if verbose {
+                    tracing::info!(step = game.step(), player, "forfeit");
+                }
+                game.forfeit(player);
+                let cause = if timers.is_flag_fallen(player) {
+                    EliminationCause::FlagFall
+                } else {
+                    EliminationCause::Forfeit
+                };
+                eliminations.push(Elimination {
+                    player,
+                    killer: None,
+                    cause,
+                });
+                if verbose {
+                    game.trace();
+                }
+                for (index, bot) in bots.iter_mut().enumerate() {
+                    if game.is_player_active(index) {
+                        bot.as_mut()
+                            .after_forfeit(&timers.get_player_view(game, index), player);
+                    }
+                }
+                continue;
+            }
+        };
         if verbose {
-            println!("play {:?}", action);
+            tracing::info!(
+                step = game.step(),
+                player = action.player,
+                action = ?action.action_type,
+                "play"
+            );
         }
+        let view = game.get_anonymous_view();
+        if matches!(view.state_type, StateType::Turn { .. }) {
+            turn_action_type = Some(action.action_type.clone());
+        }
+        let coins_before = view.player_coins.to_vec();
+        *action_rounds
+            .entry((
+                action.player,
+                game.round(),
+                get_action_type_name(&action.action_type),
+            ))
+            .or_insert(0) += 1;
+        let last_card = view.player_hands[action.player] == 1;
+        let reveal = match action.action_type {
+            ActionType::RevealCard(card) => {
+                get_elimination(action.player, view.state_type, turn_action_type.as_ref())
+                    .map(|elimination| (card, elimination))
+            }
+            _ => None,
+        };
         assert_eq!(game.play(&action, rng), Ok(()));
+        metrics::record_move();
+        if let Some((card, elimination)) = reveal {
+            revealed_cards.push((card, elimination.cause));
+            if last_card {
+                eliminations.push(elimination);
+            }
+        }
+        if let Some(action_type) = turn_action_type.as_ref() {
+            let name = get_action_type_name(action_type);
+            let flow = coins.entry(name).or_default();
+            for (player, (before, after)) in coins_before
+                .iter()
+                .zip(game.get_anonymous_view().player_coins.iter())
+                .enumerate()
+            {
+                let delta = CoinsFlow {
+                    earned: after.saturating_sub(*before),
+                    spent: before.saturating_sub(*after),
+                };
+                flow.add(&delta);
+                if delta.earned > 0 || delta.spent > 0 {
+                    player_coins[player].entry(name).or_default().add(&delta);
+                }
+            }
+        }
         if verbose {
-            game.print();
+            game.trace();
         }
+        let views = game.get_all_views();
         for (player, bot) in bots.iter_mut().enumerate() {
-            let view = game.get_player_view(player);
+            let view = timers.with_remaining_time(views.players[player].player_view());
             if write_player == Some(player) {
                 println!("{}", serde_json::to_string(&view).unwrap());
             }
             if game.is_player_active(player) {
-                if player == action.player {
-                    bot.as_mut().after_player_action(&view, &action);
-                } else {
-                    bot.as_mut()
-                        .after_opponent_action(&view, &ActionView::from_action(&action));
-                }
+                notify_bot(bot.as_mut(), &view, &action);
             }
         }
     }
+    if game.is_done() {
+        metrics::record_game();
+    }
+    if verbose {
+        tracing::info!(
+            winner = ?game.get_winner(),
+            rounds = game.round(),
+            turns = game.turn(),
+            steps = game.step(),
+            "done"
+        );
+    }
+    GameLog {
+        timeouts: timers.timeouts,
+        coins,
+        player_coins,
+        eliminations,
+        revealed_cards,
+        action_rounds,
+    }
 }
 
-pub fn get_action<B: AsMut<dyn Bot>>(
-    available_actions: &[Action],
-    bots: &mut [B],
-    game: &Game,
-) -> Action {
+fn get_elimination(
+    player: usize,
+    state_type: &StateType,
+    turn_action_type: Option<&ActionType>,
+) -> Option<Elimination> {
+    let (killer, cause) = match state_type {
+        StateType::LostInfluence { current_player, .. } => match turn_action_type {
+            Some(ActionType::Coup(..)) => (*current_player, EliminationCause::Coup),
+            _ => (*current_player, EliminationCause::Assassination),
+        },
+        StateType::Challenge { state, .. } => match state {
+            ChallengeState::Initial { initiator, .. } => (*initiator, EliminationCause::Bluff),
+            ChallengeState::ShownCard { target, .. } => {
+                (*target, EliminationCause::FailedChallenge)
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(Elimination {
+        player,
+        killer: Some(killer),
+        cause,
+    })
+}
+
+pub fn replay_history<R: Rng>(
+    game: &mut Game,
+    history: &[HistoryEntry],
+    rng: &mut R,
+) -> Result<(), String> {
+    for (index, entry) in history.iter().enumerate() {
+        match &entry.decision {
+            Decision::Play(action) => game
+                .play(action, rng)
+                .map_err(|e| format!("Failed to replay decision {}: {}", index, e))?,
+            Decision::Forfeit(player) => game.forfeit(*player),
+        }
+        if game.state_hash() != entry.hash {
+            return Err(format!(
+                "Replayed game state hash doesn't match after decision {}: {:?}",
+                index, entry.decision
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn for_each_played_action<F: FnMut(&Game, &Action)>(
+    seed: u64,
+    result: &RunResult,
+    mut f: F,
+) -> Result<(), String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new(result.begin.get_settings(), &mut rng);
+    if game != result.begin {
+        return Err(format!(
+            "Initial game state doesn't match for seed {}",
+            seed
+        ));
+    }
+    for (index, decision) in result.end.decisions().enumerate() {
+        match decision {
+            Decision::Play(action) => {
+                f(&game, action);
+                game.play(action, &mut rng)
+                    .map_err(|e| format!("Failed to replay decision {}: {}", index, e))?;
+            }
+            Decision::Forfeit(player) => game.forfeit(*player),
+        }
+    }
+    Ok(())
+}
+
+pub fn verify_replay<R: Rng>(mut game: Game, rng: &mut R, expected: &Game) -> Result<(), String> {
+    game.record_history();
+    let start = game.history().len();
+    if expected.history().get(..start) != Some(game.history()) {
+        return Err(String::from(
+            "Initial game history is not a prefix of the expected game history",
+        ));
+    }
+    replay_history(&mut game, &expected.history()[start..], rng)?;
+    if game != *expected {
+        return Err(format!(
+            "Replayed game state doesn't match:\nexpected: {}\nreplayed: {}",
+            serde_json::to_string(expected).unwrap(),
+            serde_json::to_string(&game).unwrap()
+        ));
+    }
+    Ok(())
+}
+
+pub struct Timers {
+    move_timeout: Option<MoveTimeout>,
+    clocks: Option<Clocks>,
+    timeouts: Vec<usize>,
+}
+
+impl Timers {
+    fn new(time_control: &TimeControl, players_number: usize) -> Self {
+        Self {
+            move_timeout: time_control.move_timeout,
+            clocks: time_control
+                .clock
+                .map(|clock| Clocks::new(&clock, players_number)),
+            timeouts: vec![0; players_number],
+        }
+    }
+
+    fn is_flag_fallen(&self, player: usize) -> bool {
+        self.clocks
+            .as_ref()
+            .is_some_and(|v| v.is_flag_fallen(player))
+    }
+
+    fn get_player_view<'a>(&self, game: &'a Game, player: usize) -> PlayerView<'a> {
+        self.with_remaining_time(game.get_player_view(player))
+    }
+
+    fn with_remaining_time<'a>(&self, mut view: PlayerView<'a>) -> PlayerView<'a> {
+        view.remaining_time = self.clocks.as_ref().map(|v| v.remaining(view.player));
+        view
+    }
+
+    // Time is measured after the bot returns and a bot is never interrupted, so a timeout
+    // penalizes a slow bot but a bot that never returns still hangs the game.
+    fn call_bot<T, F: FnOnce() -> T>(&mut self, player: usize, f: F) -> Result<T, TimeoutPolicy> {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        if let Some(clocks) = self.clocks.as_mut() {
+            if !clocks.charge(player, elapsed) {
+                tracing::warn!(
+                    player,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    "bot clock flag fell"
+                );
+                return Err(TimeoutPolicy::Forfeit);
+            }
+        }
+        if let Some(move_timeout) = self.move_timeout.as_ref() {
+            if elapsed > move_timeout.duration {
+                self.timeouts[player] += 1;
+                tracing::warn!(
+                    player,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    policy = ?move_timeout.policy,
+                    "bot exceeded move timeout"
+                );
+                return Err(move_timeout.policy);
+            }
+        }
+        Ok(result)
+    }
+}
+
+pub fn get_responders(available_actions: &[Action]) -> (usize, Vec<usize>) {
     let mut players = Vec::new();
     for action in available_actions.iter() {
         if !players.contains(&action.player) {
             players.push(action.player);
         }
     }
-    if players.len() > 1 {
-        for player in &players[0..players.len() - 1] {
-            let player_available_actions: Vec<Action> = available_actions
+    let required = players.pop().unwrap();
+    (required, players)
+}
+
+pub fn get_action<B: AsMut<dyn Bot>, R: Rng>(
+    available_actions: &[Action],
+    bots: &mut [B],
+    game: &Game,
+    timers: &mut Timers,
+    rng: &mut R,
+) -> Decision {
+    let (required, players) = get_responders(available_actions);
+    if players.is_empty() {
+        return get_required_action(required, available_actions, bots, game, timers, rng);
+    }
+    let priority = game.get_anonymous_view().rules.response_priority;
+    let mut responses = Vec::new();
+    for player in players.iter() {
+        let player_available_actions: Vec<Action> = available_actions
+            .iter()
+            .cloned()
+            .of_player(*player)
+            .collect();
+        let bot = bots[*player].as_mut();
+        let view = timers.get_player_view(game, *player);
+        match timers.call_bot(*player, || {
+            bot.get_optional_action(&view, &player_available_actions)
+        }) {
+            Ok(Some(action)) => responses.push(action),
+            Ok(None) | Err(TimeoutPolicy::Fallback) => (),
+            Err(TimeoutPolicy::Forfeit) => return Decision::Forfeit(*player),
+        }
+    }
+    if let Some(action) = resolve_responses(&responses, required, bots.len(), priority, rng) {
+        return Decision::Play(action.clone());
+    }
+    let required_available_actions: Vec<Action> = available_actions
+        .iter()
+        .cloned()
+        .of_player(required)
+        .collect();
+    get_required_action(
+        required,
+        &required_available_actions,
+        bots,
+        game,
+        timers,
+        rng,
+    )
+}
+
+fn get_required_action<B: AsMut<dyn Bot>, R: Rng>(
+    player: usize,
+    available_actions: &[Action],
+    bots: &mut [B],
+    game: &Game,
+    timers: &mut Timers,
+    rng: &mut R,
+) -> Decision {
+    let bot = bots[player].as_mut();
+    let view = timers.get_player_view(game, player);
+    match timers.call_bot(player, || bot.get_action(&view, available_actions)) {
+        Ok(action) => Decision::Play(action),
+        Err(TimeoutPolicy::Fallback) => {
+            let allowed_actions: Vec<&Action> = available_actions
                 .iter()
-                .filter(|action| action.player == *player)
-                .cloned()
+                .filter(|action| is_allowed_action_type(&action.action_type, view.cards))
                 .collect();
-            if let Some(action) = bots[*player]
-                .as_mut()
-                .get_optional_action(&game.get_player_view(*player), &player_available_actions)
-            {
-                return action;
-            }
+            Decision::Play((*allowed_actions.choose(rng).unwrap()).clone())
         }
-        let last_player = players[players.len() - 1];
-        let last_player_available_actions: Vec<Action> = available_actions
+        Err(TimeoutPolicy::Forfeit) => Decision::Forfeit(player),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    #[test]
+    fn clocks_charge_should_add_increment_until_flag_fall() {
+        let mut clocks = Clocks::new(
+            &Clock {
+                total: Duration::from_millis(100),
+                increment: Duration::from_millis(10),
+            },
+            2,
+        );
+        assert!(clocks.charge(0, Duration::from_millis(50)));
+        assert_eq!(clocks.remaining(0), Duration::from_millis(60));
+        assert!(!clocks.charge(0, Duration::from_millis(60)));
+        assert!(clocks.is_flag_fallen(0));
+        assert_eq!(clocks.remaining(0), Duration::ZERO);
+        assert!(!clocks.is_flag_fallen(1));
+    }
+
+    #[test]
+    fn run_game_with_empty_clocks_should_eliminate_by_flag_fall() {
+        let result = run_game_with_bots(
+            42,
+            &[BotType::Random, BotType::Random, BotType::Random],
+            Settings {
+                players_number: 3,
+                card_counts: CardCounts::uniform(3),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+            false,
+            None,
+            0,
+            TimeControl {
+                move_timeout: None,
+                clock: Some(Clock {
+                    total: Duration::ZERO,
+                    increment: Duration::ZERO,
+                }),
+            },
+        );
+        assert!(result.end.is_done());
+        assert_eq!(result.log.eliminations.len(), 2);
+        assert!(result
+            .log
+            .eliminations
             .iter()
-            .filter(|action| action.player == last_player)
-            .cloned()
-            .collect();
-        bots[last_player].as_mut().get_action(
-            &game.get_player_view(last_player),
-            &last_player_available_actions,
-        )
-    } else {
-        let player = players[0];
-        bots[player]
-            .as_mut()
-            .get_action(&game.get_player_view(player), available_actions)
+            .all(|v| v.cause == EliminationCause::FlagFall));
+    }
+
+    #[test]
+    fn run_match_game_should_carry_models_between_games() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let bot_types = [BotType::HonestCarefulRandom, BotType::Random];
+        let mut context = MatchContext::new(bot_types.len());
+        run_match_game(
+            42,
+            &bot_types,
+            vec![0, 1, 1],
+            settings.clone(),
+            TimeControl::default(),
+            &mut context,
+        );
+        let first = context.model(0).len();
+        assert!(first > 0);
+        assert_eq!(context.model(1).len(), 0);
+        run_match_game(
+            43,
+            &bot_types,
+            vec![1, 0, 1],
+            settings,
+            TimeControl::default(),
+            &mut context,
+        );
+        assert_eq!(context.games, 2);
+        assert_eq!(context.participant(1), 0);
+        assert!(context.model(0).len() > first);
     }
 }
@@ -0,0 +1,162 @@
+use std::ffi::OsString;
+use std::hash::Hasher;
+
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+pub fn hash_seed_phrase(phrase: &str) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(phrase.as_bytes());
+    hasher.finish()
+}
+
+// FNV-1a over little-endian integers of fixed width to get the same hash on any platform and
+// with any Rust version.
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_i16(&mut self, value: i16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_i128(&mut self, value: i128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_isize(&mut self, value: isize) {
+        self.write_i64(value as i64);
+    }
+}
+
+pub fn check_seed_phrase(seed: u64, seed_phrase: Option<&str>) -> Result<(), String> {
+    match seed_phrase {
+        Some(phrase) if hash_seed_phrase(phrase) != seed => Err(format!(
+            "seed {} doesn't match seed phrase {:?} that derives seed {}",
+            seed,
+            phrase,
+            hash_seed_phrase(phrase)
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn apply_seed_phrase(command: Command, args: Vec<OsString>) -> Vec<OsString> {
+    let matches = match command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(&args)
+    {
+        Ok(v) => v,
+        Err(_) => return args,
+    };
+    let phrase = match matches.get_one::<String>("seed_phrase") {
+        Some(v) => v.clone(),
+        None => return args,
+    };
+    match get_seed_args(&command, &matches, &phrase) {
+        Ok(seed_args) => args.into_iter().chain(seed_args).collect(),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn get_seed_args(
+    command: &Command,
+    matches: &ArgMatches,
+    phrase: &str,
+) -> Result<Vec<OsString>, String> {
+    let (subcommand_name, subcommand_matches) = match matches.subcommand() {
+        Some(v) => v,
+        None => return Ok(Vec::new()),
+    };
+    let subcommand = command.find_subcommand(subcommand_name).unwrap();
+    if !subcommand.get_arguments().any(|arg| arg.get_id() == "seed") {
+        return Err(format!(
+            "--seed-phrase is not supported by {} command",
+            subcommand_name
+        ));
+    }
+    if subcommand_matches.value_source("seed") == Some(ValueSource::CommandLine) {
+        return Err(String::from("--seed-phrase can't be used with --seed"));
+    }
+    Ok(vec![
+        OsString::from("--seed"),
+        OsString::from(hash_seed_phrase(phrase).to_string()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_seed_phrase_should_be_stable() {
+        assert_eq!(hash_seed_phrase(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash_seed_phrase("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            hash_seed_phrase("friday night game 3"),
+            hash_seed_phrase("friday night game 3")
+        );
+        assert_ne!(
+            hash_seed_phrase("friday night game 3"),
+            hash_seed_phrase("friday night game 4")
+        );
+    }
+
+    #[test]
+    fn check_seed_phrase_should_reject_mismatched_seed() {
+        let seed = hash_seed_phrase("friday night game 3");
+        assert_eq!(check_seed_phrase(seed, Some("friday night game 3")), Ok(()));
+        assert_eq!(check_seed_phrase(42, None), Ok(()));
+        assert!(check_seed_phrase(42, Some("friday night game 3")).is_err());
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::bots::{notify_bot, ActionScore, Bot};
+use crate::fsm::Action;
+use crate::game::{available_actions_for, GameView, Settings};
+use crate::run::{make_bot, BotType};
+use crate::target::{rank_targets, TargetAdvice};
+
+#[derive(Debug, Serialize)]
+pub struct Suggestion {
+    pub actions: Vec<Action>,
+    pub scores: Vec<ActionScore>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<TargetAdvice>,
+}
+
+pub fn make_suggestion<B: Bot + ?Sized>(bot: &mut B, view: &GameView) -> Suggestion {
+    let player_view = view.player_view();
+    let available_actions: Vec<Action> = available_actions_for(&player_view, view.player).collect();
+    let actions: Vec<Action> = bot
+        .suggest_actions(&player_view, &available_actions)
+        .iter()
+        .map(|v| (*v).clone())
+        .collect();
+    let mut scores = bot.score_actions(&player_view, &actions);
+    scores.sort_by(|l, r| r.win_probability.total_cmp(&l.win_probability));
+    let targets = bot
+        .cards_tracker()
+        .map(|tracker| rank_targets(tracker, &player_view))
+        .unwrap_or_default();
+    Suggestion {
+        actions,
+        scores,
+        targets,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionRequest {
+    pub game_id: String,
+    #[serde(flatten)]
+    pub command: SessionCommand,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionCommand {
+    Start { settings: Settings, view: GameView },
+    Update { action: Action, view: GameView },
+    Ping,
+    Sync { view: GameView },
+    End,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub game_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_hash: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<Suggestion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SessionResponse {
+    pub fn error(game_id: Option<String>, error: String) -> Self {
+        Self {
+            game_id,
+            step: None,
+            state_hash: None,
+            suggestion: None,
+            error: Some(error),
+        }
+    }
+}
+
+struct Session {
+    settings: Settings,
+    bot: Box<dyn Bot>,
+    step: usize,
+    state_hash: u64,
+}
+
+impl Session {
+    fn update(&mut self, view: &GameView) -> SessionReply {
+        let player_view = view.player_view();
+        self.step = view.step;
+        self.state_hash = player_view.state_hash();
+        SessionReply {
+            step: self.step,
+            state_hash: self.state_hash,
+            suggestion: Some(make_suggestion(self.bot.as_mut(), view)),
+        }
+    }
+}
+
+struct SessionReply {
+    step: usize,
+    state_hash: u64,
+    suggestion: Option<Suggestion>,
+}
+
+pub struct SessionManager {
+    bot_type: BotType,
+    rng: StdRng,
+    sessions: HashMap<String, Session>,
+}
+
+impl SessionManager {
+    pub fn new(bot_type: BotType, seed: u64) -> Self {
+        Self {
+            bot_type,
+            rng: StdRng::seed_from_u64(seed),
+            sessions: HashMap::new(),
+        }
+    }
+
+    pub fn handle(&mut self, request: SessionRequest) -> SessionResponse {
+        let game_id = request.game_id;
+        match self.handle_command(&game_id, request.command) {
+            Ok(v) => SessionResponse {
+                game_id: Some(game_id),
+                step: v.as_ref().map(|v| v.step),
+                state_hash: v.as_ref().map(|v| v.state_hash),
+                suggestion: v.and_then(|v| v.suggestion),
+                error: None,
+            },
+            Err(e) => SessionResponse::error(Some(game_id), e),
+        }
+    }
+
+    fn handle_command(
+        &mut self,
+        game_id: &str,
+        command: SessionCommand,
+    ) -> Result<Option<SessionReply>, String> {
+        match command {
+            SessionCommand::Start { settings, view } => {
+                if self.sessions.contains_key(game_id) {
+                    return Err(format!("game {} is already started", game_id));
+                }
+                settings.validate()?;
+                let bot = self.make_bot(&settings, &view);
+                let mut session = Session {
+                    settings,
+                    bot,
+                    step: 0,
+                    state_hash: 0,
+                };
+                let reply = session.update(&view);
+                self.sessions.insert(String::from(game_id), session);
+                Ok(Some(reply))
+            }
+            SessionCommand::Update { action, view } => {
+                let session = self.get_session(game_id)?;
+                notify_bot(session.bot.as_mut(), &view.player_view(), &action);
+                Ok(Some(session.update(&view)))
+            }
+            SessionCommand::Ping => {
+                let session = self.get_session(game_id)?;
+                Ok(Some(SessionReply {
+                    step: session.step,
+                    state_hash: session.state_hash,
+                    suggestion: None,
+                }))
+            }
+            SessionCommand::Sync { view } => {
+                let settings = self.get_session(game_id)?.settings.clone();
+                let bot = self.make_bot(&settings, &view);
+                let session = self.get_session(game_id)?;
+                session.bot = bot;
+                Ok(Some(session.update(&view)))
+            }
+            SessionCommand::End => {
+                self.sessions
+                    .remove(game_id)
+                    .ok_or_else(|| format!("game {} is not started", game_id))?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn get_session(&mut self, game_id: &str) -> Result<&mut Session, String> {
+        self.sessions
+            .get_mut(game_id)
+            .ok_or_else(|| format!("game {} is not started", game_id))
+    }
+
+    fn make_bot(&mut self, settings: &Settings, view: &GameView) -> Box<dyn Bot> {
+        let mut bot = make_bot(self.bot_type, &view.player_view(), settings, &mut self.rng);
+        bot.on_game_start(settings, view.player);
+        bot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::fsm::{ActionType, Rules};
+    use crate::game::{CardCounts, Game};
+
+    use crate::bots::{ActionView, RandomBot};
+    use crate::game::PlayerView;
+
+    use super::*;
+
+    struct ScoringBot;
+
+    impl Bot for ScoringBot {
+        fn suggest_actions<'a>(
+            &mut self,
+            _: &PlayerView,
+            available_actions: &'a [Action],
+        ) -> Vec<&'a Action> {
+            available_actions.iter().collect()
+        }
+
+        fn suggest_optional_actions<'a>(
+            &mut self,
+            _: &PlayerView,
+            available_actions: &'a [Action],
+        ) -> Vec<&'a Action> {
+            available_actions.iter().collect()
+        }
+
+        fn get_action(&mut self, _: &PlayerView, available_actions: &[Action]) -> Action {
+            available_actions[0].clone()
+        }
+
+        fn get_optional_action(&mut self, _: &PlayerView, _: &[Action]) -> Option<Action> {
+            None
+        }
+
+        fn after_player_action(&mut self, _: &PlayerView, _: &Action) {}
+
+        fn after_opponent_action(&mut self, _: &PlayerView, _: &ActionView) {}
+
+        fn query(&self, _: &str) {}
+
+        fn score_actions(
+            &mut self,
+            _: &PlayerView,
+            available_actions: &[Action],
+        ) -> Vec<ActionScore> {
+            available_actions
+                .iter()
+                .enumerate()
+                .map(|(index, action)| ActionScore {
+                    action: action.clone(),
+                    win_probability: index as f64 / available_actions.len() as f64,
+                    simulations: index,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn make_suggestion_should_sort_scores_provided_by_bot() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let game = Game::new(settings, &mut rng);
+        let view = GameView::from(&game.get_player_view(0));
+        let suggestion = make_suggestion(&mut ScoringBot, &view);
+        assert!(suggestion.actions.len() > 1);
+        assert_eq!(suggestion.scores.len(), suggestion.actions.len());
+        assert!(suggestion
+            .scores
+            .windows(2)
+            .all(|v| v[0].win_probability >= v[1].win_probability));
+        assert_eq!(
+            suggestion.scores.last().unwrap().action,
+            suggestion.actions[0]
+        );
+        let suggestion = make_suggestion(&mut RandomBot::new(rng), &view);
+        assert!(!suggestion.actions.is_empty());
+        assert!(suggestion.scores.is_empty());
+    }
+
+    #[test]
+    fn ping_should_return_state_hash_of_last_view_and_sync_should_replace_it() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(3),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut game = Game::new(settings.clone(), &mut StdRng::seed_from_u64(42));
+        let view = |game: &Game| GameView::from(&game.get_player_view(0));
+        let mut sessions = SessionManager::new(BotType::HonestCarefulRandom, 42);
+        let request = |command| SessionRequest {
+            game_id: String::from("game"),
+            command,
+        };
+        let start = sessions.handle(request(SessionCommand::Start {
+            settings,
+            view: view(&game),
+        }));
+        let expected = game.get_player_view(0).state_hash();
+        assert_eq!(start.state_hash, Some(expected));
+        let ping = sessions.handle(request(SessionCommand::Ping));
+        assert_eq!((ping.step, ping.state_hash), (Some(0), Some(expected)));
+        assert!(ping.suggestion.is_none());
+        let action = Action {
+            player: 0,
+            action_type: ActionType::Income,
+        };
+        game.play(&action, &mut StdRng::seed_from_u64(42)).unwrap();
+        let sync = sessions.handle(request(SessionCommand::Sync { view: view(&game) }));
+        assert!(sync.suggestion.is_some());
+        let ping = sessions.handle(request(SessionCommand::Ping));
+        assert_eq!(ping.step, Some(1));
+        assert_eq!(ping.state_hash, Some(game.get_player_view(0).state_hash()));
+        assert_ne!(ping.state_hash, Some(expected));
+        sessions.handle(request(SessionCommand::End));
+        let ping = sessions.handle(request(SessionCommand::Ping));
+        assert_eq!(ping.error.as_deref(), Some("game game is not started"));
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::completeness::get_state_type_name;
+use crate::fsm::{Card, ChallengeState, Rules, StateType, ASSASSINATION_COST, COUP_COST};
+use crate::game::PlayerView;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoinsBucket {
+    Poor,
+    CanAssassinate,
+    CanCoup,
+    MustCoup,
+}
+
+impl CoinsBucket {
+    pub fn from_coins(coins: usize, rules: &Rules) -> Self {
+        if coins >= rules.forced_coup_coins {
+            CoinsBucket::MustCoup
+        } else if coins >= COUP_COST {
+            CoinsBucket::CanCoup
+        } else if coins >= ASSASSINATION_COST {
+            CoinsBucket::CanAssassinate
+        } else {
+            CoinsBucket::Poor
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct StateClass {
+    pub state_type: &'static str,
+    pub coins: CoinsBucket,
+    pub opponents_alive: usize,
+    pub cards: Vec<Card>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_claim: Option<&'static str>,
+}
+
+impl StateClass {
+    pub fn classify(view: &PlayerView) -> Self {
+        let mut cards = view.cards.to_vec();
+        cards.sort();
+        Self {
+            state_type: get_state_type_name(view.state_type),
+            coins: CoinsBucket::from_coins(view.coins, view.rules),
+            opponents_alive: view
+                .player_hands
+                .iter()
+                .enumerate()
+                .filter(|(player, hands)| *player != view.player && **hands > 0)
+                .count(),
+            cards,
+            pending_claim: get_pending_claim_name(view.state_type),
+        }
+    }
+}
+
+impl Display for StateClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} coins={:?} opponents={} cards={}",
+            self.state_type,
+            self.coins,
+            self.opponents_alive,
+            self.cards.iter().map(|v| format!("{:?}", v)).join(",")
+        )?;
+        if let Some(claim) = self.pending_claim {
+            write!(f, " claim={}", claim)?;
+        }
+        Ok(())
+    }
+}
+
+fn get_pending_claim_name(state_type: &StateType) -> Option<&'static str> {
+    match state_type {
+        StateType::Tax { .. } => Some("Tax"),
+        StateType::Exchange { .. } => Some("Exchange"),
+        StateType::Assassination { .. } => Some("Assassinate"),
+        StateType::Steal { .. } => Some("Steal"),
+        StateType::BlockForeignAid { .. } => Some("BlockForeignAid"),
+        StateType::BlockAssassination { .. } => Some("BlockAssassination"),
+        StateType::BlockSteal { .. } => Some("BlockSteal"),
+        StateType::Challenge {
+            source,
+            state: ChallengeState::Initial { .. },
+            ..
+        } => get_pending_claim_name(source),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::fsm::{Action, ActionType};
+    use crate::game::Game;
+
+    use super::*;
+
+    #[test]
+    fn classify_should_bucket_coins_and_detect_pending_claim() {
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Duke, Card::Assassin],
+                vec![Card::Captain, Card::Contessa],
+                vec![Card::Duke, Card::Ambassador],
+            ],
+            vec![Card::Ambassador, Card::Captain],
+        );
+        let class = StateClass::classify(&game.get_player_view(0));
+        assert_eq!(class.state_type, "Turn");
+        assert_eq!(class.coins, CoinsBucket::Poor);
+        assert_eq!(class.opponents_alive, 2);
+        assert_eq!(class.cards, vec![Card::Assassin, Card::Duke]);
+        assert_eq!(class.pending_claim, None);
+        let action = Action {
+            player: 0,
+            action_type: ActionType::Tax,
+        };
+        game.play(&action, &mut StdRng::seed_from_u64(42)).unwrap();
+        let class = StateClass::classify(&game.get_player_view(1));
+        assert_eq!(class.pending_claim, Some("Tax"));
+        assert_eq!(
+            class.to_string(),
+            "Tax coins=Poor opponents=2 cards=Captain,Contessa claim=Tax"
+        );
+    }
+
+    #[test]
+    fn coins_bucket_should_follow_rules() {
+        let rules = Rules::default();
+        assert_eq!(CoinsBucket::from_coins(2, &rules), CoinsBucket::Poor);
+        assert_eq!(
+            CoinsBucket::from_coins(3, &rules),
+            CoinsBucket::CanAssassinate
+        );
+        assert_eq!(CoinsBucket::from_coins(7, &rules), CoinsBucket::CanCoup);
+        assert_eq!(
+            CoinsBucket::from_coins(rules.forced_coup_coins, &rules),
+            CoinsBucket::MustCoup
+        );
+    }
+}
@@ -1,14 +1,27 @@
 use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::str::FromStr;
+use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
+use itertools::Itertools;
 use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
+use crate::archive::{Archive, ArchivedGame};
+use crate::bots::{notify_tracker, CardsTracker, TrackerAccuracy};
 use crate::fsm::Card;
-use crate::game::{Settings, ALL_CARDS};
-use crate::run::{run_game_with_bots, BotType, ALL_BOT_TYPES};
+use crate::game::{Decision, Game, Settings, ALL_CARDS};
+use crate::isolate::{IsolatedGame, Isolation};
+use crate::run::{
+    has_cards_tracker, run_game_with_bots, run_match_game, BotType, CoinsFlow, EliminationCause,
+    MatchContext, RunResult, TimeControl, ALL_BOT_TYPES,
+};
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Stats {
     games: usize,
     steps: Vec<usize>,
@@ -17,58 +30,677 @@ pub struct Stats {
     winner_bot_type: Vec<BotType>,
     winner_initial_cards: Vec<Vec<Card>>,
     winner_bot_type_and_initial_cards: Vec<(BotType, Vec<Card>)>,
+    #[serde(with = "pairs")]
+    timeouts: BTreeMap<BotType, usize>,
+    #[serde(with = "pairs")]
+    matchups: BTreeMap<Vec<BotType>, MatchupStats>,
+    #[serde(with = "pairs")]
+    kills: BTreeMap<BotType, usize>,
+    #[serde(with = "pairs")]
+    deaths: BTreeMap<(BotType, EliminationCause), usize>,
+    #[serde(default, with = "pairs")]
+    failures: BTreeMap<BotType, usize>,
+    #[serde(default)]
+    failed_games: usize,
+    #[serde(default)]
+    tracker_accuracy: TrackerAccuracy,
+    #[serde(default, with = "pairs")]
+    bot_games: BTreeMap<BotType, usize>,
+    #[serde(default, with = "pairs")]
+    bot_coins: BTreeMap<BotType, BTreeMap<String, CoinsFlow>>,
+    #[serde(default, with = "pairs")]
+    cards: BTreeMap<Card, CardStats>,
+    #[serde(default, with = "pairs")]
+    action_rounds: BTreeMap<(BotType, usize, String), usize>,
 }
 
-pub fn collect_random_games_stats(
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct CardStats {
+    dealt: usize,
+    dealt_to_winner: usize,
+    survived: usize,
+    #[serde(with = "pairs")]
+    revealed: BTreeMap<EliminationCause, usize>,
+}
+
+impl CardStats {
+    fn add(&mut self, other: &CardStats) {
+        self.dealt += other.dealt;
+        self.dealt_to_winner += other.dealt_to_winner;
+        self.survived += other.survived;
+        for (cause, count) in other.revealed.iter() {
+            *self.revealed.entry(*cause).or_insert(0) += *count;
+        }
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct MatchupStats {
+    games: usize,
+    steps: Vec<usize>,
+    turns: Vec<usize>,
+    rounds: Vec<usize>,
+    coins: BTreeMap<String, CoinsFlow>,
+}
+
+impl Stats {
+    pub fn add(&mut self, bot_types: &[BotType], result: &RunResult) {
+        self.games += 1;
+        self.steps.push(result.end.step());
+        self.turns.push(result.end.turn());
+        self.rounds.push(result.end.round());
+        let winner = result.end.get_winner().unwrap();
+        self.winner_bot_type.push(bot_types[winner]);
+        let cards: Vec<Card> = result.begin.get_player_view(winner).cards.into();
+        self.winner_initial_cards.push(cards.clone());
+        self.winner_bot_type_and_initial_cards
+            .push((bot_types[winner], cards));
+        for (player, timeouts) in result.log.timeouts.iter().enumerate() {
+            *self.timeouts.entry(bot_types[player]).or_insert(0) += *timeouts;
+        }
+        for elimination in result.log.eliminations.iter() {
+            if let Some(killer) = elimination.killer {
+                *self.kills.entry(bot_types[killer]).or_insert(0) += 1;
+            }
+            *self
+                .deaths
+                .entry((bot_types[elimination.player], elimination.cause))
+                .or_insert(0) += 1;
+        }
+        for player in 0..bot_types.len() {
+            for card in result.begin.get_player_view(player).cards.iter() {
+                let card_stats = self.cards.entry(*card).or_default();
+                card_stats.dealt += 1;
+                if player == winner {
+                    card_stats.dealt_to_winner += 1;
+                }
+            }
+            if result.end.is_player_active(player) {
+                for card in result.end.get_player_view(player).cards.iter() {
+                    self.cards.entry(*card).or_default().survived += 1;
+                }
+            }
+        }
+        for ((player, round, name), count) in result.log.action_rounds.iter() {
+            *self
+                .action_rounds
+                .entry((bot_types[*player], *round, String::from(*name)))
+                .or_insert(0) += *count;
+        }
+        for (card, cause) in result.log.revealed_cards.iter() {
+            *self
+                .cards
+                .entry(*card)
+                .or_default()
+                .revealed
+                .entry(*cause)
+                .or_insert(0) += 1;
+        }
+        for (player, bot_type) in bot_types.iter().enumerate() {
+            *self.bot_games.entry(*bot_type).or_insert(0) += 1;
+            let bot_coins = self.bot_coins.entry(*bot_type).or_default();
+            for (name, flow) in result.log.player_coins[player].iter() {
+                bot_coins.entry(String::from(*name)).or_default().add(flow);
+            }
+        }
+        let matchup = self.matchups.entry(bot_types.to_vec()).or_default();
+        matchup.games += 1;
+        matchup.steps.push(result.end.step());
+        matchup.turns.push(result.end.turn());
+        matchup.rounds.push(result.end.round());
+        for (name, flow) in result.log.coins.iter() {
+            matchup
+                .coins
+                .entry(String::from(*name))
+                .or_default()
+                .add(flow);
+        }
+    }
+
+    pub fn add_failure(&mut self, bot_types: &[BotType], player: Option<usize>) {
+        self.games += 1;
+        self.failed_games += 1;
+        if let Some(player) = player {
+            *self.failures.entry(bot_types[player]).or_insert(0) += 1;
+            *self
+                .deaths
+                .entry((bot_types[player], EliminationCause::Forfeit))
+                .or_insert(0) += 1;
+        }
+    }
+
+    fn merge(&mut self, other: Stats) {
+        self.games += other.games;
+        self.steps.extend(other.steps);
+        self.turns.extend(other.turns);
+        self.rounds.extend(other.rounds);
+        self.winner_bot_type.extend(other.winner_bot_type);
+        self.winner_initial_cards.extend(other.winner_initial_cards);
+        self.winner_bot_type_and_initial_cards
+            .extend(other.winner_bot_type_and_initial_cards);
+        for (bot_type, timeouts) in other.timeouts {
+            *self.timeouts.entry(bot_type).or_insert(0) += timeouts;
+        }
+        for (bot_type, kills) in other.kills {
+            *self.kills.entry(bot_type).or_insert(0) += kills;
+        }
+        for (key, deaths) in other.deaths {
+            *self.deaths.entry(key).or_insert(0) += deaths;
+        }
+        for (bot_type, failures) in other.failures {
+            *self.failures.entry(bot_type).or_insert(0) += failures;
+        }
+        self.failed_games += other.failed_games;
+        self.tracker_accuracy.add(&other.tracker_accuracy);
+        for (key, count) in other.action_rounds {
+            *self.action_rounds.entry(key).or_insert(0) += count;
+        }
+        for (card, card_stats) in other.cards {
+            self.cards.entry(card).or_default().add(&card_stats);
+        }
+        for (bot_type, games) in other.bot_games {
+            *self.bot_games.entry(bot_type).or_insert(0) += games;
+        }
+        for (bot_type, other_coins) in other.bot_coins {
+            let bot_coins = self.bot_coins.entry(bot_type).or_default();
+            for (name, flow) in other_coins {
+                bot_coins.entry(name).or_default().add(&flow);
+            }
+        }
+        for (bot_types, other_matchup) in other.matchups {
+            let matchup = self.matchups.entry(bot_types).or_default();
+            matchup.games += other_matchup.games;
+            matchup.steps.extend(other_matchup.steps);
+            matchup.turns.extend(other_matchup.turns);
+            matchup.rounds.extend(other_matchup.rounds);
+            for (name, flow) in other_matchup.coins {
+                matchup.coins.entry(name).or_default().add(&flow);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for HeatmapFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(HeatmapFormat::Csv),
+            "json" => Ok(HeatmapFormat::Json),
+            _ => Err(format!("invalid heatmap format: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HeatmapCell<'a> {
+    bot_type: BotType,
+    round: usize,
+    action_type: &'a str,
+    count: usize,
+    share: f64,
+}
+
+pub fn write_action_heatmap<W: Write>(
+    stats: &Stats,
+    format: HeatmapFormat,
+    mut output: W,
+) -> std::io::Result<()> {
+    let mut totals: BTreeMap<(BotType, usize), usize> = BTreeMap::new();
+    for ((bot_type, round, _), count) in stats.action_rounds.iter() {
+        *totals.entry((*bot_type, *round)).or_insert(0) += *count;
+    }
+    let cells: Vec<HeatmapCell> = stats
+        .action_rounds
+        .iter()
+        .map(|((bot_type, round, action_type), count)| HeatmapCell {
+            bot_type: *bot_type,
+            round: *round,
+            action_type,
+            count: *count,
+            share: *count as f64 / totals[&(*bot_type, *round)] as f64,
+        })
+        .collect();
+    match format {
+        HeatmapFormat::Csv => {
+            writeln!(output, "bot_type,round,action_type,count,share")?;
+            for cell in cells.iter() {
+                writeln!(
+                    output,
+                    "{:?},{},{},{},{:.4}",
+                    cell.bot_type, cell.round, cell.action_type, cell.count, cell.share
+                )?;
+            }
+        }
+        HeatmapFormat::Json => {
+            serde_json::to_writer(&mut output, &cells)?;
+            writeln!(output)?;
+        }
+    }
+    Ok(())
+}
+
+mod pairs {
+    use std::iter::FromIterator;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, M, K, V, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        M: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StatsCheckpoint {
     seed: u64,
+    settings: Settings,
+    bot_types: Vec<BotType>,
+    random_lineups: bool,
+    #[serde(default)]
+    tracker_accuracy: bool,
+    stats: Stats,
+}
+
+impl StatsCheckpoint {
+    pub fn new(
+        seed: u64,
+        settings: Settings,
+        bot_types: Vec<BotType>,
+        random_lineups: bool,
+    ) -> Self {
+        Self {
+            seed,
+            settings,
+            bot_types,
+            random_lineups,
+            tracker_accuracy: false,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn with_tracker_accuracy(mut self, value: bool) -> Self {
+        self.tracker_accuracy = value;
+        self
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("failed to read stats checkpoint from {}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let tmp_path = format!("{}.tmp", path);
+        let file =
+            File::create(&tmp_path).map_err(|e| format!("failed to create {}: {}", tmp_path, e))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|e| format!("failed to write stats checkpoint to {}: {}", tmp_path, e))?;
+        std::fs::rename(&tmp_path, path)
+            .map_err(|e| format!("failed to rename {} to {}: {}", tmp_path, path, e))
+    }
+
+    pub fn resume(self, path: &str) -> Result<Self, String> {
+        let checkpoint = Self::load(path)?;
+        if checkpoint.seed != self.seed
+            || checkpoint.settings != self.settings
+            || checkpoint.bot_types != self.bot_types
+            || checkpoint.random_lineups != self.random_lineups
+            || checkpoint.tracker_accuracy != self.tracker_accuracy
+        {
+            return Err(format!(
+                "stats checkpoint {} has different parameters: seed={} settings={:?} bot_types={:?} random_lineups={} tracker_accuracy={}",
+                path,
+                checkpoint.seed,
+                checkpoint.settings,
+                checkpoint.bot_types,
+                checkpoint.random_lineups,
+                checkpoint.tracker_accuracy
+            ));
+        }
+        Ok(checkpoint)
+    }
+
+    pub fn games(&self) -> usize {
+        self.stats.games
+    }
+}
+
+pub struct CheckpointOutput<'a> {
+    pub path: &'a str,
+    pub interval: usize,
+}
+
+pub fn collect_random_games_stats(
+    mut checkpoint: StatsCheckpoint,
     number: usize,
     workers: usize,
-    bot_types: Vec<BotType>,
-    settings: Settings,
-) -> Stats {
-    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
-    let stats = Arc::new(Mutex::new(Stats::default()));
-    let threads = (0..workers)
+    time_control: TimeControl,
+    output: Option<CheckpointOutput>,
+    mut archive: Option<&mut Archive>,
+    isolation: Option<Isolation>,
+) -> Result<Stats, String> {
+    let record = archive.is_some();
+    let lineup_bot_types: Vec<BotType> = checkpoint.bot_types.iter().cloned().unique().collect();
+    let next = Arc::new(Mutex::new(checkpoint.stats.games));
+    let (sender, receiver) = mpsc::channel();
+    let threads = (0..workers.max(1))
         .map(|_| {
-            let worker_stats = stats.clone();
-            let worker_rng = rng.clone();
-            let worker_settings = settings.clone();
-            let worker_bot_types = bot_types.clone();
+            let worker_next = next.clone();
+            let worker_sender = sender.clone();
+            let worker_seed = checkpoint.seed;
+            let worker_settings = checkpoint.settings.clone();
+            let worker_bot_types = checkpoint.bot_types.clone();
+            let worker_lineup_bot_types = lineup_bot_types.clone();
+            let random_lineups = checkpoint.random_lineups;
+            let tracker_accuracy = checkpoint.tracker_accuracy;
+            let worker_isolation = isolation.clone();
             std::thread::spawn(move || loop {
-                {
-                    let mut locked_stats = worker_stats.lock().unwrap();
-                    if locked_stats.games >= number {
+                let index = {
+                    let mut locked_next = worker_next.lock().unwrap();
+                    if *locked_next >= number {
                         break;
                     }
-                    locked_stats.games += 1;
+                    *locked_next += 1;
+                    *locked_next - 1
+                };
+                let mut rng = StdRng::seed_from_u64(worker_seed.wrapping_add(index as u64));
+                let bot_types: Vec<BotType> = if random_lineups {
+                    (0..worker_bot_types.len())
+                        .map(|_| *worker_lineup_bot_types.choose(&mut rng).unwrap())
+                        .collect()
+                } else {
+                    worker_bot_types.clone()
+                };
+                let seed = rng.gen::<u64>();
+                let (game_stats, game) = if let Some(isolation) = worker_isolation.as_ref() {
+                    isolation
+                        .run(&IsolatedGame {
+                            seed,
+                            bot_types: bot_types.clone(),
+                            settings: worker_settings.clone(),
+                            time_control,
+                            record,
+                        })
+                        .into_stats(&bot_types)
+                } else {
+                    let result = run_game_with_bots(
+                        seed,
+                        &bot_types,
+                        worker_settings.clone(),
+                        false,
+                        None,
+                        0,
+                        time_control,
+                    );
+                    let mut game_stats = Stats::default();
+                    game_stats.add(&bot_types, &result);
+                    if tracker_accuracy {
+                        game_stats.tracker_accuracy =
+                            get_tracker_accuracy(seed, &bot_types, &result);
+                    }
+                    let game = if record {
+                        Some(ArchivedGame::new(seed, &bot_types, &result))
+                    } else {
+                        None
+                    };
+                    (game_stats, game)
+                };
+                if worker_sender.send((index, game_stats, game)).is_err() {
+                    break;
                 }
-                let seed = worker_rng.lock().unwrap().gen::<u64>();
-                let result = run_game_with_bots(
-                    seed,
-                    &worker_bot_types,
-                    worker_settings.clone(),
-                    false,
-                    None,
-                );
-                let mut locked_stats = worker_stats.lock().unwrap();
-                locked_stats.steps.push(result.end.step());
-                locked_stats.turns.push(result.end.turn());
-                locked_stats.rounds.push(result.end.round());
-                let winner = result.end.get_winner().unwrap();
-                locked_stats.winner_bot_type.push(worker_bot_types[winner]);
-                let cards: Vec<Card> = result.begin.get_player_view(winner).cards.into();
-                locked_stats.winner_initial_cards.push(cards.clone());
-                locked_stats
-                    .winner_bot_type_and_initial_cards
-                    .push((worker_bot_types[winner], cards));
             })
         })
         .collect::<Vec<_>>();
+    drop(sender);
+    let mut pending = BTreeMap::new();
+    for (index, game_stats, game) in receiver {
+        pending.insert(index, (game_stats, game));
+        while let Some((game_stats, game)) = pending.remove(&checkpoint.stats.games) {
+            checkpoint.stats.merge(game_stats);
+            if let (Some(archive), Some(game)) = (archive.as_mut(), game) {
+                archive.add(game)?;
+            }
+            if let Some(output) = output.as_ref() {
+                if checkpoint
+                    .stats
+                    .games
+                    .is_multiple_of(output.interval.max(1))
+                {
+                    checkpoint.save(output.path)?;
+                    tracing::info!(
+                        games = checkpoint.stats.games,
+                        path = output.path,
+                        "stats checkpoint saved"
+                    );
+                }
+            }
+        }
+    }
     for thread in threads {
         thread.join().unwrap();
     }
-    let result: Stats = stats.lock().unwrap().clone();
-    result
+    if let Some(output) = output.as_ref() {
+        checkpoint.save(output.path)?;
+    }
+    Ok(checkpoint.stats)
+}
+
+pub fn get_tracker_accuracy(
+    seed: u64,
+    bot_types: &[BotType],
+    result: &RunResult,
+) -> TrackerAccuracy {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new(result.begin.get_settings(), &mut rng);
+    let settings = game.get_settings();
+    let mut trackers: Vec<(usize, CardsTracker)> = bot_types
+        .iter()
+        .enumerate()
+        .filter(|(_, bot_type)| has_cards_tracker(**bot_type))
+        .map(|(player, _)| {
+            let view = game.get_player_view(player);
+            (player, CardsTracker::new(player, view.cards, &settings))
+        })
+        .collect();
+    let mut accuracy = TrackerAccuracy::default();
+    for decision in result.end.decisions() {
+        match decision {
+            Decision::Play(action) => {
+                assert_eq!(game.play(action, &mut rng), Ok(()));
+                for (player, tracker) in trackers.iter_mut() {
+                    if game.is_player_active(*player) {
+                        notify_tracker(tracker, &game.get_player_view(*player), action);
+                    }
+                }
+            }
+            Decision::Forfeit(forfeited) => {
+                game.forfeit(*forfeited);
+                for (player, tracker) in trackers.iter_mut() {
+                    if game.is_player_active(*player) {
+                        tracker.reset(&game.get_player_view(*player));
+                    }
+                }
+            }
+        }
+        if game.is_done() {
+            break;
+        }
+        for (player, tracker) in trackers.iter() {
+            if game.is_player_active(*player) {
+                accuracy.add(&tracker.get_accuracy(&game));
+            }
+        }
+    }
+    accuracy
+}
+
+#[derive(Default, Clone)]
+pub struct DuelStats {
+    games: usize,
+    wins: [usize; 2],
+    wins_by_seat: [usize; 2],
+    // Number of pairs of swapped seat games where the first bot won 0, 1 or 2 games.
+    pair_wins: [usize; 3],
+}
+
+impl DuelStats {
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    pub fn pairs(&self) -> usize {
+        self.pair_wins.iter().sum()
+    }
+
+    pub fn win_rate(&self, index: usize) -> f64 {
+        self.wins[index] as f64 / self.games.max(1) as f64
+    }
+}
+
+pub fn collect_duel_stats(
+    seed: u64,
+    pairs: usize,
+    bot_types: [BotType; 2],
+    settings: Settings,
+    time_control: TimeControl,
+) -> DuelStats {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut stats = DuelStats::default();
+    let mut context = MatchContext::new(bot_types.len());
+    for _ in 0..pairs {
+        let seed = rng.gen::<u64>();
+        let mut pair_wins = 0;
+        for first in 0..2 {
+            let lineup: Vec<usize> = (0..settings.players_number)
+                .map(|seat| (first + seat) % 2)
+                .collect();
+            let result = run_match_game(
+                seed,
+                &bot_types,
+                lineup,
+                settings.clone(),
+                time_control,
+                &mut context,
+            );
+            let winner = result.end.get_winner().unwrap();
+            stats.games += 1;
+            stats.wins[context.participant(winner)] += 1;
+            if context.participant(winner) == 0 {
+                stats.wins_by_seat[first] += 1;
+                pair_wins += 1;
+            }
+        }
+        stats.pair_wins[pair_wins] += 1;
+    }
+    stats
+}
+
+pub fn print_duel_stats(stats: &DuelStats, bot_types: [BotType; 2]) {
+    println!("games: {}", stats.games);
+    println!("pairs: {}", stats.pairs());
+    for (index, bot_type) in bot_types.iter().enumerate() {
+        let mut pair_wins = stats.pair_wins;
+        if index == 1 {
+            pair_wins.reverse();
+        }
+        let (low, high) = get_paired_interval(&pair_wins);
+        println!(
+            "{:?} wins={} win_rate={:.3} 95%_ci=[{:.3}, {:.3}]",
+            bot_type,
+            stats.wins[index],
+            stats.wins[index] as f64 / stats.games as f64,
+            low,
+            high
+        );
+    }
+    println!(
+        "{:?} wins as first seat={} as second seat={}",
+        bot_types[0], stats.wins_by_seat[0], stats.wins_by_seat[1]
+    );
+    println!(
+        "{:?} pairs won={} split={} lost={} sign_test_p={:.4}",
+        bot_types[0],
+        stats.pair_wins[2],
+        stats.pair_wins[1],
+        stats.pair_wins[0],
+        get_sign_test_p_value(stats.pair_wins[2], stats.pair_wins[0])
+    );
+}
+
+// Games of a pair share the seed so they are not independent. Each pair is scored as a single
+// observation of 0, 0.5 or 1 and the interval is computed for the mean pair score.
+pub fn get_paired_interval(pair_wins: &[usize; 3]) -> (f64, f64) {
+    let pairs = pair_wins.iter().sum::<usize>();
+    if pairs < 2 {
+        return (0.0, 1.0);
+    }
+    const Z: f64 = 1.96;
+    let n = pairs as f64;
+    let scores = [0.0, 0.5, 1.0];
+    let mean = pair_wins
+        .iter()
+        .zip(scores)
+        .map(|(count, score)| *count as f64 * score)
+        .sum::<f64>()
+        / n;
+    let variance = pair_wins
+        .iter()
+        .zip(scores)
+        .map(|(count, score)| *count as f64 * (score - mean).powi(2))
+        .sum::<f64>()
+        / (n - 1.0);
+    let margin = Z * (variance / n).sqrt();
+    ((mean - margin).max(0.0), (mean + margin).min(1.0))
+}
+
+// Two-sided exact sign test over pairs won and lost by the same bot, split pairs are ties and
+// don't count.
+pub fn get_sign_test_p_value(wins: usize, losses: usize) -> f64 {
+    let n = wins + losses;
+    let mut log_probability = n as f64 * 0.5f64.ln();
+    let mut tail = 0.0;
+    for k in 0..=wins.min(losses) {
+        tail += log_probability.exp();
+        log_probability += ((n - k) as f64 / (k + 1) as f64).ln();
+    }
+    (2.0 * tail).min(1.0)
+}
+
+pub fn get_wilson_interval(successes: usize, trials: usize) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    const Z: f64 = 1.96;
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let denominator = 1.0 + Z * Z / n;
+    let center = (p + Z * Z / (2.0 * n)) / denominator;
+    let margin = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt() / denominator;
+    (center - margin, center + margin)
 }
 
 pub fn print_stats(stats: &Stats) {
@@ -170,6 +802,149 @@ pub fn print_stats(stats: &Stats) {
         );
     }
     println!();
+    println!("card lifecycle");
+    for (card, card_stats) in stats.cards.iter() {
+        print!(
+            "{:?} dealt={} dealt_to_winner={} win_rate={:.3} survived={} {:.1}% revealed",
+            card,
+            card_stats.dealt,
+            card_stats.dealt_to_winner,
+            card_stats.dealt_to_winner as f64 / card_stats.dealt.max(1) as f64,
+            card_stats.survived,
+            card_stats.survived as f64 / card_stats.dealt.max(1) as f64 * 100.0
+        );
+        for (cause, count) in card_stats.revealed.iter() {
+            print!(" {:?}={}", cause, count);
+        }
+        println!();
+    }
+    println!();
+    let mut timeouts: Vec<(BotType, usize)> = stats
+        .timeouts
+        .iter()
+        .map(|(bot_type, timeouts)| (*bot_type, *timeouts))
+        .collect();
+    timeouts.sort();
+    println!("timeouts");
+    for (bot_type, timeouts) in timeouts.iter() {
+        println!("{:?} {}", bot_type, timeouts);
+    }
+    println!();
+    let mut kills: Vec<(BotType, usize)> = stats
+        .kills
+        .iter()
+        .map(|(bot_type, kills)| (*bot_type, *kills))
+        .collect();
+    kills.sort();
+    println!("kills");
+    for (bot_type, kills) in kills.iter() {
+        println!("{:?} {}", bot_type, kills);
+    }
+    println!();
+    let mut deaths: Vec<((BotType, EliminationCause), usize)> = stats
+        .deaths
+        .iter()
+        .map(|(key, deaths)| (*key, *deaths))
+        .collect();
+    deaths.sort();
+    println!("deaths by cause");
+    for ((bot_type, cause), deaths) in deaths.iter() {
+        println!("{:?} {:?} {}", bot_type, cause, deaths);
+    }
+    println!();
+    if stats.failed_games > 0 {
+        println!("failed games: {}", stats.failed_games);
+        for (bot_type, failures) in stats.failures.iter() {
+            println!("{:?} {}", bot_type, failures);
+        }
+        println!();
+    }
+    let accuracy = &stats.tracker_accuracy;
+    if accuracy.steps > 0 {
+        println!("tracker accuracy");
+        println!("steps {}", accuracy.steps);
+        println!(
+            "hypotheses mean={:.2} max={}",
+            accuracy.hypotheses as f64 / accuracy.steps as f64,
+            accuracy.max_hypotheses
+        );
+        println!(
+            "true_state_found {} {}%",
+            accuracy.true_state_found,
+            accuracy.true_state_found as f64 / accuracy.steps as f64 * 100.0
+        );
+        println!(
+            "true_hand hands={} mean_probability={:.4} mean_rank={:.2}",
+            accuracy.hands,
+            accuracy.true_hand_probability / accuracy.hands.max(1) as f64,
+            accuracy.true_hand_rank as f64 / accuracy.hands.max(1) as f64
+        );
+        println!();
+    }
+    if !stats.bot_coins.is_empty() {
+        println!("coins by bot type");
+        for (bot_type, bot_coins) in stats.bot_coins.iter() {
+            let games = stats.bot_games.get(bot_type).cloned().unwrap_or(0);
+            println!("{:?} games={}", bot_type, games);
+            let games = games.max(1) as f64;
+            print_coins_per_game(
+                "earned",
+                bot_coins.iter().map(|(name, flow)| (name, flow.earned)),
+                games,
+            );
+            print_coins_per_game(
+                "spent",
+                bot_coins.iter().map(|(name, flow)| (name, flow.spent)),
+                games,
+            );
+        }
+        println!();
+    }
+    println!("matchups: {}", stats.matchups.len());
+    for (bot_types, matchup) in stats.matchups.iter() {
+        println!("{:?} games={}", bot_types, matchup.games);
+        print_distribution("steps", &matchup.steps);
+        print_distribution("turns", &matchup.turns);
+        print_distribution("rounds", &matchup.rounds);
+        for (name, flow) in matchup.coins.iter() {
+            println!(
+                "  coins {} earned={} spent={} earned_per_game={:.2} spent_per_game={:.2}",
+                name,
+                flow.earned,
+                flow.spent,
+                flow.earned as f64 / matchup.games as f64,
+                flow.spent as f64 / matchup.games as f64
+            );
+        }
+    }
+    println!();
+}
+
+fn print_coins_per_game<'a>(
+    name: &str,
+    values: impl Iterator<Item = (&'a String, usize)>,
+    games: f64,
+) {
+    let values: Vec<(&String, usize)> = values.filter(|(_, coins)| *coins > 0).collect();
+    let total: usize = values.iter().map(|(_, coins)| *coins).sum();
+    print!("  {} per_game={:.2}", name, total as f64 / games);
+    for (source, coins) in values.iter() {
+        print!(" {}={:.2}", source, *coins as f64 / games);
+    }
+    println!();
+}
+
+fn print_distribution(name: &str, values: &[usize]) {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    println!(
+        "  {} min={} median={} mean={:.2} max={}",
+        name,
+        sorted[0],
+        sorted[sorted.len() / 2],
+        sorted.iter().sum::<usize>() as f64 / sorted.len() as f64,
+        sorted[sorted.len() - 1]
+    );
 }
 
 fn count(values: &[usize]) -> BTreeMap<usize, usize> {
@@ -179,3 +954,224 @@ fn count(values: &[usize]) -> BTreeMap<usize, usize> {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    fn make_checkpoint() -> StatsCheckpoint {
+        StatsCheckpoint::new(
+            42,
+            Settings {
+                players_number: 3,
+                card_counts: CardCounts::uniform(2),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+            vec![
+                BotType::Random,
+                BotType::HonestCarefulRandom,
+                BotType::Random,
+            ],
+            true,
+        )
+    }
+
+    #[test]
+    fn duel_stats_should_count_each_pair_once() {
+        let stats = collect_duel_stats(
+            42,
+            10,
+            [BotType::HonestCarefulRandom, BotType::Random],
+            Settings {
+                players_number: 2,
+                card_counts: CardCounts::uniform(3),
+                cards_per_player: 2,
+                rules: Rules::default(),
+            },
+            TimeControl::default(),
+        );
+        assert_eq!(stats.games(), 20);
+        assert_eq!(stats.pairs(), 10);
+        assert_eq!(stats.pair_wins[1] + 2 * stats.pair_wins[2], stats.wins[0]);
+        assert_eq!(stats.pair_wins[1] + 2 * stats.pair_wins[0], stats.wins[1]);
+    }
+
+    #[test]
+    fn paired_interval_should_be_narrow_when_every_pair_is_split() {
+        assert_eq!(get_paired_interval(&[0, 100, 0]), (0.5, 0.5));
+        let (low, high) = get_paired_interval(&[50, 0, 50]);
+        let (wilson_low, wilson_high) = get_wilson_interval(100, 200);
+        assert!((low + high - 1.0).abs() < 1e-9);
+        assert!(high - low > wilson_high - wilson_low);
+        assert_eq!(get_paired_interval(&[0, 1, 0]), (0.0, 1.0));
+    }
+
+    #[test]
+    fn sign_test_should_ignore_split_pairs() {
+        assert_eq!(get_sign_test_p_value(0, 0), 1.0);
+        assert_eq!(get_sign_test_p_value(5, 5), 1.0);
+        assert!((get_sign_test_p_value(10, 0) - 2.0 / 1024.0).abs() < 1e-12);
+        assert!((get_sign_test_p_value(1, 9) - 22.0 / 1024.0).abs() < 1e-12);
+        assert!(get_sign_test_p_value(600, 400) < 1e-9);
+    }
+
+    #[test]
+    fn resumed_stats_should_match_uninterrupted_run() {
+        let path = std::env::temp_dir()
+            .join(format!("coup-stats-checkpoint-{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let full = collect_random_games_stats(
+            make_checkpoint(),
+            6,
+            2,
+            TimeControl::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        collect_random_games_stats(
+            make_checkpoint(),
+            4,
+            2,
+            TimeControl::default(),
+            Some(CheckpointOutput {
+                path: &path,
+                interval: 2,
+            }),
+            None,
+            None,
+        )
+        .unwrap();
+        let checkpoint = make_checkpoint().resume(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(checkpoint.games(), 4);
+        let resumed =
+            collect_random_games_stats(checkpoint, 6, 3, TimeControl::default(), None, None, None)
+                .unwrap();
+        assert_eq!(
+            serde_json::to_string(&resumed).unwrap(),
+            serde_json::to_string(&full).unwrap()
+        );
+    }
+
+    #[test]
+    fn bot_coins_should_balance_steal_transfers() {
+        let stats = collect_random_games_stats(
+            make_checkpoint(),
+            10,
+            2,
+            TimeControl::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(stats.bot_games.values().sum::<usize>(), 30);
+        let total = |name: &str| {
+            let mut flow = CoinsFlow::default();
+            for bot_coins in stats.bot_coins.values() {
+                if let Some(v) = bot_coins.get(name) {
+                    flow.add(v);
+                }
+            }
+            flow
+        };
+        assert!(total("Income").earned > 0);
+        assert_eq!(total("Income").spent, 0);
+        assert_eq!(total("Steal").earned, total("Steal").spent);
+        assert_eq!(total("Coup").earned, 0);
+    }
+
+    #[test]
+    fn card_stats_should_account_for_dealt_cards() {
+        let stats = collect_random_games_stats(
+            make_checkpoint(),
+            10,
+            2,
+            TimeControl::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let dealt: usize = stats.cards.values().map(|v| v.dealt).sum();
+        let dealt_to_winner: usize = stats.cards.values().map(|v| v.dealt_to_winner).sum();
+        let survived: usize = stats.cards.values().map(|v| v.survived).sum();
+        let revealed: usize = stats
+            .cards
+            .values()
+            .map(|v| v.revealed.values().sum::<usize>())
+            .sum();
+        assert_eq!(dealt, 60);
+        assert_eq!(dealt_to_winner, 20);
+        assert!((10..=20).contains(&survived));
+        assert!((40..=50).contains(&revealed));
+        assert!(revealed + survived <= dealt);
+    }
+
+    #[test]
+    fn action_heatmap_should_have_a_row_per_bot_type_round_and_action_type() {
+        let stats = collect_random_games_stats(
+            make_checkpoint(),
+            4,
+            1,
+            TimeControl::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut csv = Vec::new();
+        write_action_heatmap(&stats, HeatmapFormat::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("bot_type,round,action_type,count,share"));
+        assert_eq!(lines.count(), stats.action_rounds.len());
+        assert!(csv.contains("HonestCarefulRandom,1,"));
+        let mut json = Vec::new();
+        write_action_heatmap(&stats, HeatmapFormat::Json, &mut json).unwrap();
+        let cells: Vec<serde_json::Value> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(cells.len(), stats.action_rounds.len());
+        let total: u64 = cells.iter().map(|v| v["count"].as_u64().unwrap()).sum();
+        let steps: usize = stats.steps.iter().sum();
+        assert_eq!(total as usize, steps);
+    }
+
+    #[test]
+    fn tracker_accuracy_should_always_find_true_state() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let bot_types = [
+            BotType::HonestCarefulRandom,
+            BotType::Random,
+            BotType::InfoSeeking,
+        ];
+        for seed in 0..5 {
+            let result = run_game_with_bots(
+                seed,
+                &bot_types,
+                settings.clone(),
+                false,
+                None,
+                0,
+                TimeControl::default(),
+            );
+            let accuracy = get_tracker_accuracy(seed, &bot_types, &result);
+            assert!(accuracy.steps > 0);
+            assert_eq!(accuracy.true_state_found, accuracy.steps);
+            assert!(accuracy.true_hand_probability > 0.0);
+            assert!(accuracy.true_hand_probability <= accuracy.hands as f64);
+            assert!(accuracy.true_hand_rank >= accuracy.hands);
+        }
+    }
+}
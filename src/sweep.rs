@@ -0,0 +1,206 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bots::{Bot, BotParams, HonestCarefulRandomBot};
+use crate::game::{Game, Settings};
+use crate::run::{make_bot, make_bots_rng, run_game, BotType, TimeControl};
+
+const PRECISION: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamRange {
+    pub name: String,
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+impl FromStr for ParamRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, range) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid param range, expected name=start:end:step: {}", s))?;
+        let values: Vec<f64> = range
+            .split(':')
+            .map(|v| {
+                v.parse::<f64>()
+                    .map_err(|e| format!("invalid param range value {}: {}", v, e))
+            })
+            .collect::<Result<_, _>>()?;
+        if values.len() != 3 {
+            return Err(format!(
+                "invalid param range, expected name=start:end:step: {}",
+                s
+            ));
+        }
+        if values[2] <= 0.0 || values[0] > values[1] {
+            return Err(format!(
+                "invalid param range, expected start <= end and positive step: {}",
+                s
+            ));
+        }
+        BotParams::default().set(name, values[0])?;
+        Ok(Self {
+            name: String::from(name),
+            start: values[0],
+            end: values[1],
+            step: values[2],
+        })
+    }
+}
+
+impl ParamRange {
+    pub fn values(&self) -> Vec<f64> {
+        let count = ((self.end - self.start) / self.step + PRECISION).floor() as usize;
+        (0..=count)
+            .map(|index| self.start + index as f64 * self.step)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepPoint {
+    pub value: f64,
+    pub games: usize,
+    pub wins: usize,
+}
+
+pub fn run_sweep(
+    range: &ParamRange,
+    opponent: BotType,
+    pairs: usize,
+    seed: u64,
+    settings: &Settings,
+) -> Result<Vec<SweepPoint>, String> {
+    let mut result = Vec::new();
+    for value in range.values() {
+        let mut params = BotParams::default();
+        params.set(&range.name, value)?;
+        let mut point = SweepPoint {
+            value,
+            games: 0,
+            wins: 0,
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        // Each pair plays the same deal once per seat, a game without a winner counts as lost.
+        for _ in 0..pairs {
+            let seed = rng.gen::<u64>();
+            for seat in 0..settings.players_number {
+                if play_game(seed, seat, params, opponent, settings) == Some(seat) {
+                    point.wins += 1;
+                }
+                point.games += 1;
+            }
+        }
+        tracing::info!(
+            name = range.name,
+            value,
+            wins = point.wins,
+            games = point.games,
+            "sweep point"
+        );
+        result.push(point);
+    }
+    Ok(result)
+}
+
+fn play_game(
+    seed: u64,
+    seat: usize,
+    params: BotParams,
+    opponent: BotType,
+    settings: &Settings,
+) -> Option<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new(settings.clone(), &mut rng);
+    let mut bots_rng = make_bots_rng(seed);
+    let mut bots: Vec<Box<dyn Bot>> = (0..settings.players_number)
+        .map(|player| -> Box<dyn Bot> {
+            let view = game.get_player_view(player);
+            if player == seat {
+                let bot_rng = StdRng::seed_from_u64(bots_rng.gen());
+                Box::new(HonestCarefulRandomBot::new(&view, settings, bot_rng).with_params(params))
+            } else {
+                make_bot(opponent, &view, settings, &mut bots_rng)
+            }
+        })
+        .collect();
+    run_game(
+        &mut bots,
+        &mut game,
+        &mut rng,
+        false,
+        None,
+        0,
+        TimeControl::default(),
+    );
+    game.get_winner()
+}
+
+pub fn write_csv<W: Write>(
+    name: &str,
+    points: &[SweepPoint],
+    mut output: W,
+) -> std::io::Result<()> {
+    writeln!(output, "{},games,wins,win_rate", name)?;
+    for point in points.iter() {
+        writeln!(
+            output,
+            "{},{},{},{:.4}",
+            point.value,
+            point.games,
+            point.wins,
+            point.wins as f64 / point.games.max(1) as f64
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Rules;
+    use crate::game::CardCounts;
+
+    use super::*;
+
+    #[test]
+    fn param_range_should_parse_and_produce_inclusive_values() {
+        let range: ParamRange = "bluff_rate=0.0:1.0:0.25".parse().unwrap();
+        assert_eq!(range.values(), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(
+            "bluff_rate=0.0:1.0:0.1"
+                .parse::<ParamRange>()
+                .unwrap()
+                .values()
+                .len(),
+            11
+        );
+        assert!("bluff_rate=0.0:1.0".parse::<ParamRange>().is_err());
+        assert!("unknown=0.0:1.0:0.1".parse::<ParamRange>().is_err());
+        assert!("bluff_rate=1.0:0.0:0.1".parse::<ParamRange>().is_err());
+    }
+
+    #[test]
+    fn run_sweep_should_play_games_for_each_value() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let range: ParamRange = "bluff_rate=0.0:1.0:0.5".parse().unwrap();
+        let points = run_sweep(&range, BotType::Random, 3, 42, &settings).unwrap();
+        assert_eq!(points.len(), 3);
+        assert!(points.iter().all(|v| v.games == 6 && v.wins <= 6));
+        let mut output = Vec::new();
+        write_csv(&range.name, &points, &mut output).unwrap();
+        let csv = String::from_utf8(output).unwrap();
+        assert!(csv.starts_with("bluff_rate,games,wins,win_rate\n0,6,"));
+        assert_eq!(csv.lines().count(), 4);
+    }
+}
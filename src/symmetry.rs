@@ -0,0 +1,79 @@
+use crate::fsm::{Action, ActionType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeatRotation {
+    shift: usize,
+    players_number: usize,
+}
+
+impl SeatRotation {
+    pub fn new(player: usize, players_number: usize) -> Self {
+        Self {
+            shift: player,
+            players_number,
+        }
+    }
+
+    pub fn canonical_seat(&self, seat: usize) -> usize {
+        (seat + self.players_number - self.shift) % self.players_number
+    }
+
+    pub fn original_seat(&self, seat: usize) -> usize {
+        (seat + self.shift) % self.players_number
+    }
+
+    pub fn canonical_action(&self, action: &Action) -> Action {
+        relabel_action(action, |seat| self.canonical_seat(seat))
+    }
+
+    pub fn original_action(&self, action: &Action) -> Action {
+        relabel_action(action, |seat| self.original_seat(seat))
+    }
+}
+
+fn relabel_action<F: Fn(usize) -> usize>(action: &Action, f: F) -> Action {
+    let action_type = match &action.action_type {
+        ActionType::Coup(target) => ActionType::Coup(f(*target)),
+        ActionType::Assassinate(target) => ActionType::Assassinate(f(*target)),
+        ActionType::Steal(target) => ActionType::Steal(f(*target)),
+        v => v.clone(),
+    };
+    Action {
+        player: f(action.player),
+        action_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_action_should_put_player_at_first_seat() {
+        let action = Action {
+            player: 2,
+            action_type: ActionType::Steal(0),
+        };
+        let rotation = SeatRotation::new(action.player, 3);
+        let canonical_action = rotation.canonical_action(&action);
+        assert_eq!(
+            canonical_action,
+            Action {
+                player: 0,
+                action_type: ActionType::Steal(1),
+            }
+        );
+        assert_eq!(rotation.original_action(&canonical_action), action);
+    }
+
+    #[test]
+    fn seat_rotation_should_round_trip_all_seats() {
+        for player in 0..4 {
+            let rotation = SeatRotation::new(player, 4);
+            assert_eq!(rotation.canonical_seat(player), 0);
+            for seat in 0..4 {
+                assert_eq!(rotation.original_seat(rotation.canonical_seat(seat)), seat);
+            }
+        }
+    }
+}
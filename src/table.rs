@@ -0,0 +1,1252 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bots::{notify_bot, Bot};
+use crate::chat::{get_chat_path, ChatMessage};
+use crate::fairness::Reveal;
+use crate::fsm::{
+    get_claimant_pass, resolve_responses, Action, ActionType, Card, StateType, CARDS_PER_PLAYER,
+};
+use crate::game::{get_available_actions, ActionsIterator, CardCounts, Game, Settings};
+use crate::interactive::{get_action_type, parse_game_action_type, to_game_command};
+use crate::run::{make_bot, BotType};
+use crate::GameParams;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recipient {
+    Channel,
+    User(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub recipient: Recipient,
+    pub text: String,
+}
+
+impl Reply {
+    fn channel(text: String) -> Self {
+        Self {
+            recipient: Recipient::Channel,
+            text,
+        }
+    }
+
+    fn user(user: &str, text: String) -> Self {
+        Self {
+            recipient: Recipient::User(String::from(user)),
+            text,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AutoPass {
+    pub all_passed: bool,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WindowState {
+    Open { deadline: Option<Instant> },
+    AllPassed,
+    Expired,
+}
+
+// Challenge or block window where the claimant passes unless one of the waiting players responds.
+// The window closes by the auto pass policy once all waiting players passed or the deadline is
+// reached, after that the claimant's pass is played without waiting for humans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResponseWindow {
+    pass: Action,
+    waiting: Vec<usize>,
+    state: WindowState,
+}
+
+impl ResponseWindow {
+    fn open(
+        state_type: &StateType,
+        available_actions: &[Action],
+        policy: &AutoPass,
+        now: Instant,
+    ) -> Option<Self> {
+        let pass = get_claimant_pass(state_type, available_actions)?.clone();
+        let mut waiting = Vec::new();
+        for action in available_actions.iter() {
+            if action.player != pass.player && !waiting.contains(&action.player) {
+                waiting.push(action.player);
+            }
+        }
+        let mut window = Self {
+            pass,
+            waiting,
+            state: WindowState::Open {
+                deadline: policy.timeout.map(|v| now + v),
+            },
+        };
+        window.close_if_all_passed(policy);
+        Some(window)
+    }
+
+    fn pass(&mut self, player: usize, policy: &AutoPass) -> bool {
+        let waiting = self.waiting.len();
+        self.waiting.retain(|v| *v != player);
+        if self.waiting.len() == waiting {
+            return false;
+        }
+        self.close_if_all_passed(policy);
+        true
+    }
+
+    fn update(&mut self, now: Instant) {
+        if self.deadline().is_some_and(|v| v <= now) {
+            self.state = WindowState::Expired;
+        }
+    }
+
+    fn close_if_all_passed(&mut self, policy: &AutoPass) {
+        if policy.all_passed && self.waiting.is_empty() && self.is_open() {
+            self.state = WindowState::AllPassed;
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        match self.state {
+            WindowState::Open { deadline } => deadline,
+            _ => None,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(self.state, WindowState::Open { .. })
+    }
+
+    fn is_waiting(&self, player: usize) -> bool {
+        self.waiting.contains(&player)
+    }
+
+    fn get_auto_pass(&self) -> Option<&Action> {
+        (!self.is_open()).then_some(&self.pass)
+    }
+}
+
+struct Seat {
+    name: String,
+    user: Option<String>,
+    reserved: Option<String>,
+    bot: Option<Box<dyn Bot>>,
+    seen: usize,
+}
+
+struct Spectator {
+    user: String,
+    sent: usize,
+}
+
+struct Table {
+    seats: Vec<Seat>,
+    bot_types: Vec<BotType>,
+    game: Option<Game>,
+    reveal: Option<Reveal>,
+    rng: StdRng,
+    auto_pass: AutoPass,
+    window: Option<ResponseWindow>,
+    log: Vec<String>,
+    spectators: Vec<Spectator>,
+    spectator_log: Vec<String>,
+    spectator_delay: usize,
+    actions: Vec<Action>,
+    chat: Vec<ChatMessage>,
+    replay_dir: Option<PathBuf>,
+}
+
+pub struct TableManager {
+    cards_per_type: usize,
+    cards_per_player: usize,
+    bot_type: BotType,
+    spectator_delay: usize,
+    replay_dir: Option<PathBuf>,
+    auto_pass: AutoPass,
+    rng: StdRng,
+    tables: HashMap<String, Table>,
+}
+
+impl TableManager {
+    pub fn new(cards_per_type: usize, bot_type: BotType, seed: Option<u64>) -> Self {
+        Self {
+            cards_per_type,
+            cards_per_player: CARDS_PER_PLAYER,
+            bot_type,
+            spectator_delay: 0,
+            replay_dir: None,
+            auto_pass: AutoPass::default(),
+            rng: match seed {
+                Some(v) => StdRng::seed_from_u64(v),
+                None => StdRng::from_entropy(),
+            },
+            tables: HashMap::new(),
+        }
+    }
+
+    pub fn with_cards_per_player(mut self, value: usize) -> Self {
+        self.cards_per_player = value;
+        self
+    }
+
+    pub fn with_spectator_delay(mut self, value: usize) -> Self {
+        self.spectator_delay = value;
+        self
+    }
+
+    pub fn with_replay_dir(mut self, value: Option<PathBuf>) -> Self {
+        self.replay_dir = value;
+        self
+    }
+
+    pub fn with_auto_pass(mut self, value: AutoPass) -> Self {
+        self.auto_pass = value;
+        self
+    }
+
+    pub fn handle(&mut self, channel: &str, user: &str, user_name: &str, text: &str) -> Vec<Reply> {
+        match self.handle_command(channel, user, user_name, text) {
+            Ok(v) => v,
+            Err(e) => vec![Reply::channel(e)],
+        }
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.tables
+            .values()
+            .filter_map(|v| v.window.as_ref()?.deadline())
+            .min()
+    }
+
+    // Closes response windows with the deadline before now and plays the auto pass on each of
+    // these tables, returns the replies per channel.
+    pub fn expire(&mut self, now: Instant) -> Vec<(String, Vec<Reply>)> {
+        let mut result = Vec::new();
+        for (channel, table) in self.tables.iter_mut() {
+            let Some(window) = table.window.as_mut() else {
+                continue;
+            };
+            window.update(now);
+            if window.state == WindowState::Expired {
+                result.push((channel.clone(), advance(table)));
+            }
+        }
+        result
+    }
+
+    fn handle_command(
+        &mut self,
+        channel: &str,
+        user: &str,
+        user_name: &str,
+        text: &str,
+    ) -> Result<Vec<Reply>, String> {
+        let mut words = text.split_whitespace();
+        match words.next() {
+            None | Some("help") => Ok(vec![Reply::channel(String::from(HELP))]),
+            Some("new") => {
+                if self.tables.get(channel).is_some_and(|v| v.game.is_some()) {
+                    return Err(String::from("Game is in progress, end it first"));
+                }
+                self.tables.insert(
+                    String::from(channel),
+                    Table {
+                        seats: Vec::new(),
+                        bot_types: Vec::new(),
+                        game: None,
+                        reveal: None,
+                        rng: StdRng::seed_from_u64(self.rng.gen()),
+                        auto_pass: self.auto_pass,
+                        window: None,
+                        log: Vec::new(),
+                        spectators: Vec::new(),
+                        spectator_log: Vec::new(),
+                        spectator_delay: self.spectator_delay,
+                        actions: Vec::new(),
+                        chat: Vec::new(),
+                        replay_dir: self.replay_dir.clone(),
+                    },
+                );
+                Ok(vec![Reply::channel(String::from(
+                    "New table is open, use join to take a seat",
+                ))])
+            }
+            Some("join") => {
+                let table = self.get_waiting_table(channel)?;
+                if table.seats.iter().any(|v| v.user.as_deref() == Some(user)) {
+                    return Err(format!("{} already has a seat", user_name));
+                }
+                if let Some(index) = table
+                    .seats
+                    .iter()
+                    .position(|v| v.user.is_none() && v.reserved.as_deref() == Some(user))
+                {
+                    if table.seats[index].name == format_mention(user) {
+                        table.seats[index].name = get_seat_name(&table.seats, user_name);
+                    }
+                    table.seats[index].user = Some(String::from(user));
+                    return Ok(vec![Reply::channel(format!(
+                        "{} takes reserved seat {}",
+                        table.seats[index].name, index
+                    ))]);
+                }
+                let name = get_seat_name(&table.seats, user_name);
+                table.seats.push(Seat {
+                    name: name.clone(),
+                    user: Some(String::from(user)),
+                    reserved: None,
+                    bot: None,
+                    seen: 0,
+                });
+                Ok(vec![Reply::channel(format!(
+                    "{} takes seat {}",
+                    name,
+                    table.seats.len() - 1
+                ))])
+            }
+            Some("reserve") => {
+                let reserved = match words.next() {
+                    Some(v) => parse_user_id(v)?,
+                    None => return Err(String::from("Reserve requires a user mention")),
+                };
+                let name = words.collect::<Vec<_>>().join("_");
+                let table = self.get_waiting_table(channel)?;
+                if table.seats.iter().any(|v| {
+                    v.user.as_deref() == Some(reserved.as_str())
+                        || v.reserved.as_deref() == Some(reserved.as_str())
+                }) {
+                    return Err(format!("{} already has a seat", format_mention(&reserved)));
+                }
+                let name = if name.is_empty() {
+                    format_mention(&reserved)
+                } else if table.seats.iter().any(|v| v.name == name) {
+                    return Err(format!("{} already has a seat", name));
+                } else {
+                    name
+                };
+                table.seats.push(Seat {
+                    name: name.clone(),
+                    user: None,
+                    reserved: Some(reserved),
+                    bot: None,
+                    seen: 0,
+                });
+                Ok(vec![Reply::channel(format!(
+                    "Seat {} is reserved for {}",
+                    table.seats.len() - 1,
+                    name
+                ))])
+            }
+            Some("bot") => {
+                let bot_type = match words.next() {
+                    Some(v) => v.parse()?,
+                    None => self.bot_type,
+                };
+                let table = self.get_waiting_table(channel)?;
+                let name = get_seat_name(&table.seats, &format!("bot{}", table.seats.len()));
+                table.seats.push(Seat {
+                    name: name.clone(),
+                    user: None,
+                    reserved: None,
+                    bot: None,
+                    seen: 0,
+                });
+                table.bot_types.push(bot_type);
+                Ok(vec![Reply::channel(format!(
+                    "{} ({:?}) takes seat {}",
+                    name,
+                    bot_type,
+                    table.seats.len() - 1
+                ))])
+            }
+            Some("watch") => {
+                let table = self
+                    .tables
+                    .get_mut(channel)
+                    .ok_or_else(|| String::from("No table in this channel"))?;
+                if table.seats.iter().any(|v| v.user.as_deref() == Some(user)) {
+                    return Err(format!("{} has a seat and can't watch", user_name));
+                }
+                if table.spectators.iter().any(|v| v.user == user) {
+                    return Err(format!("{} is already watching", user_name));
+                }
+                table.spectators.push(Spectator {
+                    user: String::from(user),
+                    sent: 0,
+                });
+                let mut replies = vec![Reply::channel(format!(
+                    "{} is watching with delay of {} actions",
+                    user_name, table.spectator_delay
+                ))];
+                replies.extend(send_to_spectators(table));
+                Ok(replies)
+            }
+            Some("unwatch") => {
+                let table = self
+                    .tables
+                    .get_mut(channel)
+                    .ok_or_else(|| String::from("No table in this channel"))?;
+                let index = table
+                    .spectators
+                    .iter()
+                    .position(|v| v.user == user)
+                    .ok_or_else(|| format!("{} is not watching", user_name))?;
+                table.spectators.remove(index);
+                Ok(vec![Reply::channel(format!(
+                    "{} stopped watching",
+                    user_name
+                ))])
+            }
+            Some("say") => {
+                let text = words.collect::<Vec<_>>().join(" ");
+                if text.is_empty() {
+                    return Err(String::from("Say requires a message"));
+                }
+                let table = self.get_playing_table(channel)?;
+                let seat = get_user_seat(table, user)?;
+                let step = table.game.as_ref().unwrap().step();
+                table.chat.push(ChatMessage {
+                    step,
+                    player: seat,
+                    text: text.clone(),
+                });
+                Ok(vec![Reply::channel(format!(
+                    "{} says: {}",
+                    table.seats[seat].name, text
+                ))])
+            }
+            Some("start") => self.start(channel),
+            Some("end") => {
+                let table = self
+                    .tables
+                    .remove(channel)
+                    .ok_or_else(|| String::from("No table in this channel"))?;
+                let mut replies = Vec::new();
+                if let Some(reveal) = table.reveal.as_ref() {
+                    replies.push(format_reveal(reveal));
+                }
+                replies.push(Reply::channel(String::from("Table is closed")));
+                Ok(replies)
+            }
+            Some("state") => {
+                let table = self.get_playing_table(channel)?;
+                Ok(vec![Reply::channel(format_state(table))])
+            }
+            Some("cards") => {
+                let table = self.get_playing_table(channel)?;
+                let seat = get_user_seat(table, user)?;
+                Ok(vec![format_cards(table, seat, user)])
+            }
+            Some("ping") => {
+                let table = self.get_playing_table(channel)?;
+                let seat = get_user_seat(table, user)?;
+                let view = table.game.as_ref().unwrap().get_player_view(seat);
+                Ok(vec![Reply::user(
+                    user,
+                    format!("step {} state {:016x}", view.step, view.state_hash()),
+                )])
+            }
+            Some("sync") => {
+                let table = self.get_playing_table(channel)?;
+                let seat = get_user_seat(table, user)?;
+                let mut text = format!(
+                    "Missed actions: {}",
+                    table.log.len() - table.seats[seat].seen
+                );
+                for line in table.log[table.seats[seat].seen..].iter() {
+                    text += &format!("\n{}", line);
+                }
+                text += &format!("\n{}", format_state(table));
+                table.seats[seat].seen = table.log.len();
+                Ok(vec![
+                    Reply::user(user, text),
+                    format_cards(table, seat, user),
+                ])
+            }
+            Some("pass") => {
+                let table = self.get_playing_table(channel)?;
+                let seat = get_user_seat(table, user)?;
+                let available_actions = get_table_available_actions(table);
+                if let Some(action) = available_actions.iter().find(|v| {
+                    v.player == seat
+                        && matches!(
+                            v.action_type,
+                            ActionType::PassChallenge | ActionType::PassBlock
+                        )
+                }) {
+                    let action = action.clone();
+                    return play(table, action);
+                }
+                let auto_pass = table.auto_pass;
+                if !table
+                    .window
+                    .as_mut()
+                    .is_some_and(|v| v.pass(seat, &auto_pass))
+                {
+                    return Err(format!("{} has nothing to pass", table.seats[seat].name));
+                }
+                let mut replies =
+                    vec![Reply::channel(format!("{} passes", table.seats[seat].name))];
+                replies.extend(advance(table));
+                Ok(replies)
+            }
+            Some(_) => {
+                let table = self.get_playing_table(channel)?;
+                let seat = get_user_seat(table, user)?;
+                let action_type = if text.trim() == "take" {
+                    ActionType::TakeCard
+                } else {
+                    let names: Vec<String> = table.seats.iter().map(|v| v.name.clone()).collect();
+                    get_action_type(
+                        &parse_game_action_type(text.trim()).map_err(|e| e.to_string())?,
+                        &names,
+                    )?
+                };
+                let action = Action {
+                    player: seat,
+                    action_type,
+                };
+                if !get_table_available_actions(table).contains(&action) {
+                    return Err(format!("Action is not available: {}", text.trim()));
+                }
+                play(table, action)
+            }
+        }
+    }
+
+    fn start(&mut self, channel: &str) -> Result<Vec<Reply>, String> {
+        let table = self
+            .tables
+            .get_mut(channel)
+            .ok_or_else(|| String::from("No table in this channel, use new to open one"))?;
+        if table.game.is_some() {
+            return Err(String::from("Game is already started"));
+        }
+        let settings = Settings {
+            players_number: table.seats.len(),
+            card_counts: CardCounts::uniform(self.cards_per_type),
+            cards_per_player: self.cards_per_player,
+            rules: Default::default(),
+        };
+        settings.validate()?;
+        if let Some(seat) = table
+            .seats
+            .iter()
+            .find(|v| v.user.is_none() && v.reserved.is_some())
+        {
+            return Err(format!("{} hasn't joined the reserved seat yet", seat.name));
+        }
+        let reveal = Reveal {
+            seed: table.rng.gen(),
+            salt: table.rng.gen(),
+        };
+        table.rng = StdRng::seed_from_u64(reveal.seed);
+        let game = Game::new(settings.clone(), &mut table.rng);
+        let mut bot_types = table.bot_types.iter();
+        table.log.clear();
+        table.spectator_log.clear();
+        table.actions.clear();
+        table.chat.clear();
+        for spectator in table.spectators.iter_mut() {
+            spectator.sent = 0;
+        }
+        for (index, seat) in table.seats.iter_mut().enumerate() {
+            seat.seen = 0;
+            if seat.user.is_none() {
+                let mut bot = make_bot(
+                    *bot_types.next().unwrap(),
+                    &game.get_player_view(index),
+                    &settings,
+                    &mut self.rng,
+                );
+                bot.on_game_start(&settings, index);
+                seat.bot = Some(bot);
+            }
+        }
+        table.game = Some(game);
+        let mut replies = vec![Reply::channel(format!(
+            "Game is started\nSeed commitment: {}\n{}",
+            reveal.commit(),
+            format_state(table)
+        ))];
+        table.reveal = Some(reveal);
+        table
+            .spectator_log
+            .push(format!("initial {}", format_hands(table)));
+        replies.extend(send_to_spectators(table));
+        for (index, seat) in table.seats.iter().enumerate() {
+            if let Some(user) = seat.user.as_ref() {
+                replies.push(format_cards(table, index, user));
+            }
+        }
+        replies.extend(advance(table));
+        Ok(replies)
+    }
+
+    fn get_waiting_table(&mut self, channel: &str) -> Result<&mut Table, String> {
+        let table = self
+            .tables
+            .get_mut(channel)
+            .ok_or_else(|| String::from("No table in this channel, use new to open one"))?;
+        if table.game.is_some() {
+            return Err(String::from("Game is already started"));
+        }
+        Ok(table)
+    }
+
+    fn get_playing_table(&mut self, channel: &str) -> Result<&mut Table, String> {
+        let table = self
+            .tables
+            .get_mut(channel)
+            .ok_or_else(|| String::from("No table in this channel, use new to open one"))?;
+        if table.game.is_none() {
+            return Err(String::from("Game is not started"));
+        }
+        Ok(table)
+    }
+}
+
+fn get_seat_name(seats: &[Seat], name: &str) -> String {
+    let name: String = name.split_whitespace().collect::<Vec<_>>().join("_");
+    if seats.iter().any(|v| v.name == name) {
+        format!("{}{}", name, seats.len())
+    } else {
+        name
+    }
+}
+
+fn parse_user_id(value: &str) -> Result<String, String> {
+    let id = value
+        .strip_prefix("<@")
+        .and_then(|v| v.strip_suffix('>'))
+        .map(|v| v.trim_start_matches('!'))
+        .unwrap_or(value);
+    if id.is_empty() || !id.bytes().all(|v| v.is_ascii_digit()) {
+        return Err(format!("Invalid user mention or id: {}", value));
+    }
+    Ok(String::from(id))
+}
+
+fn format_mention(user: &str) -> String {
+    format!("<@{}>", user)
+}
+
+fn get_user_seat(table: &Table, user: &str) -> Result<usize, String> {
+    table
+        .seats
+        .iter()
+        .position(|v| v.user.as_deref() == Some(user))
+        .ok_or_else(|| String::from("You don't have a seat at this table"))
+}
+
+fn get_table_available_actions(table: &Table) -> Vec<Action> {
+    let view = table.game.as_ref().unwrap().get_anonymous_view();
+    get_available_actions(
+        view.state_type,
+        view.player_coins,
+        view.player_hands,
+        view.rules,
+    )
+}
+
+fn play(table: &mut Table, action: Action) -> Result<Vec<Reply>, String> {
+    let mut replies = play_action(table, &action)?;
+    replies.extend(advance(table));
+    Ok(replies)
+}
+
+fn play_action(table: &mut Table, action: &Action) -> Result<Vec<Reply>, String> {
+    let names: Vec<String> = table.seats.iter().map(|v| v.name.clone()).collect();
+    let game = table.game.as_mut().unwrap();
+    let cards_before: Vec<Card> = game.get_player_view(action.player).cards.to_vec();
+    game.play(action, &mut table.rng)?;
+    table.window = None;
+    let text = match action.action_type {
+        ActionType::DropCard(..) => format!("play {} drop card", names[action.player]),
+        _ => to_game_command(action, &names),
+    };
+    table.log.push(text.clone());
+    table.actions.push(action.clone());
+    if table.seats[action.player].user.is_some() {
+        table.seats[action.player].seen = table.log.len();
+    }
+    table.spectator_log.push(format!(
+        "{}\n{}",
+        to_game_command(action, &names),
+        format_hands(table)
+    ));
+    let game = table.game.as_ref().unwrap();
+    let mut replies = vec![Reply::channel(text)];
+    for (index, seat) in table.seats.iter_mut().enumerate() {
+        if !game.is_player_active(index) {
+            continue;
+        }
+        let view = game.get_player_view(index);
+        if let Some(bot) = seat.bot.as_mut() {
+            notify_bot(bot.as_mut(), &view, action);
+        }
+    }
+    let cards_changed = game.is_player_active(action.player)
+        && game.get_player_view(action.player).cards != cards_before.as_slice();
+    if let Some(user) = table.seats[action.player].user.as_ref() {
+        if cards_changed {
+            replies.push(format_cards(table, action.player, user));
+        }
+    }
+    replies.extend(send_to_spectators(table));
+    Ok(replies)
+}
+
+fn advance(table: &mut Table) -> Vec<Reply> {
+    let mut replies = Vec::new();
+    loop {
+        if let Some(winner) = table.game.as_ref().unwrap().get_winner() {
+            if let Some(reveal) = table.reveal.take() {
+                replies.push(format_reveal(&reveal));
+                if let Some(dir) = table.replay_dir.as_ref() {
+                    if let Err(e) = save_replay(table, dir, reveal.seed) {
+                        tracing::warn!(error = %e, "failed to save replay");
+                        replies.push(Reply::channel(format!("Failed to save replay: {}", e)));
+                    }
+                }
+            }
+            replies.push(Reply::channel(format!(
+                "Full reveal:\n{}",
+                table.spectator_log.join("\n")
+            )));
+            for spectator in table.spectators.iter_mut() {
+                spectator.sent = table.spectator_log.len();
+            }
+            replies.push(Reply::channel(format!(
+                "{} wins the game",
+                table.seats[winner].name
+            )));
+            table.game = None;
+            table.window = None;
+            return replies;
+        }
+        let available_actions = get_table_available_actions(table);
+        let now = Instant::now();
+        if table.window.is_none() {
+            table.window = ResponseWindow::open(
+                table.game.as_ref().unwrap().get_anonymous_view().state_type,
+                &available_actions,
+                &table.auto_pass,
+                now,
+            );
+        }
+        if let Some(window) = table.window.as_mut() {
+            window.update(now);
+        }
+        let closed = table.window.as_ref().is_some_and(|v| !v.is_open());
+        let mut players: Vec<usize> = Vec::new();
+        for action in available_actions.iter() {
+            if !players.contains(&action.player) {
+                players.push(action.player);
+            }
+        }
+        let required = players.pop().unwrap();
+        let waiting = |player: &usize| table.window.as_ref().is_some_and(|v| v.is_waiting(*player));
+        players.retain(waiting);
+        if !closed && players.iter().any(|v| table.seats[*v].bot.is_none()) {
+            return replies;
+        }
+        let game = table.game.as_ref().unwrap();
+        let view = game.get_anonymous_view();
+        let (priority, players_number) = (view.rules.response_priority, view.player_hands.len());
+        let mut responses = Vec::new();
+        for player in players.iter() {
+            let player_actions: Vec<Action> = available_actions
+                .iter()
+                .cloned()
+                .of_player(*player)
+                .collect();
+            let view = game.get_player_view(*player);
+            if let Some(bot) = table.seats[*player].bot.as_mut() {
+                if let Some(action) = bot.get_optional_action(&view, &player_actions) {
+                    responses.push(action);
+                } else if let Some(window) = table.window.as_mut() {
+                    window.pass(*player, &table.auto_pass);
+                }
+            }
+        }
+        let optional_action = resolve_responses(
+            &responses,
+            required,
+            players_number,
+            priority,
+            &mut table.rng,
+        );
+        let action = if let Some(action) = optional_action {
+            action.clone()
+        } else {
+            let player_actions: Vec<Action> = available_actions
+                .iter()
+                .cloned()
+                .of_player(required)
+                .collect();
+            let view = game.get_player_view(required);
+            match table.seats[required].bot.as_mut() {
+                Some(bot) => bot.get_action(&view, &player_actions),
+                None => match table.window.as_ref().and_then(|v| v.get_auto_pass()) {
+                    Some(action) => action.clone(),
+                    None => {
+                        replies.push(Reply::channel(format!(
+                            "Waiting for {}",
+                            table.seats[required].name
+                        )));
+                        return replies;
+                    }
+                },
+            }
+        };
+        match play_action(table, &action) {
+            Ok(v) => replies.extend(v),
+            Err(e) => {
+                replies.push(Reply::channel(format!("Bot failed to play: {}", e)));
+                return replies;
+            }
+        }
+    }
+}
+
+fn save_replay(table: &Table, dir: &Path, seed: u64) -> Result<(), String> {
+    let params = GameParams {
+        seed,
+        seed_phrase: None,
+        settings: table.game.as_ref().unwrap().get_settings(),
+    };
+    let mut lines = vec![serde_json::to_string(&params).unwrap()];
+    lines.extend(
+        table
+            .actions
+            .iter()
+            .map(|v| serde_json::to_string(v).unwrap()),
+    );
+    let path = dir.join(format!("{}.jsonl", seed));
+    std::fs::write(&path, lines.join("\n") + "\n")
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    let chat: String = table
+        .chat
+        .iter()
+        .map(|v| serde_json::to_string(v).unwrap() + "\n")
+        .collect();
+    let chat_path = get_chat_path(&path);
+    std::fs::write(&chat_path, chat)
+        .map_err(|e| format!("failed to write {}: {}", chat_path.display(), e))
+}
+
+fn send_to_spectators(table: &mut Table) -> Vec<Reply> {
+    let available = table
+        .spectator_log
+        .len()
+        .saturating_sub(table.spectator_delay);
+    let mut replies = Vec::new();
+    for spectator in table.spectators.iter_mut() {
+        if spectator.sent < available {
+            replies.push(Reply::user(
+                &spectator.user,
+                table.spectator_log[spectator.sent..available].join("\n"),
+            ));
+            spectator.sent = available;
+        }
+    }
+    replies
+}
+
+fn format_hands(table: &Table) -> String {
+    let game = table.game.as_ref().unwrap();
+    let mut result = String::from("cards:");
+    for (index, seat) in table.seats.iter().enumerate() {
+        result += &format!(" {}={:?}", seat.name, game.get_player_view(index).cards);
+    }
+    result
+}
+
+fn format_reveal(reveal: &Reveal) -> Reply {
+    Reply::channel(format!(
+        "Game is over, seed: {} salt: {:032x}\nVerify with: coup validate --commitment <commitment> --seed {} --salt {:032x}",
+        reveal.seed, reveal.salt, reveal.seed, reveal.salt
+    ))
+}
+
+fn format_state(table: &Table) -> String {
+    let game = table.game.as_ref().unwrap();
+    let view = game.get_anonymous_view();
+    let mut result = format!("step {} round {}", view.step, view.round);
+    for (index, seat) in table.seats.iter().enumerate() {
+        result += &format!(
+            "\n{} {}: coins={} cards={}",
+            index, seat.name, view.player_coins[index], view.player_hands[index]
+        );
+    }
+    result += &format!("\nrevealed: {:?}", view.revealed_cards);
+    result
+}
+
+fn format_cards(table: &Table, seat: usize, user: &str) -> Reply {
+    let game = table.game.as_ref().unwrap();
+    Reply::user(
+        user,
+        format!("Your cards: {:?}", game.get_player_view(seat).cards),
+    )
+}
+
+const HELP: &str = r#"
+new - open a table in this channel
+join - take a seat at the table, or the seat reserved for you
+reserve <@user> [name] - hold a seat for a user who joins later, the seat takes the user name on join unless a name is given
+bot [type] - add a bot player
+start - start the game
+end - close the table
+state - show public game state
+cards - get your cards in a direct message
+watch - get delayed actions with all player cards in direct messages
+unwatch - stop watching
+say <text> - send a message that is stored with the game replay
+ping - get the current step and your view state hash in a direct message to detect a desync
+sync - get actions played since your last action or sync, the state and your cards in a direct message
+pass - decline to challenge or block, or close the window for your own claim; windows may also close automatically once everyone passed or after a timeout, depending on the server settings
+<action> - play an action using interactive mode syntax without player name, e.g.: income, coup <name>, block Duke, reveal Contessa, take
+"#;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn table_with_only_bots_should_play_game_until_winner() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42));
+        tables.handle("channel", "user", "user", "new");
+        tables.handle("channel", "user", "user", "bot");
+        tables.handle("channel", "user", "user", "bot honest_careful_random");
+        let replies = tables.handle("channel", "user", "user", "start");
+        assert!(replies.last().unwrap().text.ends_with("wins the game"));
+        let commitment = replies[0]
+            .text
+            .lines()
+            .find_map(|v| v.strip_prefix("Seed commitment: "))
+            .unwrap();
+        let reveal = replies
+            .iter()
+            .find_map(|v| {
+                scan_fmt!(&v.text, "Game is over, seed: {d} salt: {x}", u64, [hex u128]).ok()
+            })
+            .map(|(seed, salt)| Reveal { seed, salt })
+            .unwrap();
+        assert_eq!(reveal.verify(commitment), Ok(()));
+        assert!(replies[replies.len() - 2]
+            .text
+            .starts_with("Full reveal:\ninitial cards:"));
+    }
+
+    #[test]
+    fn table_should_wait_for_human_turn() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42));
+        tables.handle("channel", "user", "user", "new");
+        tables.handle("channel", "user", "user", "join");
+        tables.handle("channel", "user", "user", "bot");
+        let replies = tables.handle("channel", "user", "user", "start");
+        assert_eq!(
+            replies
+                .iter()
+                .filter(|v| v.recipient == Recipient::User(String::from("user")))
+                .count(),
+            1
+        );
+        assert_eq!(replies.last().unwrap().text, "Waiting for user");
+        let replies = tables.handle("channel", "user", "user", "income");
+        assert_eq!(replies[0].text, "play user income");
+    }
+
+    #[test]
+    fn table_should_auto_pass_when_all_responders_passed() {
+        let play_tax = |tables: &mut TableManager| {
+            tables.handle("channel", "a", "a", "new");
+            tables.handle("channel", "a", "a", "join");
+            tables.handle("channel", "b", "b", "join");
+            let replies = tables.handle("channel", "a", "a", "start");
+            let current = replies
+                .last()
+                .unwrap()
+                .text
+                .strip_prefix("Waiting for ")
+                .map(String::from)
+                .unwrap();
+            let other = if current == "a" { "b" } else { "a" };
+            tables.handle("channel", &current, &current, "tax");
+            let replies = tables.handle("channel", other, other, "pass");
+            (current, other, replies)
+        };
+        let mut tables = TableManager::new(3, BotType::Random, Some(42));
+        let (current, other, replies) = play_tax(&mut tables);
+        assert_eq!(replies[0].text, format!("{} passes", other));
+        assert_eq!(
+            replies.last().unwrap().text,
+            format!("Waiting for {}", current)
+        );
+        let mut tables = TableManager::new(3, BotType::Random, Some(42)).with_auto_pass(AutoPass {
+            all_passed: true,
+            timeout: None,
+        });
+        let (current, other, replies) = play_tax(&mut tables);
+        assert_eq!(replies[1].text, format!("play {} pass_challenge", current));
+        assert_eq!(
+            replies.last().unwrap().text,
+            format!("Waiting for {}", other)
+        );
+        let replies = tables.handle("channel", other, other, "pass");
+        assert_eq!(replies[0].text, format!("{} has nothing to pass", other));
+    }
+
+    #[test]
+    fn table_should_auto_pass_after_timeout() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42)).with_auto_pass(AutoPass {
+            all_passed: false,
+            timeout: Some(Duration::ZERO),
+        });
+        tables.handle("channel", "a", "a", "new");
+        tables.handle("channel", "a", "a", "join");
+        tables.handle("channel", "b", "b", "join");
+        let replies = tables.handle("channel", "a", "a", "start");
+        let current = replies
+            .last()
+            .unwrap()
+            .text
+            .strip_prefix("Waiting for ")
+            .map(String::from)
+            .unwrap();
+        let replies = tables.handle("channel", &current, &current, "tax");
+        assert_eq!(replies[1].text, format!("play {} pass_challenge", current));
+    }
+
+    #[test]
+    fn expire_should_auto_pass_on_idle_table() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42)).with_auto_pass(AutoPass {
+            all_passed: false,
+            timeout: Some(Duration::from_secs(30)),
+        });
+        tables.handle("channel", "a", "a", "new");
+        tables.handle("channel", "a", "a", "join");
+        tables.handle("channel", "b", "b", "join");
+        let replies = tables.handle("channel", "a", "a", "start");
+        let current = replies
+            .last()
+            .unwrap()
+            .text
+            .strip_prefix("Waiting for ")
+            .map(String::from)
+            .unwrap();
+        assert_eq!(tables.next_deadline(), None);
+        tables.handle("channel", &current, &current, "tax");
+        let deadline = tables.next_deadline().unwrap();
+        assert!(tables
+            .expire(deadline - Duration::from_millis(1))
+            .is_empty());
+        let expired = tables.expire(deadline);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, "channel");
+        assert_eq!(
+            expired[0].1[0].text,
+            format!("play {} pass_challenge", current)
+        );
+        assert_eq!(tables.next_deadline(), None);
+    }
+
+    #[test]
+    fn response_window_should_close_by_policy() {
+        let state_type = StateType::Tax { player: 0 };
+        let available_actions = [
+            Action {
+                player: 1,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 2,
+                action_type: ActionType::Challenge,
+            },
+            Action {
+                player: 0,
+                action_type: ActionType::PassChallenge,
+            },
+        ];
+        let now = Instant::now();
+        let timeout = Duration::from_secs(30);
+        let all_passed = AutoPass {
+            all_passed: true,
+            timeout: Some(timeout),
+        };
+        let mut window =
+            ResponseWindow::open(&state_type, &available_actions, &all_passed, now).unwrap();
+        assert_eq!(window.waiting, vec![1, 2]);
+        assert_eq!(window.deadline(), Some(now + timeout));
+        assert!(window.pass(1, &all_passed));
+        assert!(!window.pass(1, &all_passed));
+        assert!(!window.pass(0, &all_passed));
+        window.update(now + timeout - Duration::from_secs(1));
+        assert_eq!(window.get_auto_pass(), None);
+        assert!(window.pass(2, &all_passed));
+        assert_eq!(window.state, WindowState::AllPassed);
+        assert_eq!(window.get_auto_pass(), Some(&available_actions[2]));
+        assert_eq!(window.deadline(), None);
+        let timeout_only = AutoPass {
+            all_passed: false,
+            timeout: Some(timeout),
+        };
+        let mut window =
+            ResponseWindow::open(&state_type, &available_actions, &timeout_only, now).unwrap();
+        assert!(window.pass(1, &timeout_only));
+        assert!(window.pass(2, &timeout_only));
+        assert_eq!(window.get_auto_pass(), None);
+        window.update(now + timeout);
+        assert_eq!(window.state, WindowState::Expired);
+        assert_eq!(window.get_auto_pass(), Some(&available_actions[2]));
+        let mut window =
+            ResponseWindow::open(&state_type, &available_actions, &AutoPass::default(), now)
+                .unwrap();
+        window.update(now + timeout);
+        assert_eq!(window.get_auto_pass(), None);
+        assert_eq!(
+            ResponseWindow::open(
+                &StateType::Turn { player: 0 },
+                &available_actions,
+                &all_passed,
+                now
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn ping_should_report_step_and_view_state_hash() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42));
+        tables.handle("channel", "user", "user", "new");
+        tables.handle("channel", "user", "user", "join");
+        tables.handle("channel", "user", "user", "bot");
+        tables.handle("channel", "user", "user", "start");
+        let replies = tables.handle("channel", "user", "user", "ping");
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].recipient, Recipient::User(String::from("user")));
+        let game = tables.tables["channel"].game.as_ref().unwrap();
+        let view = game.get_player_view(0);
+        assert_eq!(
+            replies[0].text,
+            format!("step {} state {:016x}", view.step, view.state_hash())
+        );
+        let replies = tables.handle("channel", "other", "other", "ping");
+        assert_eq!(replies[0].text, "You don't have a seat at this table");
+    }
+
+    #[test]
+    fn reserved_seat_should_be_taken_only_by_reserved_user() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42));
+        tables.handle("channel", "1", "host", "new");
+        tables.handle("channel", "1", "host", "reserve <@!2>");
+        tables.handle("channel", "1", "host", "reserve <@3> carol");
+        let replies = tables.handle("channel", "1", "host", "start");
+        assert_eq!(replies[0].text, "<@2> hasn't joined the reserved seat yet");
+        let replies = tables.handle("channel", "4", "user", "join");
+        assert_eq!(replies[0].text, "user takes seat 2");
+        let replies = tables.handle("channel", "2", "user", "join");
+        assert_eq!(replies[0].text, "user3 takes reserved seat 0");
+        let replies = tables.handle("channel", "3", "user", "join");
+        assert_eq!(replies[0].text, "carol takes reserved seat 1");
+        let replies = tables.handle("channel", "1", "host", "reserve alice");
+        assert_eq!(replies[0].text, "Invalid user mention or id: alice");
+        let replies = tables.handle("channel", "1", "host", "reserve 2");
+        assert_eq!(replies[0].text, "<@2> already has a seat");
+    }
+
+    #[test]
+    fn spectator_should_get_actions_with_delay() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42)).with_spectator_delay(1);
+        tables.handle("channel", "user", "user", "new");
+        tables.handle("channel", "user", "user", "join");
+        tables.handle("channel", "user", "user", "bot");
+        let replies = tables.handle("channel", "user", "user", "watch");
+        assert_eq!(replies[0].text, "user has a seat and can't watch");
+        tables.handle("channel", "spectator", "spectator", "watch");
+        let replies = tables.handle("channel", "user", "user", "start");
+        let spectator = Recipient::User(String::from("spectator"));
+        assert!(replies.iter().all(|v| v.recipient != spectator));
+        let replies = tables.handle("channel", "user", "user", "income");
+        let sent: Vec<&Reply> = replies
+            .iter()
+            .filter(|v| v.recipient == spectator)
+            .collect();
+        assert!(sent[0].text.starts_with("initial cards: user=["));
+        let table = &tables.tables["channel"];
+        assert_eq!(table.spectators[0].sent, table.spectator_log.len() - 1);
+    }
+
+    #[test]
+    fn saved_replay_should_reproduce_game_with_chat() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42));
+        tables.handle("channel", "user", "user", "new");
+        tables.handle("channel", "user", "user", "join");
+        tables.handle("channel", "user", "user", "bot");
+        tables.handle("channel", "user", "user", "start");
+        let replies = tables.handle("channel", "user", "user", "say I have a Duke");
+        assert_eq!(replies[0].text, "user says: I have a Duke");
+        tables.handle("channel", "user", "user", "income");
+        let dir = std::env::temp_dir().join(format!("coup-table-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let table = &tables.tables["channel"];
+        let seed = table.reveal.as_ref().unwrap().seed;
+        save_replay(table, &dir, seed).unwrap();
+        let path = dir.join(format!("{}.jsonl", seed));
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        let params: GameParams = serde_json::from_str(lines.next().unwrap()).unwrap();
+        let actions: Vec<Action> = lines.map(|v| serde_json::from_str(v).unwrap()).collect();
+        let game = Game::from_history(params.settings, params.seed, &actions).unwrap();
+        assert_eq!(
+            game.get_player_view(0).cards,
+            table.game.as_ref().unwrap().get_player_view(0).cards
+        );
+        assert_eq!(
+            crate::chat::read_replay_chat(&path.to_string_lossy()).unwrap(),
+            vec![ChatMessage {
+                step: 0,
+                player: 0,
+                text: String::from("I have a Duke"),
+            }]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sync_should_send_missed_actions() {
+        let mut tables = TableManager::new(3, BotType::Random, Some(42));
+        tables.handle("channel", "user", "user", "new");
+        tables.handle("channel", "user", "user", "join");
+        tables.handle("channel", "user", "user", "bot");
+        tables.handle("channel", "user", "user", "start");
+        let played = tables.handle("channel", "user", "user", "income");
+        let missed: Vec<&str> = played[1..]
+            .iter()
+            .filter(|v| v.text.starts_with("play "))
+            .map(|v| v.text.as_str())
+            .collect();
+        let replies = tables.handle("channel", "user", "user", "sync");
+        assert_eq!(replies.len(), 2);
+        assert!(replies
+            .iter()
+            .all(|v| v.recipient == Recipient::User(String::from("user"))));
+        let lines: Vec<&str> = replies[0].text.lines().collect();
+        assert_eq!(lines[0], format!("Missed actions: {}", missed.len()));
+        assert_eq!(&lines[1..=missed.len()], missed.as_slice());
+        let replies = tables.handle("channel", "user", "user", "sync");
+        assert!(replies[0].text.starts_with("Missed actions: 0"));
+    }
+}
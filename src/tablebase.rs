@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use crate::bots::ActionScore;
+use crate::fsm::{Action, ActionType, ConstRng, CARDS_PER_PLAYER};
+use crate::game::{get_available_actions, make_deck, Game, PlayerView, Scenario, ALL_CARDS};
+
+pub const MAX_HIDDEN_CARDS: usize = 2;
+pub const MAX_DECK_CARDS: usize = 6;
+const MAX_ITERATIONS: usize = 10000;
+const PRECISION: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    Winner(usize),
+    Position(usize),
+}
+
+type Outcomes = Vec<(f64, Outcome)>;
+
+struct Node {
+    first: usize,
+    players: Vec<(usize, Vec<Outcomes>)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Tablebase {
+    index: HashMap<Game, usize>,
+    first: Vec<u8>,
+    values: Vec<u16>,
+}
+
+impl Tablebase {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn probe(&mut self, view: &PlayerView, actions: &[Action]) -> Option<Vec<ActionScore>> {
+        if !is_in_range(view) {
+            return None;
+        }
+        let worlds = get_worlds(view);
+        if worlds.is_empty() {
+            return None;
+        }
+        let mut scores = Vec::with_capacity(actions.len());
+        for action in actions.iter() {
+            let mut weight = 0.0;
+            let mut win_probability = 0.0;
+            for (world_weight, world) in worlds.iter() {
+                for (outcome_weight, game) in get_outcomes(world, action) {
+                    let outcome = self.solve(&game);
+                    weight += world_weight * outcome_weight;
+                    win_probability +=
+                        world_weight * outcome_weight * self.get_value(outcome, view.player);
+                }
+            }
+            if weight > 0.0 {
+                scores.push(ActionScore {
+                    action: action.clone(),
+                    win_probability: win_probability / weight,
+                    simulations: worlds.len(),
+                });
+            }
+        }
+        Some(scores)
+    }
+
+    fn get_value(&self, outcome: Outcome, player: usize) -> f64 {
+        match outcome {
+            Outcome::Winner(winner) => (winner == player) as u8 as f64,
+            Outcome::Position(index) => {
+                let value = self.values[index] as f64 / u16::MAX as f64;
+                if self.first[index] as usize == player {
+                    value
+                } else {
+                    1.0 - value
+                }
+            }
+        }
+    }
+
+    fn solve(&mut self, root: &Game) -> Outcome {
+        if let Some(winner) = root.get_winner() {
+            return Outcome::Winner(winner);
+        }
+        let base = self.values.len();
+        let mut games = Vec::new();
+        let root = self.insert(root.normalized(), &mut games);
+        if root < base {
+            return Outcome::Position(root);
+        }
+        let mut nodes = Vec::new();
+        while nodes.len() < games.len() {
+            let game = games[nodes.len()].clone();
+            let mut players: Vec<(usize, Vec<Outcomes>)> = Vec::new();
+            for action in get_game_actions(&game) {
+                let outcomes: Outcomes = get_outcomes(&game, &action)
+                    .into_iter()
+                    .map(|(weight, next)| {
+                        let outcome = match next.get_winner() {
+                            Some(winner) => Outcome::Winner(winner),
+                            None => Outcome::Position(self.insert(next.normalized(), &mut games)),
+                        };
+                        (weight, outcome)
+                    })
+                    .collect();
+                if outcomes.is_empty() {
+                    continue;
+                }
+                match players
+                    .iter_mut()
+                    .find(|(player, _)| *player == action.player)
+                {
+                    Some((_, actions)) => actions.push(outcomes),
+                    None => players.push((action.player, vec![outcomes])),
+                }
+            }
+            nodes.push(Node {
+                first: get_first_player(&game),
+                players,
+            });
+        }
+        let mut values = vec![0.5; nodes.len()];
+        for _ in 0..MAX_ITERATIONS {
+            let mut delta: f64 = 0.0;
+            for (index, node) in nodes.iter().enumerate().rev() {
+                let value = get_node_value(node, |outcome, player| match outcome {
+                    Outcome::Position(v) if v >= base => {
+                        let value = values[v - base];
+                        if self.first[v] as usize == player {
+                            value
+                        } else {
+                            1.0 - value
+                        }
+                    }
+                    _ => self.get_value(outcome, player),
+                });
+                delta = delta.max((value - values[index]).abs());
+                values[index] = value;
+            }
+            if delta < PRECISION {
+                break;
+            }
+        }
+        for (index, value) in values.into_iter().enumerate() {
+            self.values[base + index] = (value * u16::MAX as f64).round() as u16;
+        }
+        Outcome::Position(root)
+    }
+
+    fn insert(&mut self, game: Game, games: &mut Vec<Game>) -> usize {
+        if let Some(index) = self.index.get(&game) {
+            return *index;
+        }
+        let index = self.values.len();
+        self.first.push(get_first_player(&game) as u8);
+        self.values.push(u16::MAX / 2);
+        games.push(game.clone());
+        self.index.insert(game, index);
+        index
+    }
+}
+
+pub fn is_in_range(view: &PlayerView) -> bool {
+    view.player_hands.iter().filter(|hand| **hand > 0).count() == 2
+        && view.player_hands.iter().sum::<usize>() <= MAX_HIDDEN_CARDS
+        && view.player_cards == view.player_hands
+        && view.deck <= MAX_DECK_CARDS
+}
+
+fn get_first_player(game: &Game) -> usize {
+    (0..game.get_settings().players_number)
+        .find(|player| game.is_player_active(*player))
+        .unwrap()
+}
+
+fn get_game_actions(game: &Game) -> Vec<Action> {
+    let view = game.get_anonymous_view();
+    get_available_actions(
+        view.state_type,
+        view.player_coins,
+        view.player_hands,
+        view.rules,
+    )
+}
+
+fn get_outcomes(game: &Game, action: &Action) -> Vec<(f64, Game)> {
+    if action.action_type != ActionType::TakeCard {
+        let mut next = game.clone();
+        return match next.play(action, &mut ConstRng) {
+            Ok(()) => vec![(1.0, next)],
+            Err(_) => Vec::new(),
+        };
+    }
+    let deck = game.deck();
+    ALL_CARDS
+        .iter()
+        .filter_map(|card| {
+            let count = deck.iter().filter(|v| *v == card).count();
+            if count == 0 {
+                return None;
+            }
+            let mut next = game.clone();
+            next.put_deck_card_on_top(*card).ok()?;
+            next.play(action, &mut ConstRng).ok()?;
+            Some((count as f64 / deck.len() as f64, next))
+        })
+        .collect()
+}
+
+fn get_node_value<F: Fn(Outcome, usize) -> f64>(node: &Node, value: F) -> f64 {
+    let mut result = None;
+    for (player, actions) in node.players.iter().rev() {
+        let options = actions
+            .iter()
+            .map(|outcomes| {
+                outcomes
+                    .iter()
+                    .map(|(weight, outcome)| weight * value(*outcome, node.first))
+                    .sum::<f64>()
+            })
+            .chain(result);
+        result = if *player == node.first {
+            options.reduce(f64::max)
+        } else {
+            options.reduce(f64::min)
+        };
+    }
+    result.unwrap_or(0.5)
+}
+
+fn get_worlds(view: &PlayerView) -> Vec<(f64, Game)> {
+    let cards = view.deck + view.revealed_cards.len() + view.player_cards.iter().sum::<usize>();
+    let mut unseen = make_deck(cards / ALL_CARDS.len());
+    for card in view.revealed_cards.iter().chain(view.cards.iter()) {
+        match unseen.iter().position(|v| v == card) {
+            Some(index) => {
+                unseen.remove(index);
+            }
+            None => return Vec::new(),
+        }
+    }
+    let opponent = (0..view.player_hands.len())
+        .find(|player| *player != view.player && view.player_hands[*player] > 0)
+        .unwrap();
+    ALL_CARDS
+        .iter()
+        .filter_map(|card| {
+            let count = unseen.iter().filter(|v| *v == card).count();
+            if count == 0 {
+                return None;
+            }
+            let mut deck = unseen.clone();
+            deck.remove(deck.iter().position(|v| v == card).unwrap());
+            let mut player_cards = vec![Vec::new(); view.player_hands.len()];
+            player_cards[view.player] = view.cards.to_vec();
+            player_cards[opponent] = vec![*card];
+            let game = Game::from_scenario(&Scenario {
+                player_cards,
+                deck,
+                player_coins: Some(view.player_coins.to_vec()),
+                revealed_cards: view.revealed_cards.to_vec(),
+                state_type: Some(view.state_type.clone()),
+                cards_per_player: CARDS_PER_PLAYER,
+                rules: *view.rules,
+                card_counts: None,
+            })
+            .ok()?;
+            Some((count as f64 / unseen.len() as f64, game))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fsm::Card;
+
+    use super::*;
+
+    fn make_game(player_cards: Vec<Vec<Card>>, player_coins: Vec<usize>) -> Game {
+        let mut deck = make_deck(1);
+        let mut revealed_cards = Vec::new();
+        for card in player_cards.iter().flatten() {
+            deck.remove(deck.iter().position(|v| v == card).unwrap());
+        }
+        for _ in player_cards.iter() {
+            revealed_cards.push(deck.pop().unwrap());
+        }
+        Game::from_scenario(&Scenario {
+            player_cards,
+            deck,
+            player_coins: Some(player_coins),
+            revealed_cards,
+            state_type: None,
+            cards_per_player: 2,
+            rules: Default::default(),
+            card_counts: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn probe_should_find_winning_coup() {
+        let game = make_game(vec![vec![Card::Duke], vec![Card::Captain]], vec![7, 0]);
+        let view = game.get_player_view(0);
+        assert!(is_in_range(&view));
+        let actions = get_game_actions(&game);
+        let mut tablebase = Tablebase::default();
+        let scores = tablebase.probe(&view, &actions).unwrap();
+        let coup = scores
+            .iter()
+            .find(|v| v.action.action_type == ActionType::Coup(1))
+            .unwrap();
+        assert_eq!(coup.win_probability, 1.0);
+        assert_eq!(coup.simulations, 2);
+        assert!(scores.iter().all(|v| v.win_probability <= 1.0));
+        assert!(tablebase.len() > 0);
+    }
+
+    #[test]
+    fn solve_should_return_complementary_values_for_both_players() {
+        let game = make_game(vec![vec![Card::Assassin], vec![Card::Contessa]], vec![3, 3]);
+        let mut tablebase = Tablebase::default();
+        let outcome = tablebase.solve(&game);
+        let first = tablebase.get_value(outcome, 0);
+        let second = tablebase.get_value(outcome, 1);
+        assert!((first + second - 1.0).abs() < 1e-4);
+        let size = tablebase.len();
+        tablebase.solve(&game);
+        assert_eq!(tablebase.len(), size);
+    }
+
+    #[test]
+    fn probe_should_skip_positions_out_of_range() {
+        let game = Game::custom(
+            vec![vec![Card::Duke, Card::Captain], vec![Card::Contessa]],
+            vec![Card::Assassin, Card::Ambassador],
+        );
+        let view = game.get_player_view(0);
+        assert!(!is_in_range(&view));
+        assert!(Tablebase::default().probe(&view, &[]).is_none());
+    }
+}
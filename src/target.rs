@@ -0,0 +1,107 @@
+use serde::Serialize;
+
+use crate::bots::CardsTracker;
+use crate::eval::{Evaluator, LinearEvaluator};
+use crate::fsm::{ActionType, Card, COUP_COST, MAX_STEAL};
+use crate::game::{available_actions_for, PlayerView};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TargetAdvice {
+    pub action_type: ActionType,
+    pub target: usize,
+    pub block_probability: f64,
+    pub coins: usize,
+    pub threat: f64,
+    pub score: f64,
+}
+
+pub fn rank_targets(tracker: &CardsTracker, view: &PlayerView) -> Vec<TargetAdvice> {
+    let threats = LinearEvaluator::default().get_win_probabilities(view);
+    let mut result: Vec<TargetAdvice> = available_actions_for(view, view.player)
+        .filter_map(|action| {
+            let (target, blockers, value): (usize, &[Card], f64) = match &action.action_type {
+                ActionType::Steal(target) => (
+                    *target,
+                    &[Card::Ambassador, Card::Captain],
+                    view.player_coins[*target].min(MAX_STEAL) as f64 / COUP_COST as f64,
+                ),
+                ActionType::Assassinate(target) => (*target, &[Card::Contessa], 1.0),
+                ActionType::Coup(target) => (*target, &[], 1.0),
+                _ => return None,
+            };
+            let block_probability = if blockers.is_empty() {
+                0.0
+            } else {
+                tracker.get_any_card_probability(target, blockers)
+            };
+            Some(TargetAdvice {
+                action_type: action.action_type,
+                target,
+                block_probability,
+                coins: view.player_coins[target],
+                threat: threats[target],
+                score: (1.0 - block_probability) * (value + threats[target]),
+            })
+        })
+        .collect();
+    result.sort_by(|l, r| r.score.total_cmp(&l.score));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::bots::notify_tracker;
+    use crate::fsm::{Action, Rules};
+    use crate::game::{CardCounts, Game, Settings};
+
+    use super::*;
+
+    #[test]
+    fn rank_targets_should_prefer_targets_unlikely_to_block() {
+        let settings = Settings {
+            players_number: 3,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Contessa, Card::Contessa],
+                vec![Card::Duke, Card::Assassin],
+                vec![Card::Captain, Card::Captain],
+            ],
+            vec![
+                Card::Duke,
+                Card::Assassin,
+                Card::Ambassador,
+                Card::Ambassador,
+            ],
+        );
+        let mut tracker = CardsTracker::from_view(&game.get_player_view(0), &settings);
+        for player in [0, 1, 2].iter() {
+            let action = Action {
+                player: *player,
+                action_type: ActionType::Income,
+            };
+            game.play(&action, &mut rng).unwrap();
+            notify_tracker(&mut tracker, &game.get_player_view(0), &action);
+        }
+        let advice = rank_targets(&tracker, &game.get_player_view(0));
+        assert_eq!(advice.len(), 4);
+        assert!(advice.iter().all(|v| matches!(
+            v.action_type,
+            ActionType::Steal(..) | ActionType::Assassinate(..)
+        )));
+        let assassinations: Vec<&TargetAdvice> = advice
+            .iter()
+            .filter(|v| matches!(v.action_type, ActionType::Assassinate(..)))
+            .collect();
+        assert_eq!(assassinations.len(), 2);
+        assert!(assassinations.iter().all(|v| v.block_probability == 0.0));
+        assert!(advice.windows(2).all(|v| v[0].score >= v[1].score));
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::bots::CardsTracker;
+use crate::fsm::Action;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackerEvent {
+    Initial,
+    Branched,
+    Collapsed,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub step: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<Action>,
+    pub hypotheses: usize,
+    pub entropy: Vec<f64>,
+    pub event: TrackerEvent,
+}
+
+#[derive(Debug, Default)]
+pub struct TrackerTimeline {
+    entries: Vec<TimelineEntry>,
+}
+
+impl TrackerTimeline {
+    pub fn add(
+        &mut self,
+        step: usize,
+        action: Option<&Action>,
+        tracker: &CardsTracker,
+    ) -> &TimelineEntry {
+        let summary = tracker.summary();
+        let event = match self.entries.last() {
+            None => TrackerEvent::Initial,
+            Some(last) if summary.states > last.hypotheses => TrackerEvent::Branched,
+            Some(last) if summary.states < last.hypotheses => TrackerEvent::Collapsed,
+            Some(..) => TrackerEvent::Unchanged,
+        };
+        self.entries.push(TimelineEntry {
+            step,
+            action: action.cloned(),
+            hypotheses: summary.states,
+            entropy: summary.entropy,
+            event,
+        });
+        self.entries.last().unwrap()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut result =
+            String::from("digraph tracker {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (index, entry) in self.entries.iter().enumerate() {
+            writeln!(
+                result,
+                "    s{} [label=\"step {}\\nhypotheses={}\\nentropy={}\", color={}];",
+                index,
+                entry.step,
+                entry.hypotheses,
+                entry
+                    .entropy
+                    .iter()
+                    .map(|v| format!("{:.2}", v))
+                    .collect::<Vec<_>>()
+                    .join(","),
+                get_event_color(entry.event)
+            )
+            .unwrap();
+            if index == 0 {
+                continue;
+            }
+            let label = match &entry.action {
+                Some(action) => format!("{} {:?}", action.player, action.action_type),
+                None => String::new(),
+            };
+            writeln!(
+                result,
+                "    s{} -> s{} [label=\"{}\"];",
+                index - 1,
+                index,
+                label.replace('"', "\\\"")
+            )
+            .unwrap();
+        }
+        result.push_str("}\n");
+        result
+    }
+
+    pub fn save_dot(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_dot()).map_err(|e| format!("failed to write {}: {}", path, e))
+    }
+}
+
+fn get_event_color(event: TrackerEvent) -> &'static str {
+    match event {
+        TrackerEvent::Initial => "black",
+        TrackerEvent::Branched => "orange",
+        TrackerEvent::Collapsed => "green",
+        TrackerEvent::Unchanged => "gray",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::bots::ActionView;
+    use crate::fsm::{ActionType, Card, Rules};
+    use crate::game::{CardCounts, Game, Settings};
+
+    use super::*;
+
+    #[test]
+    fn timeline_should_record_collapse_after_reveal() {
+        let settings = Settings {
+            players_number: 2,
+            card_counts: CardCounts::uniform(2),
+            cards_per_player: 2,
+            rules: Rules::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = Game::custom(
+            vec![
+                vec![Card::Ambassador, Card::Duke],
+                vec![Card::Assassin, Card::Assassin],
+            ],
+            vec![
+                Card::Captain,
+                Card::Duke,
+                Card::Contessa,
+                Card::Ambassador,
+                Card::Captain,
+                Card::Contessa,
+            ],
+        );
+        let mut tracker = CardsTracker::from_view(&game.get_player_view(0), &settings);
+        let mut timeline = TrackerTimeline::default();
+        assert_eq!(
+            timeline.add(game.step(), None, &tracker).event,
+            TrackerEvent::Initial
+        );
+        for (player, action_type) in [
+            (0, ActionType::Income),
+            (1, ActionType::Tax),
+            (0, ActionType::Challenge),
+            (1, ActionType::RevealCard(Card::Assassin)),
+        ] {
+            let action = Action {
+                player,
+                action_type,
+            };
+            game.play(&action, &mut rng).unwrap();
+            if player == 0 {
+                tracker.after_player_action(&game.get_player_view(0), &action);
+            } else {
+                tracker.after_opponent_action(
+                    &game.get_player_view(0),
+                    &ActionView::from_action(&action),
+                );
+            }
+            timeline.add(game.step(), Some(&action), &tracker);
+        }
+        assert_eq!(timeline.entries.len(), 5);
+        assert!(timeline
+            .entries
+            .iter()
+            .any(|v| v.event == TrackerEvent::Collapsed));
+        let dot = timeline.to_dot();
+        assert!(dot.starts_with("digraph tracker {"));
+        assert!(dot.contains("s3 -> s4 [label=\"1 RevealCard(Assassin)\"];"));
+    }
+}